@@ -0,0 +1,25 @@
+// Simple Dart example
+import 'dart:math';
+
+class Sample {
+  final int _value;
+
+  Sample(this._value);
+
+  int add(int a, int b) {
+    return a + b;
+  }
+
+  void _log(String message) {
+    print(message);
+  }
+
+  Future<int> load() async {
+    return max(_value, 0);
+  }
+}
+
+void main() {
+  var result = Sample(1).add(5, 3);
+  print('Result: $result');
+}
@@ -0,0 +1,22 @@
+# Simple Terraform example
+variable "region" {
+  type    = string
+  default = "eu-west-1"
+}
+
+module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "5.0.0"
+  cidr    = var.cidr
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "${var.env}-logs"
+  tags = {
+    team = "platform"
+  }
+}
+
+resource "aws_s3_bucket" "assets" {
+  bucket = "assets"
+}
@@ -0,0 +1,21 @@
+// Simple Kotlin example
+package sample
+
+import kotlin.math.max
+
+class Sample(private val value: Int) {
+    fun add(a: Int, b: Int): Int {
+        return a + b
+    }
+
+    private fun log(message: String) {
+        println(message)
+    }
+
+    suspend fun load(): Int = max(value, 0)
+}
+
+fun main() {
+    val result = Sample(1).add(5, 3)
+    println("Result: $result")
+}
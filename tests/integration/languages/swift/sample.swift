@@ -0,0 +1,27 @@
+// Simple Swift example
+import Foundation
+
+class Sample {
+    private var value: Int
+
+    init(value: Int) {
+        self.value = value
+    }
+
+    func add(_ a: Int, _ b: Int) -> Int {
+        return a + b
+    }
+
+    private func log(_ message: String) {
+        print(message)
+    }
+
+    func load() async -> Int {
+        return max(value, 0)
+    }
+}
+
+func main() {
+    let result = Sample(value: 1).add(5, 3)
+    print("Result: \(result)")
+}
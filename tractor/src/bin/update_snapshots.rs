@@ -697,9 +697,10 @@ fn main() {
             let xml_path = format!("{}.xml", path_str);
             let output = run_tractor(&tractor_bin, &path_str, &["-f", "xml"]);
 
-            // Raw TreeSitter XML
+            // Raw TreeSitter XML; tables have no TreeSitter tree
             let raw_xml_path = format!("{}.raw.xml", path_str);
-            let raw_output = run_tractor(&tractor_bin, &path_str, &["-t", "raw", "-f", "xml"]);
+            let raw_output = (!tractor::languages::data_tree_only(&lang_name))
+                .then(|| run_tractor(&tractor_bin, &path_str, &["-t", "raw", "-f", "xml"]));
 
             if check_mode {
                 match fs::read_to_string(&xml_path) {
@@ -709,17 +710,24 @@ fn main() {
                     Err(_) => mismatches.push(Mismatch::missing(&xml_path, &output)),
                     _ => {}
                 }
-                match fs::read_to_string(&raw_xml_path) {
-                    Ok(existing) if existing != raw_output => {
-                        mismatches.push(Mismatch::changed(&raw_xml_path, &existing, &raw_output));
+                if let Some(raw_output) = &raw_output {
+                    match fs::read_to_string(&raw_xml_path) {
+                        Ok(existing) if existing != *raw_output => {
+                            mismatches.push(Mismatch::changed(&raw_xml_path, &existing, raw_output));
+                        }
+                        Err(_) => mismatches.push(Mismatch::missing(&raw_xml_path, raw_output)),
+                        _ => {}
                     }
-                    Err(_) => mismatches.push(Mismatch::missing(&raw_xml_path, &raw_output)),
-                    _ => {}
                 }
             } else {
                 fs::write(&xml_path, &output).expect("cannot write .xml snapshot");
-                fs::write(&raw_xml_path, &raw_output).expect("cannot write .raw.xml snapshot");
-                println!("  {}/{} -> .xml, .raw.xml", lang_name, file_name);
+                match &raw_output {
+                    Some(raw_output) => {
+                        fs::write(&raw_xml_path, raw_output).expect("cannot write .raw.xml snapshot");
+                        println!("  {}/{} -> .xml, .raw.xml", lang_name, file_name);
+                    }
+                    None => println!("  {}/{} -> .xml", lang_name, file_name),
+                }
             }
 
             processed += 1;
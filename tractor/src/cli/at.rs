@@ -0,0 +1,126 @@
+use clap::Args;
+use tractor::language_info::get_language_info;
+use tractor::output::{should_use_color, RenderOptions};
+use tractor::{parse, ParseInput, ParseOptions};
+
+/// Position mode: the elements at a source position, with XPaths for each
+#[derive(Args, Debug)]
+pub struct AtArgs {
+    /// Source position; COL defaults to the line's first non-blank character
+    #[arg(value_name = "FILE:LINE[:COL]")]
+    pub position: String,
+
+    /// Language override (auto-detected from file extension)
+    #[arg(short = 'l', long = "lang")]
+    pub lang: Option<String>,
+
+    /// Tree mode: raw, structure, common, data [default: auto]
+    #[arg(short = 't', long = "tree")]
+    pub tree: Option<String>,
+
+    /// Limit the depth of the innermost element's sub-tree
+    #[arg(short = 'd', long = "depth")]
+    pub depth: Option<usize>,
+
+    /// Include metadata attributes (start/end, kind, field) in the sub-tree
+    #[arg(long = "meta")]
+    pub meta: bool,
+
+    /// Encoding for a file without a BOM that is not UTF-8 (e.g. windows-1252),
+    /// instead of guessing it
+    #[arg(long = "encoding")]
+    pub encoding: Option<String>,
+
+    /// Color output: auto (default), always, never
+    #[arg(long = "color", default_value = "auto")]
+    pub color: String,
+
+    /// Disable color output
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+}
+use super::context::parse_tree_arg;
+
+pub fn run_at(args: AtArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (file, line, column) = parse_position(&args.position)?;
+    let encoding = match &args.encoding {
+        Some(name) => Some(tractor::encoding::lookup(name).ok_or_else(|| format!("unknown encoding: {}", name))?),
+        None => None,
+    };
+    let lang = match &args.lang {
+        Some(lang) => Some(get_language_info(lang).ok_or_else(|| format!("unknown language: {}", lang))?.name),
+        None => None,
+    };
+    // The same parse as queries get, so positions land in the same tree
+    let result = parse(
+        ParseInput::Disk { path: std::path::Path::new(&file) },
+        ParseOptions {
+            language: lang,
+            tree_mode: parse_tree_arg(args.tree.as_deref())?,
+            encoding,
+            ..ParseOptions::default()
+        },
+    )?;
+    let column = match column {
+        Some(column) => column,
+        None => first_non_blank_column(&result.source_lines, line),
+    };
+
+    let xot = result.documents.xot();
+    let root = result.documents.document_node(result.doc_handle)
+        .ok_or_else(|| format!("{} has no document", file))?;
+    let chain = tractor::elements_at(xot, root, result.node_locations.as_deref(), line, column);
+    let innermost = chain.last()
+        .ok_or_else(|| format!("{}:{}:{} is outside the parsed tree", file, line, column))?;
+
+    let width = chain.iter().map(|element| element.absolute.len()).max().unwrap_or(0);
+    for element in &chain {
+        println!("{:<width$}  {}", element.absolute, element.relative, width = width);
+    }
+    println!();
+
+    let use_color = if args.no_color { false } else { should_use_color(&args.color) };
+    let options = RenderOptions::new()
+        .with_color(use_color)
+        .with_meta(args.meta)
+        .with_max_depth(args.depth)
+        .with_language(Some(result.language.clone()));
+    println!("{}", tractor::render_node(xot, innermost.node, &options).trim_end());
+    Ok(())
+}
+
+/// Split `FILE:LINE[:COL]`, from the right so Windows drive letters survive.
+fn parse_position(position: &str) -> Result<(String, u32, Option<u32>), String> {
+    let invalid = || format!("invalid position '{}': expected FILE:LINE or FILE:LINE:COL", position);
+    let number = |text: &str| text.parse::<u32>().ok().filter(|&n| n > 0);
+    let (rest, last) = position.rsplit_once(':').ok_or_else(invalid)?;
+    let last = number(last).ok_or_else(invalid)?;
+    let (file, line, column) = match rest.rsplit_once(':').and_then(|(file, line)| Some((file, number(line)?))) {
+        Some((file, line)) => (file, line, Some(last)),
+        None => (rest, last, None),
+    };
+    if file.is_empty() {
+        return Err(invalid());
+    }
+    Ok((file.to_string(), line, column))
+}
+
+/// 1-based byte column of the first non-blank character on `line`.
+fn first_non_blank_column(lines: &[String], line: u32) -> u32 {
+    let text = lines.get(line as usize - 1).map_or("", String::as_str);
+    (text.len() - text.trim_start().len()) as u32 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_split_from_the_right() {
+        assert_eq!(parse_position("src/a.cs:12:5").unwrap(), ("src/a.cs".to_string(), 12, Some(5)));
+        assert_eq!(parse_position("src/a.cs:12").unwrap(), ("src/a.cs".to_string(), 12, None));
+        assert_eq!(parse_position("C:\\src\\a.cs:3:1").unwrap(), ("C:\\src\\a.cs".to_string(), 3, Some(1)));
+        assert!(parse_position("src/a.cs").is_err());
+        assert!(parse_position("src/a.cs:0").is_err());
+    }
+}
@@ -7,13 +7,16 @@
 //!   `Check` operations, run executes everything).
 //! - **Defaults**: view fields, grouping, format — tuned per command.
 
+use std::sync::Arc;
+
 use tractor::report::{ReportMatch, Severity};
 
 use crate::cli::SharedArgs;
 use crate::executor;
 use crate::cli::context::RunContext;
 use crate::format::{ViewField, GroupDimension, render_report};
-use crate::input::{plan_multi, resolve_input, InputMode, MultiOpRequest};
+use crate::input::encoding_map::EncodingMap;
+use crate::input::{plan_multi, resolve_input, InputMode, LanguageMap, MultiOpRequest};
 use crate::matcher::prepare_report_for_output;
 use crate::tractor_config::{ConfigOperation, ConfigOperationKind};
 
@@ -74,15 +77,33 @@ pub fn run_from_config(params: ConfigRunParams) -> Result<(), Box<dyn std::error
 
     let loaded = crate::tractor_config::load_tractor_config(config_path)?;
 
+    // The config-run `base_dir` is the directory of the config file,
+    // absolutized. Planted on `RunContext` so the executor and resolver
+    // both observe the same value via `ctx.exec_ctx()` — single source of
+    // truth for environmental state.
+    let base_dir = config_path.parent()
+        .map(|p| if p.as_os_str().is_empty() { std::path::Path::new(".") } else { p })
+        .map(|p| {
+            // Absolutize without following symlinks — matches the glob
+            // walker and CLI path resolution, so `base_dir`-derived paths
+            // intersect by set equality with those pipelines.
+            let normalized = tractor::NormalizedPath::absolute(&p.to_string_lossy());
+            std::path::PathBuf::from(normalized.as_str())
+        });
+
     // Resolve CLI input once. Inline mode consumes the positional `files`
     // arg as the Source's virtual path, so it must NOT leak into
     // `ResolverOptions.cli_files` (which would ask FileResolver to
     // intersect the operation against a file the user never wanted to
-    // read from disk). Disk mode keeps the cli_files flowing as today.
+    // read from disk). Disk mode keeps the cli_files flowing as today,
+    // placed by the config's `languages:` mappings. An invalid mapping is
+    // reported by the resolver below.
+    let languages = LanguageMap::new(&loaded.languages, &base_dir).unwrap_or_default();
     let (cli_inline_source, cli_files_for_resolver) = match resolve_input(
         params.shared,
         params.cli_files.clone(),
         params.cli_content,
+        &languages,
     )? {
         InputMode::Inline(source) => (Some(source), Vec::new()),
         InputMode::Files(files) => (None, files),
@@ -112,19 +133,14 @@ pub fn run_from_config(params: ConfigRunParams) -> Result<(), Box<dyn std::error
         params.view_override, params.message, None, false, params.default_group,
     )?;
 
-    // The config-run `base_dir` is the directory of the config file,
-    // absolutized. Planted on `RunContext` so the executor and resolver
-    // both observe the same value via `ctx.exec_ctx()` — single source of
-    // truth for environmental state.
-    ctx.base_dir = config_path.parent()
-        .map(|p| if p.as_os_str().is_empty() { std::path::Path::new(".") } else { p })
-        .map(|p| {
-            // Absolutize without following symlinks — matches the glob
-            // walker and CLI path resolution, so `base_dir`-derived paths
-            // intersect by set equality with those pipelines.
-            let normalized = tractor::NormalizedPath::absolute(&p.to_string_lossy());
-            std::path::PathBuf::from(normalized.as_str())
-        });
+    ctx.base_dir = base_dir;
+
+    ctx.transforms = loaded.transforms;
+    ctx.add_config_variables(loaded.variables);
+    ctx.library = Arc::new(loaded.library);
+    // An invalid mapping is reported by the resolver below; rendering then
+    // falls back to detection alone.
+    ctx.encodings = EncodingMap::new(&loaded.encodings, &ctx.base_dir).unwrap_or_default();
 
     let mut builder = tractor::ReportBuilder::new();
 
@@ -143,12 +159,17 @@ pub fn run_from_config(params: ConfigRunParams) -> Result<(), Box<dyn std::error
         // one `FileResolver` for the whole config run and resolves each op
         // through it. Operations that hit a fatal diagnostic are dropped
         // from the plan.
+        if (params.op_filter)(ConfigOperationKind::Check) {
+            builder.add_all(loaded.warnings);
+        }
         let env = ctx.exec_ctx();
         let plan = plan_multi(
             MultiOpRequest {
                 operations: config_ops,
                 cli_files: cli_files_for_resolver,
                 config_root_files: loaded.root_files,
+                languages: loaded.languages,
+                encodings: loaded.encodings,
                 shared_diff_files: params.shared.diff_files.clone(),
                 shared_diff_lines: params.shared.diff_lines.clone(),
                 max_files: params.shared.max_files,
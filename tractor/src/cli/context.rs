@@ -1,4 +1,6 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::cli::SharedArgs;
 use crate::format::options::HookType;
@@ -6,8 +8,9 @@ use crate::format::{
     normalize_output_plan, parse_group_by, parse_view_selection, GroupDimension, OutputFormat,
     Projection, ViewField, ViewSet,
 };
-use crate::input::{resolve_input, InputMode};
-use tractor::{output::should_use_color, output::RenderOptions, NormalizedXpath, TreeMode};
+use crate::input::encoding_map::EncodingMap;
+use crate::input::{resolve_input, InputMode, LanguageMap};
+use tractor::{output::should_use_color, output::RenderOptions, CompiledQueries, LanguageTransforms, NormalizedXpath, TreeMode, XPathLibrary, XPathVariables};
 
 pub struct RunContext {
     pub xpath: Option<NormalizedXpath>,
@@ -32,6 +35,10 @@ pub struct RunContext {
     pub no_pretty: bool,
     pub ignore_whitespace: bool,
     pub verbose: bool,
+    /// Explain queries that match nothing (`--explain`).
+    pub explain: bool,
+    /// Stop evaluating a file's queries after this long (`--timeout-per-file`).
+    pub timeout_per_file: Option<Duration>,
     /// Base directory for resolving relative paths (config root). Set once
     /// per invocation — None for single-op CLI runs, Some for `run --config`.
     pub base_dir: Option<PathBuf>,
@@ -40,6 +47,31 @@ pub struct RunContext {
     pub group_by: Vec<GroupDimension>,
     /// Claude Code hook type (--hook), used with `-f claude-code`.
     pub hook_type: Option<HookType>,
+    /// What to do with syntax errors in parsed sources.
+    pub parse_errors: ParseErrorPolicy,
+    /// Language transforms from the config's `transforms:` section.
+    pub transforms: LanguageTransforms,
+    /// Variables from `--var` and the config's `variables:`, bound in every
+    /// query.
+    pub variables: Arc<XPathVariables>,
+    /// The config's `functions:` and `let:`, expanded in every query.
+    pub library: Arc<XPathLibrary>,
+    /// Default encodings from the config's `encodings:` section, used when
+    /// rendering reads a source back from disk.
+    pub encodings: EncodingMap,
+}
+
+/// What to do with syntax errors (TreeSitter ERROR/MISSING nodes) found
+/// while parsing sources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseErrorPolicy {
+    /// Don't report them.
+    #[default]
+    Ignore,
+    /// Report them as input diagnostics (`-v parse-errors`).
+    Report,
+    /// Report them as errors and fail the run (`--fail-on-parse-error`).
+    Fail,
 }
 
 /// Lightweight borrowed view of environmental state that the executor and
@@ -52,6 +84,52 @@ pub struct RunContext {
 pub struct ExecCtx<'a> {
     pub verbose: bool,
     pub base_dir: Option<&'a Path>,
+    pub parse_errors: ParseErrorPolicy,
+    /// Configured language transforms; `None` when there are none.
+    pub transforms: Option<&'a LanguageTransforms>,
+    /// Add an info diagnostic for each query that matches nothing.
+    pub explain: bool,
+    /// Stop evaluating a file's queries after this long.
+    pub timeout_per_file: Option<Duration>,
+    /// User variables bound in every query; `None` when there are none.
+    pub variables: Option<&'a Arc<XPathVariables>>,
+    /// User functions and fragments; `None` when there are none.
+    pub library: Option<&'a Arc<XPathLibrary>>,
+    /// The run's compiled queries, once `execute` has compiled them.
+    pub queries: Option<&'a Arc<CompiledQueries>>,
+}
+
+/// Parse `-t`; `None` auto-detects at parse time.
+pub fn parse_tree_arg(tree: Option<&str>) -> Result<Option<TreeMode>, String> {
+    match tree {
+        Some("raw") => Ok(Some(TreeMode::Raw)),
+        Some("structure") => Ok(Some(TreeMode::Structure)),
+        Some("common") => Ok(Some(TreeMode::Common)),
+        Some("data") => Ok(Some(TreeMode::Data)),
+        Some(other) => Err(format!(
+            "invalid --tree value '{}': use 'raw', 'structure', 'common', or 'data'",
+            other
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Parse a time limit: `500ms`, `30s`, `2m`, or a plain number of seconds.
+pub fn parse_timeout(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid timeout '{}': use e.g. '500ms', '30s' or '2m'", text);
+    let text = text.trim();
+    let (number, unit) = match text.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => text.split_at(i),
+        None => (text, "s"),
+    };
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(seconds).ok().filter(|d| !d.is_zero()).ok_or_else(invalid)
 }
 
 impl RunContext {
@@ -104,26 +182,26 @@ impl RunContext {
         for warning in &plan.warnings {
             eprintln!("{warning}");
         }
+        let parse_errors = if shared.fail_on_parse_error {
+            ParseErrorPolicy::Fail
+        } else if plan.view.has(ViewField::ParseErrors) {
+            ParseErrorPolicy::Report
+        } else {
+            ParseErrorPolicy::Ignore
+        };
         let use_color = if shared.no_color {
             false
         } else {
             should_use_color(&shared.color)
         };
-        let input = resolve_input(shared, files, content)?;
-
-        let tree_mode = match shared.tree.as_deref() {
-            Some("raw") => Some(TreeMode::Raw),
-            Some("structure") => Some(TreeMode::Structure),
-            Some("data") => Some(TreeMode::Data),
-            Some(other) => {
-                return Err(format!(
-                    "invalid --tree value '{}': use 'raw', 'structure', or 'data'",
-                    other
-                )
-                .into())
-            }
-            None => None, // auto-detect at parse time
-        };
+        let input = resolve_input(shared, files, content, &LanguageMap::default())?;
+
+        let tree_mode = parse_tree_arg(shared.tree.as_deref())?;
+
+        let mut variables = XPathVariables::new();
+        for assignment in &shared.vars {
+            variables.assign(assignment).map_err(|e| format!("--var: {}", e))?;
+        }
 
         let concurrency = shared.concurrency.unwrap_or_else(|| num_cpus::get());
         rayon::ThreadPoolBuilder::new()
@@ -148,19 +226,40 @@ impl RunContext {
             no_pretty: shared.no_pretty,
             ignore_whitespace: shared.ignore_whitespace,
             verbose: shared.verbose,
+            explain: shared.explain,
+            timeout_per_file: shared.timeout_per_file,
             base_dir: None,
             lang: shared.lang.clone(),
             debug,
             group_by,
             hook_type,
+            parse_errors,
+            transforms: LanguageTransforms::new(),
+            variables: Arc::new(variables),
+            library: Arc::new(XPathLibrary::new()),
+            encodings: EncodingMap::default(),
         })
     }
 
+    /// Add a config's `variables:`. `--var` values win over config values
+    /// of the same name.
+    pub fn add_config_variables(&mut self, mut config: XPathVariables) {
+        config.extend((*self.variables).clone());
+        self.variables = Arc::new(config);
+    }
+
     /// Borrow the environmental state the executor / resolver consume.
     pub fn exec_ctx(&self) -> ExecCtx<'_> {
         ExecCtx {
             verbose: self.verbose,
             base_dir: self.base_dir.as_deref(),
+            parse_errors: self.parse_errors,
+            transforms: Some(&self.transforms).filter(|t| !t.is_empty()),
+            explain: self.explain,
+            timeout_per_file: self.timeout_per_file,
+            variables: Some(&self.variables).filter(|v| !v.is_empty()),
+            library: Some(&self.library).filter(|l| !l.is_empty()),
+            queries: None,
         }
     }
 
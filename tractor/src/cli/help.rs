@@ -22,6 +22,7 @@ impl CommandExt for clap::Command {
             .after_long_help(AFTER_HELP);
         let cmd = augment_arg_help(cmd, &[File, Line, Tree], "text"); // root = query defaults
         cmd.mut_subcommand("query", |c| augment_arg_help(c, &[File, Line, Tree], "text"))
+           .mut_subcommand("pattern", |c| augment_arg_help(c, &[File, Line, Tree], "text"))
            .mut_subcommand("check", |c| augment_arg_help(c, &[Reason, Severity, Lines], "gcc"))
            .mut_subcommand("test",  |c| augment_arg_help(c, &[Totals], "text"))
            .mut_subcommand("set",   |c| augment_arg_help(c, &[File, Line, Status, Reason], "text"))
@@ -85,6 +86,13 @@ EXAMPLES:
     # JSON report output
     tractor check "src/**/*.cs" -x "//comment[contains(.,'TODO')]" --reason "TODO" -f json
 
+    # Query by example: print the XPath for a snippet, or run it on files
+    tractor pattern -l csharp 'Assert.AreEqual($a, $b)'
+    tractor pattern 'Assert.AreEqual($a, $b)' "tests/**/*.cs"
+
+    # From a position in an editor to XPaths for everything around it
+    tractor at src/Foo.cs:42:17
+
     # Whitespace-insensitive matching
     tractor file.cs -x "//type[.='Dictionary<string,int>']" -W
 
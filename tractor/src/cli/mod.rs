@@ -3,6 +3,8 @@
 pub mod help;
 pub mod context;
 pub mod query;
+pub mod pattern;
+pub mod at;
 pub mod check;
 pub mod test;
 pub mod set;
@@ -17,6 +19,8 @@ use clap::{Parser, Subcommand, Args};
 use tractor::NormalizedXpath;
 
 pub use query::QueryArgs;
+pub use pattern::PatternArgs;
+pub use at::AtArgs;
 pub use check::CheckArgs;
 pub use test::TestArgs;
 pub use set::SetArgs;
@@ -44,6 +48,10 @@ pub struct Cli {
 pub enum Command {
     /// Query source code ASTs with XPath (default when no subcommand given)
     Query(QueryArgs),
+    /// Query by example: match code shaped like a snippet ($name matches anything)
+    Pattern(PatternArgs),
+    /// Show the elements at FILE:LINE:COL with XPaths that select them
+    At(AtArgs),
     /// Run checks and report violations (lint mode)
     Check(CheckArgs),
     /// Test expectations against match counts
@@ -83,12 +91,14 @@ pub struct SharedArgs {
     #[arg(short = 'x', long = "extract", value_name = "QUERY", help_heading = "Extract")]
     pub xpath: Option<NormalizedXpath>,
 
-    /// Tree mode: raw, structure, data [default: auto]
+    /// Tree mode: raw, structure, common, data [default: auto]
     #[arg(short = 't', long = "tree", help_heading = "Extract", allow_hyphen_values = true,
         long_help = "\
 Tree mode [default: auto]
   raw        Raw tree-sitter AST (no semantic transforms)
   structure  Semantic syntax tree (default for code languages)
+  common     Structure tree with one vocabulary for all languages
+             (function, class, call, import, parameter, string, comment)
   data       Data projection (default for JSON/YAML)
 
 When omitted, auto-selects: data for JSON/YAML, structure for everything else.")]
@@ -98,6 +108,10 @@ When omitted, auto-selects: data for JSON/YAML, structure for everything else.")
     #[arg(short = 'W', long = "ignore-whitespace", help_heading = "Extract")]
     pub ignore_whitespace: bool,
 
+    /// Bind $NAME in every query (repeatable; repeating a name makes a sequence)
+    #[arg(long = "var", value_name = "NAME=VALUE", help_heading = "Extract")]
+    pub vars: Vec<String>,
+
     // -- View --
     /// Limit output to first N matches
     #[arg(short = 'n', long = "limit", help_heading = "View")]
@@ -172,4 +186,16 @@ When omitted, auto-selects: data for JSON/YAML, structure for everything else.")
     /// Show verbose output
     #[arg(long = "verbose", help_heading = "Advanced")]
     pub verbose: bool,
+
+    /// Report syntax errors in parsed files as errors and fail
+    #[arg(long = "fail-on-parse-error", help_heading = "Advanced")]
+    pub fail_on_parse_error: bool,
+
+    /// When a query matches nothing, show the step where matches drop to zero
+    #[arg(long = "explain", help_heading = "Advanced")]
+    pub explain: bool,
+
+    /// Stop evaluating a file's queries after this long (e.g. 500ms, 30s) and warn
+    #[arg(long = "timeout-per-file", value_name = "DURATION", value_parser = context::parse_timeout, help_heading = "Advanced")]
+    pub timeout_per_file: Option<std::time::Duration>,
 }
@@ -0,0 +1,76 @@
+use clap::Args;
+use tractor::NormalizedXpath;
+use crate::cli::SharedArgs;
+
+/// Pattern mode: query by example
+#[derive(Args, Debug)]
+pub struct PatternArgs {
+    /// Code snippet to match; `$name` identifiers match any code (e.g. 'Assert.AreEqual($a, $b)')
+    #[arg()]
+    pub pattern: String,
+
+    /// Files to search (supports glob patterns). Without files or -s, prints the generated XPath
+    #[arg()]
+    pub files: Vec<String>,
+
+    #[command(flatten)]
+    pub shared: SharedArgs,
+
+    /// Source code string to search (alternative to files, requires --lang)
+    #[arg(short = 's', long = "string", help_heading = None)]
+    pub content: Option<String>,
+
+    /// Report fields to include (e.g. tree, value, source) [default: file,line,tree]
+    #[arg(short = 'v', long = "view", help_heading = "View", allow_hyphen_values = true)]
+    pub view: Option<String>,
+
+    /// Custom message template (supports {value}, {line}, {col}, {file})
+    #[arg(short = 'm', long = "message", help_heading = "View")]
+    pub message: Option<String>,
+
+    /// Output format [default: text]
+    #[arg(short = 'f', long = "format", default_value = "text", help_heading = "Format")]
+    pub format: String,
+}
+use super::context::parse_tree_arg;
+use super::query::{run_query, QueryArgs};
+
+pub fn run_pattern(args: PatternArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.shared.xpath.is_some() {
+        return Err("pattern generates its own query; drop -x".into());
+    }
+    let lang = resolve_language(&args)?;
+    let tree_mode = parse_tree_arg(args.shared.tree.as_deref())?;
+    let xpath = tractor::pattern_to_xpath(&args.pattern, &lang, tree_mode)?;
+
+    if args.files.is_empty() && args.content.is_none() {
+        println!("{}", xpath);
+        return Ok(());
+    }
+
+    let mut shared = args.shared;
+    shared.xpath = Some(NormalizedXpath::new(&xpath));
+    run_query(QueryArgs {
+        files: args.files,
+        config: None,
+        shared,
+        content: args.content,
+        view: args.view,
+        message: args.message,
+        format: args.format,
+        debug: false,
+        version: false,
+    })
+}
+
+/// The pattern's language: `--lang`, or the first file's.
+fn resolve_language(args: &PatternArgs) -> Result<String, Box<dyn std::error::Error>> {
+    let lang = match (&args.shared.lang, args.files.first()) {
+        (Some(lang), _) => lang.as_str(),
+        (None, Some(file)) => tractor::detect_language(file),
+        (None, None) => return Err("pattern requires --lang or files to search".into()),
+    };
+    let info = tractor::language_info::get_language_info(lang)
+        .ok_or_else(|| format!("unknown language for pattern: {} (use --lang)", lang))?;
+    Ok(info.name.to_string())
+}
@@ -0,0 +1,245 @@
+//! Source file encodings.
+//!
+//! Parsing works on UTF-8 text, but source files are not always UTF-8:
+//! legacy C# and Java projects often hold Windows-1252 files, and Visual
+//! Studio writes UTF-16 with a byte order mark. A file's encoding is
+//! detected when it is read, in this order:
+//!
+//! 1. a byte order mark (UTF-8, UTF-16LE, UTF-16BE),
+//! 2. a configured UTF-16 default (`encodings:` in `tractor.yml`), which
+//!    applies to every BOM-less file it covers,
+//! 3. UTF-16 text without a BOM, recognized by its zero high bytes,
+//! 4. valid UTF-8,
+//! 5. the configured default,
+//! 6. the legacy encoding the text most likely is, guessed from its bytes
+//!    (Windows-1252 for Western European text, Shift_JIS, Windows-1251, ...).
+//!
+//! A file with control bytes no text file holds, such as a binary file, is
+//! not text: reading it fails, so it is skipped like any unreadable file.
+//! Configure the encoding of files whose guess comes out wrong.
+//!
+//! Lines and columns in matches count the decoded text, not the file's
+//! bytes. Writing a modified file re-encodes it with the detected encoding,
+//! BOM included, so an edit never changes how the file is stored.
+
+use std::io;
+use std::path::Path;
+
+pub use encoding_rs::Encoding;
+use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8};
+
+/// How a source file is stored on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceEncoding {
+    pub encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark.
+    pub bom: bool,
+}
+
+impl SourceEncoding {
+    /// Plain UTF-8 without a BOM.
+    pub const UTF8: SourceEncoding = SourceEncoding { encoding: UTF_8, bom: false };
+
+    /// The encoding's canonical name (`UTF-8`, `windows-1252`, `UTF-16LE`).
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+}
+
+impl Default for SourceEncoding {
+    fn default() -> Self {
+        SourceEncoding::UTF8
+    }
+}
+
+/// Look up an encoding by label (`utf-8`, `windows-1252`, `latin1`,
+/// `utf-16le`, `shift_jis`, ...). Labels follow the WHATWG Encoding Standard.
+pub fn lookup(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// Decode file bytes, detecting the encoding. `default` applies to files
+/// with no BOM that are not valid UTF-8; without one, the encoding of such a
+/// file is guessed. Bytes that are not text are an `InvalidData` error.
+pub fn decode(bytes: &[u8], default: Option<&'static Encoding>) -> io::Result<(String, SourceEncoding)> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let text = encoding.decode_without_bom_handling(&bytes[bom_len..]).0;
+        return Ok((text.into_owned(), SourceEncoding { encoding, bom: true }));
+    }
+    let encoding = match default.filter(|&e| is_utf16(e)).or_else(|| utf16_without_bom(bytes)) {
+        Some(utf16) => utf16,
+        None => match std::str::from_utf8(bytes) {
+            Ok(text) => return Ok((text.to_string(), SourceEncoding::UTF8)),
+            Err(_) => match default {
+                Some(default) => default,
+                None => return guess_legacy(bytes),
+            },
+        },
+    };
+    let text = encoding.decode_without_bom_handling(bytes).0;
+    Ok((text.into_owned(), SourceEncoding { encoding, bom: false }))
+}
+
+/// Decode text in a legacy single- or multi-byte encoding, guessing which
+/// from its bytes.
+fn guess_legacy(bytes: &[u8]) -> io::Result<(String, SourceEncoding)> {
+    let not_text = || io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not text in a known encoding (set its encoding under `encodings:` in tractor.yml)",
+    );
+    // Text files hold no control bytes besides whitespace, form feeds and
+    // escapes; binary files are full of them
+    if bytes.iter().any(|&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) {
+        return Err(not_text());
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, false);
+    let text = encoding.decode_without_bom_handling_and_without_replacement(bytes).ok_or_else(not_text)?;
+    Ok((text.into_owned(), SourceEncoding { encoding, bom: false }))
+}
+
+fn is_utf16(encoding: &'static Encoding) -> bool {
+    encoding == UTF_16LE || encoding == UTF_16BE
+}
+
+/// UTF-16 without a BOM: text whose code units mostly have a zero high byte
+/// (as ASCII text in UTF-16 does) and that decodes without errors.
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let sample = &bytes[..bytes.len().min(4096)];
+    let units = sample.len() / 2;
+    let zero_at = |i: usize| sample.chunks_exact(2).filter(|unit| unit[i] == 0 && unit[1 - i] != 0).count();
+    let (low_first, high_first) = (zero_at(1), zero_at(0));
+    let encoding = if low_first * 2 > units && high_first == 0 {
+        UTF_16LE
+    } else if high_first * 2 > units && low_first == 0 {
+        UTF_16BE
+    } else {
+        return None;
+    };
+    encoding.decode_without_bom_handling_and_without_replacement(bytes).map(|_| encoding)
+}
+
+/// Encode text for writing back in `encoding`. Fails when the text holds
+/// characters the encoding cannot represent, rather than writing them as
+/// HTML character references like `encoding_rs` would.
+pub fn encode(text: &str, encoding: SourceEncoding) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    let enc = encoding.encoding;
+    if enc == UTF_16LE || enc == UTF_16BE {
+        if encoding.bom {
+            bytes.extend_from_slice(if enc == UTF_16LE { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&if enc == UTF_16LE { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+        return Ok(bytes);
+    }
+    if encoding.bom && enc == UTF_8 {
+        bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    let (encoded, _, unmappable) = enc.encode(text);
+    if unmappable {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("text contains characters that cannot be written as {}", enc.name()),
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+/// Read and decode a source file. See [`decode`].
+pub fn read_file(path: &Path, default: Option<&'static Encoding>) -> io::Result<(String, SourceEncoding)> {
+    let bytes = std::fs::read(path)?;
+    decode(&bytes, default)
+}
+
+/// Encode `text` and write it to `path`. See [`encode`].
+pub fn write_file(path: &Path, text: &str, encoding: SourceEncoding) -> io::Result<()> {
+    std::fs::write(path, encode(text, encoding)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_is_detected_without_a_default() {
+        let (text, encoding) = decode("naïve".as_bytes(), None).unwrap();
+        assert_eq!(text, "naïve");
+        assert_eq!(encoding, SourceEncoding::UTF8);
+    }
+
+    #[test]
+    fn bom_wins_over_default() {
+        let bytes = [0xFF, 0xFE, b'h', 0, b'i', 0];
+        let (text, encoding) = decode(&bytes, lookup("shift_jis")).unwrap();
+        assert_eq!(text, "hi");
+        assert_eq!(encoding, SourceEncoding { encoding: UTF_16LE, bom: true });
+        assert_eq!(encode(&text, encoding).unwrap(), bytes);
+
+        let bytes = [0xEF, 0xBB, 0xBF, b'x'];
+        let (text, encoding) = decode(&bytes, None).unwrap();
+        assert_eq!(text, "x");
+        assert_eq!(encode(&text, encoding).unwrap(), bytes);
+    }
+
+    #[test]
+    fn legacy_encodings_are_guessed() {
+        // "café" in Windows-1252
+        let bytes = [b'c', b'a', b'f', 0xE9];
+        let (text, encoding) = decode(&bytes, None).unwrap();
+        assert_eq!(text, "café");
+        assert_eq!(encoding.name(), "windows-1252");
+        assert_eq!(encode("cafés", encoding).unwrap(), b"caf\xE9s");
+
+        let comment = "// 日本語のコメントです。これはテストです";
+        let bytes = encoding_rs::SHIFT_JIS.encode(comment).0;
+        let (text, encoding) = decode(&bytes, None).unwrap();
+        assert_eq!(text, comment);
+        assert_eq!(encoding.name(), "Shift_JIS");
+    }
+
+    #[test]
+    fn configured_encoding_wins_over_the_guess() {
+        let bytes = [b'c', b'a', b'f', 0xE9];
+        let (text, encoding) = decode(&bytes, lookup("iso-8859-15")).unwrap();
+        assert_eq!(text, "café");
+        assert_eq!(encoding.name(), "ISO-8859-15");
+    }
+
+    #[test]
+    fn binary_files_are_not_text() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D, b'I', b'H', b'D', b'R'];
+        assert!(decode(&png, None).is_err());
+    }
+
+    #[test]
+    fn configured_utf16_applies_without_bom() {
+        let (text, encoding) = decode(&[b'o', 0, b'k', 0], lookup("utf-16le")).unwrap();
+        assert_eq!(text, "ok");
+        assert!(!encoding.bom);
+    }
+
+    #[test]
+    fn utf16_is_detected_without_bom() {
+        let bytes: Vec<u8> = "class Café {}".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        let (text, encoding) = decode(&bytes, None).unwrap();
+        assert_eq!(text, "class Café {}");
+        assert_eq!(encoding, SourceEncoding { encoding: UTF_16LE, bom: false });
+        assert_eq!(encode(&text, encoding).unwrap(), bytes);
+
+        let bytes: Vec<u8> = "ok".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        assert_eq!(decode(&bytes, None).unwrap().1.encoding, UTF_16BE);
+    }
+
+    #[test]
+    fn unmappable_characters_are_an_error() {
+        let encoding = SourceEncoding { encoding: encoding_rs::WINDOWS_1252, bom: false };
+        assert!(encode("→", encoding).is_err());
+    }
+}
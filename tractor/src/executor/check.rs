@@ -1,18 +1,21 @@
 //! Check operation: run XPath rules against sources, report violations.
 
+use std::borrow::Cow;
+use std::sync::Arc;
+
 use tractor::report::{ReportBuilder, ReportMatch, Severity};
 use tractor::tree_mode::TreeMode;
 use tractor::rule::CompiledRule;
-use tractor::{parse, ParseInput, ParseOptions};
+use tractor::{parse, wants_doc_comments, wants_injections, CompiledQueries, LanguageTransforms, ParseInput, ParseOptions};
 
-use crate::matcher::validate_xpath_diagnostic;
-use crate::matcher::run_rules;
+use crate::matcher::{rule_language_matches_source, run_rules, RuleMatch};
 use crate::input::filter::Filters;
 use crate::input::Source;
 
 use crate::cli::context::ExecCtx;
 
-use super::match_to_report_match;
+use super::explain::explain_no_matches;
+use super::{add_parse_errors, match_to_report_match};
 
 // ---------------------------------------------------------------------------
 // Operation type
@@ -61,23 +64,16 @@ pub(crate) fn execute_check(
         return Ok(());
     }
 
-    // --- Phase 0: Validate XPath expressions upfront ---
-    let diagnostics: Vec<_> = op.compiled_rules.iter()
-        .filter_map(|rule| validate_xpath_diagnostic(&rule.xpath, "check"))
-        .collect();
-    if !diagnostics.is_empty() {
-        report.add_all(diagnostics);
-        return Ok(());
-    }
+    // XPath expressions were validated up front by `execute`
 
     // --- Phase 1: Validate rule examples inline ---
-    validate_rule_examples(&op.compiled_rules, op.tree_mode, report)?;
+    validate_rule_examples(&op.compiled_rules, op.tree_mode, ctx.transforms, ctx.queries, report)?;
 
     if op.sources.is_empty() {
         return Ok(());
     }
 
-    let rule_matches = run_rules(
+    let (rule_matches, parse_errors) = run_rules(
         &op.compiled_rules,
         &op.sources,
         op.tree_mode,
@@ -85,7 +81,16 @@ pub(crate) fn execute_check(
         op.parse_depth,
         ctx.verbose,
         &op.filters,
+        ctx.parse_errors,
+        ctx.transforms,
+        ctx.queries,
+        ctx.timeout_per_file,
     )?;
+    add_parse_errors(report, parse_errors, ctx.parse_errors);
+
+    if ctx.explain {
+        explain_unmatched_rules(op, &rule_matches, ctx, report);
+    }
 
     for rm in rule_matches {
         let rule = &op.compiled_rules[rm.rule_index];
@@ -112,6 +117,38 @@ pub(crate) fn execute_check(
     Ok(())
 }
 
+/// Explain each rule that matched nothing in the files it applies to (by
+/// its globs and language), parsing them as the rule does.
+fn explain_unmatched_rules(
+    op: &CheckOperationPlan,
+    rule_matches: &[RuleMatch],
+    ctx: &ExecCtx<'_>,
+    report: &mut ReportBuilder,
+) {
+    for (i, rule) in op.compiled_rules.iter().enumerate() {
+        if rule_matches.iter().any(|rm| rm.rule_index == i) {
+            continue;
+        }
+        let sources: Vec<Source> = op.sources.iter()
+            .filter(|source| rule.glob.matches(&source.path) && rule_language_matches_source(rule, &source.language))
+            .cloned()
+            .collect();
+        let options = ParseOptions {
+            language: rule.language.as_deref(),
+            tree_mode: rule.tree_mode.or(op.tree_mode),
+            ignore_whitespace: op.ignore_whitespace,
+            parse_depth: op.parse_depth,
+            transforms: ctx.transforms,
+            queries: ctx.queries,
+            ..ParseOptions::default()
+        };
+        if let Some(mut diagnostic) = explain_no_matches(&sources, &rule.xpath, options, "check") {
+            diagnostic.rule_id = Some(rule.id.clone());
+            report.add(diagnostic);
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Example validation
 // ---------------------------------------------------------------------------
@@ -123,6 +160,8 @@ pub(crate) fn execute_check(
 fn validate_rule_examples(
     rules: &[CompiledRule],
     default_tree_mode: Option<TreeMode>,
+    transforms: Option<&LanguageTransforms>,
+    queries: Option<&Arc<CompiledQueries>>,
     report: &mut ReportBuilder,
 ) -> Result<(), Box<dyn std::error::Error>> {
     for rule in rules {
@@ -137,6 +176,7 @@ fn validate_rule_examples(
             ))?;
 
         let tree_mode = rule.tree_mode.or(default_tree_mode);
+        let xpath = queries.map_or(Cow::Borrowed(rule.xpath.as_str()), |queries| queries.expand(rule.xpath.as_str()));
 
         // Validate valid examples: expect "none" (query should NOT match valid code)
         for (i, example) in rule.valid_examples.iter().enumerate() {
@@ -150,6 +190,12 @@ fn validate_rule_examples(
                     tree_mode,
                     ignore_whitespace: false,
                     parse_depth: None,
+                    injections: wants_injections(&xpath),
+                    doc_comments: wants_doc_comments(&xpath),
+                    embedded_language: None,
+                    transforms,
+                    encoding: None,
+                    queries,
                 },
             )?;
             let matches = result.query(rule.xpath.as_str())?;
@@ -176,6 +222,12 @@ fn validate_rule_examples(
                     tree_mode,
                     ignore_whitespace: false,
                     parse_depth: None,
+                    injections: wants_injections(&xpath),
+                    doc_comments: wants_doc_comments(&xpath),
+                    embedded_language: None,
+                    transforms,
+                    encoding: None,
+                    queries,
                 },
             )?;
             let matches = result.query(rule.xpath.as_str())?;
@@ -243,7 +295,7 @@ mod tests {
             None,
         )];
         let mut builder = ReportBuilder::new();
-        validate_rule_examples(&rules, None, &mut builder).unwrap();
+        validate_rule_examples(&rules, None, None, None, &mut builder).unwrap();
         let report = builder.build();
         assert!(report.all_matches().is_empty(), "expected no failures: {:?}", report.all_matches());
     }
@@ -257,7 +309,7 @@ mod tests {
             None,
         )];
         let mut builder = ReportBuilder::new();
-        validate_rule_examples(&rules, None, &mut builder).unwrap();
+        validate_rule_examples(&rules, None, None, None, &mut builder).unwrap();
         let report = builder.build();
         let matches = report.all_matches();
         assert_eq!(matches.len(), 1);
@@ -273,7 +325,7 @@ mod tests {
             None,
         )];
         let mut builder = ReportBuilder::new();
-        validate_rule_examples(&rules, None, &mut builder).unwrap();
+        validate_rule_examples(&rules, None, None, None, &mut builder).unwrap();
         let report = builder.build();
         let matches = report.all_matches();
         assert_eq!(matches.len(), 1);
@@ -288,7 +340,7 @@ mod tests {
             Some("rust"),
         )];
         let mut builder = ReportBuilder::new();
-        validate_rule_examples(&rules, None, &mut builder).unwrap();
+        validate_rule_examples(&rules, None, None, None, &mut builder).unwrap();
         let report = builder.build();
         assert!(report.all_matches().is_empty());
     }
@@ -301,7 +353,7 @@ mod tests {
             None,
         )];
         let mut builder = ReportBuilder::new();
-        let err = validate_rule_examples(&rules, None, &mut builder).unwrap_err();
+        let err = validate_rule_examples(&rules, None, None, None, &mut builder).unwrap_err();
         assert!(err.to_string().contains("no language specified"));
     }
 
@@ -309,8 +361,37 @@ mod tests {
     fn test_validate_examples_no_examples_is_noop() {
         let rules = vec![compile(Rule::new("simple", "//function"), None)];
         let mut builder = ReportBuilder::new();
-        validate_rule_examples(&rules, None, &mut builder).unwrap();
+        validate_rule_examples(&rules, None, None, None, &mut builder).unwrap();
         let report = builder.build();
         assert!(report.all_matches().is_empty());
     }
+
+    #[test]
+    fn explain_only_looks_at_the_files_a_rule_applies_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources: Vec<Source> = [("lib.rs", "fn main() {}\n", "rust"), ("app.py", "def main(): pass\n", "python")]
+            .iter()
+            .map(|(name, content, language)| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, content).unwrap();
+                Source::disk(tractor::NormalizedPath::absolute(path.to_str().unwrap()), *language)
+            })
+            .collect();
+        let op = CheckOperationPlan {
+            sources,
+            filters: Filters::default(),
+            compiled_rules: vec![compile(Rule::new("other", "//function[name = 'other']").with_language("rust"), None)],
+            tree_mode: None,
+            ignore_whitespace: false,
+            parse_depth: None,
+        };
+        let ctx = ExecCtx { explain: true, ..ExecCtx::default() };
+        let mut builder = ReportBuilder::new();
+        execute_check(&op, &ctx, &mut builder).unwrap();
+        let report = builder.build();
+        let reasons: Vec<_> = report.all_matches().iter().filter_map(|m| m.reason.clone()).collect();
+        // Only the Rust function, not the Python one
+        assert_eq!(reasons, ["'//function' matches 1 node, but none satisfy '[name = 'other']'"]);
+        assert_eq!(report.all_matches()[0].rule_id.as_deref(), Some("other"));
+    }
 }
@@ -0,0 +1,146 @@
+//! `--explain`: say why a query matched nothing.
+//!
+//! The query's location path is evaluated one step at a time against the
+//! sources to find the step where matches drop to zero. When that step
+//! tests for an element name, the names found just before it are collected
+//! with a [`SchemaCollector`] and similar ones are suggested.
+
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+use tractor::normalized_xpath::NormalizedXpath;
+use tractor::report::{DiagnosticOrigin, ReportMatch, Severity};
+use tractor::{ParseOptions, SchemaCollector};
+
+use crate::input::Source;
+
+/// Most matches per file whose trees are collected for suggestions.
+const SCHEMA_MATCHES_PER_FILE: usize = 50;
+
+/// An info diagnostic explaining why `xpath` matches nothing in `sources`,
+/// or `None` when it is not a location path or does match somewhere.
+pub(crate) fn explain_no_matches(
+    sources: &[Source],
+    xpath: &NormalizedXpath,
+    options: ParseOptions<'_>,
+    command: &str,
+) -> Option<ReportMatch> {
+    let prefixes = tractor::path_prefixes(xpath.as_str());
+    if prefixes.is_empty() {
+        return None;
+    }
+    let expanded = options.queries.map_or(Cow::Borrowed(xpath.as_str()), |queries| queries.expand(xpath.as_str()));
+    let options = ParseOptions {
+        injections: tractor::wants_injections(&expanded),
+        doc_comments: tractor::wants_doc_comments(&expanded),
+        ..options
+    };
+
+    // Matches per prefix, with and without the step's predicates
+    let counts = sources
+        .par_iter()
+        .filter_map(|source| {
+            // Containers (notebooks, components) parse their embedded code themselves
+            let language = options.language.filter(|_| tractor::languages::embedded_languages(&source.language).is_empty());
+            let mut result = source.parse(ParseOptions { language, ..options }).ok()?;
+            let mut counts = vec![(0, 0); prefixes.len()];
+            for (i, prefix) in prefixes.iter().enumerate() {
+                let mut count = |xpath: &str| result.query(xpath).map(|m| m.len()).unwrap_or(0);
+                let full = count(&prefix.xpath);
+                let bare = prefix.without_predicates.as_deref().map_or(full, &mut count);
+                counts[i] = (bare, full);
+                if bare == 0 {
+                    break;
+                }
+            }
+            Some(counts)
+        })
+        .reduce_with(|a, b| a.iter().zip(&b).map(|(x, y)| (x.0 + y.0, x.1 + y.1)).collect())?;
+
+    let zero = counts.iter().position(|&(_, full)| full == 0)?;
+    let step = &prefixes[zero];
+    let (bare, _) = counts[zero];
+    let previous = zero.checked_sub(1).map(|i| (&prefixes[i], counts[i].1));
+
+    if bare > 0 {
+        let bare_xpath = step.without_predicates.as_deref().unwrap_or(&step.xpath);
+        let predicates = step.predicate_span.unwrap_or(step.span);
+        let reason = format!(
+            "'{}' matches {}, but none satisfy '{}'",
+            bare_xpath,
+            plural(bare, "node"),
+            &xpath.as_str()[predicates.0..predicates.1],
+        );
+        return Some(diagnostic(xpath, predicates, reason, command));
+    }
+
+    let mut reason = match previous {
+        Some((before, count)) => format!(
+            "'{}' matches {}, but '{}' matches none",
+            before.xpath, plural(count, "node"), step.without_predicates.as_deref().unwrap_or(&step.xpath),
+        ),
+        None => format!("'{}' matches nothing", step.without_predicates.as_deref().unwrap_or(&step.xpath)),
+    };
+    if let Some(ref name) = step.name {
+        let collector = collect_schema(sources, previous.map_or("/*", |(before, _)| before.xpath.as_str()), options);
+        let suggestions = tractor::similar_names(name, collector.element_names());
+        if !suggestions.is_empty() {
+            let quoted: Vec<String> = suggestions.iter().map(|s| format!("'{}'", s)).collect();
+            reason.push_str(&format!("; did you mean {}?", quoted.join(" or ")));
+        }
+    }
+    let name_end = step.predicate_span.map_or(step.span.1, |(start, _)| start);
+    Some(diagnostic(xpath, (step.span.0, name_end), reason, command))
+}
+
+/// The element paths under the nodes `xpath` matches in `sources`.
+fn collect_schema(sources: &[Source], xpath: &str, options: ParseOptions<'_>) -> SchemaCollector {
+    sources
+        .par_iter()
+        .map(|source| {
+            let mut collector = SchemaCollector::new();
+            let matches = source.parse(options).ok()
+                .and_then(|mut result| result.query(xpath).ok())
+                .unwrap_or_default();
+            for node in matches.iter().filter_map(|m| m.xml_node.as_ref()).take(SCHEMA_MATCHES_PER_FILE) {
+                collector.collect_from_xml_node(node);
+            }
+            collector
+        })
+        .reduce(SchemaCollector::new, |mut a, b| {
+            a.merge(b);
+            a
+        })
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// An info diagnostic on the query itself, highlighting `span`.
+fn diagnostic(xpath: &NormalizedXpath, span: (usize, usize), reason: String, command: &str) -> ReportMatch {
+    ReportMatch {
+        file: String::new(),
+        line: 1,
+        column: span.0 as u32 + 1,
+        end_line: 1,
+        end_column: span.1 as u32 + 1,
+        command: command.to_string(),
+        tree: None,
+        value: None,
+        source: Some(xpath.to_string()),
+        lines: Some(vec![xpath.to_string()]),
+        reason: Some(reason),
+        severity: Some(Severity::Info),
+        message: None,
+        origin: Some(DiagnosticOrigin::Xpath),
+        rule_id: None,
+        status: None,
+        output: None,
+    }
+}
+
@@ -19,16 +19,26 @@
 
 mod query;
 mod check;
+mod explain;
 mod test;
 mod set;
+mod timeout;
 mod update;
 
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
 use rayon::prelude::*;
-use tractor::report::{ReportBuilder, ReportMatch};
+use tractor::report::{DiagnosticOrigin, ReportBuilder, ReportMatch};
 use tractor::tree_mode::TreeMode;
-use tractor::Match;
+use tractor::normalized_xpath::NormalizedXpath;
+use tractor::{CompiledQueries, LanguageTransforms, Match, XPathLibrary, XPathVariables};
+
+use crate::matcher::validate_xpath_diagnostic;
 
-use crate::cli::context::ExecCtx;
+use crate::cli::context::{ExecCtx, ParseErrorPolicy};
 use crate::input::filter::Filters;
 use crate::input::Source;
 
@@ -37,6 +47,7 @@ pub use check::CheckOperationPlan;
 pub use test::{TestOperation, TestOperationPlan, TestAssertion};
 pub use set::{SetOperation, SetOperationPlan, SetMapping, SetWriteMode, SetReportMode};
 pub use update::{UpdateOperation, UpdateOperationPlan};
+pub(crate) use timeout::{evaluate_source, SourceParse, TimedQuery};
 
 // ---------------------------------------------------------------------------
 // Operation types (stable API)
@@ -77,6 +88,16 @@ pub fn execute(
     ctx: &ExecCtx<'_>,
     report: &mut ReportBuilder,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    // Compile every query of the run before any file is parsed, so all
+    // invalid queries are reported together and nothing runs half-way
+    let (queries, diagnostics) = compile_queries(operations, ctx.variables, ctx.library);
+    if !diagnostics.is_empty() {
+        report.add_all(diagnostics);
+        return Ok(());
+    }
+    let queries = Arc::new(queries);
+    let ctx = &ExecCtx { queries: Some(&queries), ..*ctx };
+
     for op in operations {
         match op {
             OperationPlan::Query(q) => query::execute_query(q, ctx, report)?,
@@ -90,6 +111,47 @@ pub fn execute(
     Ok(())
 }
 
+/// Compile each distinct query of the run once, with a fatal diagnostic
+/// for every one that does not compile. Check rules keep their id so the
+/// diagnostic names the rule.
+fn compile_queries(
+    operations: &[OperationPlan],
+    variables: Option<&Arc<XPathVariables>>,
+    library: Option<&Arc<XPathLibrary>>,
+) -> (CompiledQueries, Vec<ReportMatch>) {
+    let mut queries = CompiledQueries::new(variables.cloned()).with_library(library.cloned());
+    let mut seen = HashSet::new();
+    let diagnostics = operations.iter()
+        .flat_map(operation_queries)
+        .filter(|(xpath, _, rule_id)| seen.insert((xpath.clone(), rule_id.clone())))
+        .filter_map(|(xpath, command, rule_id)| {
+            let mut diagnostic = validate_xpath_diagnostic(&xpath, command, &mut queries)?;
+            diagnostic.rule_id = rule_id;
+            Some(diagnostic)
+        })
+        .collect();
+    (queries, diagnostics)
+}
+
+/// The queries an operation runs, with the command and rule they belong to.
+fn operation_queries(op: &OperationPlan) -> Vec<(NormalizedXpath, &'static str, Option<String>)> {
+    match op {
+        OperationPlan::Query(q) => q.queries.iter()
+            .map(|q| (q.xpath.clone(), "query", None))
+            .collect(),
+        OperationPlan::Check(c) => c.compiled_rules.iter()
+            .map(|r| (r.xpath.clone(), "check", Some(r.id.clone())))
+            .collect(),
+        OperationPlan::Test(t) => t.assertions.iter()
+            .map(|a| (a.xpath.clone(), "test", None))
+            .collect(),
+        OperationPlan::Set(s) => s.mappings.iter()
+            .map(|m| (NormalizedXpath::new(&m.xpath), "set", None))
+            .collect(),
+        OperationPlan::Update(u) => vec![(NormalizedXpath::new(&u.xpath), "update", None)],
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers
 // ---------------------------------------------------------------------------
@@ -124,28 +186,100 @@ pub(crate) fn match_to_report_match(m: Match, command: &str) -> ReportMatch {
     }
 }
 
+/// Fail when `tree_mode` asks for a tree a source's language doesn't have
+/// (a table has only its data tree); such a file would otherwise be
+/// skipped as one that fails to parse.
+pub(crate) fn check_tree_mode(sources: &[Source], lang: Option<&str>, tree_mode: Option<TreeMode>) -> Result<(), String> {
+    if tree_mode.is_none() {
+        return Ok(());
+    }
+    for source in sources {
+        let lang = lang.unwrap_or(&source.language);
+        if tractor::languages::data_tree_only(lang) {
+            TreeMode::resolve(tree_mode, lang)?;
+        }
+    }
+    Ok(())
+}
+
 /// Parse and query sources in parallel with multiple XPath expressions.
 /// Each source is parsed once and all expressions are evaluated against it.
+/// Returns at most `limit` matches; see [`query_files_each`].
+pub(crate) fn query_files_multi(
+    sources: &[Source],
+    xpaths: &[&str],
+    lang: Option<&str>,
+    tree_mode: Option<TreeMode>,
+    ignore_whitespace: bool,
+    parse_depth: Option<usize>,
+    limit: Option<usize>,
+    verbose: bool,
+    filters: &Filters,
+    command: &str,
+    parse_errors: ParseErrorPolicy,
+    transforms: Option<&LanguageTransforms>,
+    compiled: Option<&Arc<CompiledQueries>>,
+    timeout_per_file: Option<Duration>,
+) -> Result<(Vec<Match>, Vec<ReportMatch>), Box<dyn std::error::Error>> {
+    let (matches, diagnostics) = query_files_each(
+        sources, xpaths, lang, tree_mode, ignore_whitespace, parse_depth,
+        verbose, filters, command, parse_errors, transforms, compiled, timeout_per_file,
+    )?;
+    let limit = limit.unwrap_or(usize::MAX);
+    Ok((matches.into_iter().take(limit).map(|(_, m)| m).collect(), diagnostics))
+}
+
+/// [`query_files_multi`] without a limit, pairing each match with the index
+/// of the expression in `xpaths` that found it.
 ///
 /// Virtual and disk sources flow through the same loop — `source.parse()`
 /// dispatches on content kind so the caller doesn't branch.
-pub(crate) fn query_files_multi(
+///
+/// Syntax errors in the parsed sources are returned as diagnostics for
+/// `command`, according to `parse_errors`, as are warnings for sources
+/// stopped by `timeout_per_file`.
+pub(crate) fn query_files_each(
     sources: &[Source],
     xpaths: &[&str],
     lang: Option<&str>,
     tree_mode: Option<TreeMode>,
     ignore_whitespace: bool,
     parse_depth: Option<usize>,
-    limit: Option<usize>,
     verbose: bool,
     filters: &Filters,
-) -> Result<Vec<Match>, Box<dyn std::error::Error>> {
-    let mut all_matches: Vec<Match> = sources
+    command: &str,
+    parse_errors: ParseErrorPolicy,
+    transforms: Option<&LanguageTransforms>,
+    compiled: Option<&Arc<CompiledQueries>>,
+    timeout_per_file: Option<Duration>,
+) -> Result<(Vec<(usize, Match)>, Vec<ReportMatch>), Box<dyn std::error::Error>> {
+    check_tree_mode(sources, lang, tree_mode)?;
+    let queries: Arc<[TimedQuery]> = xpaths
+        .iter()
+        .map(|xpath| TimedQuery::new(xpath, None).with_queries(compiled.map(Arc::as_ref)))
+        .collect();
+    let parse = SourceParse {
+        language: lang.map(str::to_string),
+        tree_mode,
+        ignore_whitespace,
+        parse_depth,
+        transforms: transforms.map(|t| Arc::new(t.clone())),
+        queries: compiled.cloned(),
+        command: command.to_string(),
+        parse_errors,
+    };
+    let literals: Vec<Vec<String>> = xpaths.iter().map(|xpath| tractor::required_literals(xpath)).collect();
+    let results: Vec<(Vec<(usize, Match)>, Vec<ReportMatch>)> = sources
         .par_iter()
         .filter_map(|source| {
             let path_str = source.path.as_str();
-            let mut result = match source.parse(lang, tree_mode, ignore_whitespace, parse_depth) {
-                Ok(r) => r,
+            let (all, read) = prefilter_queries(source, &parse, &queries, &literals, (0..queries.len()).collect());
+            if all.is_empty() {
+                return None;
+            }
+            let source = read.as_ref().unwrap_or(source);
+            let outcome = match evaluate_source(source, &parse, &queries, all, timeout_per_file) {
+                Ok(outcome) => outcome,
                 Err(e) => {
                     if verbose {
                         eprintln!("warning: {}: {}", path_str, e);
@@ -154,35 +288,97 @@ pub(crate) fn query_files_multi(
                 }
             };
 
+            let mut diagnostics = outcome.parse_errors;
+            diagnostics.extend(outcome.timeouts.iter().map(|timeout| timeout.diagnostic(command, None)));
             let mut file_matches = Vec::new();
-            for xpath_expr in xpaths {
-                match result.query(xpath_expr) {
-                    Ok(matches) => file_matches.extend(matches),
-                    Err(e) => {
+            for (i, result) in outcome.results.into_iter().enumerate() {
+                match result {
+                    Some(Ok(matches)) => file_matches.extend(matches.into_iter().map(|m| (i, m))),
+                    Some(Err(e)) => {
                         if verbose {
                             eprintln!("warning: {}: query error: {}", path_str, e);
                         }
                     }
+                    None => {}
                 }
             }
 
             // Apply result filters at the query engine level.
             if !filters.is_empty() {
-                file_matches.retain(|m| filters.include(m));
+                file_matches.retain(|(_, m)| filters.include(m));
             }
 
-            if file_matches.is_empty() { None } else { Some(file_matches) }
+            if file_matches.is_empty() && diagnostics.is_empty() {
+                None
+            } else {
+                Some((file_matches, diagnostics))
+            }
         })
-        .flatten()
         .collect();
 
-    all_matches.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+    let (matches, diagnostics): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+    let mut all_matches: Vec<(usize, Match)> = matches.into_iter().flatten().collect();
+    all_matches.sort_by(|(_, a), (_, b)| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+
+    let mut all_diagnostics: Vec<ReportMatch> = diagnostics.into_iter().flatten().collect();
+    all_diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+
+    Ok((all_matches, all_diagnostics))
+}
 
-    if let Some(limit) = limit {
-        all_matches.truncate(limit);
+/// Drop the queries in `indices` whose required literals (see
+/// [`tractor::required_literals`]) don't all occur in the source's text, so
+/// a file none of them can match is never parsed. Keeps every query when
+/// the tree's text isn't the source text (data trees, XML, containers,
+/// `--ignore-whitespace`), when parse errors are reported (a skipped file
+/// would hide them), or when the source can't be read, and always keeps
+/// queries over injected sub-trees.
+///
+/// When it read a disk source, also returns that source carrying the text
+/// it read (see [`Source::with_text`]), so parsing doesn't read it again.
+/// It stays a disk source, so the configured encoding, write-back and
+/// content sniffing of `unknown` files are unchanged.
+pub(crate) fn prefilter_queries(
+    source: &Source,
+    parse: &SourceParse,
+    queries: &[TimedQuery],
+    literals: &[Vec<String>],
+    indices: Vec<usize>,
+) -> (Vec<usize>, Option<Source>) {
+    if parse.ignore_whitespace
+        || parse.parse_errors != ParseErrorPolicy::Ignore
+        || indices.iter().all(|&i| literals[i].is_empty())
+    {
+        return (indices, None);
+    }
+    let lang = parse.language.as_deref().unwrap_or(&source.language);
+    let source_text = lang != "xml"
+        && tractor::languages::embedded_languages(lang).is_empty()
+        && matches!(TreeMode::resolve(parse.tree_mode, lang), Ok(mode) if mode != TreeMode::Data);
+    if !source_text {
+        return (indices, None);
     }
+    let Ok((text, encoding)) = source.read() else {
+        return (indices, None);
+    };
+    let indices = indices
+        .into_iter()
+        .filter(|&i| queries[i].injections || literals[i].iter().all(|word| text.contains(word.as_str())))
+        .collect();
+    let read = match text {
+        Cow::Owned(text) => Some(source.with_text(text, encoding)),
+        Cow::Borrowed(_) => None,
+    };
+    (indices, read)
+}
 
-    Ok(all_matches)
+/// Add parse-error diagnostics to the report, failing it under
+/// `--fail-on-parse-error`. Timeout warnings that come along don't fail it.
+pub(crate) fn add_parse_errors(report: &mut ReportBuilder, diagnostics: Vec<ReportMatch>, policy: ParseErrorPolicy) {
+    if policy == ParseErrorPolicy::Fail && diagnostics.iter().any(|d| d.origin == Some(DiagnosticOrigin::Input)) {
+        report.fail();
+    }
+    report.add_all(diagnostics);
 }
 
 /// Check whether an expectation is met.
@@ -342,6 +538,37 @@ mod tests {
         assert_eq!(report.all_matches().len(), 0);
     }
 
+    #[test]
+    fn check_reports_parse_errors_when_requested() {
+        let (_dir, path) = temp_json_file(r#"{"debug": false,, "verbose": true}"#);
+        let plan = || OperationPlan::Check(CheckOperationPlan {
+            sources: disk_sources(&[&path]),
+            filters: Filters::default(),
+            compiled_rules: compile(
+                vec![Rule::new("no-debug", "//debug[.='true']").with_reason("debug should not be enabled")],
+                None,
+            ),
+            tree_mode: None,
+            ignore_whitespace: false,
+            parse_depth: None,
+        });
+
+        let report = run(&[plan()]);
+        assert!(report.success.unwrap(), "parse errors are ignored by default");
+        assert_eq!(report.all_matches().len(), 0);
+
+        let mut builder = ReportBuilder::new();
+        let ctx = ExecCtx { parse_errors: ParseErrorPolicy::Fail, ..ExecCtx::default() };
+        execute(&[plan()], &ctx, &mut builder).unwrap();
+        let report = builder.build();
+        assert!(!report.success.unwrap(), "--fail-on-parse-error should fail the check");
+        let matches = report.all_matches();
+        assert!(!matches.is_empty());
+        assert_eq!(matches[0].severity, Some(Severity::Error));
+        assert_eq!(matches[0].origin, Some(tractor::report::DiagnosticOrigin::Input));
+        assert!(matches[0].reason.as_deref().unwrap().starts_with("syntax error"));
+    }
+
     #[test]
     fn mixed_check_and_set() {
         let dir = tempfile::tempdir().unwrap();
@@ -386,4 +613,138 @@ mod tests {
         let content = std::fs::read_to_string(&config_path).unwrap();
         assert!(content.contains("new-host"));
     }
+
+    #[test]
+    fn invalid_queries_are_reported_before_any_operation_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        std::fs::write(&config_path, r#"{"host": "old"}"#).unwrap();
+
+        let ops = vec![
+            OperationPlan::Set(set::SetOperationPlan {
+                sources: disk_sources(&[config_path.to_str().unwrap()]),
+                filters: Filters::default(),
+                mappings: vec![set::SetMapping {
+                    xpath: "//host".into(),
+                    value: "new-host".into(),
+                    value_kind: Some("string".into()),
+                }],
+                tree_mode: None,
+                limit: None,
+                ignore_whitespace: false,
+                write_mode: set::SetWriteMode::InPlace,
+                report_mode: set::SetReportMode::PerMatch,
+            }),
+            OperationPlan::Check(CheckOperationPlan {
+                sources: disk_sources(&[config_path.to_str().unwrap()]),
+                filters: Filters::default(),
+                compiled_rules: compile(
+                    vec![
+                        Rule::new("broken", "//host["),
+                        Rule::new("also-broken", "//host[[1]"),
+                        Rule::new("fine", "//host"),
+                    ],
+                    None,
+                ),
+                tree_mode: None,
+                ignore_whitespace: false,
+                parse_depth: None,
+            }),
+        ];
+
+        let report = run(&ops);
+        assert!(!report.success.unwrap());
+        let matches = report.all_matches();
+        assert_eq!(matches.len(), 2, "one diagnostic per invalid query");
+        assert!(matches.iter().all(|m| m.severity == Some(Severity::Fatal)));
+        assert_eq!(matches[0].rule_id.as_deref(), Some("broken"));
+        assert_eq!(matches[1].rule_id.as_deref(), Some("also-broken"));
+        let content = std::fs::read_to_string(&config_path).unwrap();
+        assert!(content.contains("old"), "set must not run when a later query is invalid");
+    }
+
+    #[test]
+    fn prefilter_drops_queries_whose_words_are_missing() {
+        let source = Source::inline_pathless("python", "def f():\n    eval(x)\n".to_string());
+        let xpaths = ["//call[function/name = 'eval']", "//call[name = 'exec']", "//call"];
+        let queries: Vec<TimedQuery> = xpaths.iter().map(|xpath| TimedQuery::new(xpath, None)).collect();
+        let literals: Vec<Vec<String>> = xpaths.iter().map(|xpath| tractor::required_literals(xpath)).collect();
+        let mut parse = query_parse();
+        assert_eq!(prefilter_queries(&source, &parse, &queries, &literals, vec![0, 1, 2]).0, [0, 2]);
+
+        // A skipped file would hide its parse errors
+        parse.parse_errors = ParseErrorPolicy::Report;
+        assert_eq!(prefilter_queries(&source, &parse, &queries, &literals, vec![0, 1, 2]).0, [0, 1, 2]);
+    }
+
+    #[test]
+    fn prefilter_hands_on_the_text_it_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.py");
+        std::fs::write(&path, "eval(x)\n").unwrap();
+        let source = disk_sources(&[path.to_str().unwrap()]).remove(0);
+        let (indices, read) = prefilter_eval(&source);
+        assert_eq!(indices, [0]);
+        let read = read.unwrap();
+        assert_eq!(read.path, source.path);
+        assert!(!read.is_virtual());
+        assert_eq!(read.read().unwrap().0, "eval(x)\n");
+    }
+
+    #[test]
+    fn prefilter_keeps_the_encoding_of_what_it_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.py");
+        // "café" in Windows-1252
+        std::fs::write(&path, b"s = 'caf\xE9'\neval(s)\n").unwrap();
+        let source = Source::disk(NormalizedPath::absolute(path.to_str().unwrap()), "python")
+            .with_encoding(tractor::encoding::lookup("windows-1252"));
+        let (indices, read) = prefilter_eval(&source);
+        let read = read.unwrap();
+        assert_eq!(read.encoding.map(|e| e.name()), Some("windows-1252"));
+        let (text, encoding) = read.read().unwrap();
+        assert!(text.contains("café"));
+        assert_eq!(encoding.name(), "windows-1252");
+        assert_eq!(evaluated(&read, indices), 1);
+    }
+
+    #[test]
+    fn prefilter_keeps_sniffing_the_language_of_what_it_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool");
+        std::fs::write(&path, "#!/usr/bin/env python3\neval(x)\n").unwrap();
+        let source = Source::disk(NormalizedPath::absolute(path.to_str().unwrap()), "unknown");
+        let (indices, read) = prefilter_eval(&source);
+        let read = read.unwrap();
+        assert!(!read.is_virtual());
+        assert_eq!(evaluated(&read, indices), 1);
+    }
+
+    fn query_parse() -> SourceParse {
+        SourceParse {
+            language: None,
+            tree_mode: None,
+            ignore_whitespace: false,
+            parse_depth: None,
+            transforms: None,
+            queries: None,
+            command: "query".to_string(),
+            parse_errors: ParseErrorPolicy::Ignore,
+        }
+    }
+
+    const EVAL: &str = "//call[name = 'eval']";
+
+    /// Prefilter `source` for a query over `eval` calls.
+    fn prefilter_eval(source: &Source) -> (Vec<usize>, Option<Source>) {
+        let queries = [TimedQuery::new(EVAL, None)];
+        prefilter_queries(source, &query_parse(), &queries, &[tractor::required_literals(EVAL)], vec![0])
+    }
+
+    /// How many `eval` calls the query finds in `source`.
+    fn evaluated(source: &Source, indices: Vec<usize>) -> usize {
+        let queries: Arc<[TimedQuery]> = vec![TimedQuery::new(EVAL, None)].into();
+        let outcome = evaluate_source(source, &query_parse(), &queries, indices, None).unwrap();
+        outcome.results[0].as_ref().unwrap().as_ref().unwrap().len()
+    }
 }
@@ -3,14 +3,15 @@
 use tractor::normalized_xpath::NormalizedXpath;
 use tractor::report::ReportBuilder;
 use tractor::tree_mode::TreeMode;
+use tractor::ParseOptions;
 
-use crate::matcher::validate_xpath_diagnostic;
 use crate::input::filter::Filters;
 use crate::input::Source;
 
 use crate::cli::context::ExecCtx;
 
-use super::{match_to_report_match, query_files_multi};
+use super::explain::explain_no_matches;
+use super::{add_parse_errors, match_to_report_match, query_files_each};
 
 // ---------------------------------------------------------------------------
 // Operation type
@@ -96,28 +97,40 @@ pub(crate) fn execute_query(
     ctx: &ExecCtx<'_>,
     report: &mut ReportBuilder,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // Validate all XPath expressions upfront — add fatal diagnostics on failure
-    let diagnostics: Vec<_> = op.queries.iter()
-        .filter_map(|q| validate_xpath_diagnostic(&q.xpath, "query"))
-        .collect();
-    if !diagnostics.is_empty() {
-        report.add_all(diagnostics);
-        return Ok(());
-    }
-
     if op.sources.is_empty() {
         return Ok(());
     }
 
     let xpaths: Vec<&str> = op.queries.iter().map(|q| q.xpath.as_str()).collect();
 
-    let matches = query_files_multi(
+    let (matches, parse_errors) = query_files_each(
         &op.sources, &xpaths, op.language.as_deref(),
         op.tree_mode, op.ignore_whitespace, op.parse_depth,
-        op.limit, ctx.verbose, &op.filters,
+        ctx.verbose, &op.filters,
+        "query", ctx.parse_errors, ctx.transforms, ctx.queries, ctx.timeout_per_file,
     )?;
 
-    report.add_all(matches.into_iter().map(|m| match_to_report_match(m, "query")));
+    if ctx.explain {
+        let options = ParseOptions {
+            language: op.language.as_deref(),
+            tree_mode: op.tree_mode,
+            ignore_whitespace: op.ignore_whitespace,
+            parse_depth: op.parse_depth,
+            transforms: ctx.transforms,
+            queries: ctx.queries,
+            ..ParseOptions::default()
+        };
+        // Explain each query that matched nothing, whether or not the others did
+        for (i, query) in op.queries.iter().enumerate() {
+            if !matches.iter().any(|&(query, _)| query == i) {
+                report.add_all(explain_no_matches(&op.sources, &query.xpath, options, "query"));
+            }
+        }
+    }
+
+    let limit = op.limit.unwrap_or(usize::MAX);
+    report.add_all(matches.into_iter().take(limit).map(|(_, m)| match_to_report_match(m, "query")));
+    add_parse_errors(report, parse_errors, ctx.parse_errors);
 
     Ok(())
 }
@@ -206,4 +219,42 @@ mod tests {
         assert_eq!(report.all_matches().len(), 0);
         assert!(report.success.is_none());
     }
+
+    #[test]
+    fn explain_points_at_the_step_that_matches_nothing() {
+        let (_dir, path) = temp_json_file(r#"{"name": "alice", "age": 30}"#);
+        let explain = |xpaths: &[&str]| {
+            let ops = vec![OperationPlan::Query(QueryOperationPlan {
+                sources: vec![disk_source(&path)],
+                filters: Filters::default(),
+                queries: xpaths.iter().map(|xpath| QueryExpr { xpath: (*xpath).into() }).collect(),
+                tree_mode: None,
+                language: None,
+                limit: None,
+                ignore_whitespace: false,
+                parse_depth: None,
+            })];
+            let mut builder = ReportBuilder::new();
+            builder.set_no_verdict();
+            let ctx = ExecCtx { explain: true, ..ExecCtx::default() };
+            execute(&ops, &ctx, &mut builder).unwrap();
+            let report = builder.build();
+            assert!(report.success.is_none(), "explanations are not failures");
+            report.all_matches().iter().filter_map(|m| m.reason.clone()).collect::<Vec<_>>()
+        };
+
+        let misspelled = explain(&["//nme"]);
+        assert_eq!(misspelled.len(), 1);
+        assert!(misspelled[0].contains("did you mean 'name'"), "{}", misspelled[0]);
+
+        let filtered = explain(&["//name[. = 'bob']"]);
+        assert_eq!(filtered, ["'//name' matches 1 node, but none satisfy '[. = 'bob']'"]);
+
+        assert!(explain(&["//name"]).is_empty());
+
+        // Each query that matched nothing is explained, even when another matched
+        let mixed = explain(&["//name", "//nme"]);
+        assert_eq!(mixed.len(), 1);
+        assert!(mixed[0].contains("did you mean 'name'"), "{}", mixed[0]);
+    }
 }
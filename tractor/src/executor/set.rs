@@ -1,8 +1,10 @@
 //! Set operation: ensure values exist at specified XPaths.
 
+use std::sync::Arc;
+
 use tractor::report::{ReportBuilder, ReportMatch, ReportOutput};
 use tractor::tree_mode::TreeMode;
-use tractor::{parse, ParseInput, ParseOptions, Match};
+use tractor::{parse, CompiledQueries, ParseInput, ParseOptions, Match};
 use tractor::xpath_upsert::upsert_typed;
 
 use crate::input::filter::Filters;
@@ -107,7 +109,7 @@ pub enum SetReportMode {
 
 pub(crate) fn execute_set(
     op: &SetOperationPlan,
-    _ctx: &ExecCtx<'_>,
+    ctx: &ExecCtx<'_>,
     report: &mut ReportBuilder,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if op.mappings.is_empty() {
@@ -115,7 +117,7 @@ pub(crate) fn execute_set(
     }
 
     for source in &op.sources {
-        let content = source.read()?;
+        let (content, encoding) = source.read()?;
 
         // Disposition drives write-mode routing only:
         //   Disk              → honour the requested write mode; writes go
@@ -151,13 +153,14 @@ pub(crate) fn execute_set(
             op,
             effective_write_mode,
             &op.filters,
+            ctx.queries,
         )?;
 
         if outcome.changed
             && matches!(effective_write_mode, SetWriteMode::InPlace)
             && matches!(disposition, SourceDisposition::Disk)
         {
-            std::fs::write(source.path.as_str(), &outcome.content)?;
+            source.write(&outcome.content, encoding)?;
         }
         if matches!(op.write_mode, SetWriteMode::Verify) && outcome.changed {
             report.fail();
@@ -188,6 +191,7 @@ fn execute_set_target(
     op: &SetOperationPlan,
     effective_write_mode: SetWriteMode,
     filters: &Filters,
+    queries: Option<&Arc<CompiledQueries>>,
 ) -> Result<SetTargetOutcome, Box<dyn std::error::Error>> {
     let file_label = source.path_str();
     let lang = source.language.as_str();
@@ -197,12 +201,12 @@ fn execute_set_target(
 
     for mapping in &op.mappings {
         let before_matches = if matches!(op.report_mode, SetReportMode::PerMatch) {
-            query_set_matches(&current, file_label, lang, mapping, op, filters)?
+            query_set_matches(&current, file_label, lang, mapping, op, filters, queries)?
         } else {
             Vec::new()
         };
 
-        let result = apply_set_mapping(&current, file_label, lang, mapping, op, filters, &before_matches)?;
+        let result = apply_set_mapping(&current, file_label, lang, mapping, op, filters, &before_matches, queries)?;
         let was_modified = result.source != current;
         changed |= was_modified;
 
@@ -210,7 +214,7 @@ fn execute_set_target(
             let mut report_matches = if !result.matches.is_empty() {
                 result.matches
             } else if was_modified {
-                query_set_matches(&result.source, file_label, lang, mapping, op, filters)?
+                query_set_matches(&result.source, file_label, lang, mapping, op, filters, queries)?
             } else {
                 before_matches
             };
@@ -291,6 +295,7 @@ fn apply_set_mapping(
     op: &SetOperationPlan,
     filters: &Filters,
     before_matches: &[Match],
+    queries: Option<&Arc<CompiledQueries>>,
 ) -> Result<SetMappingResult, Box<dyn std::error::Error>> {
     match upsert_typed(
         source,
@@ -299,6 +304,7 @@ fn apply_set_mapping(
         &mapping.value,
         op.limit,
         mapping.value_kind.as_deref(),
+        queries,
     ) {
         Ok(result) => Ok(SetMappingResult {
             source: result.source,
@@ -316,7 +322,7 @@ fn apply_set_mapping(
             }
 
             let fallback_matches = if before_matches.is_empty() {
-                query_set_matches(source, file_label, lang, mapping, op, filters)?
+                query_set_matches(source, file_label, lang, mapping, op, filters, queries)?
             } else {
                 before_matches.to_vec()
             };
@@ -345,6 +351,7 @@ fn query_set_matches(
     mapping: &SetMapping,
     op: &SetOperationPlan,
     filters: &Filters,
+    queries: Option<&Arc<CompiledQueries>>,
 ) -> Result<Vec<Match>, Box<dyn std::error::Error>> {
     let mut result = parse(
         ParseInput::Inline {
@@ -356,6 +363,12 @@ fn query_set_matches(
             tree_mode: op.tree_mode,
             ignore_whitespace: op.ignore_whitespace,
             parse_depth: None,
+            injections: false,
+            doc_comments: false,
+            embedded_language: None,
+            transforms: None,
+            encoding: None,
+            queries,
         },
     )?;
     let mut matches = result.query(&mapping.xpath)?;
@@ -465,6 +478,27 @@ mod tests {
         assert!(!content.contains("old"), "file should not contain old value: {}", content);
     }
 
+    #[test]
+    fn set_preserves_file_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.json").to_str().unwrap().to_string();
+        // UTF-16LE with BOM, as Visual Studio saves it
+        let utf16 = |text: &str| -> Vec<u8> {
+            let mut bytes = vec![0xFF, 0xFE];
+            bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+            bytes
+        };
+        std::fs::write(&path, utf16(r#"{"name": "café"}"#)).unwrap();
+        let ops = vec![set_operation(
+            path.clone(),
+            vec![string_mapping("//name", "crème")],
+            SetWriteMode::InPlace,
+        )];
+        let report = run(&ops);
+        assert!(report.success.unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), utf16(r#"{"name": "crème"}"#));
+    }
+
     #[test]
     fn set_creates_missing_node() {
         let (_dir, path) = temp_json_file(r#"{"database": {}}"#);
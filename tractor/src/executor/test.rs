@@ -3,13 +3,15 @@
 use tractor::normalized_xpath::NormalizedXpath;
 use tractor::report::ReportBuilder;
 use tractor::tree_mode::TreeMode;
+use tractor::ParseOptions;
 
 use crate::input::filter::Filters;
 use crate::input::Source;
 
-use crate::cli::context::ExecCtx;
+use crate::cli::context::{ExecCtx, ParseErrorPolicy};
 
-use super::{match_to_report_match, query_files_multi, check_expectation};
+use super::explain::explain_no_matches;
+use super::{add_parse_errors, match_to_report_match, query_files_multi, check_expectation};
 
 // ---------------------------------------------------------------------------
 // Operation type
@@ -104,16 +106,32 @@ pub(crate) fn execute_test(
     }
 
     // Query each assertion's xpath individually to get per-assertion counts.
-    for assertion in &op.assertions {
-        let matches = query_files_multi(
+    // Every assertion re-parses the same sources: report their syntax errors once.
+    for (i, assertion) in op.assertions.iter().enumerate() {
+        let parse_errors = if i == 0 { ctx.parse_errors } else { ParseErrorPolicy::Ignore };
+        let (matches, diagnostics) = query_files_multi(
             &op.sources, &[assertion.xpath.as_str()], op.language.as_deref(),
             op.tree_mode, op.ignore_whitespace, op.parse_depth,
             op.limit, ctx.verbose, &op.filters,
+            "test", parse_errors, ctx.transforms, ctx.queries, ctx.timeout_per_file,
         )?;
         if !check_expectation(&assertion.expect, matches.len())? {
             report.fail();
         }
+        if ctx.explain && matches.is_empty() {
+            let options = ParseOptions {
+                language: op.language.as_deref(),
+                tree_mode: op.tree_mode,
+                ignore_whitespace: op.ignore_whitespace,
+                parse_depth: op.parse_depth,
+                transforms: ctx.transforms,
+                queries: ctx.queries,
+                ..ParseOptions::default()
+            };
+            report.add_all(explain_no_matches(&op.sources, &assertion.xpath, options, "test"));
+        }
         report.add_all(matches.into_iter().map(|m| match_to_report_match(m, "test")));
+        add_parse_errors(report, diagnostics, parse_errors);
     }
 
     Ok(())
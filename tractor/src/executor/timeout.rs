@@ -0,0 +1,609 @@
+//! Per-file and per-query timeouts (`--timeout-per-file`, a rule's `timeout`).
+//!
+//! XPath evaluation can't be interrupted from outside, so a source whose
+//! queries have a time limit is evaluated on a helper thread while the
+//! worker waits on it with a deadline. When a deadline passes, the worker
+//! abandons the helper — it keeps running the stopped query in the
+//! background and exits when it finishes — and moves on: to the source's
+//! remaining queries on a fresh helper (on a snapshot of the parsed tree,
+//! so the source isn't parsed again), or to the next source when the
+//! source's own limit is spent. A worker keeps its helper between sources,
+//! so compiled queries stay cached.
+//!
+//! Abandoned helpers are capped at one per core (and at least four): a
+//! worker that needs a new helper while that many are still running doesn't
+//! wait for them, it skips the source's time-limited queries with a warning
+//! instead. So besides the workers' own helpers, no more than the cap plus
+//! one stray thread per worker is ever left running.
+//!
+//! Without a limit, the queries run on the worker itself.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tractor::report::{DiagnosticOrigin, ReportMatch, Severity};
+use tractor::language_info::parse_language;
+use tractor::languages::embedded_languages;
+use tractor::tree_mode::TreeMode;
+use tractor::{wants_doc_comments, wants_injections, CompiledQueries, LanguageTransforms, Match, ParseOptions, TreeSnapshot};
+
+use crate::cli::context::ParseErrorPolicy;
+use crate::input::Source;
+use crate::matcher::parse_error_diagnostics;
+
+/// How to parse a source, owned so it can move to a helper thread.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceParse {
+    pub language: Option<String>,
+    pub tree_mode: Option<TreeMode>,
+    pub ignore_whitespace: bool,
+    pub parse_depth: Option<usize>,
+    pub transforms: Option<Arc<LanguageTransforms>>,
+    pub queries: Option<Arc<CompiledQueries>>,
+    /// Command the syntax-error diagnostics are reported for.
+    pub command: String,
+    pub parse_errors: ParseErrorPolicy,
+}
+
+/// A query to evaluate against each source, with its own time limit.
+#[derive(Debug, Clone)]
+pub(crate) struct TimedQuery {
+    pub xpath: String,
+    pub timeout: Option<Duration>,
+    /// Whether the query needs injected sub-trees (see [`wants_injections`]).
+    pub injections: bool,
+    /// Whether the query needs doc comments attached (see [`wants_doc_comments`]).
+    pub doc_comments: bool,
+    /// The rule's language, if it names one.
+    pub language: Option<&'static str>,
+}
+
+impl TimedQuery {
+    pub(crate) fn new(xpath: &str, timeout: Option<Duration>) -> Self {
+        TimedQuery {
+            xpath: xpath.to_string(),
+            timeout,
+            injections: wants_injections(xpath),
+            doc_comments: wants_doc_comments(xpath),
+            language: None,
+        }
+    }
+
+    /// Work out the tree passes the query needs with the config's functions
+    /// and fragments in `queries` expanded.
+    pub(crate) fn with_queries(mut self, queries: Option<&CompiledQueries>) -> Self {
+        if let Some(queries) = queries {
+            let xpath = queries.expand(&self.xpath);
+            self.injections = wants_injections(&xpath);
+            self.doc_comments = wants_doc_comments(&xpath);
+        }
+        self
+    }
+
+    /// Set the language the query was written for. On containers
+    /// (notebooks, components) it only sees the parts in that language.
+    pub(crate) fn with_language(mut self, language: Option<&str>) -> Self {
+        self.language = language.map(|lang| parse_language(lang).as_str());
+        self
+    }
+
+    /// The optional tree passes this query needs on a source whose
+    /// language embeds `embedded`; queries that need different ones get
+    /// separate parses.
+    fn tree_passes(&self, embedded: &[&str]) -> TreePasses {
+        TreePasses {
+            injections: self.injections,
+            doc_comments: self.doc_comments,
+            embedded_language: self.language.filter(|lang| embedded.contains(lang)),
+        }
+    }
+}
+
+/// Optional passes over a parsed tree, requested per query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct TreePasses {
+    injections: bool,
+    doc_comments: bool,
+    /// Only the parts of a container in this language
+    embedded_language: Option<&'static str>,
+}
+
+/// What evaluating a source's queries produced.
+pub(crate) struct SourceOutcome {
+    /// Syntax-error diagnostics, per the parse-error policy.
+    pub parse_errors: Vec<ReportMatch>,
+    /// Each query's matches or error, by query index. `None` for queries
+    /// that were not requested, timed out, or were skipped.
+    pub results: Vec<Option<Result<Vec<Match>, String>>>,
+    pub timeouts: Vec<TimedOut>,
+}
+
+/// A query stopped or skipped on a source for lack of time.
+#[derive(Debug, Clone)]
+pub(crate) struct TimedOut {
+    /// Path of the source.
+    pub file: String,
+    /// The query stopped or skipped; `None` when the source was still
+    /// being parsed.
+    pub query: Option<usize>,
+    pub stop: Stop,
+}
+
+/// Why a query didn't finish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stop {
+    /// The query's own limit ran out.
+    Query(Duration),
+    /// The source's limit ran out, skipping the queries after this one.
+    File(Duration),
+    /// Not run: one stopped query per core was still running.
+    Busy,
+}
+
+impl TimedOut {
+    /// A warning at the top of the source, for the rule `rule_id` when the
+    /// query is a rule's.
+    pub(crate) fn diagnostic(&self, command: &str, rule_id: Option<String>) -> ReportMatch {
+        let running = "keeps running in the background until it finishes";
+        let reason = match (self.stop, self.query) {
+            (Stop::Query(limit), _) => format!("query timed out after {limit:?} and {running}"),
+            (Stop::File(limit), Some(_)) => format!(
+                "query timed out after {limit:?} on this file and {running}; the remaining queries on this file were skipped",
+            ),
+            (Stop::File(limit), None) => format!("timed out after {limit:?} while parsing; the queries on this file were skipped"),
+            (Stop::Busy, _) => "query skipped: as many stopped queries as cores are still running in the background".to_string(),
+        };
+        ReportMatch {
+            file: self.file.clone(),
+            line: 1,
+            column: 1,
+            end_line: 1,
+            end_column: 1,
+            command: command.to_string(),
+            tree: None,
+            value: None,
+            source: None,
+            lines: None,
+            reason: Some(reason),
+            severity: Some(Severity::Warning),
+            message: None,
+            origin: Some(DiagnosticOrigin::Xpath),
+            rule_id,
+            status: None,
+            output: None,
+        }
+    }
+}
+
+/// Parse `source` and evaluate the queries at `indices` against it, giving
+/// each query its own timeout and all of them together `per_file`.
+///
+/// Queries that need injected sub-trees or attached doc comments, and
+/// queries for one language of a container, get a parse of their own, so
+/// every query sees the tree it would see alone.
+///
+/// Returns the parse error message when the source can't be parsed.
+pub(crate) fn evaluate_source(
+    source: &Source,
+    parse: &SourceParse,
+    queries: &Arc<[TimedQuery]>,
+    indices: Vec<usize>,
+    per_file: Option<Duration>,
+) -> Result<SourceOutcome, String> {
+    let mut outcome = SourceOutcome {
+        parse_errors: Vec::new(),
+        results: (0..queries.len()).map(|_| None).collect(),
+        timeouts: Vec::new(),
+    };
+    let file_deadline = per_file.map(|limit| (Instant::now() + limit, limit));
+    let mut parsed = false;
+    let embedded = embedded_languages(parse.language.as_deref().unwrap_or(&source.language));
+    let mut groups: BTreeMap<TreePasses, Vec<usize>> = BTreeMap::new();
+    for i in indices {
+        groups.entry(queries[i].tree_passes(embedded)).or_default().push(i);
+    }
+    for (passes, pending) in groups {
+        let job = Job {
+            source: source.clone(),
+            parse: parse.clone(),
+            passes,
+            queries: Arc::clone(queries),
+            pending,
+            snapshot: None,
+        };
+        if !evaluate_job(job, file_deadline, &mut outcome, &mut parsed)? {
+            break;
+        }
+    }
+    Ok(outcome)
+}
+
+/// Evaluate one job into `outcome`, reporting parse errors only for the
+/// source's first parse. Returns false when the source's own time limit ran
+/// out, so nothing more should be evaluated on it.
+fn evaluate_job(
+    mut job: Job,
+    file_deadline: Option<(Instant, Duration)>,
+    outcome: &mut SourceOutcome,
+    parsed: &mut bool,
+) -> Result<bool, String> {
+    let queries = Arc::clone(&job.queries);
+    if file_deadline.is_none() && job.pending.iter().all(|&i| queries[i].timeout.is_none()) {
+        let mut failed = None;
+        run_job(&job, &mut |event| {
+            match event {
+                Event::Parsed(Ok(diagnostics)) => {
+                    if !*parsed {
+                        outcome.parse_errors = diagnostics;
+                        *parsed = true;
+                    }
+                }
+                Event::Parsed(Err(e)) => failed = Some(e),
+                Event::Snapshot(_) | Event::Started(_) => {}
+                Event::Finished(i, result) => outcome.results[i] = Some(result),
+            }
+            true
+        });
+        return match failed {
+            Some(e) => Err(e),
+            None => Ok(true),
+        };
+    }
+
+    let file = || job.source.path_str().to_string();
+    while !job.pending.is_empty() {
+        if !helper_available() {
+            let skipped = job.pending.iter().map(|&i| TimedOut { file: file(), query: Some(i), stop: Stop::Busy });
+            outcome.timeouts.extend(skipped);
+            return Ok(true);
+        }
+        let events = dispatch(job.clone());
+        let mut running: Option<(usize, Option<Instant>)> = None;
+        loop {
+            let deadline = [file_deadline.map(|(at, _)| at), running.and_then(|(_, at)| at)]
+                .into_iter()
+                .flatten()
+                .min();
+            let event = match deadline {
+                Some(at) => events.recv_timeout(at.saturating_duration_since(Instant::now())),
+                None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match event {
+                Ok(Event::Parsed(Ok(diagnostics))) => {
+                    // Trees restored after a timeout report their errors once
+                    if !*parsed {
+                        outcome.parse_errors = diagnostics;
+                        *parsed = true;
+                    }
+                }
+                Ok(Event::Parsed(Err(e))) => return Err(e),
+                Ok(Event::Snapshot(snapshot)) => job.snapshot = Some(snapshot),
+                Ok(Event::Started(i)) => {
+                    running = Some((i, queries[i].timeout.map(|limit| Instant::now() + limit)));
+                }
+                Ok(Event::Finished(i, result)) => {
+                    running = None;
+                    outcome.results[i] = Some(result);
+                    job.pending.retain(|&p| p != i);
+                    if job.pending.is_empty() {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    let query = running.map(|(i, _)| i);
+                    if let Some((_, limit)) = file_deadline.filter(|&(at, _)| at <= now) {
+                        abandon_helper();
+                        outcome.timeouts.push(TimedOut { file: file(), query, stop: Stop::File(limit) });
+                        return Ok(false);
+                    }
+                    if let Some((i, _)) = running.filter(|&(_, at)| at.is_some_and(|at| at <= now)) {
+                        // Carry on with the rest of the queries on a new helper,
+                        // from the snapshot of the tree
+                        abandon_helper();
+                        let limit = queries[i].timeout.unwrap_or_default();
+                        outcome.timeouts.push(TimedOut { file: file(), query: Some(i), stop: Stop::Query(limit) });
+                        job.pending.retain(|&p| p != i);
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    abandon_helper();
+                    return Err("query evaluation stopped unexpectedly".to_string());
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// A source and the queries still to evaluate against it.
+#[derive(Clone)]
+struct Job {
+    source: Source,
+    parse: SourceParse,
+    /// The tree passes this job's queries need
+    passes: TreePasses,
+    queries: Arc<[TimedQuery]>,
+    pending: Vec<usize>,
+    /// The parsed tree, once a helper that may be abandoned has parsed it
+    snapshot: Option<Arc<TreeSnapshot>>,
+}
+
+/// Progress of a job, as the helper reports it.
+enum Event {
+    Parsed(Result<Vec<ReportMatch>, String>),
+    /// Sent after parsing when a query with a time limit is followed by
+    /// others, which a new helper evaluates from it if that query times out
+    Snapshot(Arc<TreeSnapshot>),
+    Started(usize),
+    Finished(usize, Result<Vec<Match>, String>),
+}
+
+/// Parse the job's source, or restore its snapshot, and run its pending
+/// queries, reporting each step to `emit`. Stops early when `emit` returns
+/// false (nobody is listening).
+fn run_job(job: &Job, emit: &mut dyn FnMut(Event) -> bool) {
+    let parse = &job.parse;
+    let parsed = match &job.snapshot {
+        Some(snapshot) => snapshot.restore(parse.queries.as_ref()),
+        None => job.source.parse(ParseOptions {
+            language: parse.language.as_deref(),
+            tree_mode: parse.tree_mode,
+            ignore_whitespace: parse.ignore_whitespace,
+            parse_depth: parse.parse_depth,
+            injections: job.passes.injections,
+            doc_comments: job.passes.doc_comments,
+            embedded_language: job.passes.embedded_language,
+            transforms: parse.transforms.as_deref(),
+            encoding: None,
+            queries: parse.queries.as_ref(),
+        }),
+    };
+    let mut result = match parsed {
+        Ok(r) => r,
+        Err(e) => {
+            emit(Event::Parsed(Err(e.to_string())));
+            return;
+        }
+    };
+    if !emit(Event::Parsed(Ok(parse_error_diagnostics(&result, &parse.command, parse.parse_errors)))) {
+        return;
+    }
+    // A new helper carries on from the tree when a query before the last times out
+    let resumable = job.pending.split_last().is_some_and(|(_, before)| {
+        before.iter().any(|&i| job.queries[i].timeout.is_some())
+    });
+    if resumable && job.snapshot.is_none() {
+        if let Ok(snapshot) = result.snapshot() {
+            if !emit(Event::Snapshot(Arc::new(snapshot))) {
+                return;
+            }
+        }
+    }
+    for &i in &job.pending {
+        if !emit(Event::Started(i)) {
+            return;
+        }
+        let matches = result.query(&job.queries[i].xpath).map_err(|e| e.to_string());
+        if !emit(Event::Finished(i, matches)) {
+            return;
+        }
+    }
+}
+
+/// A worker's helper thread.
+struct Helper {
+    jobs: Sender<(Job, Sender<Event>)>,
+    /// Whether the worker left it running a stopped query, and whether it
+    /// has exited; both change only under the [`RUNAWAY`] lock
+    abandoned: Arc<AtomicBool>,
+    exited: Arc<AtomicBool>,
+}
+
+thread_local! {
+    /// This worker's helper thread, kept until a query on it times out.
+    static HELPER: RefCell<Option<Helper>> = const { RefCell::new(None) };
+}
+
+/// How many abandoned helpers are still running their stopped queries.
+static RUNAWAY: Mutex<usize> = Mutex::new(0);
+
+/// How many abandoned helpers may run before no new ones are started: one
+/// per core, but a few at least, so one runaway query doesn't stop every
+/// time-limited query on a small machine.
+fn runaway_limit() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get).max(4)
+}
+
+/// Whether this worker has a helper or may start one.
+fn helper_available() -> bool {
+    HELPER.with(|helper| helper.borrow().is_some())
+        || *RUNAWAY.lock().unwrap_or_else(|e| e.into_inner()) < runaway_limit()
+}
+
+/// Hand `job` to this worker's helper, starting one if needed.
+fn dispatch(job: Job) -> Receiver<Event> {
+    let (events, receiver) = mpsc::channel();
+    HELPER.with(|helper| {
+        let mut helper = helper.borrow_mut();
+        let current = helper.get_or_insert_with(spawn_helper);
+        if let Err(mpsc::SendError((job, events))) = current.jobs.send((job, events)) {
+            // The helper is gone (a query panicked); start a new one
+            let fresh = spawn_helper();
+            let _ = fresh.jobs.send((job, events));
+            *helper = Some(fresh);
+        }
+    });
+    receiver
+}
+
+fn spawn_helper() -> Helper {
+    let (jobs, receiver) = mpsc::channel::<(Job, Sender<Event>)>();
+    let abandoned = Arc::new(AtomicBool::new(false));
+    let exited = Arc::new(AtomicBool::new(false));
+    let exit = HelperExit { abandoned: Arc::clone(&abandoned), exited: Arc::clone(&exited) };
+    thread::spawn(move || {
+        let _exit = exit;
+        for (job, events) in receiver {
+            run_job(&job, &mut |event| events.send(event).is_ok());
+        }
+    });
+    Helper { jobs, abandoned, exited }
+}
+
+/// Marks a helper as exited when its thread ends (also by a panic), freeing
+/// its place among the running abandoned helpers.
+struct HelperExit {
+    abandoned: Arc<AtomicBool>,
+    exited: Arc<AtomicBool>,
+}
+
+impl Drop for HelperExit {
+    fn drop(&mut self) {
+        let mut running = RUNAWAY.lock().unwrap_or_else(|e| e.into_inner());
+        self.exited.store(true, Ordering::Relaxed);
+        if self.abandoned.load(Ordering::Relaxed) {
+            *running -= 1;
+        }
+    }
+}
+
+/// Leave the helper to finish its query on its own; it exits afterwards,
+/// since nothing can send it more jobs.
+fn abandon_helper() {
+    let Some(helper) = HELPER.with(|helper| helper.borrow_mut().take()) else {
+        return;
+    };
+    let mut running = RUNAWAY.lock().unwrap_or_else(|e| e.into_inner());
+    if !helper.exited.load(Ordering::Relaxed) {
+        *running += 1;
+        helper.abandoned.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tractor::NormalizedPath;
+
+    fn source(content: &str) -> Source {
+        Source::inline_pathless("python", Arc::new(content.to_string()))
+    }
+
+    fn parse() -> SourceParse {
+        SourceParse {
+            language: Some("python".to_string()),
+            tree_mode: None,
+            ignore_whitespace: false,
+            parse_depth: None,
+            transforms: None,
+            queries: None,
+            command: "check".to_string(),
+            parse_errors: ParseErrorPolicy::Ignore,
+        }
+    }
+
+    fn query(xpath: &str, timeout: Option<Duration>) -> TimedQuery {
+        TimedQuery::new(xpath, timeout)
+    }
+
+    /// A query that visits every node after every node after every node.
+    const SLOW: &str = "//*[count(following::*[count(following::*) > 0]) > 0]";
+
+    fn large_source() -> Source {
+        source(&"x = [a, b, c, d, e, f, g, h]\n".repeat(400))
+    }
+
+    #[test]
+    fn runs_queries_without_limits_inline() {
+        let queries: Arc<[TimedQuery]> = vec![query("//call", None), query("//name", None)].into();
+        let outcome = evaluate_source(&source("f(a)\n"), &parse(), &queries, vec![0], None).unwrap();
+        assert_eq!(outcome.results[0].as_ref().unwrap().as_ref().unwrap().len(), 1);
+        assert!(outcome.results[1].is_none());
+        assert!(outcome.timeouts.is_empty());
+    }
+
+    #[test]
+    fn only_queries_over_injected_trees_see_them() {
+        let source = Source::inline_pathless("markdown", Arc::new("```sql\nSELECT 1\n```\n".to_string()));
+        let parse = SourceParse { language: Some("markdown".to_string()), ..parse() };
+        let queries: Arc<[TimedQuery]> = vec![query("//code_block/*", None), query("//code_block/injected", None)].into();
+        let outcome = evaluate_source(&source, &parse, &queries, vec![0, 1], None).unwrap();
+        // `language` and `code`, without the injected tree
+        assert_eq!(outcome.results[0].as_ref().unwrap().as_ref().unwrap().len(), 2);
+        assert_eq!(outcome.results[1].as_ref().unwrap().as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn only_queries_over_doc_elements_see_attached_comments() {
+        let source = Source::inline_pathless("csharp", Arc::new("class A {\n  /// <summary>Hi</summary>\n  void M() {}\n}\n".to_string()));
+        let parse = SourceParse { language: Some("csharp".to_string()), ..parse() };
+        let queries: Arc<[TimedQuery]> = vec![query("//class/body/comment", None), query("//method/doc", None)].into();
+        let outcome = evaluate_source(&source, &parse, &queries, vec![0, 1], None).unwrap();
+        assert_eq!(outcome.results[0].as_ref().unwrap().as_ref().unwrap().len(), 1);
+        assert_eq!(outcome.results[1].as_ref().unwrap().as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn queries_for_an_embedded_language_see_only_its_cells() {
+        let notebook = r##"{"cells": [
+            {"cell_type": "markdown", "source": ["# Title"]},
+            {"cell_type": "code", "source": ["print(1)"]}
+        ]}"##;
+        let source = Source::inline_pathless("notebook", Arc::new(notebook.to_string()));
+        let parse = SourceParse { language: Some("notebook".to_string()), ..parse() };
+        let queries: Arc<[TimedQuery]> = vec![
+            query("//cell/*", None).with_language(Some("python")),
+            query("//cell/*", None).with_language(Some("markdown")),
+            query("//cell/*", None),
+        ].into();
+        let outcome = evaluate_source(&source, &parse, &queries, vec![0, 1, 2], None).unwrap();
+        assert_eq!(outcome.results[0].as_ref().unwrap().as_ref().unwrap().len(), 1);
+        assert_eq!(outcome.results[1].as_ref().unwrap().as_ref().unwrap().len(), 1);
+        assert_eq!(outcome.results[2].as_ref().unwrap().as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn query_timeout_skips_only_that_query() {
+        let queries: Arc<[TimedQuery]> = vec![
+            query(SLOW, Some(Duration::from_millis(50))),
+            query("//call", Some(Duration::from_secs(60))),
+        ].into();
+        let outcome = evaluate_source(&large_source(), &parse(), &queries, vec![0, 1], None).unwrap();
+        assert!(outcome.results[0].is_none());
+        assert!(outcome.results[1].is_some());
+        assert_eq!(outcome.timeouts.len(), 1);
+        assert_eq!(outcome.timeouts[0].query, Some(0));
+        assert_eq!(outcome.timeouts[0].stop, Stop::Query(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn timeouts_are_reported_against_the_file_and_rule() {
+        let source = Source::inline_at(NormalizedPath::new("src/big.py"), "python", Arc::new("x = [a, b, c, d, e, f, g, h]\n".repeat(400)));
+        let queries: Arc<[TimedQuery]> = vec![query(SLOW, Some(Duration::from_millis(50)))].into();
+        let outcome = evaluate_source(&source, &parse(), &queries, vec![0], None).unwrap();
+        let diagnostic = outcome.timeouts[0].diagnostic("check", Some("slow".to_string()));
+        assert_eq!(diagnostic.file, "src/big.py");
+        assert_eq!(diagnostic.rule_id.as_deref(), Some("slow"));
+        assert_eq!((diagnostic.line, diagnostic.column), (1, 1));
+        assert!(diagnostic.reason.unwrap().contains("timed out after 50ms"));
+    }
+
+    #[test]
+    fn file_timeout_skips_the_remaining_queries() {
+        let queries: Arc<[TimedQuery]> = vec![query(SLOW, None), query("//call", None)].into();
+        let outcome = evaluate_source(
+            &large_source(), &parse(), &queries, vec![0, 1], Some(Duration::from_millis(50)),
+        ).unwrap();
+        assert!(outcome.results.iter().all(Option::is_none));
+        assert_eq!(outcome.timeouts.len(), 1);
+        assert_eq!(outcome.timeouts[0].stop, Stop::File(Duration::from_millis(50)));
+    }
+}
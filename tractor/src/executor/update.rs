@@ -2,7 +2,7 @@
 
 use tractor::report::{ReportBuilder, ReportMatch};
 use tractor::tree_mode::TreeMode;
-use tractor::{apply_replacements, NormalizedPath};
+use tractor::apply_replacements;
 use tractor::xpath_upsert::update_only;
 
 use crate::input::filter::Filters;
@@ -10,7 +10,7 @@ use crate::input::Source;
 
 use crate::cli::context::ExecCtx;
 
-use super::{match_to_report_match, query_files_multi};
+use super::{add_parse_errors, match_to_report_match, query_files_multi};
 
 // ---------------------------------------------------------------------------
 // Operation type
@@ -100,13 +100,12 @@ pub(crate) fn execute_update(
             continue;
         }
         let lang = op.language.as_deref().unwrap_or(&source.language);
-        let file_path: &NormalizedPath = &source.path;
-        let disk_bytes = std::fs::read_to_string(file_path)?;
+        let (content, encoding) = source.read()?;
 
-        match update_only(&disk_bytes, lang, &op.xpath, &op.value, op.limit) {
+        match update_only(&content, lang, &op.xpath, &op.value, op.limit, ctx.queries) {
             Ok(result) => {
-                if result.source != disk_bytes {
-                    std::fs::write(file_path, &result.source)?;
+                if result.source != content {
+                    source.write(&result.source, encoding)?;
                     for m in &result.matches {
                         let mut rm = match_to_report_match(m.clone(), "update");
                         rm.status = Some("updated".to_string());
@@ -123,13 +122,18 @@ pub(crate) fn execute_update(
 
     // Legacy fallback for languages without renderers
     if !fallback_sources.is_empty() {
-        let matches = query_files_multi(
+        let (matches, parse_errors) = query_files_multi(
             &fallback_sources, &[op.xpath.as_str()], op.language.as_deref(),
             op.tree_mode, op.ignore_whitespace, op.parse_depth,
             None, ctx.verbose, &op.filters,
+            "update", ctx.parse_errors, ctx.transforms, ctx.queries, ctx.timeout_per_file,
         )?;
+        add_parse_errors(report, parse_errors, ctx.parse_errors);
         if !matches.is_empty() {
-            let summary = apply_replacements(&matches, &op.value)?;
+            let encoding_for = |path: &str| fallback_sources.iter()
+                .find(|source| source.path_str() == path)
+                .and_then(|source| source.encoding);
+            let summary = apply_replacements(&matches, &op.value, encoding_for)?;
             for m in &matches[..summary.replacements_made.min(matches.len())] {
                 report.add(ReportMatch {
                     file: m.file.clone(),
@@ -128,6 +128,7 @@ pub fn render_report(
             &dims,
             ctx.projection,
             ctx.single,
+            &ctx.encodings,
         )
         .map(|rendered| print!("{rendered}")),
         OutputFormat::Gcc => {
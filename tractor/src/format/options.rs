@@ -167,6 +167,8 @@ pub enum ViewField {
     Command,
     /// Diagnostic origin: "xpath", "cli", "config", "input".
     Origin,
+    /// Syntax errors in parsed files, reported as input diagnostics.
+    ParseErrors,
 }
 
 impl ViewField {
@@ -189,6 +191,7 @@ impl ViewField {
         ViewField::Output,
         ViewField::Command,
         ViewField::Origin,
+        ViewField::ParseErrors,
     ];
 
     /// Canonical CLI name for this field (the primary name accepted by `-v`).
@@ -211,6 +214,7 @@ impl ViewField {
             ViewField::Output => "output",
             ViewField::Command => "command",
             ViewField::Origin => "origin",
+            ViewField::ParseErrors => "parse-errors",
         }
     }
 
@@ -234,6 +238,7 @@ impl ViewField {
             ViewField::Output => "Full modified content (for --stdout)",
             ViewField::Command => "Operation type (check, query, etc.)",
             ViewField::Origin => "Diagnostic origin (xpath, cli, etc.)",
+            ViewField::ParseErrors => "Syntax errors in parsed files",
         }
     }
 
@@ -246,9 +251,11 @@ impl ViewField {
             | ViewField::Lines
             | ViewField::Schema => "content",
             ViewField::File | ViewField::Line | ViewField::Column => "location",
-            ViewField::Reason | ViewField::Severity | ViewField::Origin | ViewField::Command => {
-                "diagnostic"
-            }
+            ViewField::Reason
+            | ViewField::Severity
+            | ViewField::Origin
+            | ViewField::Command
+            | ViewField::ParseErrors => "diagnostic",
             ViewField::Count | ViewField::Totals | ViewField::Query => "summary",
             ViewField::Status | ViewField::Output => "set mode",
         }
@@ -327,6 +334,7 @@ impl ViewField {
             "output" => Ok(ViewField::Output),
             "command" => Ok(ViewField::Command),
             "origin" => Ok(ViewField::Origin),
+            "parse-errors" => Ok(ViewField::ParseErrors),
             "gcc" | "github" => Err(format!(
                 "'{}' is a format, not a view. Use -f {} instead of -v {}",
                 s, s, s,
@@ -17,11 +17,12 @@ use tractor::{
 use super::options::{ViewField, ViewSet};
 use super::shared::{should_show_totals, render_fields_for_match};
 use super::{Projection, ProjectionRenderError};
+use crate::input::encoding_map::EncodingMap;
 
 /// Text is human-readable — grouping affects display structure but matches
 /// are rendered with inherited file context from groups, not field omission.
 pub fn render_text_report(report: &Report, view: &ViewSet, render_opts: &RenderOptions, _dimensions: &[&str]) -> String {
-    render_text_results(report, view, render_opts, true)
+    render_text_results(report, view, render_opts, true, &EncodingMap::default())
 }
 
 pub fn render_text_output(
@@ -31,14 +32,15 @@ pub fn render_text_output(
     _dimensions: &[&str],
     projection: Projection,
     single: bool,
+    encodings: &EncodingMap,
 ) -> Result<String, ProjectionRenderError> {
     match projection {
-        Projection::Report => Ok(render_text_report(report, view, render_opts, &[])),
+        Projection::Report => Ok(render_text_results(report, view, render_opts, true, encodings)),
         Projection::Results => {
             if single {
-                render_single_text_result(report, view, render_opts)
+                render_single_text_result(report, view, render_opts, encodings)
             } else {
-                Ok(render_text_results(report, view, render_opts, false))
+                Ok(render_text_results(report, view, render_opts, false, encodings))
             }
         }
         Projection::Summary => Ok(render_text_summary(report)),
@@ -59,16 +61,17 @@ fn render_text_results(
     view: &ViewSet,
     render_opts: &RenderOptions,
     include_summary: bool,
+    encodings: &EncodingMap,
 ) -> String {
     let mut out = String::new();
-    let mut source_cache: HashMap<String, Option<String>> = HashMap::new();
+    let mut sources = SourceFiles::new(encodings);
 
     // Set stdout mode: groups with captured outputs — render group by group.
     let has_group_output = view.has(ViewField::Output) && report.results.iter().any(|item| {
         matches!(item, ResultItem::Group(g) if !g.outputs.is_empty())
     });
     if has_group_output {
-        out.push_str(&render_set_stdout_results(&report.results, view, render_opts));
+        out.push_str(&render_set_stdout_results(&report.results, view, render_opts, encodings));
         return out;
     }
 
@@ -95,7 +98,7 @@ fn render_text_results(
         if needs_separator && i > 0 {
             out.push('\n');
         }
-        append_match(&mut out, rm, view, render_opts, *group_file, &mut source_cache);
+        append_match(&mut out, rm, view, render_opts, *group_file, &mut sources);
     }
 
     if report.schema.is_some() {
@@ -127,14 +130,15 @@ fn render_single_text_result(
     report: &Report,
     view: &ViewSet,
     render_opts: &RenderOptions,
+    encodings: &EncodingMap,
 ) -> Result<String, ProjectionRenderError> {
     let mut out = String::new();
-    let mut source_cache: HashMap<String, Option<String>> = HashMap::new();
+    let mut sources = SourceFiles::new(encodings);
     let matches = collect_matches_with_file(&report.results, None);
     let Some((group_file, rm)) = matches.into_iter().next() else {
         return Err(ProjectionRenderError::EmptySingle);
     };
-    append_match(&mut out, rm, view, render_opts, group_file, &mut source_cache);
+    append_match(&mut out, rm, view, render_opts, group_file, &mut sources);
     Ok(out)
 }
 
@@ -249,7 +253,7 @@ fn append_match(
     view: &ViewSet,
     render_opts: &RenderOptions,
     group_file: Option<&str>,
-    source_cache: &mut HashMap<String, Option<String>>,
+    sources: &mut SourceFiles<'_>,
 ) {
 
     // When a message template was used, it is the intended primary output —
@@ -298,7 +302,7 @@ fn append_match(
     }
 
     let combined_tree_source = if view.has(ViewField::Tree) && view.has(ViewField::Source) {
-        render_combined_tree_source(rm, file, render_opts, source_cache)
+        render_combined_tree_source(rm, file, render_opts, sources)
     } else {
         None
     };
@@ -324,29 +328,44 @@ fn append_match(
 
 }
 
+/// Source files read back from disk to render trees beside their source,
+/// decoded with the configured encodings.
+struct SourceFiles<'a> {
+    encodings: &'a EncodingMap,
+    loaded: HashMap<String, Option<String>>,
+}
+
+impl<'a> SourceFiles<'a> {
+    fn new(encodings: &'a EncodingMap) -> Self {
+        SourceFiles { encodings, loaded: HashMap::new() }
+    }
+}
+
 fn render_combined_tree_source(
     rm: &ReportMatch,
     file: &str,
     render_opts: &RenderOptions,
-    source_cache: &mut HashMap<String, Option<String>>,
+    sources: &mut SourceFiles<'_>,
 ) -> Option<String> {
     let tree = rm.tree.as_ref()?;
-    let source = load_source_for_match(rm, file, source_cache)?;
+    let source = load_source_for_match(rm, file, sources)?;
     render_query_tree_with_source(tree, &source, render_opts)
 }
 
 fn load_source_for_match(
     rm: &ReportMatch,
     file: &str,
-    source_cache: &mut HashMap<String, Option<String>>,
+    sources: &mut SourceFiles<'_>,
 ) -> Option<String> {
     if !file.is_empty() && !tractor::is_pathless_file(file) {
-        if let Some(cached) = source_cache.get(file) {
+        if let Some(cached) = sources.loaded.get(file) {
             return cached.clone();
         }
 
-        let loaded = std::fs::read_to_string(file).ok();
-        source_cache.insert(file.to_string(), loaded.clone());
+        let loaded = tractor::encoding::read_file(std::path::Path::new(file), sources.encodings.get(file))
+            .ok()
+            .map(|(text, _)| text);
+        sources.loaded.insert(file.to_string(), loaded.clone());
         return loaded;
     }
 
@@ -568,9 +587,14 @@ fn should_inline_status_reason(view: &ViewSet, rm: &ReportMatch) -> bool {
 }
 
 /// Render set stdout mode from results tree (groups with captured outputs).
-fn render_set_stdout_results(items: &[ResultItem], view: &ViewSet, render_opts: &RenderOptions) -> String {
+fn render_set_stdout_results(
+    items: &[ResultItem],
+    view: &ViewSet,
+    render_opts: &RenderOptions,
+    encodings: &EncodingMap,
+) -> String {
     let mut out = String::new();
-    let mut source_cache: HashMap<String, Option<String>> = HashMap::new();
+    let mut sources = SourceFiles::new(encodings);
     let has_location = view.has(ViewField::File) || view.has(ViewField::Line) || view.has(ViewField::Column);
     let has_per_match = has_location || view.has(ViewField::Status);
 
@@ -581,7 +605,7 @@ fn render_set_stdout_results(items: &[ResultItem], view: &ViewSet, render_opts:
                 // Render leaf matches within this group
                 for child in &g.results {
                     if let ResultItem::Match(rm) = child {
-                        append_match(&mut out, rm, view, render_opts, file, &mut source_cache);
+                        append_match(&mut out, rm, view, render_opts, file, &mut sources);
                     }
                 }
             }
@@ -0,0 +1,79 @@
+//! Default encodings for disk sources.
+//!
+//! The config's `encodings:` entries pick the encoding for files that have
+//! no BOM and are not valid UTF-8 (see `tractor::encoding`). Patterns
+//! resolve like `languages:` patterns: path patterns before file-name
+//! patterns.
+
+use std::path::PathBuf;
+
+use tractor::encoding::{self, Encoding};
+use tractor::CompiledPattern;
+
+use crate::tractor_config::EncodingMapping;
+
+use super::file_resolver::resolve_globs_to_absolute;
+
+/// Compiled `encodings:` defaults.
+#[derive(Debug, Clone, Default)]
+pub struct EncodingMap {
+    /// Patterns matched against the full path (those containing `/`).
+    path_patterns: Vec<(CompiledPattern, &'static Encoding)>,
+    /// Patterns matched against the file name only.
+    name_patterns: Vec<(CompiledPattern, &'static Encoding)>,
+}
+
+impl EncodingMap {
+    /// Compile config mappings. Path patterns are anchored at `base_dir`
+    /// like every other config glob.
+    pub fn new(mappings: &[EncodingMapping], base_dir: &Option<PathBuf>) -> Result<Self, String> {
+        let mut map = EncodingMap::default();
+        for mapping in mappings {
+            let encoding = encoding::lookup(&mapping.encoding)
+                .ok_or_else(|| format!("unknown encoding '{}' in `encodings`", mapping.encoding))?;
+            let is_path = mapping.pattern.contains('/');
+            let pattern = if is_path {
+                resolve_globs_to_absolute(base_dir, std::slice::from_ref(&mapping.pattern)).remove(0)
+            } else {
+                mapping.pattern.clone()
+            };
+            let compiled = CompiledPattern::new(&pattern)
+                .map_err(|e| format!("invalid `encodings` pattern `{}`: {}", mapping.pattern, e))?;
+            if is_path {
+                map.path_patterns.push((compiled, encoding));
+            } else {
+                map.name_patterns.push((compiled, encoding));
+            }
+        }
+        Ok(map)
+    }
+
+    /// The configured default encoding for `path`, if any.
+    pub fn get(&self, path: &str) -> Option<&'static Encoding> {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        self.path_patterns.iter()
+            .find(|(p, _)| p.matches(path))
+            .or_else(|| self.name_patterns.iter().find(|(p, _)| p.matches(file_name)))
+            .map(|(_, encoding)| *encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(pattern: &str, encoding: &str) -> EncodingMapping {
+        EncodingMapping { pattern: pattern.into(), encoding: encoding.into() }
+    }
+
+    #[test]
+    fn path_patterns_win_over_file_names() {
+        let map = EncodingMap::new(
+            &[mapping("*.cs", "windows-1252"), mapping("legacy/*", "shift_jis")],
+            &Some(PathBuf::from("/repo")),
+        ).unwrap();
+        assert_eq!(map.get("/repo/src/Program.cs").map(|e| e.name()), Some("windows-1252"));
+        assert_eq!(map.get("/repo/legacy/Form.cs").map(|e| e.name()), Some("Shift_JIS"));
+        assert_eq!(map.get("/repo/src/main.rs"), None);
+    }
+}
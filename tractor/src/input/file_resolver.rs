@@ -13,12 +13,15 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
-use tractor::{expand_globs_checked, detect_language, normalize_path, pattern_literal_prefix, FilePrune, NormalizedPath, GlobPattern, CompiledPattern};
+use tractor::{expand_globs_checked, normalize_path, pattern_literal_prefix, FilePrune, NormalizedPath, GlobPattern, CompiledPattern};
 use tractor::report::{ReportBuilder, ReportMatch, Severity, DiagnosticOrigin};
 
 use crate::cli::context::ExecCtx;
+use crate::tractor_config::{EncodingMapping, LanguageMapping};
 
 use super::filter::Filters;
+use super::encoding_map::EncodingMap;
+use super::language_map::LanguageMap;
 use super::source::Source;
 use super::git;
 
@@ -41,6 +44,10 @@ pub struct ResolverOptions {
     /// Root-level file patterns from config.
     /// `None` = key missing (unrestricted); `Some(vec![])` = explicitly empty.
     pub config_root_files: Option<Vec<String>>,
+    /// Root-level `languages:` overrides from config.
+    pub languages: Vec<LanguageMapping>,
+    /// Root-level `encodings:` defaults from config.
+    pub encodings: Vec<EncodingMapping>,
 }
 
 // ---------------------------------------------------------------------------
@@ -98,6 +105,10 @@ pub struct FileResolver {
     /// Literal path prefixes of the CLI file args. Plays the same role
     /// as `root_prefixes` — sibling constraint during expansion.
     cli_prefixes: Vec<NormalizedPath>,
+    /// Config overrides plus extension and shebang detection.
+    languages: LanguageMap,
+    /// Config default encodings for legacy files.
+    encodings: EncodingMap,
     // Resolution parameters (copied from ResolverOptions for self-containment)
     verbose: bool,
     base_dir: Option<PathBuf>,
@@ -265,12 +276,17 @@ impl FileResolver {
             None
         };
 
+        let languages = LanguageMap::new(&options.languages, &base_dir)?;
+        let encodings = EncodingMap::new(&options.encodings, &base_dir)?;
+
         Ok(FileResolver {
             root_files,
             cli_files,
             global_diff_files,
             root_prefixes,
             cli_prefixes,
+            languages,
+            encodings,
             verbose,
             base_dir,
             max_files: options.max_files,
@@ -338,13 +354,14 @@ impl FileResolver {
 
         // Unify: wrap resolved paths as disk sources, then append the
         // inline source (if any). Disk sources pick up the language
-        // override; inline sources already carry their own.
+        // override or the language map; inline sources carry their own.
         let mut sources: Vec<Source> = Vec::with_capacity(paths.len() + 1);
         for path in paths {
             let lang = request.language
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| detect_language(path.as_str()).to_string());
-            sources.push(Source::disk(path, lang));
+                .unwrap_or_else(|| self.languages.detect(path.as_str()));
+            let encoding = self.encodings.get(path.as_str());
+            sources.push(Source::disk(path, lang).with_encoding(encoding));
         }
         if let Some(inline) = request.inline_source {
             sources.push(inline.clone());
@@ -529,7 +546,7 @@ impl FileResolver {
         let before_lang = files.len();
         // Filter by supported language directly on NormalizedPath — no
         // String round-trip needed.
-        files.retain(|f| detect_language(f.as_str()) != "unknown");
+        files.retain(|f| self.languages.detect(f.as_str()) != "unknown");
         if self.verbose && files.len() != before_lang {
             eprintln!("  files: {} file(s) after language filter (was {})", files.len(), before_lang);
         }
@@ -607,7 +624,7 @@ impl FileResolver {
 
 /// Resolve relative glob patterns to absolute by prepending `base_dir`.
 /// Absolute patterns are passed through unchanged.
-fn resolve_globs_to_absolute(base_dir: &Option<PathBuf>, patterns: &[String]) -> Vec<String> {
+pub(super) fn resolve_globs_to_absolute(base_dir: &Option<PathBuf>, patterns: &[String]) -> Vec<String> {
     if let Some(base) = base_dir {
         patterns.iter().map(|g| {
             if Path::new(g).is_absolute() {
@@ -801,6 +818,8 @@ mod tests {
             base_dir: Some(std::path::PathBuf::from(".")),  // config mode
             max_files: 1000,
             global_diff_lines: None,
+            languages: LanguageMap::default(),
+            encodings: EncodingMap::default(),
         };
 
         let mut builder = tractor::ReportBuilder::new();
@@ -835,6 +854,8 @@ mod tests {
             base_dir: None,  // non-config mode
             max_files: 1000,
             global_diff_lines: None,
+            languages: LanguageMap::default(),
+            encodings: EncodingMap::default(),
         };
 
         let mut builder = tractor::ReportBuilder::new();
@@ -873,6 +894,8 @@ mod tests {
             base_dir: Some(std::path::PathBuf::from(".")),
             max_files: 1000,
             global_diff_lines: None,
+            languages: LanguageMap::default(),
+            encodings: EncodingMap::default(),
         };
 
         let mut builder = tractor::ReportBuilder::new();
@@ -0,0 +1,130 @@
+//! Language resolution for disk sources.
+//!
+//! A path resolves, in order, through:
+//!
+//! 1. the config's `languages:` overrides (`Jenkinsfile`, `tools/*`),
+//! 2. the built-in extension table (`detect_language`),
+//! 3. a shebang or editor modeline in the file itself.
+//!
+//! The last step reads the start of the file, so it only runs for paths the
+//! first two could not place. Among overlapping `languages:` patterns the
+//! first one written wins.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use tractor::{detect_language, detect_language_from_content, CompiledPattern};
+
+use crate::tractor_config::LanguageMapping;
+
+use super::file_resolver::resolve_globs_to_absolute;
+
+/// Bytes read from the start of an unrecognized file when sniffing for a
+/// shebang or modeline.
+const SNIFF_BYTES: u64 = 1024;
+
+/// Compiled `languages:` overrides plus the built-in fallbacks.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageMap {
+    /// Patterns matched against the full path (those containing `/`).
+    path_patterns: Vec<(CompiledPattern, String)>,
+    /// Patterns matched against the file name only.
+    name_patterns: Vec<(CompiledPattern, String)>,
+}
+
+impl LanguageMap {
+    /// Compile config mappings. Path patterns are anchored at `base_dir`
+    /// like every other config glob.
+    pub fn new(mappings: &[LanguageMapping], base_dir: &Option<PathBuf>) -> Result<Self, String> {
+        let mut map = LanguageMap::default();
+        for mapping in mappings {
+            let is_path = mapping.pattern.contains('/');
+            let pattern = if is_path {
+                resolve_globs_to_absolute(base_dir, std::slice::from_ref(&mapping.pattern)).remove(0)
+            } else {
+                mapping.pattern.clone()
+            };
+            let compiled = CompiledPattern::new(&pattern)
+                .map_err(|e| format!("invalid `languages` pattern `{}`: {}", mapping.pattern, e))?;
+            let entry = (compiled, mapping.language.clone());
+            if is_path {
+                map.path_patterns.push(entry);
+            } else {
+                map.name_patterns.push(entry);
+            }
+        }
+        Ok(map)
+    }
+
+    /// The language for `path`, or `"unknown"`. Path patterns win over
+    /// file-name patterns, which win over the extension table.
+    pub fn detect(&self, path: &str) -> String {
+        if let Some(lang) = self.configured(path) {
+            return lang.to_string();
+        }
+        match detect_language(path) {
+            "unknown" => sniff_language(path).unwrap_or("unknown").to_string(),
+            lang => lang.to_string(),
+        }
+    }
+
+    fn configured(&self, path: &str) -> Option<&str> {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        self.path_patterns.iter()
+            .find(|(p, _)| p.matches(path))
+            .or_else(|| self.name_patterns.iter().find(|(p, _)| p.matches(file_name)))
+            .map(|(_, lang)| lang.as_str())
+    }
+}
+
+/// Read the head of `path` and look for a shebang or modeline. Unreadable
+/// files simply stay unknown.
+fn sniff_language(path: &str) -> Option<&'static str> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut bytes = Vec::new();
+    file.take(SNIFF_BYTES).read_to_end(&mut bytes).ok()?;
+    detect_language_from_content(&String::from_utf8_lossy(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(pattern: &str, language: &str) -> LanguageMapping {
+        LanguageMapping { pattern: pattern.into(), language: language.into() }
+    }
+
+    #[test]
+    fn config_mappings_take_precedence() {
+        let map = LanguageMap::new(
+            &[mapping("*.h", "cpp"), mapping("Jenkinsfile", "bash"), mapping("tools/*", "python")],
+            &Some(PathBuf::from("/repo")),
+        ).unwrap();
+        assert_eq!(map.detect("/repo/include/foo.h"), "cpp");
+        assert_eq!(map.detect("/repo/ci/Jenkinsfile"), "bash");
+        assert_eq!(map.detect("/repo/tools/release"), "python");
+        assert_eq!(map.detect("/repo/src/main.rs"), "rust");
+    }
+
+    #[test]
+    fn first_overlapping_mapping_wins() {
+        let base = Some(PathBuf::from("/repo"));
+        let map = LanguageMap::new(&[mapping("*.h", "cpp"), mapping("legacy*", "c")], &base).unwrap();
+        assert_eq!(map.detect("/repo/legacy.h"), "cpp");
+        let map = LanguageMap::new(&[mapping("legacy*", "c"), mapping("*.h", "cpp")], &base).unwrap();
+        assert_eq!(map.detect("/repo/legacy.h"), "c");
+    }
+
+    #[test]
+    fn unknown_extension_falls_back_to_shebang() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("deploy");
+        std::fs::write(&script, "#!/usr/bin/env python3\nprint('hi')\n").unwrap();
+        let notes = dir.path().join("NOTES");
+        std::fs::write(&notes, "just text\n").unwrap();
+
+        let map = LanguageMap::default();
+        assert_eq!(map.detect(script.to_str().unwrap()), "python");
+        assert_eq!(map.detect(notes.to_str().unwrap()), "unknown");
+    }
+}
@@ -1,19 +1,22 @@
 pub mod git;
 pub mod filter;
 pub mod file_resolver;
+pub mod encoding_map;
+pub mod language_map;
 pub mod plan;
 pub mod source;
 
 #[allow(unused_imports)]
 pub use source::{Source, SourceContent, SourceDisposition, PATHLESS_LABEL};
 pub use file_resolver::{FileResolver, SourceRequest, ResolverOptions, make_fatal_diagnostic};
+pub use language_map::LanguageMap;
 #[allow(unused_imports)]
 pub use plan::{plan_multi, plan_single, ExecutionPlan, MultiOpRequest, Operation, SingleOpRequest};
 
 use std::io::{self, BufRead, Read};
 use std::sync::Arc;
 
-use tractor::{detect_language, expand_globs_checked, NormalizedPath};
+use tractor::{expand_globs_checked, NormalizedPath};
 
 use crate::cli::SharedArgs;
 
@@ -43,10 +46,13 @@ pub enum InputMode {
 /// Validation: in inline mode we accept **at most one** positional path. That
 /// single path becomes the virtual path for glob matching, diff-lines, and
 /// diagnostics. Multiple paths would be ambiguous — reject with a clear error.
+///
+/// In disk mode, files `languages` can't place are dropped.
 pub fn resolve_input(
     shared: &SharedArgs,
     files: Vec<String>,
     content: Option<String>,
+    languages: &LanguageMap,
 ) -> Result<InputMode, Box<dyn std::error::Error>> {
     let expansion_limit = shared.max_files * 10;
 
@@ -97,7 +103,7 @@ pub fn resolve_input(
             }
         }
     }
-    files.retain(|f| detect_language(f) != "unknown");
+    files.retain(|f| languages.detect(f) != "unknown");
     Ok(InputMode::Files(files))
 }
 
@@ -28,7 +28,7 @@ use crate::cli::context::ExecCtx;
 use crate::executor::{
     CheckOperationPlan, OperationPlan, QueryOperation, SetOperation, TestOperation, UpdateOperation,
 };
-use crate::tractor_config::{CheckOperation, ConfigOperation, OperationInputs};
+use crate::tractor_config::{CheckOperation, ConfigOperation, EncodingMapping, LanguageMapping, OperationInputs};
 
 use super::{FileResolver, ResolverOptions, Source, SourceRequest};
 
@@ -137,6 +137,8 @@ fn build_resolver(
     max_files: usize,
     cli_files: Vec<String>,
     config_root_files: Option<Vec<String>>,
+    languages: Vec<LanguageMapping>,
+    encodings: Vec<EncodingMapping>,
     env: &ExecCtx<'_>,
 ) -> Result<FileResolver, String> {
     let resolver_opts = ResolverOptions {
@@ -145,6 +147,8 @@ fn build_resolver(
         max_files,
         cli_files,
         config_root_files,
+        languages,
+        encodings,
     };
     FileResolver::new(&resolver_opts, env)
 }
@@ -208,6 +212,8 @@ pub fn plan_single(
         max_files,
         Vec::new(),
         None,
+        Vec::new(),
+        Vec::new(),
         env,
     )
     .map_err(|e| -> Box<dyn std::error::Error> { e.into() })?;
@@ -232,6 +238,10 @@ pub struct MultiOpRequest {
     pub operations: Vec<ConfigOperation>,
     pub cli_files: Vec<String>,
     pub config_root_files: Option<Vec<String>>,
+    /// Root-level `languages:` overrides from the config.
+    pub languages: Vec<LanguageMapping>,
+    /// Root-level `encodings:` defaults from the config.
+    pub encodings: Vec<EncodingMapping>,
     pub shared_diff_files: Option<String>,
     pub shared_diff_lines: Option<String>,
     pub max_files: usize,
@@ -255,6 +265,8 @@ pub fn plan_multi(
         req.max_files,
         req.cli_files,
         req.config_root_files,
+        req.languages,
+        req.encodings,
         env,
     ) {
         Ok(r) => r,
@@ -24,8 +24,8 @@ use std::io;
 use std::path::Path;
 use std::sync::Arc;
 
-use tractor::tree_mode::TreeMode;
-use tractor::{parse, NormalizedPath, ParseInput, ParseOptions, XeeParseResult};
+use tractor::{parse, NormalizedPath, ParseInput, ParseOptions, SourceEncoding, XeeParseResult};
+use tractor::encoding::{self, Encoding};
 use tractor::parser::ParseError;
 
 // The sentinel for path-less sources lives in the library crate; re-export
@@ -42,12 +42,17 @@ pub struct Source {
     /// wrapped as a `NormalizedPath` so downstream code doesn't special-case.
     pub path: NormalizedPath,
 
-    /// Resolved language (from `-l` override or `detect_language(path)`).
+    /// Resolved language (from `-l` override or `LanguageMap::detect(path)`).
     /// Resolved once at the input boundary — downstream never re-detects.
     pub language: String,
 
     /// How to obtain the bytes.
     pub content: SourceContent,
+
+    /// Configured encoding for a disk file with no BOM that is not valid
+    /// UTF-8 (from `encodings:` in `tractor.yml`). Without one such a file
+    /// can't be read; see `tractor::encoding`.
+    pub encoding: Option<&'static Encoding>,
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +61,11 @@ pub enum SourceContent {
     /// for large file sets: no bytes materialize until the parallel worker
     /// actually parses the source.
     Disk,
+    /// A disk file whose text has already been read and decoded (e.g. by
+    /// the query prefilter), kept with how it is stored so parsing and
+    /// write-back don't read it again. Still a disk source in every other
+    /// respect: same disposition, encoding and language sniffing.
+    Read(Arc<String>, SourceEncoding),
     /// Content is already in memory (piped stdin or `-s/--string`).
     /// Wrapped in `Arc` so it can be cheaply cloned across workers and
     /// shared with the diff-builder without re-reading.
@@ -85,15 +95,31 @@ pub enum SourceDisposition {
 
 impl Source {
     /// Construct a disk source. Caller is expected to have already resolved
-    /// the language (via override or `detect_language`).
+    /// the language (via override or `LanguageMap::detect`).
     pub fn disk(path: NormalizedPath, language: impl Into<String>) -> Self {
         Self {
             path,
             language: language.into(),
             content: SourceContent::Disk,
+            encoding: None,
+        }
+    }
+
+    /// This disk source with `text` already read from it (as returned by
+    /// [`Source::read`]), so it isn't read again.
+    pub fn with_text(&self, text: String, encoding: SourceEncoding) -> Self {
+        Self {
+            content: SourceContent::Read(Arc::new(text), encoding),
+            ..self.clone()
         }
     }
 
+    /// Set the configured default encoding for a disk source.
+    pub fn with_encoding(mut self, encoding: Option<&'static Encoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Construct an inline source at the given virtual path.
     pub fn inline_at(
         path: NormalizedPath,
@@ -104,6 +130,7 @@ impl Source {
             path,
             language: language.into(),
             content: SourceContent::Inline(content.into()),
+            encoding: None,
         }
     }
 
@@ -118,6 +145,7 @@ impl Source {
             path: NormalizedPath::new(PATHLESS_LABEL),
             language: language.into(),
             content: SourceContent::Inline(content.into()),
+            encoding: None,
         }
     }
 
@@ -127,7 +155,7 @@ impl Source {
     /// call site actually cares about which of the three states applies.
     pub fn disposition(&self) -> SourceDisposition {
         match &self.content {
-            SourceContent::Disk => SourceDisposition::Disk,
+            SourceContent::Disk | SourceContent::Read(..) => SourceDisposition::Disk,
             SourceContent::Inline(_) => {
                 if self.path.as_str() == PATHLESS_LABEL {
                     SourceDisposition::InlinePathless
@@ -156,18 +184,28 @@ impl Source {
         matches!(self.disposition(), SourceDisposition::InlinePathless)
     }
 
-    /// Fetch the bytes to parse. Borrows from memory for inline sources;
-    /// reads from disk for `SourceContent::Disk`. Called inside the
-    /// parallel worker so laziness is preserved for the file flow.
-    pub fn read(&self) -> io::Result<Cow<'_, str>> {
+    /// Fetch the text to parse, and how it is stored so a modified version
+    /// can be written back the same way. Borrows from memory for inline
+    /// sources (always UTF-8); reads and decodes from disk for
+    /// `SourceContent::Disk`. Called inside the parallel worker so laziness
+    /// is preserved for the file flow.
+    pub fn read(&self) -> io::Result<(Cow<'_, str>, SourceEncoding)> {
         match &self.content {
             SourceContent::Disk => {
-                std::fs::read_to_string(Path::new(self.path.as_str())).map(Cow::Owned)
+                encoding::read_file(Path::new(self.path.as_str()), self.encoding)
+                    .map(|(text, encoding)| (Cow::Owned(text), encoding))
             }
-            SourceContent::Inline(s) => Ok(Cow::Borrowed(s.as_str())),
+            SourceContent::Read(s, encoding) => Ok((Cow::Borrowed(s.as_str()), *encoding)),
+            SourceContent::Inline(s) => Ok((Cow::Borrowed(s.as_str()), SourceEncoding::UTF8)),
         }
     }
 
+    /// Write modified text back to a disk source in `encoding` (as returned
+    /// by [`Source::read`]).
+    pub fn write(&self, text: &str, encoding: SourceEncoding) -> io::Result<()> {
+        encoding::write_file(Path::new(self.path.as_str()), text, encoding)
+    }
+
     /// The path as a `&str`, for places that still accept a bare path label
     /// (e.g. `parse` inline inputs, diagnostic `ReportMatch.file`).
     pub fn path_str(&self) -> &str {
@@ -178,7 +216,7 @@ impl Source {
     pub fn inline_content(&self) -> Option<&str> {
         match &self.content {
             SourceContent::Inline(s) => Some(s.as_str()),
-            SourceContent::Disk => None,
+            SourceContent::Disk | SourceContent::Read(..) => None,
         }
     }
 
@@ -190,35 +228,30 @@ impl Source {
     /// disk reads and `file_label` propagation on inline reads are preserved
     /// by the unified function.
     ///
-    /// `lang_override` lets the caller (e.g. `run_rules`) substitute a
-    /// rule-level language override for the source's default language.
-    pub fn parse(
-        &self,
-        lang_override: Option<&str>,
-        tree_mode: Option<TreeMode>,
-        ignore_whitespace: bool,
-        parse_depth: Option<usize>,
-    ) -> Result<XeeParseResult, ParseError> {
-        let lang = lang_override.unwrap_or(&self.language);
+    /// `options.language` lets the caller (e.g. `run_rules`) substitute a
+    /// rule-level language override for the source's default language; the
+    /// remaining options pass through unchanged.
+    pub fn parse(&self, options: ParseOptions<'_>) -> Result<XeeParseResult, ParseError> {
+        let lang = options.language.unwrap_or(&self.language);
         let path_str = self.path.as_str();
         let input = match &self.content {
             SourceContent::Disk => ParseInput::Disk {
                 path: Path::new(path_str),
             },
+            SourceContent::Read(text, _) => ParseInput::Read {
+                path: Path::new(path_str),
+                content: text.as_str(),
+            },
             SourceContent::Inline(content) => ParseInput::Inline {
                 content: content.as_str(),
                 file_label: path_str,
             },
         };
-        parse(
-            input,
-            ParseOptions {
-                language: Some(lang),
-                tree_mode,
-                ignore_whitespace,
-                parse_depth,
-            },
-        )
+        parse(input, ParseOptions {
+            language: Some(lang),
+            encoding: options.encoding.or(self.encoding),
+            ..options
+        })
     }
 }
 
@@ -293,8 +326,33 @@ mod tests {
             "text",
             Arc::new("hello".to_string()),
         );
-        let cow = s.read().unwrap();
+        let (cow, encoding) = s.read().unwrap();
         assert_eq!(cow.as_ref(), "hello");
         assert!(matches!(cow, Cow::Borrowed(_)));
+        assert_eq!(encoding, SourceEncoding::UTF8);
+    }
+
+    #[test]
+    fn disk_read_decodes_and_write_preserves_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Legacy.cs");
+        // "// café" in Windows-1252
+        std::fs::write(&path, b"// caf\xE9").unwrap();
+        let disk = || Source::disk(NormalizedPath::absolute(path.to_str().unwrap()), "csharp");
+
+        // A configured encoding wins over the guess
+        let configured = disk().with_encoding(encoding::lookup("iso-8859-15"));
+        let (text, encoding) = configured.read().unwrap();
+        assert_eq!(text.as_ref(), "// café");
+        assert_eq!(encoding.name(), "ISO-8859-15");
+
+        // Otherwise the text is read as the Windows-1252 it most likely is
+        let s = disk();
+        let (text, encoding) = s.read().unwrap();
+        assert_eq!(text.as_ref(), "// café");
+        assert_eq!(encoding.name(), "windows-1252");
+
+        s.write("// cafés", encoding).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"// caf\xE9s");
     }
 }
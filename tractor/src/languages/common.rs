@@ -0,0 +1,242 @@
+//! Common vocabulary (`-t common`): one set of element names for all languages
+//!
+//! The structure tree keeps each language's own terms: a C# `method`, a Java
+//! `ctor`, a Go `param`, a Rust `line_comment`. The common tree renames the
+//! concepts every programming language shares, so one rule covers them all:
+//!
+//! | Element     | Covers                                                    |
+//! |-------------|-----------------------------------------------------------|
+//! | `function`  | function, method and constructor declarations             |
+//! | `class`     | class declarations (not structs, interfaces or traits)    |
+//! | `call`      | function and method calls                                 |
+//! | `import`    | imports, `using` directives, `use` declarations           |
+//! | `parameter` | one declared parameter, a direct child of its `function`  |
+//! | `string`    | string literals, including interpolated ones              |
+//! | `comment`   | line, block and doc comments                              |
+//!
+//! Queryable as: `//function[count(parameter) > 5]`
+//!
+//! Nodes are matched by their TreeSitter `kind`, so the pass runs after the
+//! language transform and does not depend on its naming. Everything else is
+//! left as the structure tree has it.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::helpers::*;
+
+/// Element names that hold a function's parameter list, across languages
+/// (Dart's optional parameters sit in a list of their own inside it)
+const PARAMETER_LISTS: &[&str] = &["parameters", "params", "method_parameters", "optional_formal_parameters"];
+
+/// Rename the shared concepts under `root` to the common vocabulary.
+pub fn normalize(xot: &mut Xot, root: XotNode, lang: &str) -> Result<(), xot::Error> {
+    let elements: Vec<XotNode> = xot.descendants(root)
+        .filter(|&n| xot.element(n).is_some())
+        .collect();
+
+    let mut functions = Vec::new();
+    for node in elements {
+        let Some(kind) = get_kind(xot, node) else {
+            // TypeScript wraps call targets in a kindless <function>; in the
+            // common tree `function` is only ever a declaration
+            if get_element_name(xot, node).as_deref() == Some("function") {
+                rename(xot, node, "callee");
+            }
+            continue;
+        };
+        if let Some(name) = common_name(lang, &kind).filter(|_| covers_concept(xot, node, lang, &kind)) {
+            rename(xot, node, name);
+            if name == "function" {
+                functions.push(node);
+            }
+        } else if is_bare_parameter(xot, node, lang, &kind) {
+            wrap(xot, node, "parameter")?;
+        }
+    }
+
+    for function in functions {
+        let lists: Vec<XotNode> = get_element_children(xot, function)
+            .into_iter()
+            .filter(|&c| is_parameter_list(xot, c))
+            .collect();
+        for list in lists {
+            flatten_parameter_list(xot, list)?;
+        }
+    }
+    Ok(())
+}
+
+/// The common element name for a TreeSitter kind, if it is a shared concept.
+fn common_name(lang: &str, kind: &str) -> Option<&'static str> {
+    let name = match lang {
+        "csharp" | "cs" => match kind {
+            "method_declaration" | "constructor_declaration" | "local_function_statement" => "function",
+            "class_declaration" | "record_declaration" => "class",
+            "invocation_expression" => "call",
+            "using_directive" => "import",
+            "parameter" => "parameter",
+            "string_literal" | "verbatim_string_literal" | "raw_string_literal"
+            | "interpolated_string_expression" => "string",
+            "comment" => "comment",
+            _ => return None,
+        },
+        "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" => match kind {
+            "function_declaration" | "generator_function_declaration" | "method_definition" => "function",
+            "class_declaration" | "abstract_class_declaration" => "class",
+            "call_expression" => "call",
+            "import_statement" => "import",
+            "required_parameter" | "optional_parameter" => "parameter",
+            "string" | "template_string" => "string",
+            "comment" => "comment",
+            _ => return None,
+        },
+        "python" | "py" => match kind {
+            "function_definition" => "function",
+            "class_definition" => "class",
+            "call" => "call",
+            "import_statement" | "import_from_statement" => "import",
+            "typed_parameter" | "default_parameter" | "typed_default_parameter" => "parameter",
+            "string" => "string",
+            "comment" => "comment",
+            _ => return None,
+        },
+        "go" => match kind {
+            "function_declaration" | "method_declaration" => "function",
+            "call_expression" => "call",
+            "import_spec" => "import",
+            "parameter_declaration" | "variadic_parameter_declaration" => "parameter",
+            "interpreted_string_literal" | "raw_string_literal" => "string",
+            "comment" => "comment",
+            _ => return None,
+        },
+        "rust" | "rs" => match kind {
+            "function_item" | "function_signature_item" => "function",
+            "call_expression" => "call",
+            "use_declaration" => "import",
+            "parameter" | "self_parameter" => "parameter",
+            "string_literal" | "raw_string_literal" => "string",
+            "line_comment" | "block_comment" => "comment",
+            _ => return None,
+        },
+        "java" => match kind {
+            "method_declaration" | "constructor_declaration" => "function",
+            "class_declaration" | "record_declaration" => "class",
+            "method_invocation" => "call",
+            "import_declaration" => "import",
+            "formal_parameter" | "spread_parameter" => "parameter",
+            "string_literal" => "string",
+            "line_comment" | "block_comment" => "comment",
+            _ => return None,
+        },
+        "ruby" | "rb" => match kind {
+            "method" | "singleton_method" => "function",
+            "class" => "class",
+            "call" => "call",
+            "optional_parameter" | "keyword_parameter" | "splat_parameter"
+            | "hash_splat_parameter" | "block_parameter" => "parameter",
+            "string" => "string",
+            "comment" => "comment",
+            _ => return None,
+        },
+        "kotlin" | "kt" => match kind {
+            "function_declaration" | "primary_constructor" | "secondary_constructor" => "function",
+            "class_declaration" => "class",
+            "call_expression" => "call",
+            "import" => "import",
+            "parameter" | "class_parameter" => "parameter",
+            "string_literal" | "multiline_string_literal" => "string",
+            "line_comment" | "block_comment" => "comment",
+            _ => return None,
+        },
+        "swift" => match kind {
+            "function_declaration" | "protocol_function_declaration" | "init_declaration" => "function",
+            "class_declaration" => "class",
+            "call_expression" => "call",
+            "import_declaration" => "import",
+            "parameter" => "parameter",
+            "line_string_literal" | "multi_line_string_literal" | "raw_string_literal" => "string",
+            "comment" | "multiline_comment" => "comment",
+            _ => return None,
+        },
+        "dart" => match kind {
+            "function_declaration" | "method_declaration" | "declaration"
+            | "function_signature" | "constructor_signature" | "factory_constructor_signature" => "function",
+            "class_declaration" => "class",
+            "call_expression" | "cascade_call_expression" => "call",
+            "import_specification" => "import",
+            "formal_parameter" => "parameter",
+            "string_literal" => "string",
+            "comment" | "block_comment" | "documentation_comment" => "comment",
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Whether a node of a kind that declares more than the concept is one of
+/// it. Kotlin and Swift declare every type with `class_declaration`, and
+/// Dart every class member with `declaration` or `method_declaration`; the
+/// language transform has already named these after their keyword or
+/// signature, so that name decides.
+fn covers_concept(xot: &Xot, node: XotNode, lang: &str, kind: &str) -> bool {
+    let name = get_element_name(xot, node);
+    match (lang, kind) {
+        ("kotlin" | "kt" | "swift", "class_declaration") => name.as_deref() == Some("class"),
+        ("dart", "declaration" | "method_declaration") => matches!(name.as_deref(), Some("method" | "constructor")),
+        _ => true,
+    }
+}
+
+/// Parameters that are just a name (`def f(a, b)`, `function f(a, b)`) or a
+/// pattern (`*args`, `{ a, b }`), directly inside a parameter list. These
+/// kinds are used outside parameter lists too, so they are wrapped rather
+/// than renamed.
+fn is_bare_parameter(xot: &Xot, node: XotNode, lang: &str, kind: &str) -> bool {
+    let Some(list) = get_parent(xot, node).and_then(|p| get_kind(xot, p)) else {
+        return false;
+    };
+    match lang {
+        "python" | "py" => list == "parameters"
+            && matches!(kind, "identifier" | "list_splat_pattern" | "dictionary_splat_pattern"),
+        "ruby" | "rb" => list == "method_parameters" && kind == "identifier",
+        "javascript" | "js" | "jsx" => list == "formal_parameters" && matches!(
+            kind,
+            "identifier" | "assignment_pattern" | "rest_pattern" | "object_pattern" | "array_pattern"
+        ),
+        _ => false,
+    }
+}
+
+/// A function's parameter list. Go's receiver and named results are
+/// parameter lists too, but in their own `receiver` and `result` fields.
+fn is_parameter_list(xot: &Xot, node: XotNode) -> bool {
+    is_list_named(xot, node)
+        && !matches!(get_attr(xot, node, "field").as_deref(), Some("receiver" | "result"))
+}
+
+fn is_list_named(xot: &Xot, node: XotNode) -> bool {
+    get_element_name(xot, node).is_some_and(|name| PARAMETER_LISTS.contains(&name.as_str()))
+}
+
+/// Move the parameters of a (possibly nested) parameter list up into the
+/// function, so they are its direct children.
+fn flatten_parameter_list(xot: &mut Xot, list: XotNode) -> Result<(), xot::Error> {
+    let nested: Vec<XotNode> = get_element_children(xot, list)
+        .into_iter()
+        .filter(|&c| is_list_named(xot, c))
+        .collect();
+    for inner in nested {
+        flatten_parameter_list(xot, inner)?;
+    }
+    flatten_node(xot, list)
+}
+
+/// Wrap `node` in a new element with the same location.
+fn wrap(xot: &mut Xot, node: XotNode, name: &str) -> Result<(), xot::Error> {
+    let name_id = get_name(xot, name);
+    let wrapper = xot.new_element(name_id);
+    copy_source_location(xot, node, wrapper);
+    xot.insert_before(node, wrapper)?;
+    xot.detach(node)?;
+    xot.append(wrapper, node)
+}
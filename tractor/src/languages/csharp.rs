@@ -56,6 +56,23 @@ pub mod semantic {
     pub const LEADING: &str = "leading";
 }
 
+/// Element names the transform produces that are neither tree-sitter node
+/// kinds nor field names: the semantic names above, the plain renames in
+/// `map_element_name`, and the markers. Modifiers are listed separately in
+/// [`ACCESS_MODIFIERS`] and [`OTHER_MODIFIERS`].
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    UNIT, NAMESPACE, IMPORT, BODY,
+    CLASS, STRUCT, INTERFACE, ENUM, RECORD,
+    METHOD, CONSTRUCTOR, PROPERTY, FIELD, COMMENT,
+    NAME, TYPE, ACCESSORS, ACCESSOR, ATTRIBUTES, ATTRIBUTE, ARGUMENTS, ARGUMENT,
+    PARAMETERS, PARAMETER, VARIABLE, DECLARATOR,
+    NULLABLE, GENERIC, TRAILING, LEADING,
+    "array", "block", "return", "if", "else", "for", "foreach", "while", "try",
+    "catch", "throw", "using", "call", "member", "new", "assign", "binary", "unary",
+    "ternary", "lambda", "await", "local", "string", "int", "float", "bool", "null",
+    "this",
+];
+
 /// Check if kind is a declaration that has a name child
 /// Uses original TreeSitter kinds (from `kind` attribute) for robust detection
 fn is_named_declaration(kind: &str) -> bool {
@@ -747,6 +764,47 @@ public class Foo {
         assert!(xml.contains("<public"), "public modifier should be extracted");
     }
 
+    #[test]
+    fn test_transform_elements_cover_transformed_tree() {
+        let source = r#"
+namespace App;
+using System;
+
+/// <summary>Does things</summary>
+public sealed class Foo : Bar, IBaz {
+    private readonly int count = 0;
+    public string Name { get; set; }
+
+    public async Task<int> Run(int x, string y = "a") {
+        var items = new int[] { 1, 2 };
+        foreach (var i in items) { x += i; }
+        for (int j = 0; j < 3; j++) { x--; }
+        while (x > 0 && !done) { x = x - 1; }
+        if (x is null) { return -1; } else if (x == 2) { throw new Exception(); }
+        try { await Task.Delay(1); } catch (Exception e) { Console.WriteLine($"{e}"); }
+        using var r = this.Open();
+        Func<int, int> f = n => n * 2;
+        return x > 0 ? f(x) : count;
+    }
+}
+
+public interface IBaz { void Go(); }
+public enum Kind { A, B }
+public record Point(int X, int Y);
+"#;
+        let result = parse_string_to_xot(source, "csharp", "<test>".to_string(), None).unwrap();
+        let known = crate::parser::element_vocabulary("csharp", None, false).unwrap();
+
+        let mut missing: Vec<String> = result.xot.descendants(result.root)
+            .filter_map(|node| result.xot.element(node))
+            .map(|element| result.xot.local_name_str(element.name()).to_string())
+            .filter(|name| !known.contains(name.as_str()))
+            .collect();
+        missing.sort();
+        missing.dedup();
+        assert!(missing.is_empty(), "element names missing from TRANSFORM_ELEMENTS: {:?}", missing);
+    }
+
     // =========================================================================
     // Comment attachment tests
     // =========================================================================
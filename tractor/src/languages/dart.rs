@@ -0,0 +1,509 @@
+//! Dart transform logic
+//!
+//! Dart has no access keywords: identifiers starting with `_` are
+//! library-private. Declarations get a `<private/>` or `<public/>` marker
+//! derived from the name, mirroring Go's exported/unexported markers.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers and
+/// modifiers, including `<public/>` and `<private/>`.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "library", "import", "export", "part", "class", "mixin", "extension", "enum",
+    "function", "variable", "constant", "parameter", "argument", "name", "type", "nullable",
+    "body", "public", "private", "static", "abstract", "external", "factory", "const",
+    "final", "late", "sealed", "async", "generator", "return", "if", "for", "while", "do",
+    "switch", "try", "catch", "finally", "throw", "yield", "await", "call", "member",
+    "lambda", "assign", "binary", "unary", "logical", "ternary", "string", "interpolation",
+    "int", "float", "true", "false", "null", "comment",
+];
+
+/// Transform a Dart AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_element_name(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        "expression_statement" | "class_member" | "import_or_export" | "library_import"
+        | "configurable_uri" | "method_signature" | "initialized_identifier_list"
+        | "initialized_identifier" | "static_final_declaration_list"
+        | "static_final_declaration" | "initialized_variable_definition"
+        | "assignable_expression" | "constructor_param" | "dotted_identifier_list"
+        | "block_comment" => Ok(TransformAction::Skip),
+
+        // Bodies sit in a builder-created <body> wrapper; drop the grammar's
+        // own body and block nodes so statements are the wrapper's children.
+        "class_body" | "extension_body" | "enum_body" | "function_body"
+        | "function_expression_body" | "block" => Ok(TransformAction::Flatten),
+
+        // The builder wraps field="arguments" and field="parameters" in an
+        // element of the same name; drop the grammar node inside it.
+        "arguments" | "formal_parameter_list" if get_kind(xot, node).as_deref() == Some(kind.as_str()) => {
+            Ok(TransformAction::Flatten)
+        }
+
+        // Name wrappers created by the builder for field="name".
+        // Inline the single identifier child as text:
+        //   <name><identifier>foo</identifier></name> -> <name>foo</name>
+        "name" | "type" => {
+            inline_single_identifier(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // The signature's children (name, parameters, return type) belong to
+        // the declaration that owns the body
+        "function_signature" | "getter_signature" | "setter_signature" | "operator_signature"
+        | "constructor_signature" | "factory_constructor_signature"
+        | "redirecting_factory_constructor_signature" | "constant_constructor_signature"
+            if parent_kind_is(xot, node, &["function_declaration", "method_declaration", "method_signature", "declaration"]) =>
+        {
+            Ok(TransformAction::Skip)
+        }
+
+        // Methods, getters, setters and constructors: named after their signature
+        "method_declaration" => {
+            let name = signature_kind(xot, node).map_or("method", |k| member_name(&k));
+            declare(xot, node, name)?;
+            Ok(TransformAction::Continue)
+        }
+        // A class member without a body: an abstract or external method, a
+        // constructor, or a field
+        "declaration" => {
+            let name = signature_kind(xot, node).map_or("field", |k| member_name(&k));
+            declare(xot, node, name)?;
+            Ok(TransformAction::Continue)
+        }
+        "class_declaration" | "mixin_declaration" | "extension_declaration" | "enum_declaration"
+        | "function_declaration" | "top_level_variable_declaration" => {
+            let name = map_element_name(&kind).unwrap_or("function");
+            declare(xot, node, name)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // `on FormatException catch (e) { ... }` — the grammar leaves the
+        // exception type and the handler block as siblings of the clause
+        "try_statement" => {
+            group_catch_clauses(xot, node)?;
+            rename(xot, node, "try");
+            Ok(TransformAction::Continue)
+        }
+
+        // Keyword nodes the grammar exposes as named tokens
+        "final_builtin" | "const_builtin" | "external" => {
+            let marker = kind.trim_end_matches("_builtin").to_string();
+            rename_to_marker(xot, node, &marker)?;
+            Ok(TransformAction::Done)
+        }
+
+        // `await x` and `!x` both parse as unary expressions; only the
+        // latter carries an operator
+        "unary_expression" if !has_operator(xot, node) => Ok(TransformAction::Skip),
+
+        "additive_expression" | "multiplicative_expression" | "relational_expression"
+        | "equality_expression" | "logical_and_expression" | "logical_or_expression"
+        | "bitwise_and_expression" | "bitwise_or_expression" | "bitwise_xor_expression"
+        | "shift_expression" | "if_null_expression" | "unary_expression"
+        | "postfix_expression" | "assignment_expression" => {
+            extract_operator(xot, node)?;
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+        // Operator tokens the grammar wraps in their own node; extracted above
+        _ if kind.ends_with("_operator") && kind != "is_operator" => Ok(TransformAction::Skip),
+
+        // Identifiers are always names; type positions use `type_identifier`.
+        "identifier" | "identifier_dollar_escaped" => {
+            rename(xot, node, "name");
+            Ok(TransformAction::Continue)
+        }
+        "type_identifier" | "void_type" => {
+            rename(xot, node, "type");
+            Ok(TransformAction::Continue)
+        }
+
+        _ if kind.starts_with("string_literal_") || kind.starts_with("template_chars_") => {
+            Ok(TransformAction::Skip)
+        }
+
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Rename a declaration and prepend its modifier and visibility markers
+fn declare(xot: &mut Xot, node: XotNode, name: &str) -> Result<(), xot::Error> {
+    let marker = visibility_marker(xot, node);
+    prepend_empty_element(xot, node, marker)?;
+    for modifier in declaration_modifiers(xot, node).into_iter().rev() {
+        prepend_empty_element(xot, node, modifier)?;
+    }
+    rename(xot, node, name);
+    Ok(())
+}
+
+/// Check the parent's original TreeSitter kind (it may already be renamed)
+fn parent_kind_is(xot: &Xot, node: XotNode, kinds: &[&str]) -> bool {
+    get_parent(xot, node)
+        .and_then(|p| get_kind(xot, p))
+        .is_some_and(|k| kinds.contains(&k.as_str()))
+}
+
+/// The kind of the signature inside a method or member declaration
+/// (`function_signature`, `getter_signature`, `constructor_signature`, ...)
+fn signature_kind(xot: &Xot, node: XotNode) -> Option<String> {
+    for child in get_element_children(xot, node) {
+        let kind = get_kind(xot, child).unwrap_or_default();
+        if kind == "method_signature" {
+            return signature_kind(xot, child);
+        }
+        if kind.ends_with("_signature") {
+            return Some(kind);
+        }
+    }
+    None
+}
+
+/// Determine public/private from the declaration's name
+fn visibility_marker(xot: &Xot, node: XotNode) -> &'static str {
+    let name = find_declared_name(xot, node).unwrap_or_default();
+    if name.starts_with('_') { "private" } else { "public" }
+}
+
+/// Find the declared name: a `name` wrapper or identifier child, looking
+/// through signatures and variable lists.
+fn find_declared_name(xot: &Xot, node: XotNode) -> Option<String> {
+    for child in get_element_children(xot, node) {
+        match get_element_name(xot, child).as_deref() {
+            Some("name") | Some("identifier") => return Some(xot.string_value(child)),
+            Some(k) if k.ends_with("_signature") || is_variable_list(k) => {
+                if let Some(name) = find_declared_name(xot, child) {
+                    return Some(name);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_variable_list(kind: &str) -> bool {
+    matches!(
+        kind,
+        "initialized_identifier_list" | "initialized_identifier"
+            | "static_final_declaration_list" | "static_final_declaration"
+    )
+}
+
+/// Modifier keywords of a declaration. The grammar leaves them as anonymous
+/// text: `static` on the method signature, `factory` on the constructor
+/// signature, `async` and `sync*` on the function body.
+fn declaration_modifiers(xot: &Xot, node: XotNode) -> Vec<&'static str> {
+    let mut texts = get_text_children(xot, node);
+    for child in get_element_children(xot, node) {
+        let kind = get_kind(xot, child).unwrap_or_default();
+        if kind.ends_with("_signature") {
+            texts.extend(get_text_children(xot, child));
+            for inner in get_element_children(xot, child) {
+                if get_kind(xot, inner).is_some_and(|k| k.ends_with("_signature")) {
+                    texts.extend(get_text_children(xot, inner));
+                }
+            }
+        }
+        // The body sits in a <body> wrapper around the function_body node
+        if get_element_name(xot, child).as_deref() == Some("body") {
+            for inner in get_element_children(xot, child) {
+                if get_kind(xot, inner).as_deref() == Some("function_body") {
+                    texts.extend(get_text_children(xot, inner));
+                }
+            }
+        }
+    }
+
+    let mut modifiers = Vec::new();
+    for word in texts.iter().flat_map(|t| t.split_whitespace()) {
+        let found: &[&'static str] = match word {
+            "static" => &["static"],
+            "abstract" => &["abstract"],
+            "external" => &["external"],
+            "factory" => &["factory"],
+            "const" => &["const"],
+            "final" => &["final"],
+            "late" => &["late"],
+            "sealed" => &["sealed"],
+            "async" => &["async"],
+            "async*" => &["async", "generator"],
+            "sync*" => &["generator"],
+            _ => continue,
+        };
+        for &modifier in found {
+            if !modifiers.contains(&modifier) {
+                modifiers.push(modifier);
+            }
+        }
+    }
+    modifiers
+}
+
+/// Move each catch clause's handler block into a `<body>` inside the clause,
+/// and the `on` type that precedes it into the clause as well.
+fn group_catch_clauses(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let clauses: Vec<_> = get_element_children(xot, node)
+        .into_iter()
+        .filter(|&c| get_kind(xot, c).as_deref() == Some("catch_clause"))
+        .collect();
+    for clause in clauses {
+        let previous = xot.preceding_siblings(clause)
+            .skip(1)
+            .find(|&s| xot.element(s).is_some());
+        if let Some(on_type) = previous.filter(|&s| get_kind(xot, s).as_deref() == Some("type")) {
+            xot.detach(on_type)?;
+            xot.prepend(clause, on_type)?;
+        }
+        let next = get_following_siblings(xot, clause).into_iter().nth(1);
+        if let Some(block) = next.filter(|&s| get_kind(xot, s).as_deref() == Some("block")) {
+            let body_name = xot.add_name("body");
+            let body = xot.new_element(body_name);
+            copy_source_location(xot, block, body);
+            xot.detach(block)?;
+            xot.append(body, block)?;
+            xot.append(clause, body)?;
+        }
+    }
+    Ok(())
+}
+
+/// The element name of a class member, from the kind of its signature
+fn member_name(signature: &str) -> &'static str {
+    match signature {
+        "getter_signature" => "getter",
+        "setter_signature" => "setter",
+        "constructor_signature" | "factory_constructor_signature"
+        | "redirecting_factory_constructor_signature" | "constant_constructor_signature" => "constructor",
+        _ => "method",
+    }
+}
+
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "source_file" => Some("file"),
+        "library_name" => Some("library"),
+        "import_specification" => Some("import"),
+        "library_export" => Some("export"),
+        "part_directive" | "part_of_directive" => Some("part"),
+        "class_declaration" => Some("class"),
+        "mixin_declaration" => Some("mixin"),
+        "extension_declaration" => Some("extension"),
+        "enum_declaration" => Some("enum"),
+        "enum_constant" => Some("constant"),
+        "function_declaration" | "function_signature" => Some("function"),
+        "top_level_variable_declaration" | "local_variable_declaration" => Some("variable"),
+        "formal_parameter" => Some("parameter"),
+        "named_argument" => Some("argument"),
+        "call_expression" | "cascade_call_expression" => Some("call"),
+        "member_expression" => Some("member"),
+        "return_statement" => Some("return"),
+        "if_statement" => Some("if"),
+        "for_statement" => Some("for"),
+        "while_statement" => Some("while"),
+        "do_statement" => Some("do"),
+        "switch_statement" => Some("switch"),
+        "catch_clause" => Some("catch"),
+        "finally_clause" => Some("finally"),
+        "throw_expression" => Some("throw"),
+        "await_expression" => Some("await"),
+        "yield_statement" => Some("yield"),
+        "function_expression" => Some("lambda"),
+        "conditional_expression" => Some("ternary"),
+        "additive_expression" | "multiplicative_expression" | "shift_expression" => Some("binary"),
+        "relational_expression" | "equality_expression" => Some("binary"),
+        "bitwise_and_expression" | "bitwise_or_expression" | "bitwise_xor_expression" => Some("binary"),
+        "logical_and_expression" | "logical_or_expression" | "if_null_expression" => Some("logical"),
+        "unary_expression" | "postfix_expression" => Some("unary"),
+        "assignment_expression" => Some("assign"),
+        "string_literal" => Some("string"),
+        "template_substitution" => Some("interpolation"),
+        "decimal_integer_literal" | "hex_integer_literal" => Some("int"),
+        "decimal_floating_point_literal" => Some("float"),
+        "true" => Some("true"),
+        "false" => Some("false"),
+        "null_literal" => Some("null"),
+        "documentation_comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Whether an expression has an operator, either as text or as a
+/// `*_operator` child node
+fn has_operator(xot: &Xot, node: XotNode) -> bool {
+    operator_text(xot, node).is_some()
+}
+
+fn operator_text(xot: &Xot, node: XotNode) -> Option<String> {
+    let texts = get_text_children(xot, node);
+    let operator = texts.into_iter().map(|t| t.trim().to_string()).find(|t| {
+        !t.is_empty() && !t.chars().all(|c| matches!(c, '(' | ')' | ',' | ';' | '{' | '}' | '[' | ']'))
+    });
+    operator.or_else(|| {
+        get_element_children(xot, node)
+            .into_iter()
+            .find(|&c| get_kind(xot, c).is_some_and(|k| k.ends_with("_operator") && k != "is_operator"))
+            .map(|c| xot.string_value(c).trim().to_string())
+    })
+}
+
+fn extract_operator(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    if let Some(op) = operator_text(xot, node) {
+        prepend_op_element(xot, node, &op)?;
+    }
+    Ok(())
+}
+
+/// If `node` contains a single identifier child, replace the node's children
+/// with that identifier's text. Used to flatten builder-created wrappers like
+/// `<name><identifier>foo</identifier></name>` to `<name>foo</name>`, and
+/// `<type><type_identifier>int</type_identifier></type>` to `<type>int</type>`.
+fn inline_single_identifier(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let elements = get_element_children(xot, node);
+    let [child] = elements.as_slice() else {
+        return Ok(());
+    };
+    let child_name = get_element_name(xot, *child).unwrap_or_default();
+    if !matches!(child_name.as_str(), "identifier" | "type_identifier" | "void_type") {
+        return Ok(());
+    }
+    let Some(text) = get_text_content(xot, *child) else {
+        return Ok(());
+    };
+    // Keep trailing text such as the `?` of a nullable type
+    let rest: String = get_text_children(xot, node).concat();
+    let all_children: Vec<_> = xot.children(node).collect();
+    for c in all_children {
+        xot.detach(c)?;
+    }
+    let text_node = xot.new_text(&format!("{}{}", text, rest.trim()));
+    xot.append(node, text_node)?;
+    if !rest.trim().is_empty() {
+        append_empty_element(xot, node, "nullable")?;
+    }
+    Ok(())
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Identifiers
+        "name" => SyntaxCategory::Identifier,
+        "type" => SyntaxCategory::Type,
+
+        // Literals
+        "string" => SyntaxCategory::String,
+        "int" | "float" => SyntaxCategory::Number,
+        "true" | "false" | "null" => SyntaxCategory::Keyword,
+
+        // Keywords - declarations
+        "class" | "mixin" | "extension" | "enum" => SyntaxCategory::Keyword,
+        "function" | "method" | "getter" | "setter" | "constructor" => SyntaxCategory::Keyword,
+        "field" | "variable" | "constant" => SyntaxCategory::Keyword,
+        "parameter" | "parameters" => SyntaxCategory::Keyword,
+        "import" | "export" | "library" | "part" => SyntaxCategory::Keyword,
+
+        // Keywords - control flow
+        "if" | "for" | "while" | "do" | "switch" | "yield" => SyntaxCategory::Keyword,
+        "try" | "catch" | "finally" | "throw" | "return" | "await" => SyntaxCategory::Keyword,
+
+        // Keywords - modifiers
+        "public" | "private" => SyntaxCategory::Keyword,
+        "static" | "final" | "const" | "abstract" | "external" | "sealed" => SyntaxCategory::Keyword,
+        "late" | "covariant" | "factory" | "async" | "generator" | "nullable" => SyntaxCategory::Keyword,
+
+        // Functions/calls
+        "call" => SyntaxCategory::Function,
+        "lambda" => SyntaxCategory::Function,
+
+        // Operators
+        "op" => SyntaxCategory::Operator,
+        _ if is_operator_marker(element) => SyntaxCategory::Operator,
+        "binary" | "unary" | "logical" | "assign" | "ternary" => SyntaxCategory::Operator,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        // Structural elements - no color
+        _ => SyntaxCategory::Default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_string_to_xot;
+    use crate::output::{render_document, RenderOptions};
+
+    fn render(source: &str) -> String {
+        let result = parse_string_to_xot(source, "dart", "<test>".to_string(), None).unwrap();
+        render_document(&result.xot, result.root, &RenderOptions::default())
+    }
+
+    fn query(source: &str, xpath: &str) -> usize {
+        let mut result = crate::parser::parse(
+            crate::parser::ParseInput::Inline { content: source, file_label: "<test>" },
+            crate::parser::ParseOptions { language: Some("dart"), ..Default::default() },
+        ).unwrap();
+        result.query(xpath).unwrap().len()
+    }
+
+    #[test]
+    fn test_method_body_is_nested_under_the_method() {
+        let source = "class Greeter {\n  void greet() {\n    print('hi');\n  }\n  static int _count() => 1;\n}\n";
+        assert_eq!(query(source, "//class[name='Greeter']/body/method[name='greet']/body/call[name='print']"), 1);
+        assert_eq!(query(source, "//method//call"), 1);
+        assert_eq!(query(source, "//method[name='_count'][static][private]/body/int"), 1);
+        assert_eq!(query(source, "//class/body/call"), 0, "statements must not leak into the class body");
+    }
+
+    #[test]
+    fn test_calls_keep_their_callee() {
+        let source = "void main() {\n  print('x');\n  obj.method(1);\n}\n";
+        assert_eq!(query(source, "//call[name='print']"), 1);
+        assert_eq!(query(source, "//call[member/name='method']/arguments/int"), 1);
+    }
+
+    #[test]
+    fn test_imports_are_single_elements() {
+        let source = "import 'package:foo/bar.dart' as bar;\nimport 'dart:async';\n";
+        assert_eq!(query(source, "/file/import"), 2);
+        assert_eq!(query(source, "//import//import"), 0);
+        let xml = render(source);
+        assert!(!xml.contains("import_or_export"), "grammar wrappers should be removed, got: {xml}");
+    }
+
+    #[test]
+    fn test_async_marker_comes_from_the_body() {
+        let source = "Future<void> load() async {\n  await fetch();\n}\nStream<int> gen() async* {}\nvoid sync() {}\n";
+        assert_eq!(query(source, "//function[async]"), 2);
+        assert_eq!(query(source, "//function[name='load'][async]/body/await/call[name='fetch']"), 1);
+        assert_eq!(query(source, "//function[generator]"), 1);
+        assert_eq!(query(source, "//function[name='sync'][async]"), 0);
+    }
+
+    #[test]
+    fn test_members_and_catch_clauses() {
+        let source = "class A {\n  A(this.x);\n  int get area => 0;\n  final String _name = '';\n  void draw();\n}\n\
+            void f() {\n  try { g(); } on FormatException catch (e) { h(); }\n}\n";
+        assert_eq!(query(source, "//constructor[name='A']"), 1);
+        assert_eq!(query(source, "//getter[name='area']"), 1);
+        assert_eq!(query(source, "//field[name='_name'][final][private]"), 1);
+        assert_eq!(query(source, "//method[name='draw'][not(body)]"), 1);
+        assert_eq!(query(source, "//try/catch[type='FormatException']/body/call[name='h']"), 1);
+    }
+}
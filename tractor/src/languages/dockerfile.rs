@@ -0,0 +1,189 @@
+//! Dockerfile transform logic
+//!
+//! Each instruction becomes an element named after its keyword
+//! (`from`, `run`, `copy`, `user`, ...). Image references are split into
+//! `name`, `tag` and `digest`, and `--flag=value` parameters into
+//! `param/name` and `param/value`:
+//!
+//! ```dockerfile
+//! FROM alpine:3.19 AS build
+//! COPY --from=build /app /app
+//! ```
+//! Becomes:
+//! ```xml
+//! <from>FROM <image><name>alpine</name><tag>3.19</tag></image> AS <alias>build</alias></from>
+//! <copy>COPY <param><name>from</name><value>build</value></param> <path>/app</path> <path>/app</path></copy>
+//! ```
+//! Queryable as: `//from[not(image/digest)]` (images not pinned by digest)
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers, and
+/// instructions named after their keyword.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "from", "run", "cmd", "label", "expose", "env", "add", "copy", "entrypoint",
+    "volume", "user", "workdir", "arg", "onbuild", "stopsignal", "healthcheck", "shell",
+    "maintainer", "cross_build", "image", "image_tag", "tag", "digest", "alias", "name",
+    "value", "pair", "path", "port", "group", "mount", "exec", "command", "heredoc",
+    "expansion", "string", "comment",
+];
+
+/// Transform a Dockerfile AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_kind(xot, node) {
+        Some(k) => k,
+        // Builder-created field wrappers (<name>, <value>) have no kind
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        "source_file" => {
+            rename(xot, node, "file");
+            Ok(TransformAction::Continue)
+        }
+
+        // Instructions: FROM → <from>, RUN → <run>, ...
+        _ if kind.ends_with("_instruction") => {
+            let name = kind.trim_end_matches("_instruction");
+            rename(xot, node, name);
+            Ok(TransformAction::Continue)
+        }
+
+        // Image references: strip the `:` / `@` separators
+        "image_tag" | "image_digest" => {
+            strip_leading_separator(xot, node)?;
+            rename(xot, node, if kind == "image_tag" { "tag" } else { "digest" });
+            Ok(TransformAction::Continue)
+        }
+
+        // `--from=build`, `--chown=app:app`: split into name and value
+        "param" => {
+            split_param(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        // Unquoted words inside a name/value field wrapper are just text
+        "image_name" | "unquoted_string" if parent_is_field_wrapper(xot, node) => {
+            Ok(TransformAction::Flatten)
+        }
+
+        // USER app:staff — the user and group fields
+        "unquoted_string" => {
+            match get_attr(xot, node, "field").as_deref() {
+                Some("user") => rename(xot, node, "name"),
+                Some("group") => rename(xot, node, "group"),
+                _ => rename(xot, node, "string"),
+            }
+            Ok(TransformAction::Continue)
+        }
+
+        // Shell commands keep their text; fragments and continuations are flattened
+        "shell_fragment" | "line_continuation" => Ok(TransformAction::Flatten),
+
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "image_spec" => Some("image"),
+        "image_name" => Some("name"),
+        "image_alias" => Some("alias"),
+        "shell_command" => Some("command"),
+        "json_string_array" => Some("exec"),
+        "json_string" | "double_quoted_string" | "single_quoted_string" => Some("string"),
+        "env_pair" | "label_pair" => Some("pair"),
+        "expose_port" => Some("port"),
+        "mount_param" => Some("mount"),
+        "expansion" => Some("expansion"),
+        "variable" => Some("name"),
+        "path" => Some("path"),
+        "heredoc_block" => Some("heredoc"),
+        "comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Builder-created wrappers for wrapped fields carry no `kind` attribute
+fn parent_is_field_wrapper(xot: &Xot, node: XotNode) -> bool {
+    get_parent(xot, node).is_some_and(|p| get_kind(xot, p).is_none())
+}
+
+/// Remove the leading `:` or `@` from a tag or digest's first text child
+fn strip_leading_separator(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let first_text = xot.children(node).find(|&c| xot.text_str(c).is_some());
+    if let Some(text_node) = first_text {
+        let text = xot.text_str(text_node).unwrap_or_default().to_string();
+        let stripped = text.trim_start_matches([':', '@']).to_string();
+        if stripped.is_empty() {
+            xot.detach(text_node)?;
+        } else if stripped.len() != text.len() {
+            // Set in place: an inserted sibling text node would merge into it
+            if let Some(text) = xot.text_mut(text_node) {
+                text.set(stripped);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replace a `--name=value` parameter's text with `<name>` and `<value>` children
+fn split_param(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let text = xot.string_value(node);
+    let flag = text.trim().trim_start_matches('-');
+    let (name, value) = match flag.split_once('=') {
+        Some((n, v)) => (n.to_string(), Some(v.to_string())),
+        None => (flag.to_string(), None),
+    };
+
+    let children: Vec<XotNode> = xot.children(node).collect();
+    for child in children {
+        xot.detach(child)?;
+    }
+
+    let name_id = xot.add_name("name");
+    let name_el = xot.new_element(name_id);
+    let name_text = xot.new_text(&name);
+    xot.append(name_el, name_text)?;
+    xot.append(node, name_el)?;
+
+    if let Some(value) = value {
+        let value_id = xot.add_name("value");
+        let value_el = xot.new_element(value_id);
+        let value_text = xot.new_text(&value);
+        xot.append(value_el, value_text)?;
+        xot.append(node, value_el)?;
+    }
+    Ok(())
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Instructions
+        "from" | "run" | "cmd" | "label" | "maintainer" | "expose" | "env" | "add"
+        | "copy" | "entrypoint" | "volume" | "user" | "workdir" | "arg" | "onbuild"
+        | "stopsignal" | "healthcheck" | "shell" | "cross_build" => SyntaxCategory::Keyword,
+
+        // Images and values
+        "image" | "tag" | "digest" | "alias" => SyntaxCategory::Type,
+        "name" | "group" => SyntaxCategory::Identifier,
+        "string" | "command" | "heredoc" => SyntaxCategory::String,
+        "port" => SyntaxCategory::Number,
+        "param" | "mount" | "expansion" => SyntaxCategory::Keyword,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        _ => SyntaxCategory::Default,
+    }
+}
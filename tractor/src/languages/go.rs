@@ -4,6 +4,18 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers,
+/// including `<exported/>` and `<unexported/>`.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "package", "pkg", "import", "function", "method", "type", "typedef", "typespec",
+    "struct", "interface", "field", "param", "params", "name", "var", "const", "exported",
+    "unexported", "return", "if", "else", "for", "range", "switch", "case", "default",
+    "select", "go", "defer", "call", "member", "index", "slice", "binary", "unary",
+    "pointer", "map", "chan", "literal", "string", "rawstring", "int", "float", "true",
+    "false", "nil",
+];
+
 /// Transform a Go AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
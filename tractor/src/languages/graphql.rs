@@ -0,0 +1,199 @@
+//! GraphQL transform logic
+//!
+//! Type system definitions are named after their keyword (`type`,
+//! `interface`, `enum`, `input`, `union`, `scalar`), field definitions
+//! become `field` and their arguments `argument`. Type references are kept
+//! as their source text so `[User!]!` can be matched directly:
+//!
+//! ```graphql
+//! type Query {
+//!   user(id: ID!): User @deprecated(reason: "use node")
+//! }
+//! ```
+//! Becomes:
+//! ```xml
+//! <type>type <name>Query</name> {
+//!   <field><name>user</name>(<argument><name>id</name>: <type>ID!</type></argument>): <type>User</type>
+//!     <directive>@<name>deprecated</name>(<argument><name>reason</name>: <string>"use node"</string></argument>)</directive></field>
+//! }</type>
+//! ```
+//! Queryable as: `//type/field[directive/name='deprecated']`
+//!
+//! Operations are named after their operation type (`query`, `mutation`,
+//! `subscription`); selections become nested `field` elements.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers, and
+/// operations named after their type.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "schema", "type", "interface", "union", "enum", "input", "scalar", "extend",
+    "implements", "query", "mutation", "subscription", "operation", "fragment", "spread",
+    "inline", "field", "argument", "variable", "value", "default", "location", "list",
+    "name", "string", "int", "float", "bool", "null", "object", "comment",
+];
+
+/// Transform a GraphQL AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_kind(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        // The root wraps the document and top-level comments
+        "source_file" => {
+            rename(xot, node, "file");
+            Ok(TransformAction::Continue)
+        }
+        "document" => Ok(TransformAction::Flatten),
+
+        // Grammar-level wrappers with no meaning of their own
+        "definition" | "executable_definition" | "type_system_definition"
+        | "type_system_extension" | "type_definition" | "type_extension"
+        | "selection" | "value" => Ok(TransformAction::Skip),
+
+        // Lists whose items are already meaningful elements
+        "fields_definition" | "input_fields_definition" | "arguments_definition"
+        | "enum_values_definition" | "union_member_types" | "selection_set"
+        | "variable_definitions" | "directives" | "arguments" | "directive_locations"
+        | "type_condition" => Ok(TransformAction::Flatten),
+
+        // `extend type User { ... }` -> <type><extend/>...</type>
+        _ if kind.ends_with("_type_extension") => {
+            prepend_empty_element(xot, node, "extend")?;
+            rename(xot, node, definition_name(kind.trim_end_matches("_type_extension")));
+            Ok(TransformAction::Continue)
+        }
+        _ if kind.ends_with("_type_definition") => {
+            rename(xot, node, definition_name(kind.trim_end_matches("_type_definition")));
+            Ok(TransformAction::Continue)
+        }
+
+        // Input values are arguments of a field or directive, or fields of an input type
+        "input_value_definition" => {
+            let in_arguments = get_parent(xot, node)
+                .and_then(|p| get_kind(xot, p))
+                .is_some_and(|k| k == "arguments_definition");
+            rename(xot, node, if in_arguments { "argument" } else { "field" });
+            Ok(TransformAction::Continue)
+        }
+
+        // Type references are plain text: <type>[User!]!</type>
+        "type" | "named_type" => {
+            let text = xot.string_value(node).trim().to_string();
+            replace_with_text(xot, node, &text)?;
+            rename(xot, node, "type");
+            Ok(TransformAction::Done)
+        }
+
+        // `query GetUser { ... }` -> <query>; the shorthand `{ ... }` is a query too
+        "operation_definition" => {
+            let operation_type = get_element_children(xot, node)
+                .into_iter()
+                .find(|&c| get_kind(xot, c).as_deref() == Some("operation_type"));
+            let name = match operation_type {
+                Some(op) => {
+                    let name = xot.string_value(op).trim().to_string();
+                    detach(xot, op)?;
+                    name
+                }
+                None => "query".to_string(),
+            };
+            rename(xot, node, &name);
+            Ok(TransformAction::Continue)
+        }
+
+        // Variables: `$id: ID!` -> <variable><name>id</name>: <type>ID!</type></variable>
+        "variable" => {
+            let text = xot.string_value(node).trim().trim_start_matches('$').to_string();
+            let in_definition = get_parent(xot, node)
+                .and_then(|p| get_kind(xot, p))
+                .is_some_and(|k| k == "variable_definition");
+            replace_with_text(xot, node, &text)?;
+            rename(xot, node, if in_definition { "name" } else { "variable" });
+            Ok(TransformAction::Done)
+        }
+
+        // Enum members: the value wrapper around the name is redundant
+        "enum_value" if get_parent(xot, node)
+            .and_then(|p| get_kind(xot, p))
+            .is_some_and(|k| k == "enum_value_definition") => Ok(TransformAction::Flatten),
+
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Element name for a `*_type_definition` / `*_type_extension` prefix
+fn definition_name(prefix: &str) -> &'static str {
+    match prefix {
+        "object" => "type",
+        "interface" => "interface",
+        "union" => "union",
+        "enum" => "enum",
+        "input_object" => "input",
+        _ => "scalar",
+    }
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "field_definition" => Some("field"),
+        "enum_value_definition" => Some("value"),
+        "variable_definition" => Some("variable"),
+        "implements_interfaces" => Some("implements"),
+        "schema_definition" => Some("schema"),
+        "root_operation_type_definition" => Some("operation"),
+        "fragment_definition" => Some("fragment"),
+        "fragment_spread" => Some("spread"),
+        "fragment_name" => Some("name"),
+        "inline_fragment" => Some("inline"),
+        "default_value" => Some("default"),
+        "directive_location" => Some("location"),
+        "string_value" => Some("string"),
+        "int_value" => Some("int"),
+        "float_value" => Some("float"),
+        "boolean_value" => Some("bool"),
+        "null_value" => Some("null"),
+        "enum_value" => Some("enum"),
+        "list_value" => Some("list"),
+        "object_value" => Some("object"),
+        "object_field" => Some("field"),
+        "comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Definitions and operations
+        "interface" | "union" | "enum" | "input" | "scalar" | "schema" | "extend"
+        | "directive_definition" | "fragment" | "inline" | "implements"
+        | "query" | "mutation" | "subscription" => SyntaxCategory::Keyword,
+
+        // Types and names
+        "type" => SyntaxCategory::Type,
+        "name" | "alias" | "variable" => SyntaxCategory::Identifier,
+        "directive" => SyntaxCategory::Function,
+
+        // Literals
+        "string" | "description" => SyntaxCategory::String,
+        "int" | "float" => SyntaxCategory::Number,
+        "bool" | "null" => SyntaxCategory::Keyword,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        _ => SyntaxCategory::Default,
+    }
+}
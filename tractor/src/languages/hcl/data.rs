@@ -0,0 +1,223 @@
+//! HCL data transform — query-friendly projection
+//!
+//! Blocks keep their block type as element name (labels as attributes),
+//! attribute keys become element names, literals become text content:
+//!
+//! ```hcl
+//! module "vpc" {
+//!   source  = "terraform-aws-modules/vpc/aws"
+//!   version = "5.0.0"
+//! }
+//! ```
+//! Becomes:
+//! ```xml
+//! <module name="vpc">
+//!   <source>terraform-aws-modules/vpc/aws</source>
+//!   <version>5.0.0</version>
+//! </module>
+//! ```
+//! Queryable as: `//module[@name='vpc']/version`
+//!
+//! Non-literal expressions (references, function calls, conditionals) keep
+//! their source text with `kind="expression"` so they render back verbatim.
+//! Strings with interpolations or directives keep their unquoted source text
+//! with `kind="template"` for the same reason.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use super::{transform_block_header, strip_quotes, decode_hcl_escapes};
+
+/// Project HCL into query-friendly data view.
+pub fn data_transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_kind(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        // Root: same element name as the other data trees
+        "config_file" => {
+            rename(xot, node, "document");
+            remove_text_children(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        "body" => {
+            remove_text_children(xot, node)?;
+            Ok(TransformAction::Flatten)
+        }
+
+        // Blocks: rename to the block type, labels become attributes
+        "block" => {
+            transform_block_header(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // Attributes: rename to the key, project the value
+        "attribute" => {
+            transform_data_attribute(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        // Top-level object (e.g. a .tfvars written as a single object)
+        "object" => {
+            let old_children: Vec<XotNode> = xot.children(node).collect();
+            project_object(xot, node, node)?;
+            for child in old_children {
+                xot.detach(child)?;
+            }
+            flatten_node(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        // Comments carry no data
+        "comment" => {
+            detach(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        _ => Ok(TransformAction::Continue),
+    }
+}
+
+/// Transform an `attribute` (`key = expression`) into a key-named element.
+fn transform_data_attribute(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let children = get_element_children(xot, node);
+    let key = children.iter()
+        .find(|&&c| get_kind(xot, c).as_deref() == Some("identifier"))
+        .map(|&c| xot.string_value(c).trim().to_string());
+    let value = children.iter()
+        .find(|&&c| get_kind(xot, c).as_deref() == Some("expression"))
+        .copied();
+
+    let (key, value) = match (key, value) {
+        (Some(k), Some(v)) => (k, v),
+        _ => return Ok(()),
+    };
+
+    let safe_name = rename_to_key(xot, node, &key);
+    // Mark as a property so renderers can distinguish attributes from blocks.
+    set_attr(xot, node, "field", &safe_name);
+
+    // /specs/tractor-parse/dual-view/data-branch/source-spans.md: Value-Oriented Source Spans
+    copy_source_location(xot, value, node);
+    project_value(xot, node, value)
+}
+
+/// Replace the children of `target` with the data projection of `value`.
+///
+/// Literals become text with a `kind` of `string`, `number`, `bool` or
+/// `null`; templates keep their source text as `kind="template"`; objects and tuples become nested elements; everything else keeps
+/// its source text as `kind="expression"`.
+fn project_value(xot: &mut Xot, target: XotNode, value: XotNode) -> Result<(), xot::Error> {
+    let old_children: Vec<XotNode> = xot.children(target).collect();
+    let inner = unwrap_expression(xot, value);
+    let inner_kind = get_kind(xot, inner).unwrap_or_default();
+
+    match inner_kind.as_str() {
+        "object" => {
+            set_attr(xot, target, "kind", "object");
+            project_object(xot, target, inner)?;
+        }
+        "tuple" => {
+            set_attr(xot, target, "kind", "tuple");
+            let items: Vec<XotNode> = get_element_children(xot, inner)
+                .into_iter()
+                .filter(|&c| get_kind(xot, c).as_deref() == Some("expression"))
+                .collect();
+            for expr in items {
+                let item_name = xot.add_name("item");
+                let item = xot.new_element(item_name);
+                copy_source_location(xot, expr, item);
+                project_value(xot, item, expr)?;
+                xot.append(target, item)?;
+            }
+        }
+        _ => {
+            let (text, value_kind) = scalar_text(xot, inner, value, &inner_kind);
+            set_attr(xot, target, "kind", value_kind);
+            let text_node = xot.new_text(&text);
+            xot.append(target, text_node)?;
+        }
+    }
+
+    for child in old_children {
+        xot.detach(child)?;
+    }
+    Ok(())
+}
+
+/// Append one key-named element per `object_elem` of `object` to `target`.
+fn project_object(xot: &mut Xot, target: XotNode, object: XotNode) -> Result<(), xot::Error> {
+    let elems: Vec<XotNode> = get_element_children(xot, object)
+        .into_iter()
+        .filter(|&c| get_kind(xot, c).as_deref() == Some("object_elem"))
+        .collect();
+
+    for elem in elems {
+        let mut key = None;
+        let mut value = None;
+        for child in get_element_children(xot, elem) {
+            match get_attr(xot, child, "field").as_deref() {
+                Some("key") => key = Some(strip_quotes(xot.string_value(child).trim())),
+                Some("val") => value = Some(child),
+                _ => {}
+            }
+        }
+        let (key, value) = match (key, value) {
+            (Some(k), Some(v)) => (k, v),
+            _ => continue,
+        };
+
+        let name = xot.add_name("property");
+        let property = xot.new_element(name);
+        let safe_name = rename_to_key(xot, property, &key);
+        set_attr(xot, property, "field", &safe_name);
+        copy_source_location(xot, value, property);
+        project_value(xot, property, value)?;
+        xot.append(target, property)?;
+    }
+
+    // The object itself is only a container — drop it once projected
+    if object != target {
+        detach(xot, object)?;
+    }
+    Ok(())
+}
+
+/// Descend through single-child expression wrappers to the meaningful node.
+fn unwrap_expression(xot: &Xot, node: XotNode) -> XotNode {
+    let mut current = node;
+    loop {
+        let is_wrapper = matches!(
+            get_kind(xot, current).as_deref(),
+            Some("expression" | "expr_term" | "literal_value" | "collection_value" | "template_expr")
+        );
+        if !is_wrapper {
+            return current;
+        }
+        let children = get_element_children(xot, current);
+        let only_whitespace_text = get_text_children(xot, current)
+            .iter()
+            .all(|t| t.trim().is_empty());
+        if children.len() != 1 || !only_whitespace_text {
+            return current;
+        }
+        current = children[0];
+    }
+}
+
+/// Text and value kind for a non-collection expression.
+fn scalar_text(xot: &Xot, inner: XotNode, value: XotNode, inner_kind: &str) -> (String, &'static str) {
+    match inner_kind {
+        "string_lit" => {
+            let raw = xot.string_value(inner);
+            (decode_hcl_escapes(&strip_quotes(raw.trim())), "string")
+        }
+        "quoted_template" => (strip_quotes(xot.string_value(inner).trim()), "template"),
+        "numeric_lit" => (xot.string_value(inner).trim().to_string(), "number"),
+        "bool_lit" => (xot.string_value(inner).trim().to_string(), "bool"),
+        "null_lit" => ("null".to_string(), "null"),
+        _ => (xot.string_value(value).trim().to_string(), "expression"),
+    }
+}
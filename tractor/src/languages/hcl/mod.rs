@@ -0,0 +1,221 @@
+//! HCL / Terraform transform logic
+//!
+//! Provides two transforms for dual-branch output:
+//! - `syntax`: Normalizes TreeSitter HCL nodes into blocks named by their
+//!   block type (`resource`, `variable`, `module`, ...) with `attribute`
+//!   children. This is the default tree for queries and checks.
+//! - `data`: Projects into query-friendly data view where attribute keys
+//!   become element names and literal values become text content. Used with
+//!   `-t data`, and always by `set`.
+//!
+//! In both views block labels become XML attributes: a block with two labels
+//! gets `type` and `name`, a block with one label gets `name`, and any labels
+//! past the second become `label3`, `label4`, ...:
+//!
+//! ```hcl
+//! resource "aws_s3_bucket" "logs" {
+//!   bucket = "my-logs"
+//! }
+//! ```
+//! Becomes (data view, `-t data`):
+//! ```xml
+//! <resource type="aws_s3_bucket" name="logs">
+//!   <bucket>my-logs</bucket>
+//! </resource>
+//! ```
+//! Queryable as: `//resource[@type='aws_s3_bucket']/bucket`, or by default
+//! as `//resource[@type='aws_s3_bucket']/attribute[name='bucket']` (see
+//! [`syntax`])
+
+pub mod syntax;
+pub mod data;
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::helpers::*;
+use crate::output::syntax_highlight::SyntaxCategory;
+
+pub use syntax::syntax_transform;
+pub use data::data_transform;
+
+/// Alias for the syntax transform, matching the other data-aware languages
+pub fn ast_transform(xot: &mut Xot, node: XotNode) -> Result<crate::xot_transform::TransformAction, xot::Error> {
+    syntax_transform(xot, node)
+}
+
+// =============================================================================
+// Shared helpers used by both syntax and data transforms
+// =============================================================================
+
+/// Turn a block's type identifier and labels into element name + attributes.
+///
+/// `resource "aws_s3_bucket" "logs" { ... }` is renamed to `resource` with
+/// `type="aws_s3_bucket"` and `name="logs"`; further labels become
+/// `label3`, `label4`, ... The identifier, label and brace
+/// children are removed; the `body` child is left for the walker.
+pub(crate) fn transform_block_header(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let mut block_type: Option<String> = None;
+    let mut labels: Vec<String> = Vec::new();
+    let mut header_nodes: Vec<XotNode> = Vec::new();
+
+    for child in get_element_children(xot, node) {
+        match get_kind(xot, child).as_deref() {
+            Some("identifier") if block_type.is_none() => {
+                block_type = Some(xot.string_value(child).trim().to_string());
+                header_nodes.push(child);
+            }
+            Some("identifier") => {
+                labels.push(xot.string_value(child).trim().to_string());
+                header_nodes.push(child);
+            }
+            Some("string_lit") => {
+                labels.push(decode_hcl_escapes(&strip_quotes(xot.string_value(child).trim())));
+                header_nodes.push(child);
+            }
+            Some("block_start") | Some("block_end") => header_nodes.push(child),
+            _ => {}
+        }
+    }
+
+    for child in header_nodes {
+        xot.detach(child)?;
+    }
+    remove_text_children(xot, node)?;
+
+    if let Some(block_type) = block_type {
+        rename_to_key(xot, node, &block_type);
+    }
+    match labels.as_slice() {
+        [] => {}
+        [name] => set_attr(xot, node, "name", name),
+        [block_type, name, extra @ ..] => {
+            set_attr(xot, node, "type", block_type);
+            set_attr(xot, node, "name", name);
+            for (i, label) in extra.iter().enumerate() {
+                set_attr(xot, node, &format!("label{}", i + 3), label);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Strip surrounding double quotes from a string literal
+pub(crate) fn strip_quotes(s: &str) -> String {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        s[1..s.len() - 1].to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Decode HCL string escape sequences into their actual characters.
+///
+/// Handles: `\\`, `\"`, `\n`, `\r`, `\t`, `\uXXXX`, `\UXXXXXXXX`, and the
+/// template escapes `$${` and `%%{`. Malformed unicode escapes are kept
+/// verbatim. Only used for literal strings — templates with interpolations
+/// keep their source text.
+pub(crate) fn decode_hcl_escapes(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '\\' => {
+                let Some(next) = rest.chars().next() else {
+                    result.push('\\');
+                    break;
+                };
+                rest = &rest[next.len_utf8()..];
+                match next {
+                    '\\' => result.push('\\'),
+                    '"' => result.push('"'),
+                    'n' => result.push('\n'),
+                    'r' => result.push('\r'),
+                    't' => result.push('\t'),
+                    'u' | 'U' => {
+                        let digits = if next == 'u' { 4 } else { 8 };
+                        match decode_unicode_escape(rest, digits) {
+                            Some(ch) => {
+                                result.push(ch);
+                                rest = &rest[digits..];
+                            }
+                            None => {
+                                result.push('\\');
+                                result.push(next);
+                            }
+                        }
+                    }
+                    other => {
+                        result.push('\\');
+                        result.push(other);
+                    }
+                }
+            }
+            '$' | '%' if rest.starts_with(c) && rest[1..].starts_with('{') => {
+                result.push(c);
+                result.push('{');
+                rest = &rest[2..];
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Parse exactly `digits` hex digits at the start of `s` as a code point.
+fn decode_unicode_escape(s: &str, digits: usize) -> Option<char> {
+    let hex = s.get(..digits)?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+}
+
+/// Map element names to syntax categories for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Common Terraform block types
+        "resource" | "data" | "module" | "variable" | "output" | "provider"
+        | "locals" | "terraform" | "backend" | "dynamic" => SyntaxCategory::Keyword,
+        "attribute" | "property" => SyntaxCategory::Default,
+        "name" => SyntaxCategory::Identifier,
+        "string" | "heredoc" => SyntaxCategory::String,
+        "number" => SyntaxCategory::Number,
+        "bool" | "null" => SyntaxCategory::Keyword,
+        "call" => SyntaxCategory::Function,
+        "for" | "conditional" => SyntaxCategory::Keyword,
+        "op" | "binary" | "unary" => SyntaxCategory::Operator,
+        "comment" => SyntaxCategory::Comment,
+        _ => SyntaxCategory::Default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_hcl_escapes() {
+        assert_eq!(decode_hcl_escapes("plain"), "plain");
+        assert_eq!(decode_hcl_escapes(r"a\nb"), "a\nb");
+        assert_eq!(decode_hcl_escapes(r#"say \"hi\""#), "say \"hi\"");
+        assert_eq!(decode_hcl_escapes(r"\u0041"), "A");
+        assert_eq!(decode_hcl_escapes("${var.name}-bucket"), "${var.name}-bucket");
+        assert_eq!(decode_hcl_escapes(r"\U0001F600"), "\u{1F600}");
+        assert_eq!(decode_hcl_escapes("$${literal} 100%%{x}"), "${literal} 100%{x}");
+    }
+
+    #[test]
+    fn test_decode_hcl_escapes_keeps_malformed_unicode() {
+        assert_eq!(decode_hcl_escapes(r"\u12"), r"\u12");
+        assert_eq!(decode_hcl_escapes(r"\uZZZZ!"), r"\uZZZZ!");
+        assert_eq!(decode_hcl_escapes(r"\uD800"), r"\uD800");
+        assert_eq!(decode_hcl_escapes(r"\U0041"), r"\U0041");
+    }
+
+    #[test]
+    fn test_strip_quotes() {
+        assert_eq!(strip_quotes("\"aws_s3_bucket\""), "aws_s3_bucket");
+        assert_eq!(strip_quotes("bare"), "bare");
+        assert_eq!(strip_quotes("\""), "\"");
+    }
+}
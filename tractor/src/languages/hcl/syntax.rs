@@ -0,0 +1,97 @@
+//! HCL syntax transform — block-oriented structure view, the default tree
+//! for HCL queries and checks
+//!
+//! Blocks are named after their block type with labels as attributes,
+//! attributes become `<attribute>` elements with a `<name>` child:
+//!
+//! ```hcl
+//! resource "aws_s3_bucket" "logs" {
+//!   bucket = "my-logs"
+//! }
+//! ```
+//! Becomes:
+//! ```xml
+//! <resource type="aws_s3_bucket" name="logs">
+//!   <attribute><name>bucket</name> = <string>my-logs</string></attribute>
+//! </resource>
+//! ```
+//! Queryable as: `//resource[@type='aws_s3_bucket']/attribute[name='bucket']`
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use super::transform_block_header;
+
+/// Normalize HCL into the block/attribute structure view.
+pub fn syntax_transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_kind(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        "config_file" => {
+            rename(xot, node, "file");
+            Ok(TransformAction::Continue)
+        }
+
+        // Bodies and expression wrappers carry no meaning of their own
+        "body" | "expression" | "expr_term" | "literal_value" | "collection_value"
+        | "template_expr" | "operation" | "template_literal" => Ok(TransformAction::Skip),
+
+        // Blocks: rename to the block type, labels become attributes
+        "block" => {
+            transform_block_header(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // Quote and brace tokens are punctuation
+        "quoted_template_start" | "quoted_template_end"
+        | "block_start" | "block_end" | "tuple_start" | "tuple_end"
+        | "object_start" | "object_end"
+        | "template_interpolation_start" | "template_interpolation_end" => {
+            detach(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        "binary_operation" | "unary_operation" => {
+            let op = get_text_children(xot, node)
+                .into_iter()
+                .find(|t| !t.trim().is_empty());
+            if let Some(op) = op {
+                prepend_op_element(xot, node, op.trim())?;
+            }
+            rename(xot, node, if kind == "binary_operation" { "binary" } else { "unary" });
+            Ok(TransformAction::Continue)
+        }
+
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "identifier" => Some("name"),
+        "string_lit" | "quoted_template" => Some("string"),
+        "heredoc_template" => Some("heredoc"),
+        "template_interpolation" => Some("interpolation"),
+        "numeric_lit" => Some("number"),
+        "bool_lit" => Some("bool"),
+        "null_lit" => Some("null"),
+        "variable_expr" => Some("ref"),
+        "get_attr" => Some("member"),
+        "index" | "new_index" | "legacy_index" => Some("index"),
+        "splat" | "attr_splat" | "full_splat" => Some("splat"),
+        "function_call" => Some("call"),
+        "function_arguments" => Some("arguments"),
+        "object_elem" => Some("property"),
+        "for_expr" | "for_tuple_expr" | "for_object_expr" => Some("for"),
+        "comment" => Some("comment"),
+        _ => None,
+    }
+}
@@ -32,6 +32,8 @@ pub enum Language {
     Cpp,
     C,
     Json,
+    Jsonc,
+    Json5,
     Html,
     Css,
     Bash,
@@ -47,8 +49,20 @@ pub enum Language {
     R,
     Julia,
     Markdown,
+    Kotlin,
+    Swift,
+    Dart,
+    Hcl,
+    Dockerfile,
+    Protobuf,
+    GraphQL,
     Xml,
     TSql,
+    Notebook,
+    Vue,
+    Svelte,
+    Csv,
+    Tsv,
     /// Unknown language (for unsupported extensions)
     Unknown,
 }
@@ -154,7 +168,7 @@ pub static LANGUAGES: &[LanguageInfo] = &[
     LanguageInfo {
         language: Language::CSharp,
         name: "csharp",
-        extensions: &["cs"],
+        extensions: &["cs", "csx", "cake"],
         aliases: &["cs"],
         has_transforms: true,
         grammar_file: Some("tree-sitter-c_sharp.wasm"),
@@ -223,6 +237,22 @@ pub static LANGUAGES: &[LanguageInfo] = &[
         has_transforms: true,
         grammar_file: Some("tree-sitter-json.wasm"),
     },
+    LanguageInfo {
+        language: Language::Jsonc,
+        name: "jsonc",
+        extensions: &["jsonc"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: Some("tree-sitter-json.wasm"), // JSON grammar, trailing commas blanked
+    },
+    LanguageInfo {
+        language: Language::Json5,
+        name: "json5",
+        extensions: &["json5"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: Some("tree-sitter-json.wasm"), // JSON grammar, rewritten from JSON5
+    },
     LanguageInfo {
         language: Language::Html,
         name: "html",
@@ -343,10 +373,66 @@ pub static LANGUAGES: &[LanguageInfo] = &[
         has_transforms: true,
         grammar_file: Some("tree-sitter-markdown.wasm"),
     },
+    LanguageInfo {
+        language: Language::Kotlin,
+        name: "kotlin",
+        extensions: &["kt", "kts"],
+        aliases: &["kt"],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::Swift,
+        name: "swift",
+        extensions: &["swift"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::Dart,
+        name: "dart",
+        extensions: &["dart"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::Hcl,
+        name: "hcl",
+        extensions: &["tf", "tfvars", "hcl"],
+        aliases: &["terraform", "tf"],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::Dockerfile,
+        name: "dockerfile",
+        extensions: &["dockerfile"],
+        aliases: &["docker"],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::Protobuf,
+        name: "protobuf",
+        extensions: &["proto"],
+        aliases: &["proto"],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    LanguageInfo {
+        language: Language::GraphQL,
+        name: "graphql",
+        extensions: &["graphql", "gql"],
+        aliases: &["gql"],
+        has_transforms: true,
+        grammar_file: None,
+    },
     LanguageInfo {
         language: Language::Xml,
         name: "xml",
-        extensions: &["xml"],
+        extensions: &["xml", "csproj", "props", "targets", "resx", "xaml"],
         aliases: &[],
         has_transforms: false,
         grammar_file: None, // Pass-through, no parsing needed
@@ -360,6 +446,48 @@ pub static LANGUAGES: &[LanguageInfo] = &[
         has_transforms: true,
         grammar_file: Some("tree-sitter-sql.wasm"),
     },
+    // Containers for other languages
+    LanguageInfo {
+        language: Language::Notebook,
+        name: "notebook",
+        extensions: &["ipynb"],
+        aliases: &["ipynb", "jupyter"],
+        has_transforms: true,
+        grammar_file: None, // Cells are parsed with their own grammars
+    },
+    LanguageInfo {
+        language: Language::Vue,
+        name: "vue",
+        extensions: &["vue"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: None, // Blocks are parsed with their own grammars
+    },
+    LanguageInfo {
+        language: Language::Svelte,
+        name: "svelte",
+        extensions: &["svelte"],
+        aliases: &[],
+        has_transforms: true,
+        grammar_file: None,
+    },
+    // Delimited tables - read directly into a data tree
+    LanguageInfo {
+        language: Language::Csv,
+        name: "csv",
+        extensions: &["csv"],
+        aliases: &[],
+        has_transforms: false,
+        grammar_file: None, // Not parsed by TreeSitter
+    },
+    LanguageInfo {
+        language: Language::Tsv,
+        name: "tsv",
+        extensions: &["tsv"],
+        aliases: &[],
+        has_transforms: false,
+        grammar_file: None, // Not parsed by TreeSitter
+    },
 ];
 
 /// Get language info by name or alias
@@ -436,6 +564,10 @@ mod tests {
         assert_eq!(get_language_info("jsx").unwrap().name, "javascript");
         assert_eq!(get_language_info("mdx").unwrap().name, "markdown");
         assert_eq!(get_language_info("mssql").unwrap().name, "tsql");
+        assert_eq!(get_language_info("terraform").unwrap().name, "hcl");
+        assert_eq!(get_language_info("docker").unwrap().name, "dockerfile");
+        assert_eq!(get_language_info("proto").unwrap().name, "protobuf");
+        assert_eq!(get_language_info("gql").unwrap().name, "graphql");
     }
 
     #[test]
@@ -7,6 +7,19 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers and
+/// modifiers, including `<package-private/>`.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "program", "package", "import", "class", "interface", "enum", "method", "ctor", "field",
+    "param", "params", "args", "name", "type", "generic", "array", "public", "private",
+    "protected", "package-private", "static", "final", "abstract", "synchronized",
+    "volatile", "transient", "native", "strictfp", "return", "if", "else", "for", "foreach",
+    "while", "switch", "case", "try", "catch", "finally", "throw", "call", "new", "member",
+    "index", "lambda", "assign", "binary", "unary", "ternary", "string", "int", "float",
+    "true", "false", "null",
+];
+
 /// Transform a Java AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
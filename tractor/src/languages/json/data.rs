@@ -78,6 +78,12 @@ pub fn data_transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, x
             Ok(TransformAction::Flatten)
         }
 
+        // comment (JSONC): carries no data
+        "comment" => {
+            detach(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
         _ => Ok(TransformAction::Continue),
     }
 }
@@ -122,8 +128,9 @@ fn transform_data_pair(xot: &mut Xot, node: XotNode) -> Result<TransformAction,
         // Set kind to the scalar value type so the renderer knows how to
         // format the value (string vs number vs boolean vs null).
         let value_kind = xot.children(node)
-            .find(|&c| xot.element(c).is_some())
-            .and_then(|c| get_kind(xot, c));
+            .filter(|&c| xot.element(c).is_some())
+            .filter_map(|c| get_kind(xot, c))
+            .find(|k| k != "comment");
         if let Some(ref vk) = value_kind {
             match vk.as_str() {
                 "string" | "number" | "true" | "false" | "null" => {
@@ -165,7 +172,7 @@ fn transform_data_array(xot: &mut Xot, node: XotNode) -> Result<TransformAction,
     let wrapper_name = get_name(xot, &wrapper);
 
     let children: Vec<XotNode> = xot.children(node)
-        .filter(|&c| xot.element(c).is_some())
+        .filter(|&c| xot.element(c).is_some() && get_kind(xot, c).as_deref() != Some("comment"))
         .collect();
 
     for child in children {
@@ -5,15 +5,24 @@
 //!   (object/array/property/key/value/string/number/bool/null)
 //! - `data`: Projects into query-friendly data view where object keys
 //!   become element names and scalar values become text content.
+//!
+//! JSONC files (`tsconfig.json`, `.vscode/settings.json`) use the same
+//! grammar and transforms. The grammar already accepts comments; trailing
+//! commas are blanked out before parsing (see [`blank_trailing_commas`]).
+//! JSON5 is rewritten into JSON for the grammar (see [`json5_to_json`]) and
+//! the tree is mapped back onto the JSON5 text, so nodes keep its spelling
+//! (`'single'`, `unquoted`, `0x1F`). The data view leaves comments out.
 
 pub mod syntax;
 pub mod data;
 
+use std::borrow::Cow;
+use std::ops::Range;
 use xot::{Xot, Node as XotNode};
 use crate::xot_transform::helpers::*;
 use crate::output::syntax_highlight::SyntaxCategory;
 
-pub use syntax::syntax_transform;
+pub use syntax::{syntax_transform, SYNTAX_ELEMENTS};
 pub use data::data_transform;
 
 /// Backwards-compatible alias for the syntax transform
@@ -36,35 +45,28 @@ pub(crate) fn extract_string_content(xot: &Xot, string_node: XotNode) -> Option<
 /// Extract and decode the full content of a JSON string node for data view.
 ///
 /// TreeSitter splits JSON strings into `string_content` and `escape_sequence`
-/// child nodes. This function reassembles them, decoding escape sequences.
+/// child nodes (`\u00e9` is an `\u` escape followed by `00e9` content). This
+/// function reassembles them and decodes the escape sequences in one go.
 pub(crate) fn extract_decoded_string_content(xot: &Xot, string_node: XotNode) -> Option<String> {
-    let mut result = String::new();
+    let mut raw = String::new();
     let mut found_content = false;
     for child in xot.children(string_node) {
         if let Some(name) = get_element_name(xot, child) {
-            match name.as_str() {
-                "string_content" => {
-                    if let Some(text) = get_text_content(xot, child) {
-                        result.push_str(&text);
-                        found_content = true;
-                    }
-                }
-                "escape_sequence" => {
-                    if let Some(text) = get_text_content(xot, child) {
-                        result.push_str(&decode_json_escapes(&text));
-                        found_content = true;
-                    }
+            if matches!(name.as_str(), "string_content" | "escape_sequence") {
+                if let Some(text) = get_text_content(xot, child) {
+                    raw.push_str(&text);
+                    found_content = true;
                 }
-                _ => {}
             }
         }
     }
-    if found_content { Some(result) } else { None }
+    if found_content { Some(decode_json_escapes(&raw)) } else { None }
 }
 
 /// Decode JSON string escape sequences into their actual characters.
 ///
-/// Handles: `\\`, `\"`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t`, `\uXXXX`
+/// Handles: `\\`, `\"`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t`, `\uXXXX`, and
+/// JSON5's `\'`, `\v`, `\0`, `\xXX` and line continuations
 pub(crate) fn decode_json_escapes(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut chars = s.chars();
@@ -79,14 +81,25 @@ pub(crate) fn decode_json_escapes(s: &str) -> String {
                 Some('n') => result.push('\n'),
                 Some('r') => result.push('\r'),
                 Some('t') => result.push('\t'),
-                Some('u') => {
-                    let hex: String = chars.by_ref().take(4).collect();
+                Some(c @ ('u' | 'x')) => {
+                    let digits = if c == 'u' { 4 } else { 2 };
+                    let hex: String = chars.by_ref().take(digits).collect();
                     if let Ok(code) = u32::from_str_radix(&hex, 16) {
                         if let Some(ch) = char::from_u32(code) {
                             result.push(ch);
                         }
                     }
                 }
+                Some('\'') => result.push('\''),
+                Some('v') => result.push('\u{000B}'),
+                Some('0') => result.push('\0'),
+                // Line continuation (JSON5): the escaped line break is dropped
+                Some('\n') => {}
+                Some('\r') => {
+                    if chars.clone().next() == Some('\n') {
+                        chars.next();
+                    }
+                }
                 Some(other) => {
                     result.push('\\');
                     result.push(other);
@@ -100,6 +113,290 @@ pub(crate) fn decode_json_escapes(s: &str) -> String {
     result
 }
 
+/// Byte offsets of trailing commas (a `,` followed only by whitespace and
+/// comments before `}` or `]`), skipping strings and comments.
+pub fn trailing_comma_offsets(source: &str) -> Vec<usize> {
+    let bytes = source.as_bytes();
+    let mut offsets = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = skip_string(bytes, i),
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => i = skip_comment(bytes, i),
+            b',' => {
+                let mut j = i + 1;
+                loop {
+                    match bytes.get(j) {
+                        Some(c) if c.is_ascii_whitespace() => j += 1,
+                        Some(b'/') if matches!(bytes.get(j + 1), Some(b'/' | b'*')) => j = skip_comment(bytes, j),
+                        _ => break,
+                    }
+                }
+                if matches!(bytes.get(j), Some(b'}' | b']')) {
+                    offsets.push(i);
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    offsets
+}
+
+/// Replace trailing commas with spaces so the JSON grammar accepts JSONC.
+/// Byte offsets, and so every line and column, stay the same.
+pub fn blank_trailing_commas(source: &str) -> Cow<'_, str> {
+    let offsets = trailing_comma_offsets(source);
+    if offsets.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut bytes = source.as_bytes().to_vec();
+    for offset in offsets {
+        bytes[offset] = b' ';
+    }
+    // Only ASCII commas were replaced, so the text is still valid UTF-8
+    Cow::Owned(String::from_utf8(bytes).unwrap_or_else(|_| source.to_string()))
+}
+
+/// A stretch of the JSON text [`json5_to_json`] produces that reads
+/// differently in the JSON5 source: `json` is its range in the JSON text,
+/// `original` the range of source text it stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Json5Splice {
+    pub json: Range<usize>,
+    pub original: Range<usize>,
+}
+
+/// Rewrite JSON5 into JSON the JSON grammar accepts, with the splices that
+/// map the result back onto `source`.
+///
+/// Most rewrites keep the length, so the source text can stand in for the
+/// JSON text: single quotes become double quotes, escapes JSON lacks (`\'`,
+/// `\x`, `\v`) become ones it has, and numbers JSON lacks (`0x1F`, `.5`,
+/// `+1`, `Infinity`, `NaN`) become a same-length placeholder number. Only
+/// quotes added around unquoted keys, backslashes added before `"` inside
+/// single-quoted strings and line continuations need a splice. Trailing
+/// commas are kept; blank them as for JSONC.
+pub fn json5_to_json(source: &str) -> (String, Vec<Json5Splice>) {
+    let bytes = source.as_bytes();
+    let mut json = String::with_capacity(source.len());
+    let mut splices = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => i = json5_string(source, i, &mut json, &mut splices),
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                let end = skip_comment(bytes, i);
+                json.push_str(&source[i..end]);
+                i = end;
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => {
+                let end = json5_number_end(source, i);
+                let number = &source[i..end];
+                if is_json_number(number) {
+                    json.push_str(number);
+                } else if number.bytes().any(|c| c.is_ascii_digit()) || number.ends_with("Infinity") || number.ends_with("NaN") {
+                    json.push_str(&number_placeholder(number.len()));
+                } else {
+                    json.push_str(number);
+                }
+                i = end;
+            }
+            _ => {
+                let c = source[i..].chars().next().unwrap_or_default();
+                if !is_identifier_start(c) {
+                    json.push(c);
+                    i += c.len_utf8();
+                    continue;
+                }
+                let end = i + source[i..].find(|c: char| !is_identifier_part(c)).unwrap_or(source.len() - i);
+                let name = &source[i..end];
+                if is_member_key(bytes, end) {
+                    splices.push(Json5Splice { json: json.len()..json.len() + 1, original: i..i });
+                    json.push('"');
+                    json.push_str(name);
+                    splices.push(Json5Splice { json: json.len()..json.len() + 1, original: end..end });
+                    json.push('"');
+                } else if matches!(name, "Infinity" | "NaN") {
+                    json.push_str(&number_placeholder(name.len()));
+                } else {
+                    json.push_str(name);
+                }
+                i = end;
+            }
+        }
+    }
+    (json, splices)
+}
+
+/// Rewrite the JSON5 string starting at `start` as a double-quoted JSON
+/// string. Returns the offset just past it.
+fn json5_string(source: &str, start: usize, json: &mut String, splices: &mut Vec<Json5Splice>) -> usize {
+    let quote = source.as_bytes()[start] as char;
+    json.push('"');
+    let mut i = start + 1;
+    while let Some(c) = source[i..].chars().next() {
+        match c {
+            '\\' => {
+                let Some(escaped) = source[i + 1..].chars().next() else {
+                    json.push('\\');
+                    return source.len();
+                };
+                let mut len = 1 + escaped.len_utf8();
+                match escaped {
+                    '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u' => {
+                        json.push('\\');
+                        json.push(escaped);
+                    }
+                    // `\x41` reads as `\u` followed by two digits of content
+                    'x' => json.push_str("\\u"),
+                    _ => {
+                        if escaped == '\r' && source[i + 2..].starts_with('\n') {
+                            len += 1;
+                        }
+                        // Same length unless the escaped character is a line
+                        // break or not ASCII
+                        if matches!(escaped, '\n' | '\r') || len != 2 {
+                            splices.push(Json5Splice { json: json.len()..json.len() + 2, original: i..i + len });
+                        }
+                        json.push_str("\\/");
+                    }
+                }
+                i += len;
+            }
+            '\n' => return i,
+            c if c == quote => {
+                json.push('"');
+                return i + 1;
+            }
+            '"' => {
+                splices.push(Json5Splice { json: json.len()..json.len() + 1, original: i..i });
+                json.push_str("\\\"");
+                i += 1;
+            }
+            c => {
+                json.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    source.len()
+}
+
+/// Offset just past the JSON5 number starting at `start`: a sign, then
+/// `Infinity`, `NaN`, a hex literal or a decimal literal.
+fn json5_number_end(source: &str, start: usize) -> usize {
+    let bytes = source.as_bytes();
+    let mut i = start;
+    if matches!(bytes[i], b'+' | b'-') {
+        i += 1;
+    }
+    for word in ["Infinity", "NaN"] {
+        if source[i..].starts_with(word) {
+            return i + word.len();
+        }
+    }
+    if source[i..].starts_with("0x") || source[i..].starts_with("0X") {
+        i += 2;
+        while i < bytes.len() && bytes[i].is_ascii_hexdigit() {
+            i += 1;
+        }
+        return i;
+    }
+    let digits = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    i = digits(i);
+    if bytes.get(i) == Some(&b'.') {
+        i = digits(i + 1);
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let mut j = i + 1;
+        if matches!(bytes.get(j), Some(b'+' | b'-')) {
+            j += 1;
+        }
+        if bytes.get(j).is_some_and(u8::is_ascii_digit) {
+            i = digits(j);
+        }
+    }
+    i
+}
+
+/// Whether `number` is a number in JSON's own syntax
+fn is_json_number(number: &str) -> bool {
+    let bytes = number.strip_prefix('-').unwrap_or(number).as_bytes();
+    let int_len = bytes.iter().position(|c| !c.is_ascii_digit()).unwrap_or(bytes.len());
+    if int_len == 0 || (int_len > 1 && bytes[0] == b'0') {
+        return false;
+    }
+    let mut rest = &bytes[int_len..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let len = fraction.iter().position(|c| !c.is_ascii_digit()).unwrap_or(fraction.len());
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent.strip_prefix(b"+").or_else(|| exponent.strip_prefix(b"-")).unwrap_or(exponent);
+        return !exponent.is_empty() && exponent.iter().all(u8::is_ascii_digit);
+    }
+    rest.is_empty()
+}
+
+/// A JSON number as long as a JSON5 number JSON can't read
+fn number_placeholder(len: usize) -> String {
+    format!("1{}", "0".repeat(len.saturating_sub(1)))
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Whether the name ending at `end` is an object key: a `:` follows it,
+/// past whitespace and comments.
+fn is_member_key(bytes: &[u8], mut end: usize) -> bool {
+    loop {
+        match bytes.get(end) {
+            Some(c) if c.is_ascii_whitespace() => end += 1,
+            Some(b'/') if matches!(bytes.get(end + 1), Some(b'/' | b'*')) => end = skip_comment(bytes, end),
+            Some(b':') => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Offset just past the string starting at `start`
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// Offset just past the `//` or `/* */` comment starting at `start`
+pub(crate) fn skip_comment(bytes: &[u8], start: usize) -> usize {
+    let rest = &bytes[start + 2..];
+    let len = if bytes[start + 1] == b'/' {
+        rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len())
+    } else {
+        rest.windows(2).position(|w| w == b"*/").map_or(rest.len(), |p| p + 2)
+    };
+    start + 2 + len
+}
+
 /// Map element names to syntax categories for highlighting
 pub fn syntax_category(element: &str) -> SyntaxCategory {
     match element {
@@ -109,6 +406,7 @@ pub fn syntax_category(element: &str) -> SyntaxCategory {
         "bool" | "null" => SyntaxCategory::Keyword,
         "property" | "key" | "value" => SyntaxCategory::Default,
         "item" => SyntaxCategory::Keyword,
+        "comment" => SyntaxCategory::Comment,
         _ => SyntaxCategory::Default,
     }
 }
@@ -128,4 +426,46 @@ mod tests {
         assert_eq!(decode_json_escapes(r"a\u00e9b"), "a\u{00e9}b"); // é
     }
 
+    #[test]
+    fn test_decode_json5_escapes() {
+        assert_eq!(decode_json_escapes(r"it\'s"), "it's");
+        assert_eq!(decode_json_escapes(r"\x41é"), "A\u{00e9}");
+        assert_eq!(decode_json_escapes("a\\\nb"), "ab");
+        assert_eq!(decode_json_escapes("a\\\r\nb"), "ab");
+    }
+
+    #[test]
+    fn test_json5_to_json() {
+        let splice = |json: Range<usize>, original: Range<usize>| Json5Splice { json, original };
+        let (json, splices) = json5_to_json("{a: 'x\"y', b: 0x1F, c: +Infinity,}");
+        assert_eq!(json, "{\"a\": \"x\\\"y\", \"b\": 1000, \"c\": 100000000,}");
+        assert_eq!(splices, vec![
+            splice(1..2, 1..1), splice(3..4, 2..2),
+            splice(8..9, 6..6),
+            splice(14..15, 11..11), splice(16..17, 12..12),
+            splice(25..26, 20..20), splice(27..28, 21..21),
+        ]);
+        // Valid JSON, comments and `true` are left alone; a line continuation
+        // is the one same-length rewrite that needs a splice
+        let (json, splices) = json5_to_json("[true, -1.5e3, /* x: 'y' */ \"z\"]");
+        assert_eq!(json, "[true, -1.5e3, /* x: 'y' */ \"z\"]");
+        assert!(splices.is_empty());
+        let (json, splices) = json5_to_json("'a\\\nb'");
+        assert_eq!(json, "\"a\\/b\"");
+        assert_eq!(splices, vec![splice(2..4, 2..4)]);
+    }
+
+    #[test]
+    fn test_blank_trailing_commas() {
+        // Commas inside strings and comments, and commas before a comment
+        // that precedes another member, are left alone
+        let source = "{\n  \"b\": \"x,]\", // c,}\n  /* d, */ \"e\": 1\n}";
+        assert_eq!(blank_trailing_commas(source), source);
+        let source = "{\n  \"a\": [1, 2, /* two */],\n  \"b\": \"x,}\", // last\n}";
+        let blanked = blank_trailing_commas(source);
+        assert_eq!(blanked, "{\n  \"a\": [1, 2  /* two */],\n  \"b\": \"x,}\"  // last\n}");
+        assert_eq!(blanked.len(), source.len());
+        assert_eq!(blank_trailing_commas("[1, 2]"), "[1, 2]");
+    }
+
 }
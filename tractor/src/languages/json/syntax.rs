@@ -15,6 +15,11 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use super::extract_string_content;
 
+/// Element names of the unified syntax vocabulary, shared with YAML
+pub const SYNTAX_ELEMENTS: &[&str] = &[
+    "object", "array", "property", "key", "value", "string", "number", "bool", "null",
+];
+
 // /specs/tractor-parse/dual-view/syntax-branch/vocabulary.md: Unified Syntax Vocabulary
 /// Normalize TreeSitter JSON into unified syntax vocabulary.
 ///
@@ -0,0 +1,302 @@
+//! Kotlin transform logic
+//!
+//! Uses the same vocabulary as the other JVM and mobile languages
+//! (class, method, function, call, import, modifier markers) so that
+//! cross-platform conventions can share one rule set.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers and
+/// modifiers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "package", "imports", "import", "class", "interface", "object", "companion",
+    "function", "method", "constructor", "property", "parameters", "parameter", "arguments",
+    "argument", "name", "type", "nullable", "annotation", "modifiers", "public", "private",
+    "protected", "internal", "abstract", "final", "open", "override", "sealed", "data",
+    "enum", "inner", "value", "suspend", "inline", "operator", "infix", "tailrec",
+    "external", "const", "lateinit", "vararg", "if", "when", "case", "for", "while", "do",
+    "try", "catch", "finally", "jump", "call", "member", "index", "lambda", "assign",
+    "binary", "unary", "logical", "string", "int", "float", "bool", "null", "comment",
+];
+
+/// Transform a Kotlin AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_element_name(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        // ---------------------------------------------------------------------
+        // Flatten nodes - transform children, then remove wrapper
+        // ---------------------------------------------------------------------
+        "class_body" | "enum_class_body" | "function_body" | "statements" => {
+            Ok(TransformAction::Flatten)
+        }
+
+        // ---------------------------------------------------------------------
+        // Name wrappers created by the builder for field="name".
+        // Inline the single identifier child as text:
+        //   <name><identifier>foo</identifier></name> -> <name>foo</name>
+        // ---------------------------------------------------------------------
+        "name" => {
+            inline_single_identifier(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Modifier wrappers - Kotlin groups modifiers in a "modifiers" element
+        // whose children are visibility_modifier, member_modifier, etc.
+        // Convert <modifiers>private override</modifiers> to <private/><override/>
+        // Annotations inside the wrapper are kept as siblings.
+        // ---------------------------------------------------------------------
+        "modifiers" => {
+            let text = xot.string_value(node);
+            for word in text.split_whitespace().collect::<Vec<_>>().iter().rev() {
+                if is_known_modifier(word) {
+                    insert_empty_before(xot, node, word)?;
+                }
+            }
+            let annotations: Vec<_> = get_element_children(xot, node)
+                .into_iter()
+                .filter(|&c| get_element_name(xot, c).as_deref() == Some("annotation"))
+                .collect();
+            for annotation in annotations {
+                xot.detach(annotation)?;
+                xot.insert_before(node, annotation)?;
+            }
+            detach(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        // ---------------------------------------------------------------------
+        // Declarations — prepend <public/> if no visibility modifier (Kotlin's
+        // default visibility), and distinguish methods from top-level functions
+        // ---------------------------------------------------------------------
+        "class_declaration" | "object_declaration" | "function_declaration"
+        | "property_declaration" | "secondary_constructor" => {
+            if !has_visibility_modifier(xot, node) {
+                prepend_empty_element(xot, node, "public")?;
+            }
+            if kind == "class_declaration" && has_keyword(xot, node, "interface") {
+                rename(xot, node, "interface");
+            } else if kind == "function_declaration" && is_member(xot, node) {
+                rename(xot, node, "method");
+            } else if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Binary/unary expressions - extract operator
+        // ---------------------------------------------------------------------
+        "additive_expression" | "multiplicative_expression" | "comparison_expression"
+        | "equality_expression" | "conjunction_expression" | "disjunction_expression"
+        | "prefix_expression" | "assignment" => {
+            extract_operator(xot, node)?;
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Identifiers are always names (definitions or references).
+        // Type positions use `user_type` / `type_identifier`.
+        // ---------------------------------------------------------------------
+        "identifier" | "simple_identifier" => {
+            rename(xot, node, "name");
+            Ok(TransformAction::Continue)
+        }
+        "type_identifier" | "user_type" => {
+            rename(xot, node, "type");
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Other nodes - just rename if needed
+        // ---------------------------------------------------------------------
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Check if text is a visibility modifier keyword
+fn is_visibility_modifier(text: &str) -> bool {
+    matches!(text, "public" | "private" | "protected" | "internal")
+}
+
+/// Known Kotlin modifiers that become marker elements
+fn is_known_modifier(text: &str) -> bool {
+    matches!(text,
+        "public" | "private" | "protected" | "internal" |
+        "abstract" | "final" | "open" | "override" | "sealed" |
+        "data" | "enum" | "inner" | "annotation" | "value" |
+        "suspend" | "inline" | "operator" | "infix" | "tailrec" | "external" |
+        "const" | "lateinit" | "vararg"
+    )
+}
+
+/// Check if a declaration's `modifiers` child contains a visibility keyword
+fn has_visibility_modifier(xot: &Xot, node: XotNode) -> bool {
+    get_element_children(xot, node).into_iter().any(|child| {
+        get_element_name(xot, child).as_deref() == Some("modifiers")
+            && xot.string_value(child).split_whitespace().any(is_visibility_modifier)
+    })
+}
+
+/// Check if a node has a keyword among its direct text children
+fn has_keyword(xot: &Xot, node: XotNode, keyword: &str) -> bool {
+    get_text_children(xot, node)
+        .iter()
+        .any(|t| t.split_whitespace().any(|w| w == keyword))
+}
+
+/// A function is a method when declared inside a class or object body
+fn is_member(xot: &Xot, node: XotNode) -> bool {
+    get_parent(xot, node)
+        .and_then(|p| get_element_name(xot, p))
+        .is_some_and(|name| matches!(name.as_str(), "class_body" | "enum_class_body"))
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "source_file" => Some("file"),
+        "package_header" => Some("package"),
+        "import_list" => Some("imports"),
+        "import_header" | "import" => Some("import"),
+        "class_declaration" => Some("class"),
+        "object_declaration" => Some("object"),
+        "companion_object" => Some("companion"),
+        "function_declaration" => Some("function"),
+        "property_declaration" => Some("property"),
+        "primary_constructor" | "secondary_constructor" => Some("constructor"),
+        "function_value_parameters" | "class_parameters" => Some("parameters"),
+        "parameter" | "class_parameter" => Some("parameter"),
+        "value_arguments" => Some("arguments"),
+        "value_argument" => Some("argument"),
+        "call_expression" => Some("call"),
+        "navigation_expression" => Some("member"),
+        "indexing_expression" => Some("index"),
+        "lambda_literal" | "anonymous_function" => Some("lambda"),
+        "if_expression" => Some("if"),
+        "when_expression" => Some("when"),
+        "when_entry" => Some("case"),
+        "for_statement" => Some("for"),
+        "while_statement" => Some("while"),
+        "do_while_statement" => Some("do"),
+        "try_expression" => Some("try"),
+        "catch_block" => Some("catch"),
+        "finally_block" => Some("finally"),
+        "jump_expression" => Some("jump"),
+        "additive_expression" | "multiplicative_expression" => Some("binary"),
+        "comparison_expression" | "equality_expression" => Some("binary"),
+        "conjunction_expression" | "disjunction_expression" => Some("logical"),
+        "prefix_expression" => Some("unary"),
+        "assignment" => Some("assign"),
+        "nullable_type" => Some("nullable"),
+        "string_literal" | "multiline_string_literal" => Some("string"),
+        "integer_literal" | "long_literal" | "hex_literal" | "number_literal" => Some("int"),
+        "real_literal" | "float_literal" => Some("float"),
+        "boolean_literal" => Some("bool"),
+        "null_literal" => Some("null"),
+        "line_comment" | "multiline_comment" | "block_comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Extract operator from text children and add as `<op>` child element
+fn extract_operator(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let texts = get_text_children(xot, node);
+    let operator = texts.iter().find(|t| {
+        !t.chars().all(|c| matches!(c, '(' | ')' | ',' | ';' | '{' | '}' | '[' | ']'))
+    });
+    if let Some(op) = operator {
+        prepend_op_element(xot, node, op)?;
+    }
+    Ok(())
+}
+
+/// If `node` contains a single identifier child, replace the node's children
+/// with that identifier's text. Used to flatten builder-created wrappers like
+/// `<name><identifier>foo</identifier></name>` to `<name>foo</name>`.
+fn inline_single_identifier(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let children: Vec<_> = xot.children(node).collect();
+    for child in children {
+        let child_name = match get_element_name(xot, child) {
+            Some(n) => n,
+            None => continue,
+        };
+        if !matches!(child_name.as_str(), "identifier" | "simple_identifier" | "type_identifier") {
+            continue;
+        }
+        let text = match get_text_content(xot, child) {
+            Some(t) => t,
+            None => continue,
+        };
+        let all_children: Vec<_> = xot.children(node).collect();
+        for c in all_children {
+            xot.detach(c)?;
+        }
+        let text_node = xot.new_text(&text);
+        xot.append(node, text_node)?;
+        return Ok(());
+    }
+    Ok(())
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Identifiers
+        "name" => SyntaxCategory::Identifier,
+        "type" | "nullable" => SyntaxCategory::Type,
+
+        // Literals
+        "string" => SyntaxCategory::String,
+        "int" | "float" => SyntaxCategory::Number,
+        "bool" | "null" => SyntaxCategory::Keyword,
+
+        // Keywords - declarations
+        "class" | "interface" | "object" | "companion" => SyntaxCategory::Keyword,
+        "function" | "method" | "constructor" | "property" => SyntaxCategory::Keyword,
+        "parameter" | "parameters" => SyntaxCategory::Keyword,
+        "import" | "package" => SyntaxCategory::Keyword,
+
+        // Keywords - control flow
+        "if" | "when" | "case" => SyntaxCategory::Keyword,
+        "for" | "while" | "do" => SyntaxCategory::Keyword,
+        "try" | "catch" | "finally" | "jump" => SyntaxCategory::Keyword,
+
+        // Keywords - modifiers
+        "public" | "private" | "protected" | "internal" => SyntaxCategory::Keyword,
+        "abstract" | "final" | "open" | "override" | "sealed" => SyntaxCategory::Keyword,
+        "data" | "enum" | "inner" | "value" => SyntaxCategory::Keyword,
+        "suspend" | "inline" | "operator" | "infix" | "tailrec" => SyntaxCategory::Keyword,
+        "external" | "const" | "lateinit" | "vararg" => SyntaxCategory::Keyword,
+
+        // Functions/calls
+        "call" => SyntaxCategory::Function,
+        "lambda" => SyntaxCategory::Function,
+
+        // Operators
+        "op" => SyntaxCategory::Operator,
+        _ if is_operator_marker(element) => SyntaxCategory::Operator,
+        "binary" | "unary" | "logical" | "assign" => SyntaxCategory::Operator,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        // Structural elements - no color
+        _ => SyntaxCategory::Default,
+    }
+}
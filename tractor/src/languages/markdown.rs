@@ -4,6 +4,17 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers,
+/// including the `<h1/>`..`<h6/>` heading levels.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "document", "frontmatter", "section", "heading", "h1", "h2", "h3", "h4", "h5", "h6",
+    "paragraph", "blockquote", "hr", "br", "html", "code", "code_block", "language", "list",
+    "item", "ordered", "unordered", "checked", "unchecked", "table", "thead", "row", "cell",
+    "emphasis", "strong", "strikethrough", "link", "image", "label", "destination", "title",
+    "reference", "inline", "text", "escape", "entity", "latex",
+];
+
 /// Transform a Markdown AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
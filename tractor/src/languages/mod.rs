@@ -4,6 +4,7 @@
 //! The shared infrastructure (xot_transform) provides only the walker and helpers.
 
 pub mod info;
+pub mod common;
 pub mod typescript;
 pub mod csharp;
 pub mod python;
@@ -18,6 +19,13 @@ pub mod ini;
 pub mod env;
 pub mod markdown;
 pub mod tsql;
+pub mod kotlin;
+pub mod swift;
+pub mod dart;
+pub mod hcl;
+pub mod dockerfile;
+pub mod protobuf;
+pub mod graphql;
 
 use xot::{Xot, Node as XotNode};
 use crate::xot_transform::TransformAction;
@@ -32,7 +40,7 @@ pub type SyntaxCategoryFn = fn(&str) -> SyntaxCategory;
 
 /// Get the transform function for a language (single-branch transform)
 ///
-/// For data-aware languages (JSON, YAML), prefer `get_data_transforms()` which
+/// For data-aware languages (JSON, YAML, HCL), prefer `get_data_transforms()` which
 /// returns separate AST and data transforms for dual-branch output.
 pub fn get_transform(lang: &str) -> TransformFn {
     match lang {
@@ -43,13 +51,20 @@ pub fn get_transform(lang: &str) -> TransformFn {
         "rust" | "rs" => rust_lang::transform,
         "java" => java::transform,
         "ruby" | "rb" => ruby::transform,
-        "json" => json::data_transform,
+        "json" | "jsonc" | "json5" => json::data_transform,
         "yaml" | "yml" => yaml::data_transform,
         "toml" => toml::transform,
         "ini" => ini::transform,
         "env" => env::transform,
         "markdown" | "md" | "mdx" => markdown::transform,
         "tsql" | "mssql" => tsql::transform,
+        "kotlin" | "kt" => kotlin::transform,
+        "swift" => swift::transform,
+        "dart" => dart::transform,
+        "hcl" | "terraform" | "tf" => hcl::data_transform,
+        "dockerfile" | "docker" => dockerfile::transform,
+        "protobuf" | "proto" => protobuf::transform,
+        "graphql" | "gql" => graphql::transform,
         // Default: passthrough (no transforms)
         _ => passthrough_transform,
     }
@@ -62,15 +77,76 @@ pub fn get_transform(lang: &str) -> TransformFn {
 /// that produce both a `/syntax` and `/data` branch, or `None` for other languages.
 pub fn get_data_transforms(lang: &str) -> Option<(TransformFn, TransformFn)> {
     match lang {
-        "json" => Some((json::ast_transform, json::data_transform)),
+        "json" | "jsonc" | "json5" => Some((json::ast_transform, json::data_transform)),
         "yaml" | "yml" => Some((yaml::ast_transform, yaml::data_transform)),
+        "hcl" | "terraform" | "tf" => Some((hcl::ast_transform, hcl::data_transform)),
         _ => None,
     }
 }
 
+/// Element names a language's transform produces on top of the grammar's
+/// own node kinds and field names, for checking queries against.
+///
+/// `None` for languages whose element names come from the file's keys
+/// (TOML, INI, .env, HCL blocks), so any name may occur.
+pub fn transform_elements(lang: &str) -> Option<Vec<&'static str>> {
+    use crate::xot_transform::{helpers::OPERATOR_MARKERS, DOC_ELEMENTS};
+    let own: Vec<&'static str> = match lang {
+        "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" => typescript::TRANSFORM_ELEMENTS.to_vec(),
+        "csharp" | "cs" => [csharp::TRANSFORM_ELEMENTS, csharp::ACCESS_MODIFIERS, csharp::OTHER_MODIFIERS].concat(),
+        "python" | "py" => python::TRANSFORM_ELEMENTS.to_vec(),
+        "go" => go::TRANSFORM_ELEMENTS.to_vec(),
+        "rust" | "rs" => rust_lang::TRANSFORM_ELEMENTS.to_vec(),
+        "java" => java::TRANSFORM_ELEMENTS.to_vec(),
+        "ruby" | "rb" => ruby::TRANSFORM_ELEMENTS.to_vec(),
+        // The syntax trees of JSON and YAML share one vocabulary
+        "json" | "jsonc" | "json5" | "yaml" | "yml" => json::SYNTAX_ELEMENTS.to_vec(),
+        "toml" | "ini" | "env" | "hcl" | "terraform" | "tf" => return None,
+        "markdown" | "md" | "mdx" => markdown::TRANSFORM_ELEMENTS.to_vec(),
+        "tsql" | "mssql" => tsql::TRANSFORM_ELEMENTS.to_vec(),
+        "kotlin" | "kt" => kotlin::TRANSFORM_ELEMENTS.to_vec(),
+        "swift" => swift::TRANSFORM_ELEMENTS.to_vec(),
+        "dart" => dart::TRANSFORM_ELEMENTS.to_vec(),
+        "dockerfile" | "docker" => dockerfile::TRANSFORM_ELEMENTS.to_vec(),
+        "protobuf" | "proto" => protobuf::TRANSFORM_ELEMENTS.to_vec(),
+        "graphql" | "gql" => graphql::TRANSFORM_ELEMENTS.to_vec(),
+        // Languages without a transform keep the grammar's names
+        _ => Vec::new(),
+    };
+    // Shared helpers add `<op>` with operator markers, and `<doc>` with its
+    // tags for attached doc comments
+    Some([own.as_slice(), &["op"][..], OPERATOR_MARKERS, DOC_ELEMENTS].concat())
+}
+
+/// Languages embedded in a container language's files (notebook cells,
+/// component blocks).
+///
+/// Rules written for an embedded language also run on the container, over
+/// only the parts in that language, each parsed with its own grammar.
+pub fn embedded_languages(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "notebook" | "ipynb" | "jupyter" => &["python", "markdown"],
+        "vue" | "svelte" => &["typescript", "javascript", "html", "css"],
+        _ => &[],
+    }
+}
+
 /// Check whether a language supports the data tree projection.
 pub fn supports_data_tree(lang: &str) -> bool {
-    matches!(lang, "json" | "yaml" | "yml")
+    matches!(lang, "json" | "jsonc" | "json5" | "yaml" | "yml" | "hcl" | "terraform" | "tf" | "csv" | "tsv")
+}
+
+/// Check whether a language parses into the data tree unless another tree
+/// is asked for. HCL blocks and attributes read better as structure, so
+/// queries and checks default to that; `set` always edits its data tree.
+pub fn data_tree_by_default(lang: &str) -> bool {
+    supports_data_tree(lang) && !matches!(lang, "hcl" | "terraform" | "tf")
+}
+
+/// Check whether a language has only the data tree (tables are not parsed
+/// by TreeSitter, so there is no structure or raw tree).
+pub fn data_tree_only(lang: &str) -> bool {
+    matches!(lang, "csv" | "tsv")
 }
 
 /// Get the syntax category function for a language
@@ -84,13 +160,20 @@ pub fn get_syntax_category(lang: &str) -> SyntaxCategoryFn {
         "rust" | "rs" => rust_lang::syntax_category,
         "java" => java::syntax_category,
         "ruby" | "rb" => ruby::syntax_category,
-        "json" => json::syntax_category,
+        "json" | "jsonc" | "json5" => json::syntax_category,
         "yaml" | "yml" => yaml::syntax_category,
         "toml" => toml::syntax_category,
         "ini" => ini::syntax_category,
         "env" => env::syntax_category,
         "markdown" | "md" | "mdx" => markdown::syntax_category,
         "tsql" | "mssql" => tsql::syntax_category,
+        "kotlin" | "kt" => kotlin::syntax_category,
+        "swift" => swift::syntax_category,
+        "dart" => dart::syntax_category,
+        "hcl" | "terraform" | "tf" => hcl::syntax_category,
+        "dockerfile" | "docker" => dockerfile::syntax_category,
+        "protobuf" | "proto" => protobuf::syntax_category,
+        "graphql" | "gql" => graphql::syntax_category,
         // Default: generic fallback
         _ => default_syntax_category,
     }
@@ -105,7 +188,9 @@ pub fn get_singleton_wrappers(lang: &str) -> &'static [&'static str] {
     use crate::xot_transform::helpers::DEFAULT_SINGLETON_WRAPPERS;
     match lang {
         // Data languages don't have singleton wrappers
-        "json" | "yaml" | "yml" | "toml" | "ini" | "env" | "markdown" | "md" | "mdx" => &[],
+        "json" | "jsonc" | "json5" | "yaml" | "yml" | "toml" | "ini" | "env" | "markdown" | "md" | "mdx"
+        | "hcl" | "terraform" | "tf" | "dockerfile" | "docker"
+        | "protobuf" | "proto" | "graphql" | "gql" | "csv" | "tsv" => &[],
         // All programming languages use the default list
         _ => DEFAULT_SINGLETON_WRAPPERS,
     }
@@ -0,0 +1,191 @@
+//! Protocol Buffers transform logic
+//!
+//! Messages, enums and services keep their keyword as element name.
+//! Field numbers become a `number` attribute so they can be compared
+//! across a message, and RPC request/response types become `request` and
+//! `response` children, with a `<stream/>` marker for streaming types:
+//!
+//! ```proto
+//! message User {
+//!   repeated string emails = 2;
+//! }
+//! service Users {
+//!   rpc GetUser(GetUserRequest) returns (User);
+//! }
+//! ```
+//! Becomes:
+//! ```xml
+//! <message>message <name>User</name> {
+//!   <field number="2"><repeated/><type>string</type> <name>emails</name> = <number>2</number>;</field>
+//! }</message>
+//! <service>service <name>Users</name> {
+//!   <rpc>rpc <name>GetUser</name>(<request>GetUserRequest</request>) returns (<response>User</response>);</rpc>
+//! }</service>
+//! ```
+//! Queryable as: `//message[count(field/@number) != count(distinct-values(field/@number))]`
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers,
+/// including the `<repeated/>`, `<optional/>` and `<required/>` labels.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "field", "field_number", "map", "key", "key_type", "value", "option", "options",
+    "extensions", "request", "response", "stream", "repeated", "optional", "required",
+    "name", "type", "number", "string", "int", "float", "comment",
+];
+
+/// Transform a Protobuf AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_kind(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        "source_file" => {
+            rename(xot, node, "file");
+            Ok(TransformAction::Continue)
+        }
+
+        // Bodies are just the braces around the members
+        "message_body" | "enum_body" => Ok(TransformAction::Flatten),
+
+        // Declaration names: <message_name><identifier>User</identifier></message_name> -> <name>User</name>
+        "message_name" | "enum_name" | "service_name" | "rpc_name" => {
+            inline_as(xot, node, "name")?;
+            Ok(TransformAction::Done)
+        }
+
+        // Fields: number as attribute, labels as markers
+        "field" | "oneof_field" | "map_field" => {
+            if let Some(number) = child_text(xot, node, "field_number") {
+                set_attr(xot, node, "number", &number);
+            }
+            let labels: Vec<String> = get_text_children(xot, node)
+                .into_iter()
+                .flat_map(|t| t.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+                .filter(|t| matches!(t.as_str(), "repeated" | "optional" | "required"))
+                .collect();
+            for label in labels.iter().rev() {
+                prepend_empty_element(xot, node, label)?;
+            }
+            if kind == "map_field" {
+                prepend_empty_element(xot, node, "map")?;
+            }
+            rename(xot, node, "field");
+            Ok(TransformAction::Continue)
+        }
+
+        // Enum values: `ACTIVE = 1;` -> <value number="1">
+        "enum_field" => {
+            let negative = get_text_children(xot, node).iter().any(|t| t.contains('-'));
+            if let Some(value) = child_text(xot, node, "int_lit") {
+                let number = if negative { format!("-{}", value) } else { value };
+                set_attr(xot, node, "number", &number);
+            }
+            rename(xot, node, "value");
+            Ok(TransformAction::Continue)
+        }
+
+        // Type references are plain text: <type>string</type>, <type>google.protobuf.Timestamp</type>
+        "type" | "key_type" | "field_number" => {
+            let text = xot.string_value(node).trim().to_string();
+            replace_with_text(xot, node, &text)?;
+            rename(xot, node, match kind.as_str() {
+                "key_type" => "key",
+                "field_number" => "number",
+                _ => "type",
+            });
+            Ok(TransformAction::Done)
+        }
+
+        // RPC signature: the first type is the request, the second the response
+        "message_or_enum_type" => {
+            let is_response = xot.preceding_siblings(node)
+                .any(|s| s != node && get_element_name(xot, s).as_deref() == Some("request"));
+            // `returns (stream User)`: the keyword is in the preceding text
+            let is_stream = xot.previous_sibling(node)
+                .and_then(|s| xot.text_str(s))
+                .is_some_and(|t| t.trim_end().ends_with("stream"));
+            let text = xot.string_value(node).trim().to_string();
+            replace_with_text(xot, node, &text)?;
+            if is_stream {
+                prepend_empty_element(xot, node, "stream")?;
+            }
+            rename(xot, node, if is_response { "response" } else { "request" });
+            Ok(TransformAction::Done)
+        }
+
+        // <int_lit><decimal_lit>1</decimal_lit></int_lit> -> <int>1</int>
+        "int_lit" => {
+            inline_as(xot, node, "int")?;
+            Ok(TransformAction::Done)
+        }
+
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "identifier" | "full_ident" => Some("name"),
+        "decimal_lit" | "octal_lit" | "hex_lit" => Some("int"),
+        "float_lit" => Some("float"),
+        "string" => Some("string"),
+        "field_options" | "enum_value_option" => Some("options"),
+        "field_option" => Some("option"),
+        "extensions" => Some("extensions"),
+        "comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Replace a node's children with its trimmed text and rename it
+fn inline_as(xot: &mut Xot, node: XotNode, name: &str) -> Result<(), xot::Error> {
+    let text = xot.string_value(node).trim().to_string();
+    replace_with_text(xot, node, &text)?;
+    rename(xot, node, name);
+    Ok(())
+}
+
+/// Trimmed text of the first child element with the given kind
+fn child_text(xot: &Xot, node: XotNode, kind: &str) -> Option<String> {
+    get_element_children(xot, node)
+        .into_iter()
+        .find(|&c| get_kind(xot, c).as_deref() == Some(kind))
+        .map(|c| xot.string_value(c).trim().to_string())
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Declarations
+        "message" | "enum" | "service" | "rpc" | "oneof" | "syntax" | "package"
+        | "import" | "option" | "reserved" | "extensions" | "extend" => SyntaxCategory::Keyword,
+
+        // Modifiers
+        "repeated" | "optional" | "required" | "map" | "stream" => SyntaxCategory::Keyword,
+
+        // Types and names
+        "type" | "key" | "request" | "response" => SyntaxCategory::Type,
+        "name" => SyntaxCategory::Identifier,
+
+        // Literals
+        "string" => SyntaxCategory::String,
+        "number" | "int" | "float" => SyntaxCategory::Number,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        _ => SyntaxCategory::Default,
+    }
+}
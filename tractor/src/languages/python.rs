@@ -4,6 +4,17 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "module", "class", "function", "decorated", "decorator", "params", "param", "name",
+    "type", "return", "if", "elif", "else", "for", "while", "try", "except", "finally",
+    "with", "raise", "pass", "import", "from", "async", "await", "lambda", "call", "member",
+    "subscript", "assign", "augassign", "binary", "unary", "compare", "logical", "ternary",
+    "listcomp", "dictcomp", "setcomp", "genexp", "string", "int", "float", "true", "false",
+    "none",
+];
+
 /// Transform a Python AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
@@ -4,6 +4,14 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "program", "module", "class", "method", "if", "unless", "while", "until", "for", "case",
+    "begin", "rescue", "ensure", "call", "assign", "binary", "array", "hash", "symbol",
+    "string", "int", "float",
+];
+
 /// Transform a Ruby AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
@@ -4,6 +4,17 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "mod", "use", "crate", "self", "super", "path", "function", "struct", "enum",
+    "trait", "impl", "type", "typedef", "generic", "const", "static", "field", "param",
+    "params", "name", "let", "ref", "macro", "closure", "pub", "private", "mut", "async",
+    "unsafe", "return", "if", "else", "for", "in", "while", "loop", "match", "arm", "try",
+    "await", "call", "methodcall", "index", "binary", "unary", "string", "rawstring", "int",
+    "float", "bool",
+];
+
 /// Transform a Rust AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
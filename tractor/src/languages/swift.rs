@@ -0,0 +1,308 @@
+//! Swift transform logic
+//!
+//! Swift's grammar folds class, struct, enum, actor and extension into a
+//! single `class_declaration` node distinguished by its keyword; the
+//! transform renames each to its own element so rules read naturally.
+
+use xot::{Xot, Node as XotNode};
+use crate::xot_transform::{TransformAction, helpers::*};
+use crate::output::syntax_highlight::SyntaxCategory;
+
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers and
+/// modifiers, including `<internal/>`.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "import", "class", "struct", "enum", "actor", "extension", "protocol",
+    "typealias", "function", "method", "constructor", "destructor", "property", "parameter",
+    "arguments", "argument", "name", "type", "optional", "attribute", "modifiers", "open",
+    "public", "internal", "fileprivate", "private", "static", "final", "override",
+    "required", "convenience", "mutating", "nonmutating", "lazy", "weak", "unowned",
+    "dynamic", "indirect", "async", "if", "guard", "switch", "case", "for", "while",
+    "repeat", "do", "catch", "jump", "call", "member", "lambda", "assign", "binary",
+    "unary", "logical", "string", "int", "float", "bool", "comment",
+];
+
+/// Transform a Swift AST node
+pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+    let kind = match get_element_name(xot, node) {
+        Some(k) => k,
+        None => return Ok(TransformAction::Continue),
+    };
+
+    match kind.as_str() {
+        // ---------------------------------------------------------------------
+        // Flatten nodes - transform children, then remove wrapper
+        // ---------------------------------------------------------------------
+        "class_body" | "enum_class_body" | "protocol_body" | "function_body"
+        | "statements" => Ok(TransformAction::Flatten),
+
+        // ---------------------------------------------------------------------
+        // Name wrappers created by the builder for field="name".
+        // Inline the single identifier child as text:
+        //   <name><simple_identifier>foo</simple_identifier></name> -> <name>foo</name>
+        // ---------------------------------------------------------------------
+        "name" => {
+            inline_single_identifier(xot, node)?;
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Modifier wrappers - Swift groups modifiers in a "modifiers" element
+        // Convert <modifiers>public static</modifiers> to <public/><static/>
+        // Attributes (@objc, @MainActor) inside the wrapper are kept as siblings.
+        // ---------------------------------------------------------------------
+        "modifiers" => {
+            let text = xot.string_value(node);
+            for word in text.split_whitespace().collect::<Vec<_>>().iter().rev() {
+                if is_known_modifier(word) {
+                    insert_empty_before(xot, node, word)?;
+                }
+            }
+            let attributes: Vec<_> = get_element_children(xot, node)
+                .into_iter()
+                .filter(|&c| get_element_name(xot, c).as_deref() == Some("attribute"))
+                .collect();
+            for attribute in attributes {
+                xot.detach(attribute)?;
+                xot.insert_before(node, attribute)?;
+            }
+            detach(xot, node)?;
+            Ok(TransformAction::Done)
+        }
+
+        // ---------------------------------------------------------------------
+        // Declarations — prepend <internal/> if no access modifier (Swift's
+        // default access level). `class_declaration` covers several type
+        // kinds; pick the element name from its keyword.
+        // ---------------------------------------------------------------------
+        "class_declaration" => {
+            if !has_access_modifier(xot, node) {
+                prepend_empty_element(xot, node, "internal")?;
+            }
+            let name = declaration_kind(xot, node).unwrap_or("class");
+            rename(xot, node, name);
+            Ok(TransformAction::Continue)
+        }
+        "protocol_declaration" | "function_declaration" | "init_declaration"
+        | "property_declaration" | "protocol_function_declaration" => {
+            if !has_access_modifier(xot, node) {
+                prepend_empty_element(xot, node, "internal")?;
+            }
+            if kind == "function_declaration" && is_member(xot, node) {
+                rename(xot, node, "method");
+            } else if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Binary/unary expressions - extract operator
+        // ---------------------------------------------------------------------
+        "additive_expression" | "multiplicative_expression" | "comparison_expression"
+        | "equality_expression" | "conjunction_expression" | "disjunction_expression"
+        | "prefix_expression" | "assignment" => {
+            extract_operator(xot, node)?;
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Identifiers are always names; type positions use `type_identifier`.
+        // ---------------------------------------------------------------------
+        "simple_identifier" => {
+            rename(xot, node, "name");
+            Ok(TransformAction::Continue)
+        }
+        "type_identifier" | "user_type" => {
+            rename(xot, node, "type");
+            Ok(TransformAction::Continue)
+        }
+
+        // ---------------------------------------------------------------------
+        // Other nodes - just rename if needed
+        // ---------------------------------------------------------------------
+        _ => {
+            if let Some(new_name) = map_element_name(&kind) {
+                rename(xot, node, new_name);
+            }
+            Ok(TransformAction::Continue)
+        }
+    }
+}
+
+/// Check if text is an access-level modifier keyword
+fn is_access_modifier(text: &str) -> bool {
+    matches!(text, "open" | "public" | "internal" | "fileprivate" | "private")
+}
+
+/// Known Swift modifiers that become marker elements
+fn is_known_modifier(text: &str) -> bool {
+    matches!(text,
+        "open" | "public" | "internal" | "fileprivate" | "private" |
+        "static" | "class" | "final" | "override" | "required" | "convenience" |
+        "mutating" | "nonmutating" | "lazy" | "weak" | "unowned" | "dynamic" |
+        "optional" | "indirect" | "async"
+    )
+}
+
+/// Check if a declaration's `modifiers` child contains an access-level keyword
+fn has_access_modifier(xot: &Xot, node: XotNode) -> bool {
+    get_element_children(xot, node).into_iter().any(|child| {
+        get_element_name(xot, child).as_deref() == Some("modifiers")
+            && xot.string_value(child).split_whitespace().any(is_access_modifier)
+    })
+}
+
+/// Determine which type kind a `class_declaration` declares from its keyword
+fn declaration_kind(xot: &Xot, node: XotNode) -> Option<&'static str> {
+    for text in get_text_children(xot, node) {
+        for word in text.split_whitespace() {
+            match word {
+                "class" => return Some("class"),
+                "struct" => return Some("struct"),
+                "enum" => return Some("enum"),
+                "actor" => return Some("actor"),
+                "extension" => return Some("extension"),
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// A function is a method when declared inside a type body
+fn is_member(xot: &Xot, node: XotNode) -> bool {
+    get_parent(xot, node)
+        .and_then(|p| get_element_name(xot, p))
+        .is_some_and(|name| matches!(name.as_str(), "class_body" | "enum_class_body" | "protocol_body"))
+}
+
+/// Map tree-sitter node kinds to semantic element names
+fn map_element_name(kind: &str) -> Option<&'static str> {
+    match kind {
+        "source_file" => Some("file"),
+        "import_declaration" => Some("import"),
+        "protocol_declaration" => Some("protocol"),
+        "function_declaration" => Some("function"),
+        "protocol_function_declaration" => Some("method"),
+        "init_declaration" => Some("constructor"),
+        "deinit_declaration" => Some("destructor"),
+        "property_declaration" => Some("property"),
+        "typealias_declaration" => Some("typealias"),
+        "parameter" => Some("parameter"),
+        "value_arguments" => Some("arguments"),
+        "value_argument" => Some("argument"),
+        "call_expression" => Some("call"),
+        "navigation_expression" => Some("member"),
+        "lambda_literal" => Some("lambda"),
+        "if_statement" => Some("if"),
+        "guard_statement" => Some("guard"),
+        "switch_statement" => Some("switch"),
+        "switch_entry" => Some("case"),
+        "for_statement" => Some("for"),
+        "while_statement" => Some("while"),
+        "repeat_while_statement" => Some("repeat"),
+        "do_statement" => Some("do"),
+        "catch_block" => Some("catch"),
+        "control_transfer_statement" => Some("jump"),
+        "additive_expression" | "multiplicative_expression" => Some("binary"),
+        "comparison_expression" | "equality_expression" => Some("binary"),
+        "conjunction_expression" | "disjunction_expression" => Some("logical"),
+        "prefix_expression" => Some("unary"),
+        "assignment" => Some("assign"),
+        "optional_type" => Some("optional"),
+        "line_string_literal" | "multi_line_string_literal" | "raw_string_literal" => Some("string"),
+        "integer_literal" | "hex_literal" | "oct_literal" | "bin_literal" => Some("int"),
+        "real_literal" => Some("float"),
+        "boolean_literal" => Some("bool"),
+        "comment" | "multiline_comment" => Some("comment"),
+        _ => None,
+    }
+}
+
+/// Extract operator from text children and add as `<op>` child element
+fn extract_operator(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let texts = get_text_children(xot, node);
+    let operator = texts.iter().find(|t| {
+        !t.chars().all(|c| matches!(c, '(' | ')' | ',' | ';' | '{' | '}' | '[' | ']'))
+    });
+    if let Some(op) = operator {
+        prepend_op_element(xot, node, op)?;
+    }
+    Ok(())
+}
+
+/// If `node` contains a single identifier child, replace the node's children
+/// with that identifier's text. Used to flatten builder-created wrappers like
+/// `<name><simple_identifier>foo</simple_identifier></name>` to `<name>foo</name>`.
+fn inline_single_identifier(xot: &mut Xot, node: XotNode) -> Result<(), xot::Error> {
+    let children: Vec<_> = xot.children(node).collect();
+    for child in children {
+        let child_name = match get_element_name(xot, child) {
+            Some(n) => n,
+            None => continue,
+        };
+        if !matches!(child_name.as_str(), "simple_identifier" | "type_identifier") {
+            continue;
+        }
+        let text = match get_text_content(xot, child) {
+            Some(t) => t,
+            None => continue,
+        };
+        let all_children: Vec<_> = xot.children(node).collect();
+        for c in all_children {
+            xot.detach(c)?;
+        }
+        let text_node = xot.new_text(&text);
+        xot.append(node, text_node)?;
+        return Ok(());
+    }
+    Ok(())
+}
+
+/// Map a transformed element name to a syntax category for highlighting
+pub fn syntax_category(element: &str) -> SyntaxCategory {
+    match element {
+        // Identifiers
+        "name" => SyntaxCategory::Identifier,
+        "type" | "optional" => SyntaxCategory::Type,
+
+        // Literals
+        "string" => SyntaxCategory::String,
+        "int" | "float" => SyntaxCategory::Number,
+        "bool" => SyntaxCategory::Keyword,
+
+        // Keywords - declarations
+        "class" | "struct" | "enum" | "actor" | "extension" | "protocol" => SyntaxCategory::Keyword,
+        "function" | "method" | "constructor" | "destructor" | "property" => SyntaxCategory::Keyword,
+        "typealias" | "parameter" | "import" => SyntaxCategory::Keyword,
+
+        // Keywords - control flow
+        "if" | "guard" | "switch" | "case" => SyntaxCategory::Keyword,
+        "for" | "while" | "repeat" | "do" | "catch" | "jump" => SyntaxCategory::Keyword,
+
+        // Keywords - modifiers
+        "open" | "public" | "internal" | "fileprivate" | "private" => SyntaxCategory::Keyword,
+        "static" | "final" | "override" | "required" | "convenience" => SyntaxCategory::Keyword,
+        "mutating" | "nonmutating" | "lazy" | "weak" | "unowned" => SyntaxCategory::Keyword,
+        "dynamic" | "indirect" | "async" => SyntaxCategory::Keyword,
+
+        // Functions/calls
+        "call" => SyntaxCategory::Function,
+        "lambda" => SyntaxCategory::Function,
+
+        // Operators
+        "op" => SyntaxCategory::Operator,
+        _ if is_operator_marker(element) => SyntaxCategory::Operator,
+        "binary" | "unary" | "logical" | "assign" => SyntaxCategory::Operator,
+
+        // Comments
+        "comment" => SyntaxCategory::Comment,
+
+        // Structural elements - no color
+        _ => SyntaxCategory::Default,
+    }
+}
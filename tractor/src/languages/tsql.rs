@@ -4,6 +4,19 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "file", "statement", "go", "transaction", "select", "insert", "update", "delete",
+    "merge", "create_table", "create_function", "create_index", "alter_table", "add_column",
+    "exec", "set", "from", "where", "join", "group_by", "having", "order_by",
+    "order_target", "direction", "partition_by", "over", "window", "union", "cte",
+    "subquery", "relation", "alias", "column", "columns", "col", "col_def", "index_fields",
+    "schema", "name", "ref", "temp_ref", "var", "star", "list", "arg", "call", "cast",
+    "case", "when", "between", "exists", "assign", "compare", "body", "literal", "int",
+    "varchar", "nvarchar", "datetime",
+];
+
 /// Transform a T-SQL AST node
 pub fn transform(xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
     let kind = match get_element_name(xot, node) {
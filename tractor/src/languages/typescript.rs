@@ -7,6 +7,17 @@ use xot::{Xot, Node as XotNode};
 use crate::xot_transform::{TransformAction, helpers::*};
 use crate::output::syntax_highlight::SyntaxCategory;
 
+/// Element names the transform produces that are neither tree-sitter node kinds
+/// nor field names: the renames in `map_element_name` and the markers.
+pub const TRANSFORM_ELEMENTS: &[&str] = &[
+    "program", "class", "interface", "enum", "function", "method", "lambda", "variable",
+    "param", "params", "typeparam", "typeparams", "type", "typealias", "typeof", "name",
+    "ref", "import", "export", "default", "let", "const", "var", "async", "await",
+    "optional", "required", "block", "return", "if", "else", "for", "while", "try", "catch",
+    "throw", "call", "new", "member", "assign", "binary", "unary", "ternary", "string",
+    "number", "bool", "null",
+];
+
 /// Transform a TypeScript AST node
 ///
 /// This is the main entry point - receives each node during tree walk
@@ -28,6 +28,7 @@ pub mod model;
 
 // File discovery & path handling
 pub mod glob;
+pub mod encoding;
 
 // Code mutation
 pub mod mutation;
@@ -83,14 +84,15 @@ pub use wasm::ast as wasm_ast;
 
 #[cfg(feature = "native")]
 pub use parser::{
-    detect_language, SUPPORTED_LANGUAGES,
-    parse, ParseInput, ParseOptions,
+    detect_language, detect_language_from_content, SUPPORTED_LANGUAGES,
+    parse, ParseInput, ParseOptions, wants_injections, wants_doc_comments,
+    LanguageConfig, LanguageTransforms, TransformBase,
     load_xml_string_to_documents, load_xml_file_to_documents,
-    XeeParseResult,
+    XeeParseResult, TreeSnapshot, SyntaxError, SyntaxErrorKind,
     get_language_abi_versions, LanguageAbiInfo,
-    print_parse_timing_stats,
+    print_parse_timing_stats, element_vocabulary, pattern_to_xpath,
 };
-pub use xpath::{XPathEngine, Match, XmlNode, print_timing_stats, Documents, DocumentHandle};
+pub use xpath::{XPathEngine, XPathLibrary, PathPrefix, path_prefixes, required_literals, similar_names, unknown_element_names, XPathValue, XPathVariables, CompiledQueries, Match, XmlNode, print_timing_stats, Documents, DocumentHandle};
 pub use output::{render_tree_match, render_source_match, render_lines_match, render_source_precomputed, render_lines, format_message, normalize_path, render_node, render_document, render_xml_string, render_xml_node, render_query_tree_node, render_query_tree_with_source, xml_node_to_string, RenderOptions, format_schema, format_schema_tree, SchemaCollector, xml_node_to_json, extract_syntax_spans_from_xml_node};
 pub use replace::{apply_replacements, apply_set_to_string, ReplaceSummary, ReplaceError};
 pub use report::{Report, ReportBuilder, ReportMatch, ResultItem, Totals, Severity, DiagnosticOrigin, PATHLESS_LABEL, is_pathless_file};
@@ -100,8 +102,10 @@ pub use rule::{GlobMatcher, GlobError, CompiledRule, compile_ruleset};
 #[cfg(feature = "native")]
 pub use files::{expand_globs, expand_globs_checked, GlobExpansion, GlobExpansionError};
 pub use xot_builder::{XotBuilder, XeeBuilder};
+pub use xot::position::{elements_at, ElementAt};
 pub use normalized_xpath::NormalizedXpath;
 pub use normalized_path::NormalizedPath;
+pub use encoding::SourceEncoding;
 pub use glob_pattern::GlobPattern;
 pub use glob_match::CompiledPattern;
 #[cfg(feature = "native")]
@@ -14,7 +14,7 @@ use std::process::ExitCode;
 use clap::{CommandFactory as _, FromArgMatches as _};
 use cli::{Cli, Command, DocsCommand};
 use cli::help::CommandExt as _;
-use cli::{check::run_check, test::run_test, set::run_set, update::run_update, query::run_query, render::run_render, run::run_run, init::run_init, languages::run_languages};
+use cli::{check::run_check, test::run_test, set::run_set, update::run_update, query::run_query, pattern::run_pattern, at::run_at, render::run_render, run::run_run, init::run_init, languages::run_languages};
 use tractor::report::{ReportBuilder, ReportMatch, Severity, DiagnosticOrigin};
 use format::{OutputFormat, ViewField, ViewSet, render_gcc, render_text_report, render_json_report, render_yaml_report, render_xml_report, render_github, render_claude_code};
 use tractor::output::{should_use_color, RenderOptions};
@@ -90,27 +90,31 @@ fn main() -> ExitCode {
     let format_str = match &cli.command {
         Some(Command::Check(a)) => a.format.as_str(),
         Some(Command::Query(a)) => a.format.as_str(),
+        Some(Command::Pattern(a)) => a.format.as_str(),
         Some(Command::Test(a))  => a.format.as_str(),
         Some(Command::Set(a))   => a.format.as_str(),
         Some(Command::Run(a))   => a.format.as_str(),
-        Some(Command::Update(_)) | Some(Command::Render(_)) | Some(Command::Init(_)) | Some(Command::Docs(_)) => "text",
+        Some(Command::Update(_)) | Some(Command::At(_)) | Some(Command::Render(_)) | Some(Command::Init(_)) | Some(Command::Docs(_)) => "text",
         None => cli.query.format.as_str(),
     };
     let fallback_format = OutputFormat::from_str(format_str).unwrap_or(OutputFormat::Text);
     let shared = match &cli.command {
         Some(Command::Check(a)) => &a.shared,
         Some(Command::Query(a)) => &a.shared,
+        Some(Command::Pattern(a)) => &a.shared,
         Some(Command::Test(a))  => &a.shared,
         Some(Command::Set(a))   => &a.shared,
         Some(Command::Update(a)) => &a.shared,
         Some(Command::Run(a))   => &a.shared,
-        Some(Command::Render(_)) | Some(Command::Init(_)) | Some(Command::Docs(_)) => &cli.query.shared,
+        Some(Command::At(_)) | Some(Command::Render(_)) | Some(Command::Init(_)) | Some(Command::Docs(_)) => &cli.query.shared,
         None => &cli.query.shared,
     };
     let fallback_color = if shared.no_color { false } else { should_use_color(&shared.color) };
 
     let result = match cli.command {
         Some(Command::Query(args)) => run_query(args),
+        Some(Command::Pattern(args)) => run_pattern(args),
+        Some(Command::At(args)) => run_at(args),
         Some(Command::Check(args)) => run_check(args),
         Some(Command::Test(args)) => run_test(args),
         Some(Command::Set(args)) => run_set(args),
@@ -1,31 +1,39 @@
 use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
 
 use rayon::prelude::*;
 use tractor::{
     Match, NormalizedXpath,
     language_info::parse_language,
+    languages::embedded_languages,
     output::{render_document, RenderOptions},
-    parse, ParseInput, ParseOptions,
+    parse, wants_doc_comments, wants_injections, LanguageTransforms, ParseInput, ParseOptions, SyntaxErrorKind, XeeParseResult, CompiledQueries,
     report::{Report, ReportMatch, Severity, DiagnosticOrigin},
     rule::CompiledRule,
-    xpath::validate_xpath,
 };
+use crate::executor::{check_tree_mode, evaluate_source, prefilter_queries, SourceParse, TimedQuery};
 use crate::input::filter::Filters;
 use crate::input::Source;
 
-use crate::cli::context::RunContext;
+use crate::cli::context::{ParseErrorPolicy, RunContext};
 use crate::format::{ViewField, ViewSet};
 
 // ---------------------------------------------------------------------------
 // XPath validation
 // ---------------------------------------------------------------------------
 
-/// Validate an XPath expression upfront and return a fatal diagnostic if invalid.
+/// Compile an XPath expression upfront into the run's `queries` and return
+/// a fatal diagnostic if invalid.
 ///
 /// Builds a `ReportMatch` with `Severity::Fatal`, the XPath string as source,
 /// and the error position highlighted. Returns `None` if the XPath is valid.
-pub fn validate_xpath_diagnostic(xpath_expr: &NormalizedXpath, command: &str) -> Option<ReportMatch> {
-    let result = validate_xpath(xpath_expr.as_str());
+pub fn validate_xpath_diagnostic(
+    xpath_expr: &NormalizedXpath,
+    command: &str,
+    queries: &mut CompiledQueries,
+) -> Option<ReportMatch> {
+    let result = queries.add(xpath_expr.as_str());
     if result.valid {
         return None;
     }
@@ -63,12 +71,64 @@ pub fn validate_xpath_diagnostic(xpath_expr: &NormalizedXpath, command: &str) ->
     })
 }
 
+// ---------------------------------------------------------------------------
+// Parse errors
+// ---------------------------------------------------------------------------
+
+/// Build input diagnostics for the syntax errors TreeSitter recovered from
+/// while parsing a source.
+///
+/// Skipped text is a warning and a missing token an info: the tree is still
+/// queryable, but rules may quietly miss code in the broken region. Under
+/// `--fail-on-parse-error` both are errors. Returns nothing unless parse
+/// errors were requested.
+pub fn parse_error_diagnostics(result: &XeeParseResult, command: &str, policy: ParseErrorPolicy) -> Vec<ReportMatch> {
+    if policy == ParseErrorPolicy::Ignore {
+        return Vec::new();
+    }
+
+    result.syntax_errors.iter().map(|error| {
+        let severity = match (policy, &error.kind) {
+            (ParseErrorPolicy::Fail, _) => Severity::Error,
+            (_, SyntaxErrorKind::Missing(_)) => Severity::Info,
+            _ => Severity::Warning,
+        };
+        let lines = result.source_lines
+            .iter()
+            .skip(error.line.saturating_sub(1) as usize)
+            .take(error.end_line.saturating_sub(error.line) as usize + 1)
+            .map(|l| l.trim_end_matches('\r').to_owned())
+            .collect();
+
+        ReportMatch {
+            file: result.file_path.clone(),
+            line: error.line,
+            column: error.column,
+            end_line: error.end_line,
+            end_column: error.end_column,
+            command: command.to_string(),
+            tree: None,
+            value: None,
+            source: None,
+            lines: Some(lines),
+            reason: Some(error.message()),
+            severity: Some(severity),
+            message: None,
+            origin: Some(DiagnosticOrigin::Input),
+            rule_id: None,
+            status: None,
+            output: None,
+        }
+    }).collect()
+}
+
 // ---------------------------------------------------------------------------
 // Debug mode
 // ---------------------------------------------------------------------------
 
 pub fn run_debug(ctx: &RunContext, files: &[String], xpath_expr: &NormalizedXpath) -> Result<(), Box<dyn std::error::Error>> {
     let mut remaining_limit = ctx.limit;
+    let queries = Arc::new(CompiledQueries::new(Some(Arc::clone(&ctx.variables))));
 
     for file_path in files {
         if remaining_limit == Some(0) {
@@ -84,6 +144,12 @@ pub fn run_debug(ctx: &RunContext, files: &[String], xpath_expr: &NormalizedXpat
                 tree_mode: ctx.tree_mode,
                 ignore_whitespace: ctx.ignore_whitespace,
                 parse_depth: ctx.parse_depth,
+                injections: wants_injections(xpath_expr.as_str()),
+                doc_comments: wants_doc_comments(xpath_expr.as_str()),
+                embedded_language: None,
+                transforms: ctx.exec_ctx().transforms,
+                encoding: None,
+                queries: Some(&queries),
             },
         ) {
             Ok(r) => r,
@@ -148,13 +214,15 @@ pub struct RuleMatch {
 /// Returns `true` if:
 /// - The rule has no explicit language (uses the source's language as-is)
 /// - The rule's effective language matches the source's language
+/// - The source is a container for the rule's language (a Python rule on a
+///   notebook, a TypeScript rule on a Vue component)
 ///
 /// This prevents rules from matching sources of incompatible languages,
 /// fixing the mixed-language hang issue where e.g. a markdown rule
 /// would incorrectly apply to JavaScript sources. Works for both disk
 /// sources (language from extension detection) and inline sources
 /// (language from `-l`).
-fn rule_language_matches_source(
+pub fn rule_language_matches_source(
     rule: &CompiledRule,
     source_language: &str,
 ) -> bool {
@@ -166,6 +234,7 @@ fn rule_language_matches_source(
             let rule_language = parse_language(rule_lang);
             let source_lang_enum = parse_language(source_language);
             rule_language == source_lang_enum
+                || embedded_languages(source_lang_enum.as_str()).contains(&rule_language.as_str())
         }
     }
 }
@@ -189,6 +258,9 @@ fn rule_language_matches_source(
 /// - The source's pre-resolved language (from `-l` or extension detection)
 ///
 /// `verbose` controls whether parse/query warnings are printed to stderr.
+/// Syntax errors in parsed sources are returned as diagnostics alongside
+/// the matches, according to `parse_errors`, as are warnings for rules
+/// stopped by their `timeout` or by `timeout_per_file`.
 pub fn run_rules(
     rules: &[CompiledRule],
     sources: &[Source],
@@ -197,13 +269,29 @@ pub fn run_rules(
     parse_depth: Option<usize>,
     verbose: bool,
     filters: &Filters,
-) -> Result<Vec<RuleMatch>, Box<dyn std::error::Error>> {
+    parse_errors: ParseErrorPolicy,
+    transforms: Option<&LanguageTransforms>,
+    compiled: Option<&Arc<CompiledQueries>>,
+    timeout_per_file: Option<Duration>,
+) -> Result<(Vec<RuleMatch>, Vec<ReportMatch>), Box<dyn std::error::Error>> {
+    check_tree_mode(sources, None, tree_mode)?;
+    let queries: Arc<[TimedQuery]> = rules
+        .iter()
+        .map(|rule| {
+            TimedQuery::new(rule.xpath.as_str(), rule.timeout)
+                .with_queries(compiled.map(Arc::as_ref))
+                .with_language(rule.language.as_deref())
+        })
+        .collect();
+    let literals: Vec<Vec<String>> = rules.iter().map(|rule| tractor::required_literals(rule.xpath.as_str())).collect();
+    let transforms = transforms.map(|t| Arc::new(t.clone()));
+
     // Process sources in parallel. Each source is parsed once using either:
     // - The source's detected language (when no rules specify a language override)
     // - The effective language from the first applicable rule (when rules specify a language)
     // Note: rule_language_matches_source() ensures all applicable rules are compatible
     // with the source's language, so we won't try to parse a source in multiple languages.
-    let results: Vec<Vec<RuleMatch>> = sources
+    let results: Vec<(Vec<RuleMatch>, Vec<ReportMatch>)> = sources
         .par_iter()
         .filter_map(|source| {
             let file_path = &source.path;
@@ -228,16 +316,30 @@ pub fn run_rules(
             // All applicable rules are compatible with this source's language
             // (ensured by rule_language_matches_source filter above).
             let first_rule = &rules[applicable[0]];
-            let lang_override = first_rule.language.as_deref();
-            let effective_tree_mode = first_rule.tree_mode.or(tree_mode);
-
-            let mut result = match source.parse(
-                lang_override,
-                effective_tree_mode,
+            // Containers (notebooks, components) parse their embedded code themselves
+            let lang_override = first_rule.language.clone()
+                .filter(|_| embedded_languages(&source.language).is_empty());
+            let parse = SourceParse {
+                language: lang_override,
+                tree_mode: first_rule.tree_mode.or(tree_mode),
                 ignore_whitespace,
                 parse_depth,
-            ) {
-                Ok(r) => r,
+                transforms: transforms.clone(),
+                queries: compiled.cloned(),
+                command: "check".to_string(),
+                parse_errors,
+            };
+
+            // Skip rules whose required words are missing; skip the file if none remain
+            let (applicable, read) = prefilter_queries(source, &parse, &queries, &literals, applicable);
+            if applicable.is_empty() {
+                return None;
+            }
+            let source = read.as_ref().unwrap_or(source);
+
+            // Run all applicable rules against the parsed source
+            let outcome = match evaluate_source(source, &parse, &queries, applicable, timeout_per_file) {
+                Ok(outcome) => outcome,
                 Err(e) => {
                     if verbose {
                         eprintln!("warning: {}: {}", path_str, e);
@@ -246,12 +348,14 @@ pub fn run_rules(
                 }
             };
 
+            let mut diagnostics = outcome.parse_errors;
+            diagnostics.extend(outcome.timeouts.iter().map(|timeout| {
+                timeout.diagnostic("check", timeout.query.map(|i| rules[i].id.clone()))
+            }));
             let mut file_matches = Vec::new();
-
-            // Run all applicable rules against the parsed result
-            for rule_idx in applicable {
-                match result.query(rules[rule_idx].xpath.as_str()) {
-                    Ok(matches) => {
+            for (rule_idx, result) in outcome.results.into_iter().enumerate() {
+                match result {
+                    Some(Ok(matches)) => {
                         for m in matches {
                             file_matches.push(RuleMatch {
                                 rule_index: rule_idx,
@@ -259,7 +363,7 @@ pub fn run_rules(
                             });
                         }
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         if verbose {
                             eprintln!(
                                 "warning: {}: rule '{}' query error: {}",
@@ -267,6 +371,7 @@ pub fn run_rules(
                             );
                         }
                     }
+                    None => {}
                 }
             }
 
@@ -275,21 +380,24 @@ pub fn run_rules(
                 file_matches.retain(|rm| filters.include(&rm.m));
             }
 
-            if file_matches.is_empty() {
+            if file_matches.is_empty() && diagnostics.is_empty() {
                 None
             } else {
-                Some(file_matches)
+                Some((file_matches, diagnostics))
             }
         })
         .collect();
 
     // Flatten and sort by file, line, column for stable output.
-    let mut all_matches: Vec<RuleMatch> = results.into_iter().flatten().collect();
+    let (matches, diagnostics): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+    let mut all_matches: Vec<RuleMatch> = matches.into_iter().flatten().collect();
     all_matches.sort_by(|a, b| {
         (&a.m.file, a.m.line, a.m.column).cmp(&(&b.m.file, b.m.line, b.m.column))
     });
+    let mut all_diagnostics: Vec<ReportMatch> = diagnostics.into_iter().flatten().collect();
+    all_diagnostics.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
 
-    Ok(all_matches)
+    Ok((all_matches, all_diagnostics))
 }
 
 // ---------------------------------------------------------------------------
@@ -303,10 +411,14 @@ pub fn run_rules(
 /// for unselected fields (matching the behaviour of `match_to_report_match`).
 pub fn project_report(report: &mut Report, view: &ViewSet) {
     for m in report.all_matches_mut() {
-        // Fatal diagnostics (broken XPath, bad config) always keep their fields —
-        // the user needs to see why their query failed regardless of -v settings.
-        // Error/Warning matches from user rules are normal output, subject to view.
-        let is_diagnostic = matches!(m.severity, Some(Severity::Fatal));
+        // Fatal diagnostics (broken XPath, bad config) and notes about the queries
+        // themselves (--explain, unknown element names in config rules, timeouts)
+        // always keep their fields — the user needs to see them regardless of -v
+        // settings. Error/Warning matches from user rules are normal output,
+        // subject to view.
+        let is_diagnostic = matches!(m.severity, Some(Severity::Fatal))
+            || m.origin == Some(DiagnosticOrigin::Xpath)
+            || (m.file.is_empty() && m.origin.is_some());
         if !is_diagnostic {
             // Map/Array nodes are always kept — they're the only representation for data formats.
             // For other nodes, keep when tree/lines/source is selected (needed for rendering).
@@ -507,6 +619,26 @@ mod tests {
         assert!(!rule_language_matches_source(&rule, "markdown"));
     }
 
+    #[test]
+    fn test_rule_language_matches_container_source() {
+        // Python rules reach the code cells of notebooks
+        let rule = compile_for_lang(
+            tractor::rule::Rule::new("test", "//any").with_language("python"),
+            None,
+        );
+
+        assert!(rule_language_matches_source(&rule, "notebook"));
+        assert!(!rule_language_matches_source(&rule, "javascript"));
+
+        let rule = compile_for_lang(
+            tractor::rule::Rule::new("test", "//any").with_language("ts"),
+            None,
+        );
+        assert!(rule_language_matches_source(&rule, "vue"));
+        assert!(rule_language_matches_source(&rule, "svelte"));
+        assert!(!rule_language_matches_source(&rule, "notebook"));
+    }
+
     #[test]
     fn prepare_report_for_output_attaches_query_and_schema() {
         let mut builder = ReportBuilder::new();
@@ -553,11 +685,18 @@ mod tests {
             no_pretty: false,
             ignore_whitespace: false,
             verbose: false,
+            explain: false,
+            timeout_per_file: None,
             base_dir: None,
             lang: None,
             debug: false,
             group_by: vec![],
             hook_type: None,
+            parse_errors: ParseErrorPolicy::Ignore,
+            transforms: Default::default(),
+            variables: Default::default(),
+            library: Default::default(),
+            encodings: Default::default(),
         };
 
         prepare_report_for_output(&mut report, &ctx);
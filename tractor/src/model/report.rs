@@ -690,8 +690,9 @@ impl ReportBuilder {
         self.outputs.extend(outputs);
     }
 
-    /// Signal that the operation failed (e.g. test expectations unmet).
-    /// This forces `success` to `Some(false)` regardless of match severities.
+    /// Signal that the operation failed (e.g. test expectations unmet, or
+    /// syntax errors under `--fail-on-parse-error`). This forces `success`
+    /// to `Some(false)` regardless of match severities, even in query mode.
     pub fn fail(&mut self) {
         self.failed = true;
     }
@@ -737,7 +738,7 @@ impl ReportBuilder {
     ///
     /// Success is determined by SuccessMode:
     /// - Derive: false if any Fatal/Error matches or `fail()` was called
-    /// - NoVerdict: None (query mode)
+    /// - NoVerdict: None (query mode), false on fatals or `fail()`
     pub fn build(self) -> Report {
         let mut file_set = std::collections::HashSet::new();
         let mut fatals = 0usize;
@@ -779,8 +780,9 @@ impl ReportBuilder {
         let success = match self.success_mode {
             SuccessMode::NoVerdict => {
                 // No verdict on match results, but fatals are infrastructure
-                // errors (broken XPath, bad config) — always fail.
-                if fatals > 0 { Some(false) } else { None }
+                // errors (broken XPath, bad config) — always fail. So does an
+                // explicit `fail()` (e.g. --fail-on-parse-error).
+                if fatals > 0 || self.failed { Some(false) } else { None }
             }
             SuccessMode::Derive => {
                 let has_failures = fatals > 0 || errors > 0 || self.failed;
@@ -22,6 +22,8 @@
 //! This means `include` lists intersect (both must pass) while `exclude` lists
 //! union (either can reject).
 
+use std::time::Duration;
+
 use crate::normalized_xpath::NormalizedXpath;
 use crate::report::Severity;
 use crate::tree_mode::TreeMode;
@@ -150,6 +152,7 @@ mod glob_matcher {
 #[cfg(feature = "native")]
 mod compiled {
     use std::path::Path;
+    use std::time::Duration;
 
     use super::{GlobError, GlobMatcher, Rule};
     use crate::normalized_xpath::NormalizedXpath;
@@ -193,6 +196,8 @@ mod compiled {
         pub valid_examples: Vec<String>,
         /// Code examples that should fail the check.
         pub invalid_examples: Vec<String>,
+        /// How long the rule may run on one file.
+        pub timeout: Option<Duration>,
         /// Compiled glob matcher combining ruleset and rule layers.
         pub glob: GlobMatcher,
     }
@@ -250,6 +255,7 @@ mod compiled {
                     tree_mode: rule.tree_mode.or(ruleset_default_tree_mode),
                     valid_examples: rule.valid_examples,
                     invalid_examples: rule.invalid_examples,
+                    timeout: rule.timeout,
                     glob,
                 })
             })
@@ -302,6 +308,10 @@ pub struct Rule {
     /// Code examples that should fail the check (1+ matches expected).
     /// In config files: `expect: [{invalid: "..."}]`, CLI: `--expect-invalid`.
     pub invalid_examples: Vec<String>,
+
+    /// How long the rule may run on one file before it is stopped there
+    /// with a warning. None means no limit.
+    pub timeout: Option<Duration>,
 }
 
 impl Rule {
@@ -319,6 +329,7 @@ impl Rule {
             tree_mode: None,
             valid_examples: Vec::new(),
             invalid_examples: Vec::new(),
+            timeout: None,
         }
     }
 
@@ -376,6 +387,12 @@ impl Rule {
         self
     }
 
+    /// Set how long the rule may run on one file.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     /// Returns true if this rule has any examples to validate.
     pub fn has_examples(&self) -> bool {
         !self.valid_examples.is_empty() || !self.invalid_examples.is_empty()
@@ -7,16 +7,21 @@ pub enum TreeMode {
     Raw,
     /// Semantic syntax tree (structure). Default for non-data languages.
     Structure,
+    /// Structure tree with the concepts all languages share (function, class,
+    /// call, import, parameter, string, comment) under one set of names.
+    Common,
     /// Data projection (keys become elements, scalars become text).
-    /// Only available for data-aware languages (JSON, YAML).
+    /// Only available for data-aware languages (JSON, YAML, HCL, CSV).
     Data,
 }
 
 impl TreeMode {
     /// Resolve an optional user-specified tree mode for a given language.
     ///
-    /// When `None`, auto-selects: `Data` for data-aware languages, `Structure` for others.
-    /// Returns `Err` if `Data` is requested for a non-data language.
+    /// When `None`, auto-selects: `Data` for data-aware languages other than
+    /// HCL, `Structure` for the rest (see `languages::data_tree_by_default`).
+    /// Returns `Err` if `Data` is requested for a non-data language, or any
+    /// other mode for a language with only a data tree (CSV, TSV).
     pub fn resolve(mode: Option<TreeMode>, lang: &str) -> Result<TreeMode, String> {
         match mode {
             Some(TreeMode::Data) => {
@@ -29,9 +34,13 @@ impl TreeMode {
                     ))
                 }
             }
+            Some(_) if crate::languages::data_tree_only(lang) => Err(format!(
+                "only the data tree is available for language '{}'; use -t data",
+                lang
+            )),
             Some(m) => Ok(m),
             None => {
-                if crate::languages::supports_data_tree(lang) {
+                if crate::languages::data_tree_by_default(lang) {
                     Ok(TreeMode::Data)
                 } else {
                     Ok(TreeMode::Structure)
@@ -124,7 +124,7 @@ pub fn declarative_set(
     for op in &ops {
         let result = upsert_typed(
             &current_source, lang, &op.xpath,
-            op.value.text(), None, Some(op.value.kind()),
+            op.value.text(), None, Some(op.value.kind()), None,
         )?;
         if result.source != current_source {
             ops_applied += 1;
@@ -0,0 +1,157 @@
+//! In-place edits of JSONC and JSON5 source text.
+//!
+//! Their data trees leave comments out, so re-rendering an object would
+//! drop them. Updates already splice just the rendered value over the old
+//! one; inserts splice the new member into the original text after the
+//! object's last member, and a new document value goes between the comments
+//! around the old one. Comments, trailing commas, quoting and formatting
+//! elsewhere stay exactly as they were.
+
+use std::ops::Range;
+
+use super::xml_edit::Edit;
+use crate::languages::json::skip_comment;
+
+/// Indentation for a new member of the object spanning `object`: that of
+/// its first member when it starts its own line, otherwise one `indent_unit`
+/// deeper than the line the object starts on.
+pub(crate) fn member_indent(source: &str, object: &Range<usize>, first_member: Option<usize>, indent_unit: &str) -> String {
+    match first_member {
+        Some(start) if source[object.start..start].contains('\n') => leading_whitespace(source, start),
+        _ => format!("{}{}", leading_whitespace(source, object.start), indent_unit),
+    }
+}
+
+/// Append `member` (`"key": value`) to the object spanning `object`, from
+/// its `{` to just past its `}`. `last_member_end` is where the current last
+/// member's value ends; it gets a comma if it has none.
+pub(crate) fn append_member(
+    source: &str,
+    object: Range<usize>,
+    last_member_end: Option<usize>,
+    member: &str,
+    indent: &str,
+    newline: &str,
+    trailing_comma: bool,
+) -> Option<Vec<Edit>> {
+    let close = object.end.checked_sub(1).filter(|&at| source.as_bytes().get(at) == Some(&b'}'))?;
+    let comma = if trailing_comma { "," } else { "" };
+    let content = &source[object.start + 1..close];
+    let at = object.start + 1 + content.trim_end_matches([' ', '\t', '\r', '\n']).len();
+
+    // Edits at the same offset each land in front of the one before, so
+    // the member goes first and the previous member's comma precedes it
+    let mut edits = vec![if source[at..close].contains('\n') {
+        // `...last,\n}`: add the member on its own line before the brace
+        Edit {
+            range: at..at,
+            text: format!("{}{}{}{}", newline, indent, member, comma),
+        }
+    } else {
+        Edit {
+            range: at..close,
+            text: format!("{nl}{indent}{member}{comma}{nl}{outer}", nl = newline, outer = leading_whitespace(source, object.start)),
+        }
+    }];
+    if let Some(end) = last_member_end.filter(|&end| !followed_by_comma(source, end)) {
+        edits.push(Edit { range: end..end, text: ",".to_string() });
+    }
+    Some(edits)
+}
+
+/// Offset of the first byte at or after `at` that is not whitespace or part
+/// of a comment.
+pub(crate) fn skip_trivia(source: &str, mut at: usize) -> usize {
+    let bytes = source.as_bytes();
+    loop {
+        match bytes.get(at) {
+            Some(c) if c.is_ascii_whitespace() => at += 1,
+            Some(b'/') if matches!(bytes.get(at + 1), Some(b'/' | b'*')) => at = skip_comment(bytes, at),
+            _ => return at,
+        }
+    }
+}
+
+/// The range of the document's value, leaving out the comments and
+/// whitespace around it; empty at the end of a document that has none.
+pub(crate) fn document_value(source: &str) -> Range<usize> {
+    let bytes = source.as_bytes();
+    let start = skip_trivia(source, 0);
+    let (mut at, mut end) = (start, start);
+    while at < bytes.len() {
+        at = match bytes[at] {
+            quote @ (b'"' | b'\'') => skip_quoted(bytes, at, quote),
+            _ => at + 1,
+        };
+        end = at;
+        at = skip_trivia(source, at);
+    }
+    start..end
+}
+
+/// Offset just past the string starting at `start` and quoted by `quote`
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut at = start + 1;
+    while at < bytes.len() {
+        match bytes[at] {
+            b'\\' => at += 2,
+            c if c == quote => return at + 1,
+            _ => at += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn followed_by_comma(source: &str, at: usize) -> bool {
+    source.as_bytes().get(skip_trivia(source, at)) == Some(&b',')
+}
+
+/// Whitespace at the start of the line holding `offset`
+fn leading_whitespace(source: &str, offset: usize) -> String {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    source[line_start..offset]
+        .chars()
+        .take_while(|&c| c == ' ' || c == '\t')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::xml_edit::apply;
+
+    fn insert(source: &str, last_member_end: Option<usize>, trailing_comma: bool) -> String {
+        let start = source.find('{').unwrap();
+        let object = start..source.rfind('}').unwrap() + 1;
+        let edits = append_member(source, object, last_member_end, "\"b\": 2", "  ", "\n", trailing_comma).unwrap();
+        apply(source, edits)
+    }
+
+    #[test]
+    fn appends_after_last_member_and_its_comment() {
+        let source = "{\n  \"a\": 1 // one\n}";
+        assert_eq!(insert(source, Some(10), false), "{\n  \"a\": 1, // one\n  \"b\": 2\n}");
+        let source = "{\n  \"a\": 1,\n}";
+        assert_eq!(insert(source, Some(10), true), "{\n  \"a\": 1,\n  \"b\": 2,\n}");
+    }
+
+    #[test]
+    fn expands_empty_and_inline_objects() {
+        assert_eq!(insert("{}", None, false), "{\n  \"b\": 2\n}");
+        assert_eq!(insert("{\"a\": 1}", Some(7), false), "{\"a\": 1,\n  \"b\": 2\n}");
+    }
+
+    #[test]
+    fn finds_the_document_value_between_comments() {
+        let source = "// list\n[1, '// not a comment'] /* end */\n";
+        assert_eq!(&source[document_value(source)], "[1, '// not a comment']");
+        assert_eq!(document_value("// empty\n"), 9..9);
+    }
+
+    #[test]
+    fn indents_like_the_first_member() {
+        let source = "{\n    \"a\": 1\n}";
+        assert_eq!(member_indent(source, &(0..source.len()), Some(11), "  "), "    ");
+        assert_eq!(member_indent("  \"x\": {}", &(7..9), None, "  "), "    ");
+    }
+}
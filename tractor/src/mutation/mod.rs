@@ -5,3 +5,7 @@ pub mod replace;
 pub mod xpath_upsert;
 #[cfg(feature = "native")]
 pub mod declarative_set;
+#[cfg(feature = "native")]
+mod xml_edit;
+#[cfg(feature = "native")]
+mod json_edit;
@@ -4,11 +4,12 @@
 //! in the original files with a new value. The replacement value is used as-is
 //! (literal text splice — the caller is responsible for escaping/formatting).
 
+use crate::encoding::{self, Encoding};
 use crate::xpath::Match;
 use std::collections::HashMap;
 use std::fmt;
-use std::fs;
 use std::io;
+use std::path::Path;
 
 /// Summary of replacements applied to files.
 #[derive(Debug)]
@@ -193,6 +194,8 @@ fn apply_to_content_str(
 ///
 /// Matches are grouped by file and applied in a single pass (ascending order).
 /// The replacement value is used literally — no escaping or formatting is applied.
+/// Each file is read and written back in its own encoding; `encoding_for`
+/// gives a file's configured default (see [`crate::encoding`]).
 ///
 /// # Errors
 ///
@@ -200,7 +203,11 @@ fn apply_to_content_str(
 /// - Two matches overlap in the same file
 /// - A file cannot be read or written
 /// - A match has no valid file path (e.g. from stdin)
-pub fn apply_replacements(matches: &[Match], new_value: &str) -> Result<ReplaceSummary, ReplaceError> {
+pub fn apply_replacements(
+    matches: &[Match],
+    new_value: &str,
+    encoding_for: impl Fn(&str) -> Option<&'static Encoding>,
+) -> Result<ReplaceSummary, ReplaceError> {
     if matches.is_empty() {
         return Ok(ReplaceSummary {
             files_modified: 0,
@@ -229,8 +236,8 @@ pub fn apply_replacements(matches: &[Match], new_value: &str) -> Result<ReplaceS
     let mut replacements_made = 0;
 
     for (file_path, file_matches) in by_file {
-        // Read the original file content
-        let content = fs::read_to_string(file_path).map_err(|e| ReplaceError::Io {
+        // Read the original file content, remembering its encoding
+        let (content, encoding) = encoding::read_file(Path::new(file_path), encoding_for(file_path)).map_err(|e| ReplaceError::Io {
             path: file_path.to_string(),
             source: e,
         })?;
@@ -240,7 +247,7 @@ pub fn apply_replacements(matches: &[Match], new_value: &str) -> Result<ReplaceS
 
         // Write back only if content actually changed
         if result != content {
-            fs::write(file_path, &result).map_err(|e| ReplaceError::Io {
+            encoding::write_file(Path::new(file_path), &result, encoding).map_err(|e| ReplaceError::Io {
                 path: file_path.to_string(),
                 source: e,
             })?;
@@ -258,6 +265,7 @@ pub fn apply_replacements(matches: &[Match], new_value: &str) -> Result<ReplaceS
 mod tests {
     use super::*;
     use crate::model::report::PATHLESS_LABEL;
+    use std::fs;
     use std::sync::Arc;
 
     #[test]
@@ -315,12 +323,65 @@ mod tests {
             Arc::new(vec![r#"{"key": "old"}"#.to_string()]),
         )];
 
-        let result = apply_replacements(&matches, "\"new\"").unwrap();
+        let result = apply_replacements(&matches, "\"new\"", |_| None).unwrap();
         assert_eq!(result.replacements_made, 1);
         assert_eq!(result.files_modified, 1);
         assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"key": "new"}"#);
     }
 
+    #[test]
+    fn test_apply_replacements_keeps_utf16_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("test.json");
+        let utf16 = |text: &str| -> Vec<u8> {
+            let mut bytes = vec![0xFF, 0xFE];
+            bytes.extend(text.encode_utf16().flat_map(|u| u.to_le_bytes()));
+            bytes
+        };
+        fs::write(&file, utf16(r#"{"key": "old"}"#)).unwrap();
+
+        let matches = vec![Match::with_location(
+            file.to_str().unwrap().to_string(),
+            1,
+            9,
+            1,
+            14,
+            "\"old\"".to_string(),
+            Arc::new(vec![r#"{"key": "old"}"#.to_string()]),
+        )];
+
+        apply_replacements(&matches, "\"new\"", |_| None).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), utf16(r#"{"key": "new"}"#));
+    }
+
+    #[test]
+    fn test_apply_replacements_uses_configured_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Legacy.cs");
+        // "// café" in Windows-1252
+        fs::write(&file, b"// caf\xE9").unwrap();
+        let path = file.to_str().unwrap().to_string();
+
+        let matches = vec![Match::with_location(
+            path.clone(),
+            1,
+            4,
+            1,
+            9,
+            "café".to_string(),
+            Arc::new(vec!["// café".to_string()]),
+        )];
+
+        // Read and written back as the Windows-1252 the file most likely is
+        apply_replacements(&matches, "thé", |_| None).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"// th\xE9");
+
+        // A configured encoding wins over the guess
+        fs::write(&file, b"// caf\xE9").unwrap();
+        apply_replacements(&matches, "€", |p| (p == path).then(|| encoding::lookup("iso-8859-15")).flatten()).unwrap();
+        assert_eq!(fs::read(&file).unwrap(), b"// \xA4");
+    }
+
     #[test]
     fn test_apply_replacements_multiple_same_file() {
         let dir = tempfile::tempdir().unwrap();
@@ -335,7 +396,7 @@ mod tests {
             Match::with_location(file_str.clone(), 1, 9, 1, 12, "aaa".to_string(), Arc::clone(&source)),
         ];
 
-        let result = apply_replacements(&matches, "xxx").unwrap();
+        let result = apply_replacements(&matches, "xxx", |_| None).unwrap();
         assert_eq!(result.replacements_made, 2);
         assert_eq!(fs::read_to_string(&file).unwrap(), "xxx bbb xxx");
     }
@@ -353,7 +414,7 @@ mod tests {
             Arc::new(vec!["line1".to_string(), "OLD".to_string(), "line3".to_string()]),
         )];
 
-        let result = apply_replacements(&matches, "NEW").unwrap();
+        let result = apply_replacements(&matches, "NEW", |_| None).unwrap();
         assert_eq!(result.replacements_made, 1);
         assert_eq!(fs::read_to_string(&file).unwrap(), "line1\nNEW\nline3");
     }
@@ -372,7 +433,7 @@ mod tests {
             Match::with_location(file_str.clone(), 1, 3, 1, 7, "cdef".to_string(), Arc::clone(&source)),
         ];
 
-        let result = apply_replacements(&matches, "x");
+        let result = apply_replacements(&matches, "x", |_| None);
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), ReplaceError::OverlappingMatches { .. }));
     }
@@ -392,7 +453,7 @@ mod tests {
             Match::with_location(file_str.clone(), 1, 1, 1, 6, "hello".to_string(), Arc::clone(&source)),
         ];
 
-        let result = apply_replacements(&matches, "world").unwrap();
+        let result = apply_replacements(&matches, "world", |_| None).unwrap();
         assert_eq!(result.replacements_made, 1);
         assert_eq!(fs::read_to_string(&file).unwrap(), "world");
     }
@@ -400,13 +461,13 @@ mod tests {
     #[test]
     fn test_apply_replacements_pathless_rejected() {
         let matches = vec![Match::new(PATHLESS_LABEL.to_string(), "value".to_string())];
-        let result = apply_replacements(&matches, "x");
+        let result = apply_replacements(&matches, "x", |_| None);
         assert!(matches!(result, Err(ReplaceError::NoFilePath { .. })));
     }
 
     #[test]
     fn test_apply_replacements_empty() {
-        let result = apply_replacements(&[], "x").unwrap();
+        let result = apply_replacements(&[], "x", |_| None).unwrap();
         assert_eq!(result.replacements_made, 0);
         assert_eq!(result.files_modified, 0);
     }
@@ -425,7 +486,7 @@ mod tests {
         )];
 
         // Replace with longer string
-        let result = apply_replacements(&matches, "xyz123").unwrap();
+        let result = apply_replacements(&matches, "xyz123", |_| None).unwrap();
         assert_eq!(result.replacements_made, 1);
         assert_eq!(fs::read_to_string(&file).unwrap(), "xyz123");
     }
@@ -443,7 +504,7 @@ mod tests {
             Arc::new(vec!["remove_me".to_string()]),
         )];
 
-        let result = apply_replacements(&matches, "").unwrap();
+        let result = apply_replacements(&matches, "", |_| None).unwrap();
         assert_eq!(result.replacements_made, 1);
         assert_eq!(fs::read_to_string(&file).unwrap(), "");
     }
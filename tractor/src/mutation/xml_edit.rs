@@ -0,0 +1,277 @@
+//! In-place edits of XML source text.
+//!
+//! XML upserts don't re-render the matched element: they splice the new
+//! value into the original text, so quote style, self-closing tags,
+//! attribute order and whitespace stay exactly as they were. Each function
+//! takes the byte range of one element (from `<` to the end of its closing
+//! tag, as recorded by the XML parser) and returns a single edit.
+
+use std::ops::Range;
+
+/// A replacement of `range` in the source by `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Edit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Set attribute `name` of the element starting at `start`, replacing its
+/// value in place or adding it after the last attribute.
+pub(crate) fn set_attribute(source: &str, start: usize, name: &str, value: &str) -> Option<Edit> {
+    let tag = StartTag::scan(source, start)?;
+    match tag.attributes.iter().find(|attr| attr.name == name) {
+        Some(attr) => Some(Edit {
+            range: attr.value.clone(),
+            text: escape_attribute(value, attr.quote),
+        }),
+        None => {
+            let at = tag.attributes.last().map_or(tag.name.end, |attr| attr.value.end + 1);
+            Some(Edit {
+                range: at..at,
+                text: format!(" {}=\"{}\"", name, escape_attribute(value, '"')),
+            })
+        }
+    }
+}
+
+/// Replace the content of the element spanning `element` with `value`,
+/// expanding a self-closing tag into a start and end tag.
+pub(crate) fn set_text(source: &str, element: Range<usize>, value: &str) -> Option<Edit> {
+    let tag = StartTag::scan(source, element.start)?;
+    let name = &source[tag.name.clone()];
+    let escaped = escape_text(value);
+    if tag.self_closing {
+        let slash = source[..tag.end - 2].trim_end().len();
+        return Some(Edit {
+            range: slash..tag.end,
+            text: format!(">{}</{}>", escaped, name),
+        });
+    }
+    Some(Edit {
+        range: tag.end..end_tag_start(source, &element)?,
+        text: escaped,
+    })
+}
+
+/// Append `child` (XML text for one new element) as the last child of the
+/// element spanning `element`, indented like its existing children.
+pub(crate) fn append_child(source: &str, element: Range<usize>, child: &str, indent_unit: &str, newline: &str) -> Option<Edit> {
+    let tag = StartTag::scan(source, element.start)?;
+    let name = &source[tag.name.clone()];
+    let indent = line_indent(source, element.start);
+    let child_indent = first_child_indent(source, tag.end, &element)
+        .unwrap_or_else(|| format!("{}{}", indent, indent_unit));
+
+    if tag.self_closing {
+        let slash = source[..tag.end - 2].trim_end().len();
+        return Some(Edit {
+            range: slash..tag.end,
+            text: format!(">{nl}{ci}{child}{nl}{indent}</{name}>", nl = newline, ci = child_indent),
+        });
+    }
+
+    let close = end_tag_start(source, &element)?;
+    let content = &source[tag.end..close];
+    let trimmed = content.trim_end_matches([' ', '\t', '\r', '\n']).len();
+    if content[trimmed..].contains('\n') {
+        // `...<last/>\n  </parent>`: add the child on its own line before the closing tag
+        let at = tag.end + trimmed;
+        Some(Edit {
+            range: at..at,
+            text: format!("{}{}{}", newline, child_indent, child),
+        })
+    } else {
+        Some(Edit {
+            range: tag.end + trimmed..close,
+            text: format!("{nl}{ci}{child}{nl}{indent}", nl = newline, ci = child_indent),
+        })
+    }
+}
+
+/// Nested new elements for `keys`, the innermost holding `value`:
+/// `["a", "b"]` becomes `<a><b>value</b></a>`.
+pub(crate) fn new_elements(keys: &[String], value: &str) -> String {
+    let mut xml = escape_text(value);
+    for key in keys.iter().rev() {
+        xml = format!("<{key}>{xml}</{key}>");
+    }
+    xml
+}
+
+/// Apply edits to `source`. Edits must not overlap.
+pub(crate) fn apply(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    edits.dedup();
+    let mut result = source.to_string();
+    for edit in edits {
+        result.replace_range(edit.range, &edit.text);
+    }
+    result
+}
+
+/// The parts of a start tag needed for editing.
+struct StartTag {
+    name: Range<usize>,
+    attributes: Vec<Attribute>,
+    self_closing: bool,
+    /// Offset just past the closing `>`
+    end: usize,
+}
+
+struct Attribute {
+    name: String,
+    /// Value range, excluding the quotes
+    value: Range<usize>,
+    quote: char,
+}
+
+impl StartTag {
+    fn scan(source: &str, start: usize) -> Option<StartTag> {
+        let bytes = source.as_bytes();
+        if bytes.get(start) != Some(&b'<') {
+            return None;
+        }
+        let name_end = scan_name(bytes, start + 1);
+        let name = start + 1..name_end;
+        let mut attributes = Vec::new();
+        let mut pos = name_end;
+        loop {
+            pos = skip_whitespace(bytes, pos);
+            match bytes.get(pos)? {
+                b'>' => return Some(StartTag { name, attributes, self_closing: false, end: pos + 1 }),
+                b'/' if bytes.get(pos + 1) == Some(&b'>') => {
+                    return Some(StartTag { name, attributes, self_closing: true, end: pos + 2 });
+                }
+                _ => {
+                    let attr_end = scan_name(bytes, pos);
+                    if attr_end == pos {
+                        return None;
+                    }
+                    let attr_name = source[pos..attr_end].to_string();
+                    pos = skip_whitespace(bytes, attr_end);
+                    if bytes.get(pos) != Some(&b'=') {
+                        return None;
+                    }
+                    pos = skip_whitespace(bytes, pos + 1);
+                    let quote = *bytes.get(pos)?;
+                    if quote != b'"' && quote != b'\'' {
+                        return None;
+                    }
+                    let value_start = pos + 1;
+                    let value_end = value_start + source[value_start..].find(quote as char)?;
+                    attributes.push(Attribute {
+                        name: attr_name,
+                        value: value_start..value_end,
+                        quote: quote as char,
+                    });
+                    pos = value_end + 1;
+                }
+            }
+        }
+    }
+}
+
+fn scan_name(bytes: &[u8], mut pos: usize) -> usize {
+    while let Some(&b) = bytes.get(pos) {
+        if b.is_ascii_whitespace() || matches!(b, b'=' | b'/' | b'>') {
+            break;
+        }
+        pos += 1;
+    }
+    pos
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Offset of the `</` opening the element's end tag
+fn end_tag_start(source: &str, element: &Range<usize>) -> Option<usize> {
+    source[..element.end].rfind("</").filter(|&at| at >= element.start)
+}
+
+/// Whitespace before `offset` on its line, if only whitespace precedes it
+fn line_indent(source: &str, offset: usize) -> String {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &source[line_start..offset];
+    if prefix.chars().all(|c| c == ' ' || c == '\t') {
+        prefix.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Indentation of the first child element that starts its own line
+fn first_child_indent(source: &str, content_start: usize, element: &Range<usize>) -> Option<String> {
+    let content = &source[content_start..end_tag_start(source, element)?];
+    let first = content.find('<')?;
+    let before = &content[..first];
+    let line_start = before.rfind('\n')? + 1;
+    Some(before[line_start..].trim_end_matches('\r').to_string())
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn escape_attribute(s: &str, quote: char) -> String {
+    let escaped = s.replace('&', "&amp;").replace('<', "&lt;");
+    match quote {
+        '\'' => escaped.replace('\'', "&apos;"),
+        _ => escaped.replace('"', "&quot;"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(source: &str, edit: Option<Edit>) -> String {
+        apply(source, vec![edit.unwrap()])
+    }
+
+    #[test]
+    fn replaces_attribute_value_keeping_quotes() {
+        let source = "<a x='1'  y=\"2\"/>";
+        assert_eq!(edit(source, set_attribute(source, 0, "x", "it's")), "<a x='it&apos;s'  y=\"2\"/>");
+        assert_eq!(edit(source, set_attribute(source, 0, "y", "<3")), "<a x='1'  y=\"&lt;3\"/>");
+    }
+
+    #[test]
+    fn adds_missing_attribute_after_the_last_one() {
+        let source = "<a x=\"1\" />";
+        assert_eq!(edit(source, set_attribute(source, 0, "z", "2")), "<a x=\"1\" z=\"2\" />");
+        let source = "<a>text</a>";
+        assert_eq!(edit(source, set_attribute(source, 0, "z", "2")), "<a z=\"2\">text</a>");
+    }
+
+    #[test]
+    fn sets_text_of_element_and_empty_element() {
+        let source = "<v a='1'>old</v>";
+        assert_eq!(edit(source, set_text(source, 0..source.len(), "a&b")), "<v a='1'>a&amp;b</v>");
+        let source = "<v />";
+        assert_eq!(edit(source, set_text(source, 0..source.len(), "1")), "<v>1</v>");
+    }
+
+    #[test]
+    fn appends_child_with_sibling_indentation() {
+        let source = "<p>\n    <a/>\n</p>";
+        assert_eq!(
+            edit(source, append_child(source, 0..source.len(), "<b>1</b>", "  ", "\n")),
+            "<p>\n    <a/>\n    <b>1</b>\n</p>"
+        );
+        let source = "  <p/>";
+        assert_eq!(
+            edit(source, append_child(source, 2..source.len(), "<b>1</b>", "  ", "\n")),
+            "  <p>\n    <b>1</b>\n  </p>"
+        );
+    }
+
+    #[test]
+    fn nests_new_elements() {
+        assert_eq!(new_elements(&["a".to_string(), "b".to_string()], "x<y"), "<a><b>x&lt;y</b></a>");
+    }
+}
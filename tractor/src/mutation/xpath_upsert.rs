@@ -16,12 +16,20 @@
 //! position (`start` attribute). This avoids any re-parsing or re-querying.
 //!
 //! All language-specific knowledge lives in the parser and renderer.
-//! The upsert algorithm itself is language-agnostic.
+//! The upsert algorithm itself is language-agnostic, with two exceptions:
+//! XML input is edited in place (see [`super::xml_edit`]) since it is
+//! queried as-is and any re-rendering would change its formatting, and
+//! inserts into JSONC and JSON5 objects splice in just the new member (see
+//! [`super::json_edit`]) since their data trees have no comments to
+//! re-render. Updates of those files only ever splice the new value.
 
+use std::sync::Arc;
+
+use super::{json_edit, xml_edit};
 use crate::parser::{parse, ParseInput, ParseOptions, XeeParseResult};
 use crate::render::{self, RenderOptions};
 use crate::tree_mode::TreeMode;
-use crate::xpath::xot_node_to_xml_node;
+use crate::xpath::{xot_node_to_xml_node, CompiledQueries};
 pub use crate::xpath::Match;
 use crate::xot_transform::helpers::*;
 use xot::Xot;
@@ -69,12 +77,15 @@ pub enum UpsertError {
 /// Like [`upsert`], but if the XPath does not match any existing nodes, no
 /// changes are made (no intermediate nodes are created). Returns an
 /// `UpsertResult` with `matches_updated == 0` when nothing matched.
+/// The query is taken from the run's `queries` when given, which also bind
+/// its variables.
 pub fn update_only(
     source: &str,
     lang: &str,
     xpath: &str,
     value: &str,
     limit: Option<usize>,
+    queries: Option<&Arc<CompiledQueries>>,
 ) -> Result<UpsertResult, UpsertError> {
     // Verify the language has a renderer that supports data mode
     let test_render = render::render(
@@ -102,6 +113,12 @@ pub fn update_only(
             tree_mode: Some(TreeMode::Data),
             ignore_whitespace: false,
             parse_depth: None,
+            injections: false,
+            doc_comments: false,
+            embedded_language: None,
+            transforms: None,
+            encoding: None,
+            queries,
         },
     )
     .map_err(|e| UpsertError::Parse(e.to_string()))?;
@@ -126,7 +143,7 @@ pub fn update_only(
     } else {
         &existing
     };
-    update_existing(source, lang, value, matches, result)
+    update_existing(source, lang, value, matches, result, attribute_step(xpath))
 }
 
 /// Upsert a value into a source string at the path given by an XPath expression.
@@ -145,7 +162,7 @@ pub fn upsert(
     value: &str,
     limit: Option<usize>,
 ) -> Result<UpsertResult, UpsertError> {
-    upsert_typed(source, lang, xpath, value, limit, Some("string"))
+    upsert_typed(source, lang, xpath, value, limit, Some("string"), None)
 }
 
 /// Like [`upsert`] but with explicit control over the value kind annotation.
@@ -154,6 +171,9 @@ pub fn upsert(
 ///   - `Some("string")` — force string (default for `--value`)
 ///   - `Some("null")` / `Some("number")` etc. — force that kind
 ///   - `None` — let the renderer auto-detect from the value text
+///
+/// The query is taken from the run's `queries` when given, which also bind
+/// its variables.
 pub fn upsert_typed(
     source: &str,
     lang: &str,
@@ -161,6 +181,7 @@ pub fn upsert_typed(
     value: &str,
     limit: Option<usize>,
     value_kind: Option<&str>,
+    queries: Option<&Arc<CompiledQueries>>,
 ) -> Result<UpsertResult, UpsertError> {
     // Verify the language has a renderer that supports data mode
     let test_render = render::render(
@@ -188,6 +209,12 @@ pub fn upsert_typed(
             tree_mode: Some(TreeMode::Data),
             ignore_whitespace: false,
             parse_depth: None,
+            injections: false,
+            doc_comments: false,
+            embedded_language: None,
+            transforms: None,
+            encoding: None,
+            queries,
         },
     )
     .map_err(|e| UpsertError::Parse(e.to_string()))?;
@@ -203,7 +230,34 @@ pub fn upsert_typed(
         } else {
             &existing
         };
-        update_existing(source, lang, value, matches, result)
+        update_existing(source, lang, value, matches, result, attribute_step(xpath))
+    } else if let Some(name) = attribute_step(xpath) {
+        // Missing attribute: add it to the elements selected by the rest of the path
+        let parent_xpath = xpath.trim().rsplit_once('/').map(|(parent, _)| parent)
+            .filter(|parent| !parent.trim_end_matches('/').is_empty())
+            .ok_or_else(|| UpsertError::NoInsertionPoint(
+                format!("attribute '@{}' has no parent element", name),
+            ))?;
+        let parents = result.query(parent_xpath)
+            .map_err(|e| UpsertError::Query(e.to_string()))?;
+        if parents.is_empty() {
+            return Err(UpsertError::NoInsertionPoint(
+                format!("no element matches '{}' to add '@{}' to", parent_xpath, name),
+            ));
+        }
+        let parents = if let Some(n) = limit {
+            &parents[..n.min(parents.len())]
+        } else {
+            &parents
+        };
+        let updated = update_existing(source, lang, value, parents, result, Some(name))?;
+        Ok(UpsertResult {
+            inserted: true,
+            matches_updated: 0,
+            matches: vec![],
+            description: format!("inserted @{}", name),
+            ..updated
+        })
     } else {
         // Insert path
         insert_new(source, lang, xpath, value, value_kind, result)
@@ -215,13 +269,22 @@ pub fn upsert_typed(
 /// Handles all matches in a single pass: mutates all matched nodes in the
 /// tree, re-renders once, then splices all modified spans back into the
 /// original source (applied in reverse order to preserve byte offsets).
+///
+/// With `attribute`, the matched elements get that attribute set to `value`
+/// instead of having their text replaced (attribute matches report the
+/// location of their element).
 fn update_existing(
     source: &str,
     lang: &str,
     value: &str,
     matches: &[Match],
     mut result: XeeParseResult,
+    attribute: Option<&str>,
 ) -> Result<UpsertResult, UpsertError> {
+    if lang == "xml" {
+        return update_xml(source, value, matches, attribute);
+    }
+
     let doc_node = result.documents.document_node(result.doc_handle)
         .ok_or_else(|| UpsertError::Parse("no document node".into()))?;
 
@@ -237,10 +300,13 @@ fn update_existing(
         let orig_end = line_col_to_byte_offset(source, matched.end_line, matched.end_column)
             .ok_or_else(|| UpsertError::NoInsertionPoint("end position out of bounds".into()))?;
 
-        let target = find_node_by_span(result.documents.xot(), ast_root, matched.line, matched.column)
+        let target = find_matched_node(result.documents.xot(), ast_root, matched)
             .ok_or_else(|| UpsertError::NoInsertionPoint("could not locate matched node in tree".into()))?;
 
-        replace_text_content(result.documents.xot_mut(), target, value)?;
+        match attribute {
+            Some(name) => set_attr(result.documents.xot_mut(), target, name, value),
+            None => replace_text_content(result.documents.xot_mut(), target, value)?,
+        }
 
         let span_key = (matched.line, matched.column);
         splice_info.push((orig_start, orig_end, span_key));
@@ -248,7 +314,7 @@ fn update_existing(
 
     // Step 2: Re-render once with span tracking
     let xml_node = xot_node_to_xml_node(result.documents.xot(), ast_root);
-    let render_opts = detect_render_options(source);
+    let render_opts = detect_render_options(source, lang);
     let (rendered, span_map) = render::render_with_spans(&xml_node, lang, TreeMode::Data, &render_opts)
         .map_err(|e| UpsertError::Render(e.to_string()))?;
 
@@ -316,6 +382,8 @@ fn insert_new(
         .ok_or_else(|| UpsertError::NoInsertionPoint("no document element found".into()))?;
 
     let mut existing_depth = 0usize;
+    // XML parents are located by their match rather than by tree attributes
+    let mut xml_parent: Option<Match> = None;
     // When no prefix matches, insert under the document element.
     // Descend through structural wrappers like <document>.
     let mut ancestor_node = descend_structural_wrappers(result.documents.xot(), ast_root);
@@ -331,13 +399,13 @@ fn insert_new(
             .unwrap_or_default();
 
         if let Some(matched) = matches.first() {
+            if lang == "xml" {
+                existing_depth = depth;
+                xml_parent = Some(matched.clone());
+                break;
+            }
             // Found deepest matching prefix — locate the xot node
-            if let Some(node) = find_node_by_span(
-                result.documents.xot(),
-                ast_root,
-                matched.line,
-                matched.column,
-            ) {
+            if let Some(node) = find_matched_node(result.documents.xot(), ast_root, matched) {
                 existing_depth = depth;
                 ancestor_node = node;
                 break;
@@ -366,12 +434,35 @@ fn insert_new(
         }
     }
 
+    if lang == "xml" {
+        let parent = match &xml_parent {
+            Some(matched) => match_byte_range(source, matched)?,
+            None => {
+                let (line, column, end_line, end_column) = result.node_locations.as_ref()
+                    .and_then(|locations| locations.get(&ast_root).copied())
+                    .ok_or_else(|| UpsertError::NoInsertionPoint("document element has no source location".into()))?;
+                byte_range(source, line, column, end_line, end_column)?
+            }
+        };
+        return insert_xml(source, parent, missing_keys, value);
+    }
+
+    if has_comments(lang) {
+        if let Some(inserted) = insert_commented_json(source, &mut result, ancestor_node, missing_keys, value, value_kind)? {
+            return Ok(inserted);
+        }
+    }
+
     // Record the splice node's original span.
     // When existing_depth == 0, the splice node is the document root, so the
     // entire source is replaced with the full re-render.
     let is_root_splice = existing_depth == 0;
 
-    let (orig_start, orig_end) = if is_root_splice {
+    let (orig_start, orig_end) = if is_root_splice && has_comments(lang) {
+        // Keep the comments around the document's value
+        let value = json_edit::document_value(source);
+        (value.start, value.end)
+    } else if is_root_splice {
         (0, source.len())
     } else {
         get_node_byte_span(result.documents.xot(), ancestor_node, source)
@@ -386,7 +477,7 @@ fn insert_new(
 
     // Step 4: Re-render the full modified tree with span tracking
     let xml_node = xot_node_to_xml_node(result.documents.xot(), ast_root);
-    let render_opts = detect_render_options(source);
+    let render_opts = detect_render_options(source, lang);
     let (rendered, span_map) = render::render_with_spans(&xml_node, lang, TreeMode::Data, &render_opts)
         .map_err(|e| UpsertError::Render(e.to_string()))?;
 
@@ -418,6 +509,10 @@ fn insert_new(
     // Step 6: Splice
     let mut new_source = String::with_capacity(source.len());
     new_source.push_str(&source[..orig_start]);
+    if orig_start == orig_end && orig_start > 0 && !source[..orig_start].ends_with('\n') {
+        // A document of only comments: start the value on a line of its own
+        new_source.push_str(&detect_render_options(source, lang).newline);
+    }
     new_source.push_str(&new_content);
     new_source.push_str(&source[orig_end..]);
 
@@ -435,6 +530,125 @@ fn insert_new(
     })
 }
 
+/// Update matched XML elements by editing their source text in place.
+fn update_xml(
+    source: &str,
+    value: &str,
+    matches: &[Match],
+    attribute: Option<&str>,
+) -> Result<UpsertResult, UpsertError> {
+    let mut edits = Vec::new();
+    for matched in matches {
+        let element = match_byte_range(source, matched)?;
+        let edit = match attribute {
+            Some(name) => xml_edit::set_attribute(source, element.start, name, value),
+            None => xml_edit::set_text(source, element, value),
+        };
+        edits.push(edit.ok_or_else(|| UpsertError::NoInsertionPoint(
+            format!("no element start tag at {}:{}", matched.line, matched.column),
+        ))?);
+    }
+    edits.sort_by_key(|edit| edit.range.start);
+    edits.dedup();
+
+    let count = edits.len();
+    Ok(UpsertResult {
+        source: xml_edit::apply(source, edits),
+        inserted: false,
+        matches_updated: count,
+        matches: matches.to_vec(),
+        description: format!("updated {} existing value{}", count, if count == 1 { "" } else { "s" }),
+    })
+}
+
+/// Insert the missing XML elements as the last child of `parent`.
+fn insert_xml(
+    source: &str,
+    parent: std::ops::Range<usize>,
+    missing_keys: &[String],
+    value: &str,
+) -> Result<UpsertResult, UpsertError> {
+    let render_opts = detect_render_options(source, "xml");
+    let child = xml_edit::new_elements(missing_keys, value);
+    let edit = xml_edit::append_child(source, parent, &child, &render_opts.indent, &render_opts.newline)
+        .ok_or_else(|| UpsertError::NoInsertionPoint("parent element has no start tag".into()))?;
+
+    Ok(UpsertResult {
+        source: xml_edit::apply(source, vec![edit]),
+        inserted: true,
+        matches_updated: 0,
+        matches: vec![],
+        description: format!("inserted {}", missing_keys.join("/")),
+    })
+}
+
+/// Insert the missing keys into a JSONC or JSON5 object by splicing one new
+/// member into the source (see [`super::json_edit`]), since the data tree
+/// leaves the object's comments out. `None` when `parent` is not an object
+/// in the source, for the re-rendering path to turn it into one.
+fn insert_commented_json(
+    source: &str,
+    result: &mut XeeParseResult,
+    parent: xot::Node,
+    missing_keys: &[String],
+    value: &str,
+    value_kind: Option<&str>,
+) -> Result<Option<UpsertResult>, UpsertError> {
+    let xot = result.documents.xot_mut();
+    let Some((start, end)) = get_node_byte_span(xot, parent, source) else {
+        return Ok(None);
+    };
+    let open = json_edit::skip_trivia(source, start);
+    if source.as_bytes().get(open) != Some(&b'{') {
+        return Ok(None);
+    }
+    let Some(close) = source[..end].rfind('}').filter(|&close| close > open) else {
+        return Ok(None);
+    };
+    let object = open..close + 1;
+
+    let members: Vec<xot::Node> = xot.children(parent).filter(|&c| xot.element(c).is_some()).collect();
+    let first_member = members.first().and_then(|&m| get_node_byte_span(xot, m, source)).map(|(start, _)| start);
+    let last_member_end = members.last().and_then(|&m| get_node_byte_span(xot, m, source)).map(|(_, end)| end);
+
+    let render_opts = detect_render_options(source, &result.language);
+    let indent = json_edit::member_indent(source, &object, first_member, &render_opts.indent);
+    let indent_level = indent.len() / render_opts.indent.len().max(1);
+
+    add_nested_children(xot, parent, missing_keys, value, value_kind)?;
+    let new_member = xot.children(parent).filter(|&c| xot.element(c).is_some()).last()
+        .ok_or_else(|| UpsertError::NoInsertionPoint("new member was not added".into()))?;
+    let member = render::json::render_member(
+        &xot_node_to_xml_node(xot, new_member),
+        &RenderOptions { indent_level, ..render_opts.clone() },
+    ).map_err(|e| UpsertError::Render(e.to_string()))?;
+
+    let edits = json_edit::append_member(
+        source, object, last_member_end, &member, &indent, &render_opts.newline, render_opts.trailing_commas,
+    ).ok_or_else(|| UpsertError::NoInsertionPoint("object has no closing brace".into()))?;
+
+    Ok(Some(UpsertResult {
+        source: xml_edit::apply(source, edits),
+        inserted: true,
+        matches_updated: 0,
+        matches: vec![],
+        description: format!("inserted {}", missing_keys.join("/")),
+    }))
+}
+
+/// Byte range of a match in the source.
+fn match_byte_range(source: &str, matched: &Match) -> Result<std::ops::Range<usize>, UpsertError> {
+    byte_range(source, matched.line, matched.column, matched.end_line, matched.end_column)
+}
+
+fn byte_range(source: &str, line: u32, column: u32, end_line: u32, end_column: u32) -> Result<std::ops::Range<usize>, UpsertError> {
+    let start = line_col_to_byte_offset(source, line, column)
+        .ok_or_else(|| UpsertError::NoInsertionPoint("start position out of bounds".into()))?;
+    let end = line_col_to_byte_offset(source, end_line, end_column)
+        .ok_or_else(|| UpsertError::NoInsertionPoint("end position out of bounds".into()))?;
+    Ok(start..end)
+}
+
 // ---------------------------------------------------------------------------
 // Tree helpers
 // ---------------------------------------------------------------------------
@@ -444,6 +658,39 @@ fn find_document_element(xot: &Xot, doc_node: xot::Node) -> Option<xot::Node> {
     xot.document_element(doc_node).ok()
 }
 
+/// Find the node a match refers to. Nested elements can start at the same
+/// position (a CSV row and its first cell), so the matched element's name
+/// is checked as well when it is known.
+fn find_matched_node(xot: &Xot, root: xot::Node, matched: &Match) -> Option<xot::Node> {
+    match &matched.xml_node {
+        Some(crate::xpath::XmlNode::Element { name, .. }) => {
+            find_node_by_span_and_name(xot, root, matched.line, matched.column, name)
+                .or_else(|| find_node_by_span(xot, root, matched.line, matched.column))
+        }
+        _ => find_node_by_span(xot, root, matched.line, matched.column),
+    }
+}
+
+/// Find an element with the given name in the xot tree by its start position.
+fn find_node_by_span_and_name(xot: &Xot, root: xot::Node, target_line: u32, target_col: u32, name: &str) -> Option<xot::Node> {
+    if get_element_name(xot, root).as_deref() == Some(name) {
+        let line: Option<u32> = get_attr(xot, root, "line").and_then(|v| v.parse().ok());
+        let col: Option<u32> = get_attr(xot, root, "column").and_then(|v| v.parse().ok());
+        if line == Some(target_line) && col == Some(target_col) {
+            return Some(root);
+        }
+    }
+
+    for child in xot.children(root) {
+        if xot.element(child).is_some() {
+            if let Some(found) = find_node_by_span_and_name(xot, child, target_line, target_col, name) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
 /// Find a node in the xot tree by its start position.
 fn find_node_by_span(xot: &Xot, root: xot::Node, target_line: u32, target_col: u32) -> Option<xot::Node> {
     // Check if this node matches
@@ -593,6 +840,13 @@ fn xpath_to_key_path(xpath: &str) -> Result<(Vec<String>, Vec<String>), UpsertEr
 }
 
 
+/// Name of the attribute selected by the XPath's last step (`.../@Version`).
+fn attribute_step(xpath: &str) -> Option<&str> {
+    let last = xpath.trim().rsplit('/').next()?;
+    last.strip_prefix('@')
+        .filter(|name| !name.is_empty() && !name.contains(['[', '(', '*']))
+}
+
 // ---------------------------------------------------------------------------
 // Source utilities
 // ---------------------------------------------------------------------------
@@ -630,8 +884,14 @@ fn line_col_to_byte_offset(content: &str, line: u32, col: u32) -> Option<usize>
     None
 }
 
+/// Whether `lang`'s files may hold comments the data tree leaves out, so
+/// they are edited in place rather than re-rendered.
+fn has_comments(lang: &str) -> bool {
+    matches!(lang, "jsonc" | "json5")
+}
+
 /// Detect render options from source (indentation style, newline style).
-fn detect_render_options(source: &str) -> RenderOptions {
+fn detect_render_options(source: &str, lang: &str) -> RenderOptions {
     let newline = if source.contains("\r\n") { "\r\n" } else { "\n" };
 
     // Detect indent from first indented line
@@ -643,10 +903,21 @@ fn detect_render_options(source: &str) -> RenderOptions {
         })
         .unwrap_or("  ");
 
+    // Keep trailing commas in JSONC and JSON5 files that already use them
+    let trailing_commas = match lang {
+        "jsonc" => !crate::languages::json::trailing_comma_offsets(source).is_empty(),
+        "json5" => {
+            let (json, _) = crate::languages::json::json5_to_json(source);
+            !crate::languages::json::trailing_comma_offsets(&json).is_empty()
+        }
+        _ => false,
+    };
+
     RenderOptions {
         indent: indent.to_string(),
         indent_level: 0,
         newline: newline.to_string(),
+        trailing_commas,
     }
 }
 
@@ -1042,6 +1313,104 @@ mod tests {
         assert_eq!(parsed["age"], "30");
     }
 
+    // ---------------------------------------------------------------------------
+    // JSONC tests: comments and trailing commas
+    // ---------------------------------------------------------------------------
+
+    const TSCONFIG: &str = "{\n  // Compiler settings\n  \"compilerOptions\": {\n    \"strict\": true, // keep on\n    \"target\": \"es2020\",\n  },\n}\n";
+
+    #[test]
+    fn jsonc_update_keeps_comments_and_trailing_commas() {
+        let result = upsert(TSCONFIG, "jsonc", "//target", "es2022", None).unwrap();
+        assert!(!result.inserted);
+        assert_eq!(result.source, TSCONFIG.replace("es2020", "es2022"));
+    }
+
+    #[test]
+    fn jsonc_insert_keeps_comments_and_trailing_commas() {
+        let result = upsert(TSCONFIG, "jsonc", "//compilerOptions/outDir", "dist", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(
+            result.source,
+            "{\n  // Compiler settings\n  \"compilerOptions\": {\n    \"strict\": true, // keep on\n    \"target\": \"es2020\",\n    \"outDir\": \"dist\",\n  },\n}\n",
+        );
+    }
+
+    const FLAGS: &str = "flag,enabled,owner\nnew_checkout,false,\"Payments, EU\"\ndark_mode,true,web\n";
+
+    #[test]
+    fn csv_update_changes_cell() {
+        let result = upsert(FLAGS, "csv", "//row[flag='new_checkout']/enabled", "true", None).unwrap();
+        assert!(!result.inserted);
+        assert_eq!(result.source, FLAGS.replace("new_checkout,false", "new_checkout,true"));
+
+        // The first cell shares its start with the row
+        let result = upsert(FLAGS, "csv", "//row[2]/flag", "dark-mode", None).unwrap();
+        assert_eq!(result.source, FLAGS.replace("dark_mode", "dark-mode"));
+    }
+
+    #[test]
+    fn csv_update_quotes_when_needed() {
+        let result = upsert(FLAGS, "csv", "//row[1]/owner", "Payments", None).unwrap();
+        assert_eq!(result.source, FLAGS.replace("\"Payments, EU\"", "Payments"));
+        let result = upsert(FLAGS, "csv", "//row[2]/owner", "web, \"core\"", None).unwrap();
+        assert_eq!(result.source, FLAGS.replace(",web\n", ",\"web, \"\"core\"\"\"\n"));
+    }
+
+    #[test]
+    fn csv_update_fills_padded_cells() {
+        let source = "flag,enabled,owner\nbeta\nnew_checkout,false\n";
+        let result = upsert(source, "csv", "//row[1]/owner", "web", None).unwrap();
+        assert_eq!(result.source, "flag,enabled,owner\nbeta,,web\nnew_checkout,false\n");
+        let result = upsert(source, "csv", "//row[2]/owner", "web", None).unwrap();
+        assert_eq!(result.source, "flag,enabled,owner\nbeta\nnew_checkout,false,web\n");
+        let result = upsert(source, "csv", "//row[1]/enabled", "true", None).unwrap();
+        assert_eq!(result.source, "flag,enabled,owner\nbeta,true,\nnew_checkout,false\n");
+    }
+
+    #[test]
+    fn csv_insert_column_is_rejected() {
+        assert!(upsert(FLAGS, "csv", "//row[1]/rollout", "50", None).is_err());
+    }
+
+    #[test]
+    fn jsonc_insert_at_root_adds_comma_before_a_trailing_comment() {
+        let source = "{\n  \"a\": 1 // one\n}\n";
+        let result = upsert(source, "jsonc", "//b/c", "2", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(result.source, "{\n  \"a\": 1, // one\n  \"b\": {\n    \"c\": \"2\"\n  }\n}\n");
+    }
+
+    #[test]
+    fn jsonc_update_keeps_comments_around_the_value() {
+        let source = "{\n  \"port\": /* default */ 8080, // local\n  \"host\": \"x\"\n}\n";
+        let result = update_only(source, "jsonc", "//port", "9090", None, None).unwrap();
+        assert_eq!(result.source, source.replace("8080", "9090"));
+    }
+
+    #[test]
+    fn jsonc_insert_into_a_document_of_only_comments() {
+        let result = upsert("// settings", "jsonc", "//a", "1", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(result.source, "// settings\n{\n  \"a\": \"1\"\n}");
+    }
+
+    const APP_JSON5: &str = "// app\n{\n  name: 'tractor', // the tool\n  port: 0x1F90,\n}\n";
+
+    #[test]
+    fn json5_update_keeps_comments_and_syntax() {
+        let result = upsert(APP_JSON5, "json5", "//name", "tr", None).unwrap();
+        assert!(!result.inserted);
+        assert_eq!(result.source, APP_JSON5.replace("'tractor'", "\"tr\""));
+    }
+
+    #[test]
+    fn json5_insert_keeps_comments_and_trailing_commas() {
+        let result = upsert(APP_JSON5, "json5", "//debug", "yes", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(result.source, APP_JSON5.replace("0x1F90,\n", "0x1F90,\n  \"debug\": \"yes\",\n"));
+    }
+
     // ---------------------------------------------------------------------------
     // update_only tests
     // ---------------------------------------------------------------------------
@@ -1049,7 +1418,7 @@ mod tests {
     #[test]
     fn update_only_existing_string() {
         let source = r#"{"name": "Alice", "age": 30}"#;
-        let result = update_only(source, "json", "//name", "Bob", None).unwrap();
+        let result = update_only(source, "json", "//name", "Bob", None, None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 1);
         assert!(result.source.contains("Bob"));
@@ -1059,7 +1428,7 @@ mod tests {
     #[test]
     fn update_only_no_match_returns_unchanged() {
         let source = r#"{"name": "Alice"}"#;
-        let result = update_only(source, "json", "//nonexistent", "value", None).unwrap();
+        let result = update_only(source, "json", "//nonexistent", "value", None, None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 0);
         assert_eq!(result.source, source, "source should be unchanged when no match");
@@ -1068,7 +1437,7 @@ mod tests {
     #[test]
     fn update_only_does_not_create_missing_path() {
         let source = r#"{"name": "Alice"}"#;
-        let result = update_only(source, "json", "//db/host", "localhost", None).unwrap();
+        let result = update_only(source, "json", "//db/host", "localhost", None, None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 0);
         assert_eq!(result.source, source, "should not create //db/host");
@@ -1080,7 +1449,7 @@ mod tests {
     #[test]
     fn update_only_multiple_matches() {
         let source = r#"{"items": [{"val": 1}, {"val": 2}, {"val": 3}]}"#;
-        let result = update_only(source, "json", "//items/val", "99", None).unwrap();
+        let result = update_only(source, "json", "//items/val", "99", None, None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 3);
         let parsed: serde_json::Value = serde_json::from_str(&result.source).unwrap();
@@ -1092,7 +1461,7 @@ mod tests {
     #[test]
     fn update_only_respects_limit() {
         let source = r#"{"items": [{"val": 1}, {"val": 2}, {"val": 3}]}"#;
-        let result = update_only(source, "json", "//items/val", "99", Some(1)).unwrap();
+        let result = update_only(source, "json", "//items/val", "99", Some(1), None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 1);
         let parsed: serde_json::Value = serde_json::from_str(&result.source).unwrap();
@@ -1105,14 +1474,14 @@ mod tests {
 
     #[test]
     fn update_only_unsupported_language() {
-        let result = update_only("{}", "brainfuck", "//x", "1", None);
+        let result = update_only("{}", "brainfuck", "//x", "1", None, None);
         assert!(matches!(result.unwrap_err(), UpsertError::UnsupportedLanguage(_)));
     }
 
     #[test]
     fn yaml_update_only_existing() {
         let source = "name: Alice\nage: 30\n";
-        let result = update_only(source, "yaml", "//name", "Bob", None).unwrap();
+        let result = update_only(source, "yaml", "//name", "Bob", None, None).unwrap();
         assert!(!result.inserted);
         assert_eq!(result.matches_updated, 1);
         assert!(result.source.contains("Bob"));
@@ -1122,7 +1491,7 @@ mod tests {
     #[test]
     fn yaml_update_only_no_match() {
         let source = "name: Alice\n";
-        let result = update_only(source, "yaml", "//nonexistent", "value", None).unwrap();
+        let result = update_only(source, "yaml", "//nonexistent", "value", None, None).unwrap();
         assert_eq!(result.matches_updated, 0);
         assert_eq!(result.source, source);
     }
@@ -1130,7 +1499,7 @@ mod tests {
     #[test]
     fn yaml_update_only_does_not_create_missing_path() {
         let source = "name: Alice\n";
-        let result = update_only(source, "yaml", "//db/host", "localhost", None).unwrap();
+        let result = update_only(source, "yaml", "//db/host", "localhost", None, None).unwrap();
         assert_eq!(result.matches_updated, 0);
         assert_eq!(result.source, source, "should not create //db/host");
         assert!(!result.source.contains("db"), "db key should not be created");
@@ -1140,7 +1509,7 @@ mod tests {
     #[test]
     fn yaml_update_only_nested_existing() {
         let source = "db:\n  host: localhost\n  port: 5432\n";
-        let result = update_only(source, "yaml", "//db/host", "db.example.com", None).unwrap();
+        let result = update_only(source, "yaml", "//db/host", "db.example.com", None, None).unwrap();
         assert_eq!(result.matches_updated, 1);
         assert!(result.source.contains("db.example.com"));
         assert!(result.source.contains("port: 5432"));
@@ -1150,7 +1519,7 @@ mod tests {
     fn yaml_update_only_partial_path_no_create() {
         // db exists but port doesn't — update_only should NOT create port
         let source = "db:\n  host: localhost\n";
-        let result = update_only(source, "yaml", "//db/port", "5432", None).unwrap();
+        let result = update_only(source, "yaml", "//db/port", "5432", None, None).unwrap();
         assert_eq!(result.matches_updated, 0);
         assert_eq!(result.source, source, "should not create missing port under existing db");
     }
@@ -1168,4 +1537,79 @@ mod tests {
             assert_eq!(item["val"], 99);
         }
     }
+
+    // ---------------------------------------------------------------------------
+    // HCL tests
+    // ---------------------------------------------------------------------------
+
+    #[test]
+    fn hcl_update_module_version() {
+        let source = "module \"vpc\" {\n  source  = \"terraform-aws-modules/vpc/aws\"\n  version = \"5.0.0\"\n}\n";
+        let result = upsert(source, "hcl", "//module[@name='vpc']/version", "5.1.0", None).unwrap();
+        assert!(!result.inserted);
+        assert_eq!(
+            result.source,
+            "module \"vpc\" {\n  source  = \"terraform-aws-modules/vpc/aws\"\n  version = \"5.1.0\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn hcl_insert_attribute_into_block() {
+        let source = "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"my-logs\"\n}\n";
+        let result = upsert(source, "hcl", "//resource[@name='logs']/acl", "private", None).unwrap();
+        assert!(result.inserted);
+        assert!(result.source.contains("bucket = \"my-logs\""));
+        assert!(result.source.contains("acl = \"private\""));
+        assert!(result.source.starts_with("resource \"aws_s3_bucket\" \"logs\" {"));
+    }
+
+    #[test]
+    fn xml_update_package_reference_version() {
+        let source = "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <ItemGroup>\n    <PackageReference Include=\"Newtonsoft.Json\" Version=\"12.0.1\" />\n    <PackageReference Include=\"Serilog\" Version=\"3.1.1\" />\n  </ItemGroup>\n</Project>\n";
+        let result = upsert(source, "xml", "//PackageReference[@Include='Newtonsoft.Json']/@Version", "13.0.3", None).unwrap();
+        assert!(!result.inserted);
+        assert_eq!(result.matches_updated, 1);
+        assert_eq!(
+            result.source,
+            "<Project Sdk=\"Microsoft.NET.Sdk\">\n  <ItemGroup>\n    <PackageReference Include=\"Newtonsoft.Json\" Version=\"13.0.3\" />\n    <PackageReference Include=\"Serilog\" Version=\"3.1.1\" />\n  </ItemGroup>\n</Project>\n"
+        );
+    }
+
+    #[test]
+    fn xml_update_element_text() {
+        let source = "<?xml version=\"1.0\"?>\n<project>\n  <version>1.0.0</version>\n</project>\n";
+        let result = upsert(source, "xml", "/project/version", "1.1.0", None).unwrap();
+        assert_eq!(result.source, "<?xml version=\"1.0\"?>\n<project>\n  <version>1.1.0</version>\n</project>\n");
+    }
+
+    #[test]
+    fn xml_update_keeps_formatting_and_user_location_attributes() {
+        let source = "<shapes>\n  <point line='3' column='4'/>\n  <point line='5' column='1' label=\"x\"/>\n</shapes>\n";
+        let result = upsert(source, "xml", "//point[@line='5']/@label", "y", None).unwrap();
+        assert_eq!(result.matches_updated, 1);
+        assert_eq!(result.matches[0].line, 3);
+        assert_eq!(
+            result.source,
+            "<shapes>\n  <point line='3' column='4'/>\n  <point line='5' column='1' label=\"y\"/>\n</shapes>\n"
+        );
+    }
+
+    #[test]
+    fn xml_insert_missing_attribute() {
+        let source = "<Project>\n  <PackageReference Include='Serilog' />\n</Project>\n";
+        let result = upsert(source, "xml", "//PackageReference/@Version", "3.1.1", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(result.source, "<Project>\n  <PackageReference Include='Serilog' Version=\"3.1.1\" />\n</Project>\n");
+    }
+
+    #[test]
+    fn xml_insert_element_under_existing_parent() {
+        let source = "<project>\n  <properties>\n    <java>17</java>\n  </properties>\n</project>\n";
+        let result = upsert(source, "xml", "/project/properties/encoding", "UTF-8", None).unwrap();
+        assert!(result.inserted);
+        assert_eq!(
+            result.source,
+            "<project>\n  <properties>\n    <java>17</java>\n    <encoding>UTF-8</encoding>\n  </properties>\n</project>\n"
+        );
+    }
 }
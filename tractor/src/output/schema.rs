@@ -202,6 +202,16 @@ impl SchemaCollector {
         }
     }
 
+    /// Every element name seen, sorted.
+    pub fn element_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.paths.keys()
+            .flat_map(|path| path.iter().map(String::as_str))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
     /// Format the collected paths as a tree
     pub fn format(&self, max_depth: Option<usize>, use_color: bool) -> String {
         format_schema_tree(&self.to_schema_tree(), max_depth, use_color)
@@ -0,0 +1,328 @@
+//! Single-file components: Vue (`.vue`) and Svelte (`.svelte`)
+//!
+//! A component file holds markup, a script and styles side by side. Each
+//! block is parsed with its own language — markup as HTML, `<script>` as
+//! JavaScript or TypeScript (`lang="ts"`), `<style>` as CSS — and the trees
+//! are collected under a `component` root:
+//!
+//! ```xml
+//! <component>
+//!   <template lang="html"><document>...</document></template>
+//!   <script lang="typescript" setup=""><program>...</program></script>
+//!   <style lang="css" scoped=""><stylesheet>...</stylesheet></style>
+//! </component>
+//! ```
+//! Queryable as: `//script[@lang='typescript']//function`
+//!
+//! Vue markup is the content of the top-level `<template>`; Svelte markup is
+//! everything outside the script and style blocks. Each block is parsed from
+//! a copy of the file with everything else blanked out, so every node keeps
+//! its line and column in the component file. A block that fails to parse
+//! is reported as a syntax error; so are HTML errors in Svelte markup, except
+//! inside its `{…}` expressions, which are not HTML.
+//!
+//! A rule written for one of the block languages sees only the blocks in
+//! that language (`ParseOptions::embedded_language`); the others stay empty.
+
+use xee_xpath::Documents;
+use xot::{Node as XotNode, Xot};
+
+use super::injection::{append_element, byte_range, child_element, copy_relocated, html_attribute, line_column, line_starts};
+use super::{parse_source, parse_string_to_xot_with_options, ParseError, ParseOptions, SyntaxError, SyntaxErrorKind, XeeParseResult};
+use crate::tree_mode::TreeMode;
+use crate::xot_transform::helpers::*;
+
+/// Start tag attributes carried over to the block element
+const BLOCK_ATTRIBUTES: &[&str] = &["setup", "scoped", "module", "context"];
+
+/// A top-level block of a component
+struct Block {
+    /// `template`, `script` or `style`
+    name: &'static str,
+    /// Language to parse the block with, if tractor supports it
+    lang: Option<&'static str>,
+    /// The top-level element in the raw HTML tree
+    element: Option<XotNode>,
+    /// Byte ranges of the block's content
+    ranges: Vec<(usize, usize)>,
+}
+
+/// Parse a Vue or Svelte component into a `component` tree with one child per
+/// block.
+pub(crate) fn parse_component(
+    source: &str,
+    lang: &str,
+    file_label: String,
+    options: &ParseOptions<'_>,
+) -> Result<XeeParseResult, ParseError> {
+    let tree_mode = TreeMode::resolve(options.tree_mode, lang).map_err(ParseError::Parse)?;
+
+    // The raw HTML tree finds the blocks and their attributes
+    let html = parse_string_to_xot_with_options(source, "html", file_label.clone(), Some(TreeMode::Raw), false)?;
+    let xot = &html.xot;
+    let file_lines = line_starts(source);
+    let top_level = xot.document_element(html.root)
+        .map(|document| get_element_children(xot, document))
+        .unwrap_or_default();
+
+    let mut blocks = Vec::new();
+    let mut markup = Vec::new();
+    let mut cursor = 0;
+    for element in top_level {
+        let Some((start, end)) = byte_range(xot, element, source, &file_lines) else {
+            continue;
+        };
+        let block = match get_kind(xot, element).as_deref() {
+            Some("script_element") => raw_text_block(xot, element, source, &file_lines, "script"),
+            Some("style_element") => raw_text_block(xot, element, source, &file_lines, "style"),
+            Some("element") if lang == "vue" && tag_name(xot, element).as_deref() == Some("template") => {
+                template_block(xot, element, source, &file_lines)
+            }
+            _ => None,
+        };
+        if let Some(block) = block {
+            markup.push((cursor, start));
+            cursor = end;
+            blocks.push(block);
+        }
+    }
+    if lang == "svelte" {
+        markup.push((cursor, source.len()));
+        blocks.insert(0, Block { name: "template", lang: Some("html"), element: None, ranges: markup });
+    }
+
+    let mut documents = Documents::new();
+    let doc_handle = documents.add_string("file:///source".try_into().unwrap(), "<component/>")
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+    let doc_node = documents.document_node(doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    let host = documents.xot_mut();
+    let root = host.document_element(doc_node).map_err(|e| ParseError::Parse(e.to_string()))?;
+    let (end_line, end_column) = line_column(&file_lines, source.len());
+    set_location(host, root, (1, 1), (end_line, end_column));
+
+    let mut syntax_errors = Vec::new();
+    for block in blocks {
+        let block_element = append_element(host, root, block.name);
+        if let Some(&(start, _)) = block.ranges.first() {
+            let end = block.ranges.last().map_or(start, |&(_, end)| end);
+            set_location(host, block_element, line_column(&file_lines, start), line_column(&file_lines, end));
+        }
+        if let Some(element) = block.element {
+            for &attribute in BLOCK_ATTRIBUTES {
+                if let Some(value) = html_attribute(xot, element, attribute) {
+                    set_attr(host, block_element, attribute, &value);
+                }
+            }
+        }
+
+        let Some(block_lang) = block.lang else {
+            continue;
+        };
+        set_attr(host, block_element, "lang", block_lang);
+        if options.embedded_language.is_some_and(|only| only != block_lang) {
+            continue;
+        }
+        let content = isolate(source, &block.ranges);
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let block_options = ParseOptions {
+            language: Some(block_lang),
+            tree_mode: Some(tree_mode),
+            embedded_language: None,
+            ..*options
+        };
+        // A block that fails to parse stays empty and is reported
+        let parsed = match parse_source(&content, block_lang, file_label.clone(), &block_options) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                if let (Some(&(start, _)), Some(&(_, end))) = (block.ranges.first(), block.ranges.last()) {
+                    let (line, column) = line_column(&file_lines, start);
+                    let (end_line, end_column) = line_column(&file_lines, end);
+                    syntax_errors.push(SyntaxError {
+                        line: line as u32,
+                        column: column as u32,
+                        end_line: end_line as u32,
+                        end_column: end_column as u32,
+                        kind: SyntaxErrorKind::Unparsed(format!("{} {}: {}", block_lang, block.name, e)),
+                    });
+                }
+                continue;
+            }
+        };
+        let parsed_xot = parsed.documents.xot();
+        let Some(block_root) = parsed.documents.document_node(parsed.doc_handle)
+            .and_then(|n| parsed_xot.document_element(n).ok())
+        else {
+            continue;
+        };
+        let copy = copy_relocated(parsed_xot, block_root, host, &|line, column| (line, column))
+            .map_err(|e| ParseError::Parse(e.to_string()))?;
+        host.append(block_element, copy).map_err(|e| ParseError::Parse(e.to_string()))?;
+        // Svelte's `{expressions}` are not HTML, so errors inside them are not real
+        if lang == "svelte" && block.name == "template" {
+            let expressions = expression_spans(source, &block.ranges);
+            syntax_errors.extend(parsed.syntax_errors.into_iter().filter(|error| {
+                let offset = file_lines.get((error.line as usize).saturating_sub(1)).map_or(source.len(), |start| start + error.column as usize - 1);
+                !expressions.iter().any(|&(start, end)| (start..end).contains(&offset))
+            }));
+        } else {
+            syntax_errors.extend(parsed.syntax_errors);
+        }
+    }
+
+    Ok(XeeParseResult {
+        documents,
+        doc_handle,
+        source_lines: std::sync::Arc::new(source.lines().map(|s| s.to_string()).collect()),
+        file_path: file_label,
+        language: lang.to_string(),
+        syntax_errors,
+        node_locations: None,
+        queries: None,
+    })
+}
+
+/// `<script>` or `<style>`: the raw text between the tags
+fn raw_text_block(xot: &Xot, element: XotNode, source: &str, line_starts: &[usize], name: &'static str) -> Option<Block> {
+    let declared = html_attribute(xot, element, "lang").map(|l| l.to_lowercase());
+    let lang = match (name, declared.as_deref()) {
+        ("script", None | Some("js" | "javascript" | "jsx")) => Some("javascript"),
+        ("script", Some("ts" | "typescript")) => Some("typescript"),
+        ("script", Some("tsx")) => Some("tsx"),
+        ("style", None | Some("css")) => Some("css"),
+        // Preprocessors (scss, less, pug) keep the block without a tree
+        _ => None,
+    };
+    let ranges = child_element(xot, element, |c| get_kind(xot, c).as_deref() == Some("raw_text"))
+        .and_then(|text| byte_range(xot, text, source, line_starts))
+        .into_iter()
+        .collect();
+    Some(Block { name, lang, element: Some(element), ranges })
+}
+
+/// Vue `<template>`: everything between its start and end tags
+fn template_block(xot: &Xot, element: XotNode, source: &str, line_starts: &[usize]) -> Option<Block> {
+    let start_tag = child_element(xot, element, |c| get_kind(xot, c).as_deref() == Some("start_tag"))?;
+    let end_tag = child_element(xot, element, |c| get_kind(xot, c).as_deref() == Some("end_tag"))?;
+    let (_, start) = byte_range(xot, start_tag, source, line_starts)?;
+    let (end, _) = byte_range(xot, end_tag, source, line_starts)?;
+    let lang = match html_attribute(xot, element, "lang").as_deref() {
+        None | Some("html") => Some("html"),
+        _ => None,
+    };
+    Some(Block { name: "template", lang, element: Some(element), ranges: vec![(start, end)] })
+}
+
+fn tag_name(xot: &Xot, element: XotNode) -> Option<String> {
+    let start_tag = child_element(xot, element, |c| get_kind(xot, c).as_deref() == Some("start_tag"))?;
+    let name = child_element(xot, start_tag, |c| get_kind(xot, c).as_deref() == Some("tag_name"))?;
+    Some(xot.string_value(name).trim().to_lowercase())
+}
+
+/// Byte ranges of the Svelte `{…}` expressions and tags (`{#if}`, `{@html}`)
+/// in the markup `ranges`, braces included. Nested braces belong to the
+/// outermost expression; an unclosed one runs to the end of its range.
+fn expression_spans(source: &str, ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut spans = Vec::new();
+    for &(start, end) in ranges {
+        let mut depth = 0usize;
+        let mut open = start;
+        for (i, &b) in bytes[start..end].iter().enumerate().map(|(i, b)| (start + i, b)) {
+            match b {
+                b'{' => {
+                    if depth == 0 {
+                        open = i;
+                    }
+                    depth += 1;
+                }
+                b'}' if depth > 0 => {
+                    depth -= 1;
+                    if depth == 0 {
+                        spans.push((open, i + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if depth > 0 {
+            spans.push((open, end));
+        }
+    }
+    spans
+}
+
+/// `source` with everything outside `ranges` blanked, keeping line breaks
+/// and byte lengths, so a block parses with its positions in the file.
+fn isolate(source: &str, ranges: &[(usize, usize)]) -> String {
+    source.char_indices()
+        .map(|(i, c)| {
+            if c == '\n' || c == '\r' || ranges.iter().any(|&(start, end)| (start..end).contains(&i)) {
+                c.to_string()
+            } else {
+                " ".repeat(c.len_utf8())
+            }
+        })
+        .collect()
+}
+
+fn set_location(xot: &mut Xot, node: XotNode, start: (usize, usize), end: (usize, usize)) {
+    set_attr(xot, node, "line", &start.0.to_string());
+    set_attr(xot, node, "column", &start.1.to_string());
+    set_attr(xot, node, "end_line", &end.0.to_string());
+    set_attr(xot, node, "end_column", &end.1.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vue_component() {
+        let source = "<template>\n  <div class=\"greeting\">{{ message }}</div>\n</template>\n\n<script setup lang=\"ts\">\nfunction greet(name: string): string {\n  return name;\n}\n</script>\n\n<style scoped>\n.greeting { color: red; }\n</style>\n";
+        let mut result = parse_component(source, "vue", "Greeting.vue".to_string(), &ParseOptions::default()).unwrap();
+        assert_eq!(result.language, "vue");
+        assert_eq!(result.query("/component/template[@lang='html']").unwrap().len(), 1);
+        assert_eq!(result.query("/component/script[@lang='typescript'][@setup]").unwrap().len(), 1);
+        assert_eq!(result.query("/component/style[@scoped]").unwrap().len(), 1);
+
+        let functions = result.query("//script//function").unwrap();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].line, 6);
+        assert_eq!(functions[0].column, 1);
+    }
+
+    #[test]
+    fn test_svelte_component() {
+        let source = "<script lang=\"ts\">\n  let count: number = 0;\n</script>\n\n<button on:click={() => count++}>\n  Clicked {count}\n</button>\n\n<style>\n  button { color: red; }\n</style>\n";
+        let mut result = parse_component(source, "svelte", "Counter.svelte".to_string(), &ParseOptions::default()).unwrap();
+        assert_eq!(result.query("/component/*").unwrap().len(), 3);
+        let markup = result.query("/component/template//*[@line='5']").unwrap();
+        assert!(!markup.is_empty());
+        assert!(result.query("/component/template//script").unwrap().is_empty());
+        assert_eq!(result.query("/component/script[@lang='typescript']").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_svelte_expression_spans() {
+        let source = "<p on:click={() => f({a: 1})}>{#if x}{x}{/if}</p>{oops";
+        let spans: Vec<&str> = expression_spans(source, &[(0, source.len())])
+            .into_iter()
+            .map(|(start, end)| &source[start..end])
+            .collect();
+        assert_eq!(spans, ["{() => f({a: 1})}", "{#if x}", "{x}", "{/if}", "{oops"]);
+    }
+
+    #[test]
+    fn test_parse_only_blocks_in_one_language() {
+        let source = "<template>\n  <p>{{ a }}</p>\n</template>\n<script>\nconst a = 1;\n</script>\n<style>\np { color: red; }\n</style>\n";
+        let options = ParseOptions { embedded_language: Some("css"), ..ParseOptions::default() };
+        let mut result = parse_component(source, "vue", "A.vue".to_string(), &options).unwrap();
+        assert_eq!(result.query("/component/*").unwrap().len(), 3);
+        assert_eq!(result.query("/component/*/*").unwrap().len(), 1);
+        assert_eq!(result.query("/component/style/*").unwrap().len(), 1);
+    }
+}
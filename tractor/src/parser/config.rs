@@ -1,67 +1,167 @@
 //! Configuration for language-specific semantic tree transformations
+//!
+//! A [`LanguageConfig`] is a declarative transform: element renames, modifier
+//! kinds, flatten and skip lists. Users supply them per language (the
+//! `transforms:` section of `tractor.yml`) to make raw-only languages
+//! queryable, or to adjust the vocabulary of a built-in transform.
+//!
+//! ```yaml
+//! transforms:
+//!   lua:
+//!     rename: { function_declaration: function, identifier: name }
+//!     flatten: [block]
+//!     lift: [parameters]
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Deserialize;
+use xot::{Xot, Node as XotNode};
+
+use crate::language_info::get_language_info;
+use crate::xot_transform::{walk_transform, TransformAction, helpers::*};
+
+use super::{ParseError, XeeParseResult};
+
+/// User-configured transforms, keyed by canonical language name
+pub type LanguageTransforms = HashMap<String, LanguageConfig>;
+
+/// How a configured transform combines with the built-in one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransformBase {
+    /// Run after the built-in transform, on the tree `-v schema` shows
+    #[default]
+    Extend,
+    /// Run instead of the built-in transform, on the raw TreeSitter tree
+    Replace,
+}
 
 /// Configuration for a language's semantic tree transformation
-#[derive(Debug, Clone)]
+///
+/// Element names are matched as they appear when the transform runs: after
+/// the built-in transform for `extend`, TreeSitter kinds for `replace`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct LanguageConfig {
-    /// Map TreeSitter node kinds to semantic element names
-    /// e.g., ("class_declaration", "class")
-    pub element_mappings: &'static [(&'static str, &'static str)],
+    /// Whether to build on the built-in transform or replace it
+    #[serde(default)]
+    pub mode: TransformBase,
+
+    /// Map element names to semantic names
+    /// e.g., `function_definition: function`
+    #[serde(default, rename = "rename")]
+    pub element_mappings: BTreeMap<String, String>,
 
-    /// Node kinds that represent modifiers (become empty elements)
-    /// e.g., ["modifier"] for C#
-    pub modifier_kinds: &'static [&'static str],
+    /// Element names that represent modifiers (become empty elements named
+    /// after their text, e.g. `<modifier>public</modifier>` → `<public/>`)
+    #[serde(default, rename = "modifiers")]
+    pub modifier_kinds: Vec<String>,
 
-    /// Known modifier text values
-    /// e.g., ["public", "private", "static", "async"]
-    pub known_modifiers: &'static [&'static str],
+    /// Known modifier text values; when empty, any modifier text is accepted
+    #[serde(default, rename = "known-modifiers")]
+    pub known_modifiers: Vec<String>,
 
-    /// Node kinds that should be flattened (children promoted to parent)
-    /// e.g., ["declaration_list"] - removes unnecessary nesting
-    pub flatten_kinds: &'static [&'static str],
+    /// Element names that should be flattened (children promoted to parent)
+    /// e.g., `block` - removes unnecessary nesting
+    #[serde(default, rename = "flatten")]
+    pub flatten_kinds: Vec<String>,
 
-    /// Node kinds that should be skipped entirely
-    pub skip_kinds: &'static [&'static str],
+    /// Element names that should be removed along with their content
+    #[serde(default, rename = "skip")]
+    pub skip_kinds: Vec<String>,
+
+    /// Field names lifted into wrapper elements
+    /// e.g., `<identifier field="name">` → `<name><identifier>`
+    #[serde(default, rename = "lift")]
+    pub lift_fields: Vec<String>,
 }
 
 impl LanguageConfig {
-    /// Look up the semantic element name for a TreeSitter node kind
-    pub fn map_element_name<'a>(&self, kind: &'a str) -> &'a str {
+    /// Look up the semantic element name for an element name
+    pub fn map_element_name<'a>(&'a self, kind: &'a str) -> &'a str {
         self.element_mappings
-            .iter()
-            .find(|(from, _)| *from == kind)
-            .map(|(_, to)| *to as &'a str)
+            .get(kind)
+            .map(|to| to.as_str())
             .unwrap_or(kind)
     }
 
-    /// Check if a node kind represents a modifier
+    /// Check if an element name represents a modifier
     pub fn is_modifier_kind(&self, kind: &str) -> bool {
-        self.modifier_kinds.contains(&kind)
+        self.modifier_kinds.iter().any(|k| k == kind)
     }
 
     /// Check if text is a known modifier value
     pub fn is_known_modifier(&self, text: &str) -> bool {
-        self.known_modifiers.contains(&text)
+        self.known_modifiers.is_empty() || self.known_modifiers.iter().any(|m| m == text)
     }
 
-    /// Check if a node kind should be flattened
+    /// Check if an element should be flattened
     pub fn should_flatten(&self, kind: &str) -> bool {
-        self.flatten_kinds.contains(&kind)
+        self.flatten_kinds.iter().any(|k| k == kind)
     }
 
-    /// Check if a node kind should be skipped
+    /// Check if an element should be skipped
     pub fn should_skip(&self, kind: &str) -> bool {
-        self.skip_kinds.contains(&kind)
+        self.skip_kinds.iter().any(|k| k == kind)
     }
+
+    /// Transform one element; used as the walker callback
+    pub fn transform(&self, xot: &mut Xot, node: XotNode) -> Result<TransformAction, xot::Error> {
+        let name = match get_element_name(xot, node) {
+            Some(name) => name,
+            None => return Ok(TransformAction::Continue),
+        };
+
+        if self.should_skip(&name) {
+            // The document element has nowhere to go
+            if get_parent(xot, node).is_some_and(|p| xot.element(p).is_some()) {
+                detach(xot, node)?;
+                return Ok(TransformAction::Done);
+            }
+            return Ok(TransformAction::Continue);
+        }
+
+        if self.is_modifier_kind(&name) {
+            if let Some(text) = get_text_content(xot, node) {
+                let text = text.trim();
+                if self.is_known_modifier(text) && sanitize_xml_name(text) == text {
+                    rename_to_marker(xot, node, text)?;
+                    return Ok(TransformAction::Done);
+                }
+            }
+        }
+
+        if !self.lift_fields.is_empty() {
+            let fields: Vec<&str> = self.lift_fields.iter().map(|f| f.as_str()).collect();
+            for child in get_element_children(xot, node) {
+                promote_field_to_wrapper(xot, child, &fields)?;
+            }
+        }
+
+        if self.should_flatten(&name) {
+            return Ok(TransformAction::Flatten);
+        }
+
+        let new_name = self.map_element_name(&name);
+        if new_name != name {
+            rename(xot, node, new_name);
+        }
+        Ok(TransformAction::Continue)
+    }
+}
+
+/// The configured transform for `lang`, if any (aliases resolve to the
+/// canonical name).
+pub fn lookup<'a>(transforms: &'a LanguageTransforms, lang: &str) -> Option<&'a LanguageConfig> {
+    let name = get_language_info(lang).map(|info| info.name).unwrap_or(lang);
+    transforms.get(name)
 }
 
-/// Default configuration for unsupported languages (minimal transformation)
-pub static DEFAULT_CONFIG: LanguageConfig = LanguageConfig {
-    element_mappings: &[
-        // Common patterns across languages
-        ("identifier", "name"),
-    ],
-    modifier_kinds: &[],
-    known_modifiers: &[],
-    flatten_kinds: &[],
-    skip_kinds: &[],
-};
+/// Run a configured transform over a parsed document.
+pub(crate) fn apply(result: &mut XeeParseResult, config: &LanguageConfig) -> Result<(), ParseError> {
+    let doc_node = result.documents.document_node(result.doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    walk_transform(result.documents.xot_mut(), doc_node, |xot, node| config.transform(xot, node))
+        .map_err(|e| ParseError::Parse(e.to_string()))
+}
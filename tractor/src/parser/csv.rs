@@ -0,0 +1,268 @@
+//! CSV and TSV: one `row` per record, one element per column
+//!
+//! Delimited files are not parsed by TreeSitter. The header row names the
+//! columns and every following record becomes a `row` whose cells are named
+//! after their column:
+//!
+//! ```text
+//! email,status
+//! ada@example.com,active
+//! ```
+//! Becomes:
+//! ```xml
+//! <document>
+//!   <row><email>ada@example.com</email><status>active</status></row>
+//! </document>
+//! ```
+//! Queryable as: `//row[status='active']/email`
+//!
+//! Every cell carries the location of its field (quotes included), so
+//! matches point at the cell and `tractor set` can replace its value. Header
+//! names that are not valid element names are sanitized like JSON keys, with
+//! the original kept in a `key` attribute.
+
+use xee_xpath::Documents;
+use xot::{Node as XotNode, Xot};
+
+use super::injection::{append_element, line_column, line_starts};
+use super::{ParseError, ParseOptions, SyntaxError, SyntaxErrorKind, XeeParseResult};
+use crate::tree_mode::TreeMode;
+use crate::xot_transform::helpers::*;
+
+/// A field of a record
+struct Field {
+    /// Byte range of the field in the source, quotes included
+    start: usize,
+    end: usize,
+    /// The field's text, unquoted
+    value: String,
+}
+
+/// A record: its fields and the byte range of the line(s) it spans
+struct Record {
+    fields: Vec<Field>,
+    start: usize,
+    /// End of the record, line terminator included
+    end: usize,
+}
+
+/// The field delimiter for a delimited-text language
+pub(crate) fn delimiter(lang: &str) -> u8 {
+    match lang {
+        "tsv" => b'\t',
+        _ => b',',
+    }
+}
+
+/// Parse a CSV or TSV file into a `document` of `row` elements. Tables have
+/// only a data tree; asking for another tree mode is an error.
+pub(crate) fn parse_csv(source: &str, lang: &str, file_label: String, options: &ParseOptions<'_>) -> Result<XeeParseResult, ParseError> {
+    TreeMode::resolve(options.tree_mode, lang).map_err(ParseError::Parse)?;
+    let mut syntax_errors = Vec::new();
+    let file_lines = line_starts(source);
+    let records = read_records(source, delimiter(lang), &file_lines, &mut syntax_errors);
+
+    let mut documents = Documents::new();
+    let doc_handle = documents.add_string("file:///source".try_into().unwrap(), "<document/>")
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+    let doc_node = documents.document_node(doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    let host = documents.xot_mut();
+    let root = host.document_element(doc_node).map_err(|e| ParseError::Parse(e.to_string()))?;
+    set_location(host, root, (1, 1), line_column(&file_lines, source.len()));
+
+    let mut records = records.into_iter();
+    let columns: Vec<String> = records.next()
+        .map(|header| header.fields.into_iter().map(|f| f.value.trim().to_string()).collect())
+        .unwrap_or_default();
+
+    for record in records {
+        let row = append_element(host, root, "row");
+        set_location(host, row, line_column(&file_lines, record.start), line_column(&file_lines, record.end));
+
+        // Rows shorter than the header still get every column, so each row
+        // has the same shape; extra fields get positional names. Padding
+        // cells sit, empty, where the row's last field ends
+        let width = columns.len().max(record.fields.len());
+        let row_end = line_column(&file_lines, record.fields.last().map_or(record.start, |f| f.end));
+        let mut fields = record.fields.into_iter();
+        for i in 0..width {
+            let cell = append_cell(host, row, columns.get(i).map(String::as_str).unwrap_or(""), i);
+            let Some(field) = fields.next() else {
+                set_location(host, cell, row_end, row_end);
+                continue;
+            };
+            set_location(host, cell, line_column(&file_lines, field.start), line_column(&file_lines, field.end));
+            if !field.value.is_empty() {
+                let text = host.new_text(&field.value);
+                host.append(cell, text).map_err(|e| ParseError::Parse(e.to_string()))?;
+            }
+        }
+    }
+
+    Ok(XeeParseResult {
+        documents,
+        doc_handle,
+        source_lines: std::sync::Arc::new(source.lines().map(|s| s.to_string()).collect()),
+        file_path: file_label,
+        language: lang.to_string(),
+        syntax_errors,
+        node_locations: None,
+        queries: None,
+    })
+}
+
+/// Add a cell named after its column (`column3` for unnamed columns).
+fn append_cell(xot: &mut Xot, row: XotNode, column: &str, index: usize) -> XotNode {
+    let key = if column.is_empty() { format!("column{}", index + 1) } else { column.to_string() };
+    let name = sanitize_xml_name(&key);
+    let cell = append_element(xot, row, &name);
+    if name != key {
+        set_attr(xot, cell, "key", &key);
+    }
+    cell
+}
+
+/// Split the source into records (RFC 4180: quoted fields may hold
+/// delimiters, doubled quotes and line breaks). Blank lines are skipped.
+fn read_records(source: &str, delimiter: u8, line_starts: &[usize], errors: &mut Vec<SyntaxError>) -> Vec<Record> {
+    let bytes = source.as_bytes();
+    let mut records = Vec::new();
+    // A byte order mark is not part of the first column's name
+    let mut pos = if source.starts_with('\u{feff}') { 3 } else { 0 };
+
+    while pos < bytes.len() {
+        let start = pos;
+        let mut fields = Vec::new();
+        loop {
+            let field = if bytes[pos] == b'"' {
+                read_quoted(source, pos, delimiter, line_starts, errors)
+            } else {
+                let end = bytes[pos..].iter()
+                    .position(|&c| c == delimiter || c == b'\n' || c == b'\r')
+                    .map_or(bytes.len(), |p| pos + p);
+                Field { start: pos, end, value: source[pos..end].to_string() }
+            };
+            pos = field.end;
+            fields.push(field);
+            if bytes.get(pos) == Some(&delimiter) {
+                pos += 1;
+                if pos == bytes.len() {
+                    fields.push(Field { start: pos, end: pos, value: String::new() });
+                }
+            } else {
+                break;
+            }
+            if pos == bytes.len() {
+                break;
+            }
+        }
+
+        let blank = fields.len() == 1 && fields[0].start == fields[0].end;
+        match bytes.get(pos) {
+            Some(b'\r') if bytes.get(pos + 1) == Some(&b'\n') => pos += 2,
+            Some(b'\r' | b'\n') => pos += 1,
+            _ => {}
+        }
+        if !blank {
+            records.push(Record { fields, start, end: pos });
+        }
+    }
+    records
+}
+
+/// Read a quoted field starting at the `"` at `start`. Text after the closing
+/// quote, up to the next delimiter, is kept as written.
+fn read_quoted(source: &str, start: usize, delimiter: u8, line_starts: &[usize], errors: &mut Vec<SyntaxError>) -> Field {
+    let bytes = source.as_bytes();
+    let mut value = String::new();
+    let mut pos = start + 1;
+    loop {
+        match bytes[pos..].iter().position(|&c| c == b'"') {
+            Some(p) => {
+                value.push_str(&source[pos..pos + p]);
+                pos += p + 1;
+                if bytes.get(pos) == Some(&b'"') {
+                    value.push('"');
+                    pos += 1;
+                    continue;
+                }
+                break;
+            }
+            None => {
+                let (line, column) = line_column(line_starts, start);
+                let (end_line, end_column) = line_column(line_starts, bytes.len());
+                errors.push(SyntaxError {
+                    line: line as u32,
+                    column: column as u32,
+                    end_line: end_line as u32,
+                    end_column: end_column as u32,
+                    kind: SyntaxErrorKind::Missing("\"".to_string()),
+                });
+                value.push_str(&source[pos..]);
+                return Field { start, end: bytes.len(), value };
+            }
+        }
+    }
+    let end = bytes[pos..].iter()
+        .position(|&c| c == delimiter || c == b'\n' || c == b'\r')
+        .map_or(bytes.len(), |p| pos + p);
+    value.push_str(&source[pos..end]);
+    Field { start, end, value }
+}
+
+fn set_location(xot: &mut Xot, node: XotNode, start: (usize, usize), end: (usize, usize)) {
+    set_attr(xot, node, "line", &start.0.to_string());
+    set_attr(xot, node, "column", &start.1.to_string());
+    set_attr(xot, node, "end_line", &end.0.to_string());
+    set_attr(xot, node, "end_column", &end.1.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let source = "email,status,display name\nada@example.com,active,Ada\n\n\"grace@example.com\",\"inactive\",\"Hopper, Grace\"\nbob@example.com,active\n";
+        let mut result = parse_csv(source, "csv", "users.csv".to_string(), &ParseOptions::default()).unwrap();
+        assert!(result.syntax_errors.is_empty());
+        assert_eq!(result.query("/document/row").unwrap().len(), 3);
+
+        let emails = result.query("//row[status='active']/email").unwrap();
+        assert_eq!(emails.len(), 2);
+        assert_eq!(emails[0].value, "ada@example.com");
+        assert_eq!((emails[0].line, emails[0].column, emails[0].end_column), (2, 1, 16));
+
+        // Quoted fields are unquoted; their location includes the quotes
+        let names = result.query("//row[2]/display_name[@key='display name']").unwrap();
+        assert_eq!(names[0].value, "Hopper, Grace");
+        assert_eq!((names[0].line, names[0].column), (4, 32));
+
+        // Short rows still have every column, placed where the row ends
+        let padded = result.query("//row[3]/display_name[.='']").unwrap();
+        assert_eq!((padded[0].line, padded[0].column, padded[0].end_column), (5, 23, 23));
+    }
+
+    #[test]
+    fn test_only_data_tree() {
+        let options = ParseOptions { tree_mode: Some(TreeMode::Structure), ..ParseOptions::default() };
+        assert!(parse_csv("a,b\n1,2\n", "csv", "x.csv".to_string(), &options).is_err());
+    }
+
+    #[test]
+    fn test_parse_tsv() {
+        let source = "flag\tenabled\nnew_checkout\t\"multi\nline\"\n";
+        let mut result = parse_csv(source, "tsv", "flags.tsv".to_string(), &ParseOptions::default()).unwrap();
+        let enabled = result.query("//row[flag='new_checkout']/enabled").unwrap();
+        assert_eq!(enabled[0].value, "multi\nline");
+        assert_eq!((enabled[0].end_line, enabled[0].end_column), (3, 6));
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        let result = parse_csv("a,b\n1,\"open\n", "csv", "x.csv".to_string(), &ParseOptions::default()).unwrap();
+        assert_eq!(result.syntax_errors.len(), 1);
+        assert_eq!((result.syntax_errors[0].line, result.syntax_errors[0].column), (2, 3));
+    }
+}
@@ -0,0 +1,137 @@
+//! Language detection from file content
+//!
+//! Extensionless scripts (`bin/deploy`, `manage`) name their language in the
+//! first line (`#!/usr/bin/env python3`) or in an editor modeline
+//! (`# vim: set ft=ruby:`, `-*- mode: python -*-`). These are only consulted
+//! when the path alone does not identify the language.
+
+use crate::language_info::get_language_info;
+
+/// Number of lines at the start and end of a file where vim looks for modelines
+const MODELINE_LINES: usize = 5;
+
+/// Detect a language from a shebang or editor modeline.
+///
+/// `content` may be the whole file or just its head; only the first and last
+/// few lines are inspected.
+pub fn detect_language_from_content(content: &str) -> Option<&'static str> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    if let Some(lang) = lines.first().and_then(|line| shebang_language(line)) {
+        return Some(lang);
+    }
+    // Emacs reads the mode line from the first line, or the second after a shebang
+    if let Some(lang) = lines.iter().take(2).find_map(|line| emacs_mode(line)) {
+        return Some(lang);
+    }
+    let tail_start = lines.len().saturating_sub(MODELINE_LINES).max(MODELINE_LINES);
+    lines.iter()
+        .take(MODELINE_LINES)
+        .chain(lines.iter().skip(tail_start))
+        .find_map(|line| vim_filetype(line))
+}
+
+/// `#!/bin/bash`, `#!/usr/bin/env python3`, `#!/usr/bin/env -S node --flag`
+fn shebang_language(line: &str) -> Option<&'static str> {
+    let command = line.strip_prefix("#!")?.trim();
+    let mut words = command.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+    interpreter_language(program)
+}
+
+/// Map an interpreter name to a language, ignoring version suffixes
+/// (`python3.12`, `ruby2.7`).
+fn interpreter_language(program: &str) -> Option<&'static str> {
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match name {
+        "python" | "pypy" => Some("python"),
+        "node" | "nodejs" => Some("javascript"),
+        "ts-node" | "tsx" | "deno" => Some("typescript"),
+        "bash" | "sh" | "dash" | "zsh" | "ksh" => Some("bash"),
+        "ruby" => Some("ruby"),
+        "php" => Some("php"),
+        "lua" | "luajit" => Some("lua"),
+        "Rscript" => Some("r"),
+        "julia" => Some("julia"),
+        "scala" => Some("scala"),
+        "runghc" | "runhaskell" => Some("haskell"),
+        "ocaml" => Some("ocaml"),
+        "swift" => Some("swift"),
+        "dotnet-script" => Some("csharp"),
+        _ => None,
+    }
+}
+
+/// `-*- mode: python -*-` or the short form `-*- python -*-`
+fn emacs_mode(line: &str) -> Option<&'static str> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let vars = line[start..end].trim();
+    if !vars.contains(':') {
+        return editor_language(vars);
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        if key.trim().eq_ignore_ascii_case("mode") {
+            editor_language(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+/// `vim: set ft=python:`, `vim: filetype=ruby`, `vi: syntax=sh`
+fn vim_filetype(line: &str) -> Option<&'static str> {
+    let start = ["vim:", "vi:", "ex:"].iter()
+        .filter_map(|marker| line.find(marker).map(|i| i + marker.len()))
+        .min()?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            match key {
+                "ft" | "filetype" | "syn" | "syntax" => editor_language(value),
+                _ => None,
+            }
+        })
+}
+
+/// Resolve an editor's name for a language to a tractor language
+fn editor_language(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    match name.as_str() {
+        "sh" | "zsh" | "shell-script" => Some("bash"),
+        "sql" => Some("tsql"),
+        "terraform" => Some("hcl"),
+        "js" | "js2" => Some("javascript"),
+        "c++" => Some("cpp"),
+        _ => get_language_info(name.trim_end_matches("-ts").trim_end_matches("-mode"))
+            .map(|info| info.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shebang() {
+        assert_eq!(detect_language_from_content("#!/usr/bin/env python3\nprint(1)\n"), Some("python"));
+        assert_eq!(detect_language_from_content("#!/bin/bash\necho hi\n"), Some("bash"));
+        assert_eq!(detect_language_from_content("#!/usr/bin/env -S node --no-warnings\n"), Some("javascript"));
+        assert_eq!(detect_language_from_content("#!/usr/bin/ruby2.7 -w\n"), Some("ruby"));
+        assert_eq!(detect_language_from_content("#!/usr/bin/env perl\n"), None);
+    }
+
+    #[test]
+    fn test_modelines() {
+        assert_eq!(detect_language_from_content("# -*- mode: python; coding: utf-8 -*-\n"), Some("python"));
+        assert_eq!(detect_language_from_content("#!/bin/false\n// -*- c++ -*-\n"), Some("cpp"));
+        assert_eq!(detect_language_from_content("# vim: set ft=ruby:\nputs 1\n"), Some("ruby"));
+        assert_eq!(detect_language_from_content("a\nb\nc\nd\ne\nf\n# vim: syntax=sh\n"), Some("bash"));
+        assert_eq!(detect_language_from_content("plain text\n"), None);
+    }
+}
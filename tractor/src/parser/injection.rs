@@ -0,0 +1,430 @@
+//! Language injection: parse embedded code as sub-trees
+//!
+//! Some source files carry code in another language: fenced code blocks in
+//! Markdown, `<script>`/`<style>` in HTML, SQL strings in C#, `run:` scripts
+//! in GitHub Actions workflows. The injection layer re-parses that code with
+//! the right language and grafts the result under the host node:
+//!
+//! ```xml
+//! <code_block>
+//!   <language>sql</language>
+//!   <code>SELECT * FROM users</code>
+//!   <injected lang="tsql"><file>...</file></injected>
+//! </code_block>
+//! ```
+//! Queryable as: `//code_block/injected[@lang='tsql']//select`
+//!
+//! Injected nodes keep their positions in the host file, so matches report
+//! the line and column of the embedded code. Their text repeats the host's,
+//! so a match's value leaves `injected` sub-trees out; XPath comparisons on a
+//! host still see both. Injection only runs for queries that test for
+//! `injected` elements (see [`wants_injections`]): it costs a parse per
+//! embedded snippet, and other queries get the plain host tree.
+
+use xot::{Node as XotNode, Xot};
+
+use super::{parse_string_to_xot_with_options, ParseError, XeeParseResult};
+use crate::language_info::{get_language_for_extension, get_language_info};
+use crate::tree_mode::TreeMode;
+use crate::xot_transform::helpers::*;
+
+/// C# calls whose first string argument is SQL: EF Core migrations and raw
+/// queries, ADO.NET commands and Dapper extension methods.
+const CSHARP_SQL_CALLS: &[&str] = &[
+    "Sql", "FromSql", "FromSqlRaw", "SqlQueryRaw",
+    "ExecuteSql", "ExecuteSqlRaw", "ExecuteSqlRawAsync",
+    "SqlCommand", "SqlDataAdapter", "NpgsqlCommand", "SqliteCommand", "MySqlCommand", "OracleCommand",
+    "Query", "QueryAsync", "QueryFirst", "QueryFirstAsync", "QueryFirstOrDefault",
+    "QueryFirstOrDefaultAsync", "QuerySingle", "QuerySingleAsync", "QuerySingleOrDefault",
+    "QuerySingleOrDefaultAsync", "QueryMultiple", "QueryMultipleAsync",
+    "Execute", "ExecuteAsync", "ExecuteScalar", "ExecuteScalarAsync",
+    "ExecuteReader", "ExecuteReaderAsync",
+];
+
+/// Embedded code found in a host tree
+struct Injection {
+    /// Element the injected tree is appended to
+    host: XotNode,
+    /// Language of the embedded code
+    lang: &'static str,
+    /// Byte range of the embedded code in the host source
+    start: usize,
+    end: usize,
+}
+
+/// Whether a query needs injected sub-trees: it tests for `injected` elements.
+pub fn wants_injections(xpath: &str) -> bool {
+    crate::xpath::tests_element(xpath, "injected")
+}
+
+/// Find embedded code in a parsed file and graft its parsed tree under each host node.
+pub(crate) fn inject(result: &mut XeeParseResult, source: &str, tree_mode: Option<TreeMode>) -> Result<(), ParseError> {
+    let lang = result.language.clone();
+    if TreeMode::resolve(tree_mode, &lang).map_err(ParseError::Parse)? == TreeMode::Raw {
+        return Ok(());
+    }
+
+    let doc_node = result.documents.document_node(result.doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    let xot = result.documents.xot_mut();
+    let line_starts = line_starts(source);
+
+    let injections: Vec<Injection> = xot.descendants(doc_node)
+        .filter(|&n| xot.element(n).is_some())
+        .filter_map(|n| find_injection(xot, n, source, &lang, &result.file_path, &line_starts))
+        .collect();
+
+    for injection in injections {
+        let snippet = &source[injection.start..injection.end];
+        if snippet.trim().is_empty() {
+            continue;
+        }
+        // Embedded code that fails to parse is left as text
+        let Ok(parsed) = parse_string_to_xot_with_options(snippet, injection.lang, result.file_path.clone(), None, false) else {
+            continue;
+        };
+        let Ok(sub_root) = parsed.xot.document_element(parsed.root) else {
+            continue;
+        };
+
+        let (line, column) = line_column(&line_starts, injection.start);
+        let (end_line, end_column) = line_column(&line_starts, injection.end);
+        let injected = append_element(xot, injection.host, "injected");
+        set_attr(xot, injected, "lang", injection.lang);
+        set_attr(xot, injected, "line", &line.to_string());
+        set_attr(xot, injected, "column", &column.to_string());
+        set_attr(xot, injected, "end_line", &end_line.to_string());
+        set_attr(xot, injected, "end_column", &end_column.to_string());
+
+        let copy = copy_shifted(&parsed.xot, sub_root, xot, line - 1, column - 1)
+            .map_err(|e| ParseError::Parse(e.to_string()))?;
+        xot.append(injected, copy).map_err(|e| ParseError::Parse(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Languages [`inject`] can find embedded in `lang`'s files; `None` when
+/// that can be any language (Markdown code blocks).
+pub(super) fn injected_languages(lang: &str) -> Option<&'static [&'static str]> {
+    match lang {
+        "markdown" | "md" | "mdx" => None,
+        "html" | "htm" => Some(&["javascript", "json", "css"]),
+        "csharp" | "cs" => Some(&["tsql"]),
+        "yaml" | "yml" => Some(&["bash", "python"]),
+        _ => Some(&[]),
+    }
+}
+
+/// Dispatch to the host language's injection rule
+fn find_injection(xot: &Xot, node: XotNode, source: &str, lang: &str, file_path: &str, line_starts: &[usize]) -> Option<Injection> {
+    match lang {
+        "markdown" | "md" | "mdx" => markdown_code_block(xot, node, source, line_starts),
+        "html" | "htm" => html_raw_text(xot, node, source, line_starts),
+        "csharp" | "cs" => csharp_sql_string(xot, node, source, line_starts),
+        "yaml" | "yml" if is_workflow_file(file_path) => yaml_run_script(xot, node, source, line_starts),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Host rules
+// =============================================================================
+
+/// Markdown fenced code blocks, using the info string as language
+fn markdown_code_block(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<Injection> {
+    if get_kind(xot, node).as_deref() != Some("fenced_code_block") {
+        return None;
+    }
+    let info = child_element(xot, node, |c| get_element_name(xot, c).as_deref() == Some("language"))?;
+    let lang = resolve_language(xot.string_value(info).split_whitespace().next()?)?;
+    let content = child_element(xot, node, |c| get_kind(xot, c).as_deref() == Some("code_fence_content"))?;
+    let (start, end) = byte_range(xot, content, source, line_starts)?;
+    Some(Injection { host: node, lang, start, end })
+}
+
+/// HTML `<script>` (JavaScript, or JSON for `type="application/ld+json"` and
+/// similar) and `<style>` (CSS) elements
+fn html_raw_text(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<Injection> {
+    let lang = match get_kind(xot, node).as_deref() {
+        Some("style_element") => "css",
+        Some("script_element") => match html_type_attribute(xot, node).as_deref() {
+            None | Some("") | Some("module") | Some("text/javascript") | Some("application/javascript") => "javascript",
+            Some(t) if t.ends_with("json") => "json",
+            Some(_) => return None,
+        },
+        _ => return None,
+    };
+    let content = child_element(xot, node, |c| get_kind(xot, c).as_deref() == Some("raw_text"))?;
+    let (start, end) = byte_range(xot, content, source, line_starts)?;
+    Some(Injection { host: node, lang, start, end })
+}
+
+/// Value of the `type` attribute on an element's start tag
+fn html_type_attribute(xot: &Xot, element: XotNode) -> Option<String> {
+    html_attribute(xot, element, "type").map(|t| t.to_lowercase())
+}
+
+/// Value of an attribute on an element's start tag; empty for a bare
+/// attribute (`<style scoped>`)
+pub(super) fn html_attribute(xot: &Xot, element: XotNode, name: &str) -> Option<String> {
+    let start_tag = child_element(xot, element, |c| get_kind(xot, c).as_deref() == Some("start_tag"))?;
+    get_element_children(xot, start_tag)
+        .into_iter()
+        .filter(|&a| get_kind(xot, a).as_deref() == Some("attribute"))
+        .find_map(|attribute| {
+            let attribute_name = child_element(xot, attribute, |c| get_kind(xot, c).as_deref() == Some("attribute_name"))?;
+            if !xot.string_value(attribute_name).trim().eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let value = child_element(xot, attribute, |c| {
+                matches!(get_kind(xot, c).as_deref(), Some("attribute_value" | "quoted_attribute_value"))
+            });
+            Some(value.map_or_else(String::new, |v| xot.string_value(v).trim().trim_matches(['"', '\'']).to_string()))
+        })
+}
+
+/// C# string literals passed as the first argument of a known SQL call
+/// (`migrationBuilder.Sql("...")`, `new SqlCommand("...")`, `conn.Query<T>("...")`)
+fn csharp_sql_string(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<Injection> {
+    let kind = get_kind(xot, node)?;
+    if !matches!(kind.as_str(), "string_literal" | "verbatim_string_literal" | "raw_string_literal") {
+        return None;
+    }
+    let (start, end) = byte_range(xot, node, source, line_starts)?;
+    if !CSHARP_SQL_CALLS.contains(&csharp_callee(&source[..start])?) {
+        return None;
+    }
+
+    let literal = &source[start..end];
+    let (open, close) = if kind == "raw_string_literal" {
+        let quotes = literal.len() - literal.trim_start_matches('"').len();
+        (quotes, quotes)
+    } else if literal.starts_with('@') {
+        (2, 1)
+    } else {
+        (1, 1)
+    };
+    if open + close > literal.len() {
+        return None;
+    }
+    Some(Injection { host: node, lang: "tsql", start: start + open, end: end - close })
+}
+
+/// Name of the method or type being called when the text before a string
+/// literal ends in `Name(` or `Name<T>(` — i.e. the literal is the first argument.
+fn csharp_callee(before: &str) -> Option<&str> {
+    let mut callee = before.trim_end().strip_suffix('(')?.trim_end();
+    if callee.ends_with('>') {
+        let mut depth = 0;
+        for (i, c) in callee.char_indices().rev() {
+            match c {
+                '>' => depth += 1,
+                '<' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        callee = callee[..i].trim_end();
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let name_start = callee
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    Some(&callee[name_start..])
+}
+
+/// Whether a YAML file is a GitHub Actions workflow or composite action,
+/// the only files whose `run:` keys hold scripts
+fn is_workflow_file(file_path: &str) -> bool {
+    let path = file_path.replace('\\', "/");
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    path.contains(".github/workflows/") || matches!(file_name, "action.yml" | "action.yaml")
+}
+
+/// GitHub Actions `run:` scripts, as bash unless a sibling `shell:` says otherwise
+fn yaml_run_script(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<Injection> {
+    let value = yaml_value_of_key(xot, node, "run")?;
+    let lang = match get_parent(xot, node).and_then(|p| yaml_sibling_text(xot, p, "shell")).as_deref() {
+        None | Some("bash") | Some("sh") => "bash",
+        Some("python") => "python",
+        Some(_) => return None,
+    };
+
+    let (mut start, mut end) = byte_range(xot, value, source, line_starts)?;
+    let text = &source[start..end];
+    if text.starts_with(['|', '>']) {
+        // Block scalar: the script starts on the line after the indicator
+        start += text.find('\n')? + 1;
+    } else if text.len() >= 2 && text.starts_with(['"', '\'']) {
+        start += 1;
+        end -= 1;
+    }
+    Some(Injection { host: node, lang, start, end })
+}
+
+/// The node holding the value of a `key:` pair, in either tree mode.
+///
+/// In the data tree the pair is an element named after the key whose span is
+/// already the value's; in the structure tree it is a `property` with a `key`
+/// child followed by the value.
+fn yaml_value_of_key(xot: &Xot, node: XotNode, key: &str) -> Option<XotNode> {
+    if get_attr(xot, node, "field").as_deref() == Some(key) {
+        return (!has_element_children(xot, node)).then_some(node);
+    }
+    if get_element_name(xot, node).as_deref() != Some("property") {
+        return None;
+    }
+    let key_node = child_element(xot, node, |c| get_element_name(xot, c).as_deref() == Some("key"))?;
+    if xot.string_value(key_node).trim() != key {
+        return None;
+    }
+    child_element(xot, node, |c| c != key_node)
+}
+
+/// Trimmed value of a sibling `key:` pair in a mapping
+fn yaml_sibling_text(xot: &Xot, mapping: XotNode, key: &str) -> Option<String> {
+    get_element_children(xot, mapping)
+        .into_iter()
+        .find_map(|c| yaml_value_of_key(xot, c, key))
+        .map(|v| xot.string_value(v).trim().to_string())
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+/// Resolve a code block info string (`sql`, `ts`, `python`) to a language name
+fn resolve_language(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    get_language_info(&name)
+        .or_else(|| get_language_for_extension(&name))
+        .map(|info| info.name)
+}
+
+pub(super) fn child_element(xot: &Xot, node: XotNode, predicate: impl Fn(XotNode) -> bool) -> Option<XotNode> {
+    get_element_children(xot, node).into_iter().find(|&c| predicate(c))
+}
+
+pub(super) fn append_element(xot: &mut Xot, parent: XotNode, name: &str) -> XotNode {
+    let name_id = xot.add_name(name);
+    let element = xot.new_element(name_id);
+    // The host is an element, so appending cannot fail
+    let _ = xot.append(parent, element);
+    element
+}
+
+/// Byte range of a node from its location attributes
+pub(super) fn byte_range(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<(usize, usize)> {
+    let offset = |line: usize, column: usize| -> Option<usize> {
+        let offset = line_starts.get(line.checked_sub(1)?)? + column.checked_sub(1)?;
+        (offset <= source.len() && source.is_char_boundary(offset)).then_some(offset)
+    };
+    let start = offset(get_line(xot, node, "line")?, get_line(xot, node, "column")?)?;
+    let end = offset(get_line(xot, node, "end_line")?, get_line(xot, node, "end_column")?)?;
+    (start <= end).then_some((start, end))
+}
+
+/// Byte offsets at which each line starts
+pub(super) fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// 1-based line and byte column of a byte offset
+pub(super) fn line_column(line_starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = line_starts.partition_point(|&start| start <= offset);
+    (line, offset - line_starts[line - 1] + 1)
+}
+
+/// Copy a subtree from another Xot, moving its locations from snippet
+/// coordinates to host coordinates.
+///
+/// Every line moves down by `line_offset`; columns only move right by
+/// `column_offset` on the snippet's first line.
+fn copy_shifted(src: &Xot, node: XotNode, dst: &mut Xot, line_offset: usize, column_offset: usize) -> Result<XotNode, xot::Error> {
+    copy_relocated(src, node, dst, &|line, column| {
+        let column = if line == 1 { column + column_offset } else { column };
+        (line + line_offset, column)
+    })
+}
+
+/// Copy a subtree from another Xot, passing every `line`/`column` and
+/// `end_line`/`end_column` pair through `relocate`.
+pub(super) fn copy_relocated(
+    src: &Xot,
+    node: XotNode,
+    dst: &mut Xot,
+    relocate: &dyn Fn(usize, usize) -> (usize, usize),
+) -> Result<XotNode, xot::Error> {
+    if let Some(text) = src.text_str(node) {
+        return Ok(dst.new_text(text));
+    }
+
+    let name = src.element(node).map(|e| src.local_name_str(e.name())).unwrap_or("_");
+    let name_id = dst.add_name(name);
+    let copy = dst.new_element(name_id);
+
+    let attributes: Vec<(String, String)> = src.attributes(node)
+        .iter()
+        .map(|(name_id, value)| (src.local_name_str(name_id).to_string(), value.clone()))
+        .collect();
+    let position = |line_attr: &str, column_attr: &str| -> Option<(usize, usize)> {
+        let value = |attr: &str| attributes.iter().find(|(k, _)| k == attr)?.1.parse::<usize>().ok();
+        Some(relocate(value(line_attr)?, value(column_attr).unwrap_or(1)))
+    };
+    let start = position("line", "column");
+    let end = position("end_line", "end_column");
+    for (key, value) in &attributes {
+        let moved = match key.as_str() {
+            "line" => start.map(|(line, _)| line),
+            "column" => start.map(|(_, column)| column),
+            "end_line" => end.map(|(line, _)| line),
+            "end_column" => end.map(|(_, column)| column),
+            _ => None,
+        };
+        let value = moved.map_or_else(|| value.clone(), |v| v.to_string());
+        set_attr(dst, copy, key, &value);
+    }
+
+    for child in src.children(node) {
+        if src.element(child).is_some() || src.text_str(child).is_some() {
+            let child_copy = copy_relocated(src, child, dst, relocate)?;
+            dst.append(copy, child_copy)?;
+        }
+    }
+    Ok(copy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csharp_callee() {
+        assert_eq!(csharp_callee("migrationBuilder.Sql("), Some("Sql"));
+        assert_eq!(csharp_callee("new SqlCommand( "), Some("SqlCommand"));
+        assert_eq!(csharp_callee("conn.Query<Dictionary<string, int>>("), Some("Query"));
+        assert_eq!(csharp_callee("Log(\"x\", "), None);
+    }
+
+    #[test]
+    fn test_wants_injections() {
+        assert!(wants_injections("//injected[@lang='tsql']//select"));
+        assert!(wants_injections("//code_block[injected]"));
+        assert!(!wants_injections("//string[contains(., 'injected')]"));
+        assert!(!wants_injections("//injected_by"));
+    }
+
+    #[test]
+    fn test_is_workflow_file() {
+        assert!(is_workflow_file(".github/workflows/ci.yml"));
+        assert!(is_workflow_file("repo\\.github\\workflows\\release.yaml"));
+        assert!(is_workflow_file("actions/setup/action.yml"));
+        assert!(!is_workflow_file("deploy/k8s.yml"));
+        assert!(!is_workflow_file("config.yaml"));
+    }
+}
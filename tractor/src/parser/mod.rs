@@ -5,18 +5,33 @@
 
 pub mod config;
 pub mod raw;
+mod xml;
+mod injection;
+mod syntax_errors;
+mod detect;
+mod notebook;
+mod component;
+mod csv;
+mod pattern;
+
+pub use injection::wants_injections;
+pub use detect::detect_language_from_content;
+pub use config::{LanguageConfig, LanguageTransforms, TransformBase};
+pub use syntax_errors::{SyntaxError, SyntaxErrorKind};
+pub use pattern::pattern_to_xpath;
 
 // Re-export languages for compatibility
 pub use crate::languages;
 
+use std::collections::BTreeSet;
 use std::path::Path;
-use std::fs;
 use thiserror::Error;
 use crate::tree_mode::TreeMode;
+use crate::xot_transform::attach_doc_comments;
 
 /// Supported languages and their extensions
 pub static SUPPORTED_LANGUAGES: &[(&str, &[&str])] = &[
-    ("csharp", &["cs"]),
+    ("csharp", &["cs", "csx", "cake"]),
     ("rust", &["rs"]),
     ("javascript", &["js", "mjs", "cjs", "jsx"]),
     ("typescript", &["ts"]),
@@ -28,6 +43,8 @@ pub static SUPPORTED_LANGUAGES: &[(&str, &[&str])] = &[
     ("cpp", &["cpp", "cc", "cxx", "hpp", "hxx", "hh"]),
     ("c", &["c", "h"]),
     ("json", &["json"]),
+    ("jsonc", &["jsonc"]),
+    ("json5", &["json5"]),
     ("html", &["html", "htm"]),
     ("css", &["css"]),
     ("bash", &["sh", "bash"]),
@@ -43,10 +60,25 @@ pub static SUPPORTED_LANGUAGES: &[(&str, &[&str])] = &[
     ("r", &["r"]),
     ("julia", &["jl"]),
     ("markdown", &["md", "markdown", "mdx"]),
+    ("kotlin", &["kt", "kts"]),
+    ("swift", &["swift"]),
+    ("dart", &["dart"]),
+    ("hcl", &["tf", "tfvars", "hcl"]),
+    ("dockerfile", &["dockerfile"]),
+    ("protobuf", &["proto"]),
+    ("graphql", &["graphql", "gql"]),
     // XML pass-through (not parsed, queried directly)
-    ("xml", &["xml"]),
+    ("xml", &["xml", "csproj", "props", "targets", "resx", "xaml"]),
     // SQL dialects
     ("tsql", &["sql"]),
+    // Jupyter notebooks (cells parsed as Python and Markdown)
+    ("notebook", &["ipynb"]),
+    // Single-file components (blocks parsed as HTML, TypeScript and CSS)
+    ("vue", &["vue"]),
+    ("svelte", &["svelte"]),
+    // Delimited tables (not parsed by TreeSitter, data tree only)
+    ("csv", &["csv"]),
+    ("tsv", &["tsv"]),
 ];
 
 /// Parse result with xot document
@@ -83,10 +115,26 @@ pub enum ParseError {
 }
 
 /// Detect language from file path extension
+///
+/// Returns `"unknown"` when the path does not identify the language; see
+/// [`detect_language_from_content`] for shebang and modeline detection.
 pub fn detect_language(path: &str) -> &'static str {
+    // Dockerfiles are identified by file name (`Dockerfile`, `Dockerfile.prod`)
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or("").to_lowercase();
+    if file_name == "dockerfile" || file_name == "containerfile" || file_name.starts_with("dockerfile.") {
+        return "dockerfile";
+    }
+    if file_name == ".babelrc" {
+        return "json";
+    }
+    // JSON files read by tools that allow comments and trailing commas
+    if is_jsonc_file(path, &file_name) {
+        return "jsonc";
+    }
+
     let ext = path.rsplit('.').next().unwrap_or("");
     match ext.to_lowercase().as_str() {
-        "cs" => "csharp",
+        "cs" | "csx" | "cake" => "csharp",
         "rs" => "rust",
         "js" | "mjs" | "cjs" | "jsx" => "javascript",
         "ts" => "typescript",
@@ -98,6 +146,8 @@ pub fn detect_language(path: &str) -> &'static str {
         "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "hh" => "cpp",
         "c" | "h" => "c",
         "json" => "json",
+        "jsonc" => "jsonc",
+        "json5" => "json5",
         "html" | "htm" => "html",
         "css" => "css",
         "sh" | "bash" => "bash",
@@ -113,12 +163,38 @@ pub fn detect_language(path: &str) -> &'static str {
         "r" => "r",
         "jl" => "julia",
         "md" | "markdown" | "mdx" => "markdown",
-        "xml" => "xml",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "dart" => "dart",
+        "tf" | "tfvars" | "hcl" => "hcl",
+        "dockerfile" => "dockerfile",
+        "proto" => "protobuf",
+        "graphql" | "gql" => "graphql",
+        "xml" | "csproj" | "props" | "targets" | "resx" | "xaml" => "xml",
         "sql" => "tsql",
+        "ipynb" => "notebook",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "csv" => "csv",
+        "tsv" => "tsv",
         _ => "unknown",
     }
 }
 
+/// Whether a `.json` file is one of the well-known JSONC configuration files
+/// (`tsconfig.json`, `devcontainer.json`, VS Code settings).
+fn is_jsonc_file(path: &str, file_name: &str) -> bool {
+    if !file_name.ends_with(".json") {
+        return false;
+    }
+    let in_vscode = path.rsplit(['/', '\\']).nth(1) == Some(".vscode");
+    in_vscode
+        || file_name.starts_with("tsconfig.")
+        || file_name.starts_with("jsconfig.")
+        || file_name == "devcontainer.json"
+        || file_name == ".devcontainer.json"
+}
+
 /// Get TreeSitter language for a language name
 fn get_tree_sitter_language(lang: &str) -> Result<tree_sitter::Language, ParseError> {
     match lang {
@@ -133,7 +209,7 @@ fn get_tree_sitter_language(lang: &str) -> Result<tree_sitter::Language, ParseEr
         "ruby" | "rb" => Ok(tree_sitter_ruby::LANGUAGE.into()),
         "cpp" | "c++" => Ok(tree_sitter_cpp::LANGUAGE.into()),
         "c" => Ok(tree_sitter_c::LANGUAGE.into()),
-        "json" => Ok(tree_sitter_json::LANGUAGE.into()),
+        "json" | "jsonc" | "json5" => Ok(tree_sitter_json::LANGUAGE.into()),
         "html" | "htm" => Ok(tree_sitter_html::LANGUAGE.into()),
         "css" => Ok(tree_sitter_css::LANGUAGE.into()),
         "bash" | "sh" => Ok(tree_sitter_bash::LANGUAGE.into()),
@@ -150,10 +226,105 @@ fn get_tree_sitter_language(lang: &str) -> Result<tree_sitter::Language, ParseEr
         "julia" | "jl" => Ok(tree_sitter_julia::LANGUAGE.into()),
         "markdown" | "md" | "mdx" => Ok(tree_sitter_md::LANGUAGE.into()),
         "tsql" | "mssql" => Ok(tree_sitter_sequel_tsql::LANGUAGE.into()),
+        "kotlin" | "kt" => Ok(tree_sitter_kotlin_ng::LANGUAGE.into()),
+        "swift" => Ok(tree_sitter_swift::LANGUAGE.into()),
+        "dart" => Ok(tree_sitter_dart::LANGUAGE.into()),
+        "hcl" | "terraform" | "tf" => Ok(tree_sitter_hcl::LANGUAGE.into()),
+        "dockerfile" | "docker" => Ok(tree_sitter_containerfile::LANGUAGE.into()),
+        "protobuf" | "proto" => Ok(tree_sitter_proto::LANGUAGE.into()),
+        "graphql" | "gql" => Ok(tree_sitter_graphql::LANGUAGE.into()),
         _ => Err(ParseError::UnsupportedLanguage(lang.to_string())),
     }
 }
 
+/// Every element name a `lang` tree can contain in `tree_mode`: the
+/// grammar's node kinds and field names, plus what the language's transform
+/// adds. With `injected`, also the names of the code the file can embed,
+/// grafted under `<injected>` (see [`wants_injections`]). `None` when that
+/// is open-ended — data and common trees, and languages whose element names
+/// come from the file's keys.
+pub fn element_vocabulary(lang: &str, tree_mode: Option<TreeMode>, injected: bool) -> Option<BTreeSet<&'static str>> {
+    let mut vocabulary = own_vocabulary(lang, tree_mode)?;
+    if injected {
+        // Embedded code is parsed in its language's default tree
+        for embedded in injection::injected_languages(lang)? {
+            vocabulary.extend(own_vocabulary(embedded, None)?);
+        }
+    }
+    Some(vocabulary)
+}
+
+fn own_vocabulary(lang: &str, tree_mode: Option<TreeMode>) -> Option<BTreeSet<&'static str>> {
+    let transformed = match TreeMode::resolve(tree_mode, lang).ok()? {
+        TreeMode::Raw => Vec::new(),
+        TreeMode::Structure => languages::transform_elements(lang)?,
+        TreeMode::Common | TreeMode::Data => return None,
+    };
+    let language = get_tree_sitter_language(lang).ok()?;
+    let kinds = (0..language.node_kind_count()).filter_map(|id| language.node_kind_for_id(id as u16));
+    let fields = (1..=language.field_count()).filter_map(|id| language.field_name_for_id(id as u16));
+    // Injected code sits in `<injected>`; unparseable code is `<ERROR>`
+    let tractor = ["injected", "ERROR"];
+    Some(kinds.chain(fields).chain(transformed).chain(tractor).collect())
+}
+
+/// Parse `source` with `lang`'s grammar. Returns the tree and the text its
+/// nodes' ranges index into.
+///
+/// JSONC trailing commas are blanked (offsets unchanged) because the JSON
+/// grammar rejects them, and the tree reads the blanked text. JSON5 is
+/// rewritten into JSON (see [`languages::json::json5_to_json`]) and the
+/// tree edited back onto the JSON5 source, so it reads `source` itself.
+fn parse_tree<'a>(
+    parser: &mut tree_sitter::Parser,
+    source: &'a str,
+    lang: &str,
+) -> Result<(tree_sitter::Tree, std::borrow::Cow<'a, str>), ParseError> {
+    let parse = |parser: &mut tree_sitter::Parser, text: &str| parser.parse(text, None)
+        .ok_or_else(|| ParseError::Parse("Failed to parse source".to_string()));
+    match lang {
+        "jsonc" => {
+            let text = languages::json::blank_trailing_commas(source);
+            Ok((parse(parser, &text)?, text))
+        }
+        "json5" => {
+            let (json, splices) = languages::json::json5_to_json(source);
+            let json = languages::json::blank_trailing_commas(&json);
+            let mut tree = parse(parser, &json)?;
+            let line_starts: Vec<usize> = std::iter::once(0)
+                .chain(json.match_indices('\n').map(|(i, _)| i + 1))
+                .collect();
+            let point = |offset: usize| {
+                let row = line_starts.partition_point(|&start| start <= offset) - 1;
+                tree_sitter::Point { row, column: offset - line_starts[row] }
+            };
+            // From the end backwards, so the text before each splice is
+            // still the JSON text its offsets were taken from
+            for splice in splices.iter().rev() {
+                let start_position = point(splice.json.start);
+                let original = &source[splice.original.clone()];
+                let new_end_position = match original.rfind('\n') {
+                    Some(newline) => tree_sitter::Point {
+                        row: start_position.row + original.matches('\n').count(),
+                        column: original.len() - newline - 1,
+                    },
+                    None => tree_sitter::Point { column: start_position.column + original.len(), ..start_position },
+                };
+                tree.edit(&tree_sitter::InputEdit {
+                    start_byte: splice.json.start,
+                    old_end_byte: splice.json.end,
+                    new_end_byte: splice.json.start + original.len(),
+                    start_position,
+                    old_end_position: point(splice.json.end),
+                    new_end_position,
+                });
+            }
+            Ok((tree, std::borrow::Cow::Borrowed(source)))
+        }
+        _ => Ok((parse(parser, source)?, std::borrow::Cow::Borrowed(source))),
+    }
+}
+
 /// Language ABI version info
 #[derive(Debug, Clone)]
 pub struct LanguageAbiInfo {
@@ -189,6 +360,14 @@ pub fn get_language_abi_versions() -> Vec<LanguageAbiInfo> {
             name: "css",
             abi_version: tree_sitter::Language::from(tree_sitter_css::LANGUAGE).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "dart",
+            abi_version: tree_sitter::Language::from(tree_sitter_dart::LANGUAGE).abi_version(),
+        },
+        LanguageAbiInfo {
+            name: "dockerfile",
+            abi_version: tree_sitter::Language::from(tree_sitter_containerfile::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "env",
             abi_version: tree_sitter::Language::from(tree_sitter_bash::LANGUAGE).abi_version(),
@@ -197,10 +376,18 @@ pub fn get_language_abi_versions() -> Vec<LanguageAbiInfo> {
             name: "go",
             abi_version: tree_sitter::Language::from(tree_sitter_go::LANGUAGE).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "graphql",
+            abi_version: tree_sitter::Language::from(tree_sitter_graphql::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "haskell",
             abi_version: tree_sitter::Language::from(tree_sitter_haskell::LANGUAGE).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "hcl",
+            abi_version: tree_sitter::Language::from(tree_sitter_hcl::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "html",
             abi_version: tree_sitter::Language::from(tree_sitter_html::LANGUAGE).abi_version(),
@@ -221,6 +408,10 @@ pub fn get_language_abi_versions() -> Vec<LanguageAbiInfo> {
             name: "julia",
             abi_version: tree_sitter::Language::from(tree_sitter_julia::LANGUAGE).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "kotlin",
+            abi_version: tree_sitter::Language::from(tree_sitter_kotlin_ng::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "lua",
             abi_version: tree_sitter::Language::from(tree_sitter_lua::LANGUAGE).abi_version(),
@@ -237,6 +428,10 @@ pub fn get_language_abi_versions() -> Vec<LanguageAbiInfo> {
             name: "php",
             abi_version: tree_sitter::Language::from(tree_sitter_php::LANGUAGE_PHP).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "protobuf",
+            abi_version: tree_sitter::Language::from(tree_sitter_proto::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "python",
             abi_version: tree_sitter::Language::from(tree_sitter_python::LANGUAGE).abi_version(),
@@ -257,6 +452,10 @@ pub fn get_language_abi_versions() -> Vec<LanguageAbiInfo> {
             name: "scala",
             abi_version: tree_sitter::Language::from(tree_sitter_scala::LANGUAGE).abi_version(),
         },
+        LanguageAbiInfo {
+            name: "swift",
+            abi_version: tree_sitter::Language::from(tree_sitter_swift::LANGUAGE).abi_version(),
+        },
         LanguageAbiInfo {
             name: "tsx",
             abi_version: tree_sitter::Language::from(tree_sitter_typescript::LANGUAGE_TSX).abi_version(),
@@ -328,7 +527,7 @@ pub fn parse_file_to_xot_with_options(
     if lang_override.is_none() {
         check_ambiguous_extension(path)?;
     }
-    let source = fs::read_to_string(path)?;
+    let (source, _) = crate::encoding::read_file(path, None)?;
     let lang = lang_override.unwrap_or_else(|| detect_language(path.to_str().unwrap_or("")));
     parse_string_to_xot_with_options(&source, lang, path.to_string_lossy().to_string(), tree_mode, ignore_whitespace)
 }
@@ -358,12 +557,11 @@ pub fn parse_string_to_xot_with_options(
     parser.set_language(&language)
         .map_err(|e| ParseError::TreeSitter(e.to_string()))?;
 
-    let tree = parser.parse(source, None)
-        .ok_or_else(|| ParseError::Parse("Failed to parse source".to_string()))?;
+    let (tree, text) = parse_tree(&mut parser, source, lang)?;
 
     // Build xot document (always start with raw tree)
     let mut builder = XotBuilder::new();
-    let root = builder.build_raw_with_options(tree.root_node(), source, &file_path, ignore_whitespace)
+    let root = builder.build_raw_with_options(tree.root_node(), &text, &file_path, ignore_whitespace)
         .map_err(|e| ParseError::Parse(e.to_string()))?;
 
     let mut xot = builder.into_xot();
@@ -374,6 +572,10 @@ pub fn parse_string_to_xot_with_options(
         crate::xot_transform::walk_transform(&mut xot, root, transform_fn)
             .map_err(|e| ParseError::Parse(e.to_string()))?;
     }
+    if resolved == TreeMode::Common {
+        languages::common::normalize(&mut xot, root, lang)
+            .map_err(|e| ParseError::Parse(e.to_string()))?;
+    }
 
     Ok(XotParseResult {
         xot,
@@ -396,6 +598,13 @@ pub struct XeeParseResult {
     pub file_path: String,
     /// Language used for parsing
     pub language: String,
+    /// ERROR and MISSING nodes TreeSitter recovered from (empty for XML input)
+    pub syntax_errors: Vec<SyntaxError>,
+    /// Element locations of XML input, kept out of the document so queries
+    /// only see the file's own attributes (`None` for other languages)
+    pub node_locations: Option<std::sync::Arc<crate::xpath::NodeLocations>>,
+    /// The run's compiled queries (from [`ParseOptions::queries`])
+    pub queries: Option<std::sync::Arc<crate::xpath::CompiledQueries>>,
 }
 
 impl XeeParseResult {
@@ -404,7 +613,10 @@ impl XeeParseResult {
     /// This is a convenience method that creates an XPathEngine and calls
     /// `query_documents`, avoiding the need to destructure the parse result.
     pub fn query(&mut self, xpath: &str) -> Result<Vec<crate::xpath::Match>, crate::xpath::XPathError> {
-        let engine = crate::xpath::XPathEngine::new();
+        let engine = crate::xpath::XPathEngine::new()
+            .with_language(&self.language)
+            .with_node_locations(self.node_locations.clone())
+            .with_queries(self.queries.clone());
         engine.query_documents(
             &mut self.documents,
             self.doc_handle,
@@ -413,6 +625,72 @@ impl XeeParseResult {
             &self.file_path,
         )
     }
+
+    /// A copy of the parsed tree that can move to another thread, which
+    /// the documents can't. See [`TreeSnapshot::restore`].
+    pub fn snapshot(&self) -> Result<TreeSnapshot, ParseError> {
+        let xot = self.documents.xot();
+        let doc_node = self.documents.document_node(self.doc_handle)
+            .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+        let xml = xot.to_string(doc_node).map_err(|e| ParseError::Parse(e.to_string()))?;
+        // Elements come back in the same order, so their locations are kept by position
+        let node_locations = self.node_locations.as_ref().map(|locations| {
+            xot.descendants(doc_node)
+                .filter(|&node| xot.element(node).is_some())
+                .map(|node| locations.get(&node).copied())
+                .collect()
+        });
+        Ok(TreeSnapshot {
+            xml,
+            node_locations,
+            source_lines: std::sync::Arc::clone(&self.source_lines),
+            file_path: self.file_path.clone(),
+            language: self.language.clone(),
+            syntax_errors: self.syntax_errors.clone(),
+        })
+    }
+}
+
+/// A parsed tree kept as XML, for evaluating more queries on it from
+/// another thread without parsing the source again.
+#[derive(Debug)]
+pub struct TreeSnapshot {
+    xml: String,
+    /// Locations of the elements in document order, for XML input
+    node_locations: Option<Vec<Option<crate::xpath::NodeLocation>>>,
+    source_lines: std::sync::Arc<Vec<String>>,
+    file_path: String,
+    language: String,
+    syntax_errors: Vec<SyntaxError>,
+}
+
+impl TreeSnapshot {
+    /// Load the tree into new documents, with the run's compiled `queries`.
+    pub fn restore(&self, queries: Option<&std::sync::Arc<crate::xpath::CompiledQueries>>) -> Result<XeeParseResult, ParseError> {
+        let mut documents = Documents::new();
+        let doc_handle = documents.add_string(
+            "file:///source".try_into().unwrap(),
+            &self.xml,
+        ).map_err(|e| ParseError::Parse(e.to_string()))?;
+        let doc_node = documents.document_node(doc_handle)
+            .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+        let node_locations = self.node_locations.as_ref().map(|locations| {
+            let xot = documents.xot();
+            let elements = xot.descendants(doc_node).filter(|&node| xot.element(node).is_some());
+            let located = elements.zip(locations).filter_map(|(node, location)| Some((node, (*location)?)));
+            std::sync::Arc::new(located.collect())
+        });
+        Ok(XeeParseResult {
+            documents,
+            doc_handle,
+            source_lines: std::sync::Arc::clone(&self.source_lines),
+            file_path: self.file_path.clone(),
+            language: self.language.clone(),
+            syntax_errors: self.syntax_errors.clone(),
+            node_locations,
+            queries: queries.cloned(),
+        })
+    }
 }
 
 /// Parse a source string directly into Documents for fast XPath queries
@@ -487,16 +765,16 @@ pub fn parse_string_to_xee_with_options(
     parser.set_language(&language)
         .map_err(|e| ParseError::TreeSitter(e.to_string()))?;
 
-    let tree = parser.parse(source, None)
-        .ok_or_else(|| ParseError::Parse("Failed to parse source".to_string()))?;
+    let (tree, text) = parse_tree(&mut parser, source, lang)?;
     let t1 = Instant::now();
 
     // Build directly into Documents using XeeBuilder
     let mut builder = XeeBuilder::new();
-    let doc_handle = builder.build_with_options(tree.root_node(), source, &file_path, lang, resolved, ignore_whitespace, max_depth)
+    let doc_handle = builder.build_with_options(tree.root_node(), &text, &file_path, lang, resolved, ignore_whitespace, max_depth)
         .map_err(|e| ParseError::Parse(e.to_string()))?;
 
     let documents = builder.into_documents();
+    let syntax_errors = syntax_errors::collect_syntax_errors(tree.root_node(), &text);
     let t2 = Instant::now();
 
     let source_lines = std::sync::Arc::new(source.lines().map(|s| s.to_string()).collect());
@@ -514,6 +792,9 @@ pub fn parse_string_to_xee_with_options(
         source_lines,
         file_path,
         language: lang.to_string(),
+        syntax_errors,
+        node_locations: None,
+        queries: None,
     })
 }
 
@@ -536,7 +817,7 @@ pub fn parse_file_to_xee_with_options(
     if lang_override.is_none() {
         check_ambiguous_extension(path)?;
     }
-    let source = fs::read_to_string(path)?;
+    let (source, _) = crate::encoding::read_file(path, None)?;
     let lang = lang_override.unwrap_or_else(|| detect_language(path.to_str().unwrap_or("")));
     parse_string_to_xee_with_options(&source, lang, path.to_string_lossy().to_string(), tree_mode, ignore_whitespace, None)
 }
@@ -548,6 +829,8 @@ pub fn parse_file_to_xee_with_options(
 /// Load XML string directly into Documents for querying
 ///
 /// This is the XML passthrough path - no TreeSitter parsing, just load the XML.
+/// Element source locations are recorded alongside the document so matches
+/// can be reported and edited like any other language.
 pub fn load_xml_string_to_documents(xml: &str, file_path: String) -> Result<XeeParseResult, ParseError> {
     let mut documents = Documents::new();
 
@@ -557,18 +840,25 @@ pub fn load_xml_string_to_documents(xml: &str, file_path: String) -> Result<XeeP
         xml,
     ).map_err(|e| ParseError::Parse(e.to_string()))?;
 
+    let doc_node = documents.document_node(doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    let node_locations = xml::source_locations(documents.xot(), doc_node, xml)?;
+
     Ok(XeeParseResult {
         documents,
         doc_handle,
-        source_lines: std::sync::Arc::new(Vec::new()), // XML passthrough doesn't have source lines
+        source_lines: std::sync::Arc::new(xml.lines().map(|s| s.to_string()).collect()),
         file_path,
         language: "xml".to_string(),
+        syntax_errors: Vec::new(),
+        node_locations: Some(std::sync::Arc::new(node_locations)),
+        queries: None,
     })
 }
 
 /// Load XML file directly into Documents for querying
 pub fn load_xml_file_to_documents(path: &Path) -> Result<XeeParseResult, ParseError> {
-    let xml = fs::read_to_string(path)?;
+    let (xml, _) = crate::encoding::read_file(path, None)?;
     load_xml_string_to_documents(&xml, path.to_string_lossy().to_string())
 }
 
@@ -582,6 +872,9 @@ pub fn load_xml_file_to_documents(path: &Path) -> Result<XeeParseResult, ParseEr
 pub enum ParseInput<'a> {
     /// Read the source from a file on disk.
     Disk { path: &'a Path },
+    /// A file on disk whose text has already been read and decoded. Parsed
+    /// exactly like `Disk`, just without reading the file again.
+    Read { path: &'a Path, content: &'a str },
     /// Parse in-memory content, labelling it with `file_label` (virtual path
     /// or sentinel) for diagnostics.
     Inline { content: &'a str, file_label: &'a str },
@@ -605,6 +898,28 @@ pub struct ParseOptions<'a> {
     pub ignore_whitespace: bool,
     /// Cap tree-building depth (skip deeper nodes for speed).
     pub parse_depth: Option<usize>,
+    /// Parse embedded code (Markdown code blocks, `<script>`, SQL strings,
+    /// workflow `run:` scripts) and graft it as `injected` sub-trees.
+    /// Set from the queries via [`wants_injections`].
+    pub injections: bool,
+    /// Move leading doc comments into a `doc` child of the declaration they
+    /// document. Set from the queries via [`wants_doc_comments`].
+    pub doc_comments: bool,
+    /// For containers (notebooks, components), parse only the cells or
+    /// blocks in this language and leave the others empty. Set for rules
+    /// written for one of the container's
+    /// [`embedded_languages`](crate::languages::embedded_languages).
+    pub embedded_language: Option<&'a str>,
+    /// User-configured transforms (`transforms:` in `tractor.yml`), applied
+    /// to languages that have one.
+    pub transforms: Option<&'a LanguageTransforms>,
+    /// Encoding for disk files with no BOM that are not valid UTF-8
+    /// (`encodings:` in `tractor.yml`). See [`crate::encoding`].
+    pub encoding: Option<&'static crate::encoding::Encoding>,
+    /// The run's compiled queries, which queries on the result reuse and
+    /// which bind the user variables (`--var`, `variables:` in
+    /// `tractor.yml`).
+    pub queries: Option<&'a std::sync::Arc<crate::xpath::CompiledQueries>>,
 }
 
 /// The one principled parse entry point.
@@ -616,6 +931,7 @@ pub struct ParseOptions<'a> {
 /// - `Disk`: runs ambiguous-extension checks when the language was
 ///   auto-detected, reads the file, then routes XML to the passthrough loader
 ///   and everything else to TreeSitter + `XeeBuilder`.
+/// - `Read`: the same as `Disk`, on text the caller already read.
 /// - `Inline`: routes XML to the string passthrough and everything else to
 ///   TreeSitter, carrying `file_label` through to diagnostics.
 ///
@@ -626,8 +942,12 @@ pub fn parse(
     input: ParseInput<'_>,
     options: ParseOptions<'_>,
 ) -> Result<XeeParseResult, ParseError> {
-    match input {
-        ParseInput::Disk { path } => {
+    let mut result = match input {
+        ParseInput::Disk { path } | ParseInput::Read { path, .. } => {
+            let read = match input {
+                ParseInput::Read { content, .. } => Some(content),
+                _ => None,
+            };
             if options.language.is_none() {
                 check_ambiguous_extension(path)?;
             }
@@ -637,18 +957,22 @@ pub fn parse(
 
             if lang == "xml" {
                 // XML passthrough: load directly into Documents
-                load_xml_file_to_documents(path)
+                match read {
+                    Some(xml) => load_xml_string_to_documents(xml, path.to_string_lossy().to_string()),
+                    None => load_xml_file_to_documents(path),
+                }
             } else {
                 // Source code: TreeSitter → XeeBuilder → Documents
-                let source = fs::read_to_string(path)?;
-                parse_string_to_xee_with_options(
-                    &source,
-                    lang,
-                    path.to_string_lossy().to_string(),
-                    options.tree_mode,
-                    options.ignore_whitespace,
-                    options.parse_depth,
-                )
+                let source = match read {
+                    Some(text) => std::borrow::Cow::Borrowed(text),
+                    None => std::borrow::Cow::Owned(crate::encoding::read_file(path, options.encoding)?.0),
+                };
+                let lang = if lang == "unknown" {
+                    detect_language_from_content(&source).unwrap_or(lang)
+                } else {
+                    lang
+                };
+                parse_source(&source, lang, path.to_string_lossy().to_string(), &options)
             }
         }
         ParseInput::Inline { content, file_label } => {
@@ -662,17 +986,69 @@ pub fn parse(
             if lang == "xml" {
                 load_xml_string_to_documents(content, file_label.to_string())
             } else {
-                parse_string_to_xee_with_options(
-                    content,
-                    lang,
-                    file_label.to_string(),
-                    options.tree_mode,
-                    options.ignore_whitespace,
-                    options.parse_depth,
-                )
+                parse_source(content, lang, file_label.to_string(), &options)
             }
         }
+    }?;
+    result.queries = options.queries.cloned();
+    Ok(result)
+}
+
+/// Whether a query tests for `doc` elements, which only exist once doc
+/// comments are attached to their declarations.
+pub fn wants_doc_comments(xpath: &str) -> bool {
+    crate::xpath::tests_element(xpath, "doc")
+}
+
+/// TreeSitter → XeeBuilder → Documents, then the configured transform for
+/// `lang`, doc comment attachment and embedded-code injection as requested
+/// by `options`. Notebooks and components are split into cells and blocks,
+/// each parsed this way; CSV and TSV tables are read directly.
+fn parse_source(
+    source: &str,
+    lang: &str,
+    file_label: String,
+    options: &ParseOptions<'_>,
+) -> Result<XeeParseResult, ParseError> {
+    match lang {
+        "notebook" => return notebook::parse_notebook(source, file_label, options),
+        "vue" | "svelte" => return component::parse_component(source, lang, file_label, options),
+        "csv" | "tsv" => return csv::parse_csv(source, lang, file_label, options),
+        _ => {}
     }
+
+    // Configured transforms only shape the structure tree
+    let custom = options.transforms
+        .and_then(|transforms| config::lookup(transforms, lang))
+        .filter(|_| TreeMode::resolve(options.tree_mode, lang) == Ok(TreeMode::Structure));
+    let tree_mode = match custom {
+        Some(c) if c.mode == config::TransformBase::Replace => Some(TreeMode::Raw),
+        _ => options.tree_mode,
+    };
+
+    let mut result = parse_string_to_xee_with_options(
+        source,
+        lang,
+        file_label,
+        tree_mode,
+        options.ignore_whitespace,
+        options.parse_depth,
+    )?;
+    if let Some(custom) = custom {
+        config::apply(&mut result, custom)?;
+    }
+    if options.doc_comments
+        && matches!(TreeMode::resolve(options.tree_mode, lang), Ok(TreeMode::Structure | TreeMode::Common))
+    {
+        let doc_node = result.documents.document_node(result.doc_handle)
+            .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+        attach_doc_comments(result.documents.xot_mut(), doc_node, lang)
+            .map_err(|e| ParseError::Parse(e.to_string()))?;
+    }
+    if options.injections {
+        injection::inject(&mut result, source, options.tree_mode)?;
+    }
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -682,6 +1058,15 @@ mod tests {
     #[test]
     fn test_detect_language() {
         assert_eq!(detect_language("foo.cs"), "csharp");
+        assert_eq!(detect_language("scripts/setup.csx"), "csharp");
+        assert_eq!(detect_language("build.cake"), "csharp");
+        assert_eq!(detect_language("web/.babelrc"), "json");
+        assert_eq!(detect_language("web/tsconfig.json"), "jsonc");
+        assert_eq!(detect_language("web/tsconfig.build.json"), "jsonc");
+        assert_eq!(detect_language(".vscode/settings.json"), "jsonc");
+        assert_eq!(detect_language(".devcontainer/devcontainer.json"), "jsonc");
+        assert_eq!(detect_language("config/app.json5"), "json5");
+        assert_eq!(detect_language("web/package.json"), "json");
         assert_eq!(detect_language("foo.rs"), "rust");
         assert_eq!(detect_language("foo.py"), "python");
         assert_eq!(detect_language("foo.js"), "javascript");
@@ -689,6 +1074,29 @@ mod tests {
         assert_eq!(detect_language("foo.tsx"), "tsx");
         assert_eq!(detect_language("foo.jsx"), "javascript");
         assert_eq!(detect_language("foo.sql"), "tsql");
+        assert_eq!(detect_language("foo.kt"), "kotlin");
+        assert_eq!(detect_language("build.gradle.kts"), "kotlin");
+        assert_eq!(detect_language("foo.swift"), "swift");
+        assert_eq!(detect_language("foo.dart"), "dart");
+        assert_eq!(detect_language("infra/main.tf"), "hcl");
+        assert_eq!(detect_language("prod.tfvars"), "hcl");
+        assert_eq!(detect_language("terragrunt.hcl"), "hcl");
+        assert_eq!(detect_language("Dockerfile"), "dockerfile");
+        assert_eq!(detect_language("docker/Dockerfile.prod"), "dockerfile");
+        assert_eq!(detect_language("app.dockerfile"), "dockerfile");
+        assert_eq!(detect_language("api/v1/user.proto"), "protobuf");
+        assert_eq!(detect_language("schema.graphql"), "graphql");
+        assert_eq!(detect_language("notebooks/analysis.ipynb"), "notebook");
+        assert_eq!(detect_language("src/components/Greeting.vue"), "vue");
+        assert_eq!(detect_language("src/routes/+page.svelte"), "svelte");
+        assert_eq!(detect_language("config/feature-flags.csv"), "csv");
+        assert_eq!(detect_language("i18n/translations.tsv"), "tsv");
+        assert_eq!(detect_language("queries/user.gql"), "graphql");
+        assert_eq!(detect_language("pom.xml"), "xml");
+        assert_eq!(detect_language("src/App/App.csproj"), "xml");
+        assert_eq!(detect_language("Directory.Build.props"), "xml");
+        assert_eq!(detect_language("Resources.resx"), "xml");
+        assert_eq!(detect_language("MainWindow.xaml"), "xml");
         assert_eq!(detect_language("foo.unknown"), "unknown");
     }
 
@@ -706,6 +1114,12 @@ mod tests {
                 tree_mode: None,
                 ignore_whitespace: false,
                 parse_depth: None,
+                injections: false,
+                doc_comments: false,
+                embedded_language: None,
+                transforms: None,
+                encoding: None,
+                queries: None,
             },
         ).unwrap();
 
@@ -718,4 +1132,385 @@ mod tests {
         assert!(xml.contains("<class"), "Should contain class element");
         assert!(xml.contains("Foo"), "Should contain Foo");
     }
+
+    #[test]
+    fn test_mobile_languages_share_vocabulary() {
+        let cases = [
+            ("kotlin", "class Greeter { fun greet() { println(\"hi\") } }"),
+            ("swift", "class Greeter { func greet() { print(\"hi\") } }"),
+            ("dart", "class Greeter { void greet() { print('hi'); } }"),
+        ];
+        for (lang, source) in cases {
+            let mut result = parse(
+                ParseInput::Inline { content: source, file_label: "<test>" },
+                ParseOptions { language: Some(lang), ..Default::default() },
+            ).unwrap();
+            for xpath in ["//class", "//class//method"] {
+                let matches = result.query(xpath).unwrap();
+                assert_eq!(matches.len(), 1, "{}: expected one match for {}", lang, xpath);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_terraform_and_dockerfile() {
+        let mut tf = parse(
+            ParseInput::Inline {
+                content: "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"my-logs\"\n}\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("hcl"), tree_mode: Some(TreeMode::Structure), ..Default::default() },
+        ).unwrap();
+        let matches = tf.query("//resource[@type='aws_s3_bucket'][@name='logs']/attribute[name='bucket']").unwrap();
+        assert_eq!(matches.len(), 1);
+
+        let mut docker = parse(
+            ParseInput::Inline {
+                content: "FROM alpine:3.19\nUSER app\nCOPY --chown=app src/ /app/\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("dockerfile"), ..Default::default() },
+        ).unwrap();
+        assert_eq!(docker.query("//from/image[name='alpine'][tag='3.19']").unwrap().len(), 1);
+        assert_eq!(docker.query("//from/image[not(digest)]").unwrap().len(), 1);
+        assert_eq!(docker.query("//user[name='app']").unwrap().len(), 1);
+        assert_eq!(docker.query("//copy/param[name='chown'][value='app']").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_hcl_defaults_to_the_structure_tree() {
+        let parse_tf = |tree_mode| parse(
+            ParseInput::Inline {
+                content: "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"my-logs\"\n}\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("hcl"), tree_mode, ..Default::default() },
+        ).unwrap();
+
+        // Queries and checks see blocks with attributes by default
+        let mut tf = parse_tf(None);
+        assert_eq!(tf.query("//resource[@type='aws_s3_bucket']/attribute[name='bucket']").unwrap().len(), 1);
+        assert!(tf.query("//resource/bucket").unwrap().is_empty());
+
+        // The data tree, which `set` edits, names elements after the keys
+        let mut tf = parse_tf(Some(TreeMode::Data));
+        assert_eq!(tf.query("//resource[@type='aws_s3_bucket']/bucket").unwrap().len(), 1);
+        assert!(tf.query("//resource/attribute").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hcl_data_keeps_templates_and_extra_labels() {
+        let mut tf = parse(
+            ParseInput::Inline {
+                content: "job \"a\" \"b\" \"c\" {\n  name = \"${var.env}-app\"\n  raw = \"$${literal}\"\n}\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("hcl"), tree_mode: Some(TreeMode::Data), ..Default::default() },
+        ).unwrap();
+        assert_eq!(tf.query("//job[@type='a'][@name='b'][@label3='c']").unwrap().len(), 1);
+        assert_eq!(tf.query("//name[@kind='template'][.='${var.env}-app']").unwrap().len(), 1);
+        assert_eq!(tf.query("//raw[@kind='string'][.='${literal}']").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_protobuf_and_graphql() {
+        let mut proto = parse(
+            ParseInput::Inline {
+                content: "syntax = \"proto3\";\n\
+                          message User {\n  string id = 1;\n  repeated string emails = 2;\n}\n\
+                          enum Status {\n  UNKNOWN = 0;\n  ACTIVE = 1;\n}\n\
+                          service Users {\n  rpc GetUser(GetUserRequest) returns (User);\n}\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("protobuf"), ..Default::default() },
+        ).unwrap();
+        assert_eq!(proto.query("//message[name='User']/field[@number]").unwrap().len(), 2);
+        assert_eq!(proto.query("//field[@number='2'][repeated][name='emails']").unwrap().len(), 1);
+        assert_eq!(proto.query("//enum[name='Status']/value[name='ACTIVE'][@number='1']").unwrap().len(), 1);
+        assert_eq!(proto.query("//service/rpc[name='GetUser'][request='GetUserRequest'][response='User']").unwrap().len(), 1);
+        assert_eq!(proto.query("//enum/value/int[not(*)]").unwrap().len(), 2);
+
+        let mut graphql = parse(
+            ParseInput::Inline {
+                content: "type Query {\n  user(id: ID!): User @deprecated(reason: \"use node\")\n}\n",
+                file_label: "<test>",
+            },
+            ParseOptions { language: Some("graphql"), ..Default::default() },
+        ).unwrap();
+        assert_eq!(graphql.query("//type[name='Query']/field[name='user']/argument[name='id'][type='ID!']").unwrap().len(), 1);
+        assert_eq!(graphql.query("//field[type='User']/directive[name='deprecated']").unwrap().len(), 1);
+        assert_eq!(graphql.query("/file/type").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_injections() {
+        let markdown = "# Queries\n\n```sql\nSELECT id FROM users\n```\n";
+        let options = |lang, injections| ParseOptions { language: Some(lang), injections, ..Default::default() };
+
+        let mut plain = parse(ParseInput::Inline { content: markdown, file_label: "<test>" }, options("markdown", false)).unwrap();
+        assert!(plain.query("//injected").unwrap().is_empty());
+
+        let mut md = parse(ParseInput::Inline { content: markdown, file_label: "<test>" }, options("markdown", true)).unwrap();
+        let matches = md.query("//code_block/injected[@lang='tsql']").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line, 4);
+        assert_eq!(md.query("//code_block/injected/*[@line='4']").unwrap().len(), 1);
+        // The injected tree's text is the host's, so it isn't repeated in the host's value
+        let blocks = md.query("//code_block").unwrap();
+        assert_eq!(blocks[0].value.matches("SELECT").count(), 1);
+
+        let mut cs = parse(
+            ParseInput::Inline {
+                content: "class M { void Up() { migrationBuilder.Sql(\"DELETE FROM users\"); Log(\"not sql\"); } }",
+                file_label: "<test>",
+            },
+            options("csharp", true),
+        ).unwrap();
+        assert_eq!(cs.query("//string/injected[@lang='tsql']").unwrap().len(), 1);
+        assert_eq!(cs.query("//injected").unwrap().len(), 1);
+
+        let steps = "steps:\n  - name: Build\n    run: cargo build --release\n";
+        let mut workflow = parse(
+            ParseInput::Inline { content: steps, file_label: ".github/workflows/ci.yml" },
+            options("yaml", true),
+        ).unwrap();
+        assert_eq!(workflow.query("//run/injected[@lang='bash']").unwrap().len(), 1);
+
+        // `run:` is only a script in GitHub Actions files
+        let mut config = parse(
+            ParseInput::Inline { content: steps, file_label: "config/steps.yml" },
+            options("yaml", true),
+        ).unwrap();
+        assert!(config.query("//injected").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_configured_transforms() {
+        let lua = LanguageConfig {
+            element_mappings: [("function_declaration", "function"), ("identifier", "name")]
+                .into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            flatten_kinds: vec!["block".to_string()],
+            ..Default::default()
+        };
+        let python = LanguageConfig {
+            mode: TransformBase::Replace,
+            element_mappings: [("function_definition".to_string(), "def".to_string())].into(),
+            ..Default::default()
+        };
+        let transforms: LanguageTransforms = [("lua".to_string(), lua), ("python".to_string(), python)].into();
+        let options = |lang| ParseOptions { language: Some(lang), transforms: Some(&transforms), ..Default::default() };
+
+        let mut result = parse(
+            ParseInput::Inline { content: "function greet(who)\n  return who\nend\n", file_label: "<test>" },
+            options("lua"),
+        ).unwrap();
+        assert_eq!(result.query("//function/name[.='greet']").unwrap().len(), 1);
+        assert!(result.query("//block").unwrap().is_empty());
+
+        let mut result = parse(
+            ParseInput::Inline { content: "def greet():\n    pass\n", file_label: "<test>" },
+            options("py"),
+        ).unwrap();
+        assert_eq!(result.query("//def").unwrap().len(), 1);
+        assert!(result.query("//function").unwrap().is_empty());
+
+        // Raw trees are left alone
+        let mut raw = parse(
+            ParseInput::Inline { content: "function greet() end\n", file_label: "<test>" },
+            ParseOptions { tree_mode: Some(TreeMode::Raw), ..options("lua") },
+        ).unwrap();
+        assert!(raw.query("//function").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_common_tree() {
+        let sources = [
+            ("csharp", "using System;\nclass Box { Box(int a) {} void Put(int a, string b, bool c) { Log(\"x\"); } }\n"),
+            ("typescript", "import { x } from 'y';\nclass Box { put(a: number, b: string, c?: boolean) { log('x'); } }\n"),
+            ("javascript", "function put(a, { b }, ...c) { log('x'); }\n"),
+            ("python", "import os\nclass Box:\n    def put(self, a, *rest):\n        log('x')\n"),
+            ("go", "package box\nimport \"fmt\"\nfunc (b Box) Put(a int, s string, ok bool) { fmt.Println(\"x\") }\n"),
+            ("java", "import java.util.List;\nclass Box { void put(int a, String b, boolean c) { log(\"x\"); } }\n"),
+            ("rust", "use std::fmt;\nfn put(a: i32, b: &str, c: bool) { log(\"x\"); }\n"),
+            ("ruby", "class Box\n  def put(a, b = 1, *c)\n    log('x')\n  end\nend\n"),
+        ];
+        for (lang, content) in sources {
+            let options = ParseOptions { language: Some(lang), tree_mode: Some(TreeMode::Common), ..Default::default() };
+            let mut result = parse(ParseInput::Inline { content, file_label: "<test>" }, options).unwrap();
+            assert_eq!(result.query("//function[count(parameter) = 3]").unwrap().len(), 1, "{lang}");
+            assert_eq!(result.query("//function//call").unwrap().len(), 1, "{lang}");
+            assert_eq!(result.query("//call//string").unwrap().len(), 1, "{lang}");
+            if lang != "javascript" && lang != "rust" && lang != "go" {
+                assert_eq!(result.query("//class").unwrap().len(), 1, "{lang}");
+            }
+            if lang != "javascript" && lang != "ruby" {
+                assert_eq!(result.query("//import").unwrap().len(), 1, "{lang}");
+            }
+        }
+
+        // Go's receiver is not one of the method's parameters
+        let options = ParseOptions { language: Some("go"), tree_mode: Some(TreeMode::Common), ..Default::default() };
+        let mut result = parse(
+            ParseInput::Inline { content: "package box\nfunc (b Box) Get() {}\n", file_label: "<test>" },
+            options,
+        ).unwrap();
+        assert_eq!(result.query("//function[not(parameter)]").unwrap().len(), 1);
+    }
+
+    fn common_tree(lang: &str, content: &str) -> XeeParseResult {
+        let options = ParseOptions { language: Some(lang), tree_mode: Some(TreeMode::Common), ..Default::default() };
+        parse(ParseInput::Inline { content, file_label: "<test>" }, options).unwrap()
+    }
+
+    #[test]
+    fn test_common_tree_kotlin() {
+        let mut result = common_tree("kotlin", "import kotlin.math.max\n// shapes\ninterface Shape\nclass Box(val w: Int) : Shape {\n    constructor() : this(1)\n    fun put(a: Int, b: String, c: Boolean) { log(\"x\") }\n}\n");
+        assert_eq!(result.query("//class").unwrap().len(), 1);
+        assert_eq!(result.query("//interface").unwrap().len(), 1);
+        assert_eq!(result.query("//function").unwrap().len(), 3);
+        assert_eq!(result.query("//function[count(parameter) = 3]").unwrap().len(), 1);
+        // The primary constructor's parameters are its own
+        assert_eq!(result.query("//class/function[parameter/name='w']").unwrap().len(), 1);
+        assert_eq!(result.query("//call//string").unwrap().len(), 1);
+        assert_eq!(result.query("//import").unwrap().len(), 1);
+        assert_eq!(result.query("//comment").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_common_tree_swift() {
+        let mut result = common_tree("swift", "import Foundation\n// shapes\nstruct Point {}\nclass Box {\n    init() {}\n    func put(a: Int, b: String, c: Bool) { log(\"x\") }\n}\n");
+        assert_eq!(result.query("//class").unwrap().len(), 1);
+        assert_eq!(result.query("//struct").unwrap().len(), 1);
+        assert_eq!(result.query("//function").unwrap().len(), 2);
+        assert_eq!(result.query("//function[count(parameter) = 3]").unwrap().len(), 1);
+        assert_eq!(result.query("//call//string").unwrap().len(), 1);
+        assert_eq!(result.query("//import").unwrap().len(), 1);
+        assert_eq!(result.query("//comment").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_common_tree_dart() {
+        let mut result = common_tree("dart", "import 'dart:math';\n// shapes\nmixin Shape {}\nclass Box {\n  final int w;\n  Box(this.w);\n  void put(int a, String b, [bool c = false]) { log('x'); }\n}\n");
+        assert_eq!(result.query("//class").unwrap().len(), 1);
+        assert_eq!(result.query("//mixin").unwrap().len(), 1);
+        // The constructor and the method, not the field
+        assert_eq!(result.query("//function").unwrap().len(), 2);
+        assert_eq!(result.query("//function[count(parameter) = 3]").unwrap().len(), 1);
+        assert_eq!(result.query("//call//string").unwrap().len(), 1);
+        assert_eq!(result.query("//import").unwrap().len(), 1);
+        assert_eq!(result.query("//comment").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_jsonc() {
+        let source = "{\n  // Compiler settings\n  \"compilerOptions\": {\n    \"strict\": true,\n  },\n}\n";
+        let options = ParseOptions { language: Some("jsonc"), ..Default::default() };
+        let mut result = parse(ParseInput::Inline { content: source, file_label: "tsconfig.json" }, options).unwrap();
+        assert!(result.syntax_errors.is_empty());
+        assert_eq!(result.source_lines[3], "    \"strict\": true,");
+        let strict = result.query("//compilerOptions/strict").unwrap();
+        assert_eq!(strict.len(), 1);
+        assert_eq!(strict[0].line, 4);
+        // The data view has no comments; the structure view keeps them
+        assert!(result.query("//comment").unwrap().is_empty());
+        assert_eq!(result.query("count(/*/*)").unwrap()[0].value, "1");
+        let options = ParseOptions { language: Some("jsonc"), tree_mode: Some(TreeMode::Structure), ..Default::default() };
+        let mut structure = parse(ParseInput::Inline { content: source, file_label: "tsconfig.json" }, options).unwrap();
+        assert_eq!(structure.query("//comment").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json5() {
+        let source = "// Server settings\n{\n  name: 'tractor',\n  port: 0x1F90, // 8080\n  ratio: .5,\n  'quoted': \"say \\\"hi\\\"\",\n  note: 'it\\'s \"fine\"',\n  list: [1, 2,],\n}\n";
+        let options = ParseOptions { language: Some("json5"), ..Default::default() };
+        let mut result = parse(ParseInput::Inline { content: source, file_label: "app.json5" }, options).unwrap();
+        assert!(result.syntax_errors.is_empty());
+        assert_eq!(result.query("//name").unwrap()[0].value, "tractor");
+        assert_eq!(result.query("//port").unwrap()[0].value, "0x1F90");
+        assert_eq!(result.query("//ratio").unwrap()[0].value, ".5");
+        assert_eq!(result.query("//quoted").unwrap()[0].value, "say \"hi\"");
+        assert_eq!(result.query("//list").unwrap().len(), 2);
+        // Locations are in the JSON5 text, past the quotes the grammar saw
+        // added around keys and before `"` in single-quoted strings
+        let note = result.query("//note").unwrap();
+        assert_eq!(note[0].value, "it's \"fine\"");
+        assert_eq!((note[0].line, note[0].column, note[0].end_column), (7, 9, 23));
+        let options = ParseOptions { language: Some("json5"), tree_mode: Some(TreeMode::Structure), ..Default::default() };
+        let mut structure = parse(ParseInput::Inline { content: source, file_label: "app.json5" }, options).unwrap();
+        assert_eq!(structure.query("//comment").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let options = |lang| ParseOptions { language: Some(lang), doc_comments: true, ..Default::default() };
+        let parse_doc = |content, lang| parse(ParseInput::Inline { content, file_label: "<test>" }, options(lang)).unwrap();
+
+        let mut cs = parse_doc(
+            "class C {\n  /// <summary>Adds <see cref=\"int\"/> values.</summary>\n  /// <param name=\"a\">First</param>\n  /// <returns>The sum</returns>\n  [Pure]\n  public int Add(int a) { return a; }\n\n  /// Detached\n\n  public void Run() {}\n}\n",
+            "csharp",
+        );
+        assert_eq!(cs.query("//method[name='Add']/doc/summary[.='Adds int values.']").unwrap().len(), 1);
+        assert_eq!(cs.query("//method[name='Add']/doc/param[@name='a'][.='First']").unwrap().len(), 1);
+        assert_eq!(cs.query("//method[name='Add']/doc/returns[.='The sum']").unwrap().len(), 1);
+        assert!(cs.query("//method[name='Run']/doc").unwrap().is_empty());
+
+        let mut java = parse_doc(
+            "class C {\n  /**\n   * Greets someone.\n   * @param who the name\n   * @return a greeting\n   */\n  @Override\n  String greet(String who) { return who; }\n}\n",
+            "java",
+        );
+        assert_eq!(java.query("//method/doc/summary[.='Greets someone.']").unwrap().len(), 1);
+        assert_eq!(java.query("//method/doc/param[@name='who'][.='the name']").unwrap().len(), 1);
+        assert_eq!(java.query("//method/doc/returns[.='a greeting']").unwrap().len(), 1);
+
+        let mut rust = parse_doc("//! Crate docs\n\n/// Entry point.\n#[inline]\nfn main() {}\n", "rust");
+        assert_eq!(rust.query("//function/doc/summary[.='Entry point.']").unwrap().len(), 1);
+        assert_eq!(rust.query("//file/doc/summary[.='Crate docs']").unwrap().len(), 1);
+
+        let mut go = parse_doc("package p\n\n// Add adds\n// two numbers.\nfunc Add() {}\n\n// Loose comment\n\nfunc Sub() {}\n", "go");
+        assert_eq!(go.query("//function/doc/summary[.='Add adds two numbers.']").unwrap().len(), 1);
+        assert_eq!(go.query("//doc").unwrap().len(), 1);
+
+        let mut py = parse_doc("def add(a, b):\n    \"\"\"Add two numbers.\n\n    :param a: left\n    :returns: the sum\n    \"\"\"\n    return a + b\n", "python");
+        assert_eq!(py.query("//function/doc/summary[.='Add two numbers.']").unwrap().len(), 1);
+        assert_eq!(py.query("//function/doc/param[@name='a'][.='left']").unwrap().len(), 1);
+        assert_eq!(py.query("//function/doc/returns[.='the sum']").unwrap().len(), 1);
+        assert!(py.query("//function/body/string").unwrap().is_empty());
+
+        assert!(wants_doc_comments("//method[not(doc/summary)]"));
+        assert!(!wants_doc_comments("//document"));
+        assert!(!wants_doc_comments("//comment[contains(., 'doc')]"));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let options = ParseOptions { language: Some("java"), ..Default::default() };
+        let clean = parse(ParseInput::Inline { content: "class A { void m() { int x = 1; } }", file_label: "<test>" }, options).unwrap();
+        assert!(clean.syntax_errors.is_empty());
+
+        let broken = parse(ParseInput::Inline { content: "class A {\n  void m() { int x = 1 }\n}", file_label: "<test>" }, options).unwrap();
+        assert_eq!(broken.syntax_errors.len(), 1);
+        let error = &broken.syntax_errors[0];
+        assert_eq!(error.line, 2);
+        assert_eq!(error.kind, SyntaxErrorKind::Missing(";".to_string()));
+        assert_eq!(error.message(), "syntax error: missing `;`");
+    }
+
+    #[test]
+    fn test_snapshot_restores_tree_on_another_thread() {
+        let options = ParseOptions { language: Some("python"), ..Default::default() };
+        let parsed = parse(ParseInput::Inline { content: "x = 1
+f(x)
+", file_label: "<test>" }, options).unwrap();
+        let snapshot = parsed.snapshot().unwrap();
+        let calls = std::thread::spawn(move || {
+            let calls = snapshot.restore(None).unwrap().query("//call").unwrap();
+            calls.iter().map(|call| (call.line, call.column)).collect::<Vec<_>>()
+        });
+        assert_eq!(calls.join().unwrap(), [(2, 1)]);
+
+        let xml = load_xml_string_to_documents("<a>\n  <b/>\n</a>\n", "<test>".to_string()).unwrap();
+        let mut restored = xml.snapshot().unwrap().restore(None).unwrap();
+        let b = restored.query("//b").unwrap();
+        assert_eq!((b[0].line, b[0].column), (2, 3));
+    }
 }
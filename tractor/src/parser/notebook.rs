@@ -0,0 +1,372 @@
+//! Jupyter notebooks: one parsed tree per cell
+//!
+//! A `.ipynb` file is JSON; the code lives in the `source` of each cell. Each
+//! cell is parsed on its own — code cells in the kernel's language (Python
+//! unless the notebook says otherwise), markdown cells as Markdown — and the
+//! trees are collected under a `notebook` root:
+//!
+//! ```xml
+//! <notebook>
+//!   <cell index="1" type="markdown" lang="markdown"><document>...</document></cell>
+//!   <cell index="2" type="code" lang="python"><module>...</module></cell>
+//! </notebook>
+//! ```
+//! Queryable as: `//cell[@type='code']//call[name='print']`
+//!
+//! Locations point into the notebook file: each line of a cell maps to the
+//! JSON string it came from, so matches open at the right line in an editor
+//! and `ancestor::cell/@index` names the cell.
+//!
+//! A rule written for Python or Markdown sees only the cells in its
+//! language (`ParseOptions::embedded_language`); the other cells stay empty.
+
+use xee_xpath::Documents;
+use xot::{Node as XotNode, Xot};
+
+use super::injection::{append_element, byte_range, child_element, copy_relocated, line_column, line_starts};
+use super::{parse_source, parse_string_to_xot_with_options, ParseError, ParseOptions, SyntaxError, SyntaxErrorKind, XeeParseResult};
+use crate::language_info::get_language_info;
+use crate::tree_mode::TreeMode;
+use crate::xot_transform::helpers::*;
+
+/// A JSON string contributing to a cell's source
+struct Piece {
+    /// Byte offset of the decoded text within the cell source
+    offset: usize,
+    /// 1-based line and column of the first character inside the quotes
+    line: usize,
+    column: usize,
+    /// `(decoded, escaped)` byte offsets within the string just after each
+    /// escape sequence, which decodes shorter (or longer) than it is written
+    escapes: Vec<(usize, usize)>,
+}
+
+impl Piece {
+    /// Offset in the file's escaped string of a decoded offset
+    fn escaped_offset(&self, decoded: usize) -> usize {
+        let i = self.escapes.partition_point(|&(d, _)| d <= decoded);
+        match i.checked_sub(1).map(|i| self.escapes[i]) {
+            Some((d, e)) => e + (decoded - d),
+            None => decoded,
+        }
+    }
+}
+
+/// A cell's decoded source and where each part of it sits in the file
+struct CellSource {
+    text: String,
+    pieces: Vec<Piece>,
+}
+
+impl CellSource {
+    /// Map a 1-based line and column in the cell source to the notebook file
+    fn relocate(&self, line_starts: &[usize], line: usize, column: usize) -> (usize, usize) {
+        let offset = line_starts.get(line.saturating_sub(1)).copied().unwrap_or(self.text.len())
+            + column.saturating_sub(1);
+        let piece_index = self.pieces.partition_point(|p| p.offset <= offset).saturating_sub(1);
+        match self.pieces.get(piece_index) {
+            Some(piece) => (piece.line, piece.column + piece.escaped_offset(offset - piece.offset)),
+            None => (line, column),
+        }
+    }
+}
+
+/// Parse a notebook into a `notebook` tree with one `cell` per notebook cell.
+pub(crate) fn parse_notebook(source: &str, file_label: String, options: &ParseOptions<'_>) -> Result<XeeParseResult, ParseError> {
+    let tree_mode = TreeMode::resolve(options.tree_mode, "notebook").map_err(ParseError::Parse)?;
+
+    // The raw JSON tree gives each string its position in the file
+    let json = parse_string_to_xot_with_options(source, "json", file_label.clone(), Some(TreeMode::Raw), false)?;
+    let xot = &json.xot;
+    let file_lines = line_starts(source);
+    let notebook = xot.document_element(json.root).ok()
+        .and_then(|document| child_element(xot, document, |c| get_kind(xot, c).as_deref() == Some("object")))
+        .ok_or_else(|| ParseError::Parse("not a Jupyter notebook: expected a JSON object".to_string()))?;
+
+    let kernel_language = object_value(xot, notebook, source, &file_lines, "metadata")
+        .and_then(|metadata| {
+            let kernelspec = object_value(xot, metadata, source, &file_lines, "kernelspec")
+                .and_then(|k| object_value(xot, k, source, &file_lines, "language"));
+            let language_info = object_value(xot, metadata, source, &file_lines, "language_info")
+                .and_then(|l| object_value(xot, l, source, &file_lines, "name"));
+            kernelspec.or(language_info)
+        })
+        .and_then(|value| json_string(xot, value, source, &file_lines))
+        .and_then(|name| get_language_info(&name.to_lowercase()).map(|info| info.name))
+        .unwrap_or("python");
+
+    let mut documents = Documents::new();
+    let doc_handle = documents.add_string("file:///source".try_into().unwrap(), "<notebook/>")
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+    let doc_node = documents.document_node(doc_handle)
+        .ok_or_else(|| ParseError::Parse("Failed to get document node".to_string()))?;
+    let host = documents.xot_mut();
+    let root = host.document_element(doc_node).map_err(|e| ParseError::Parse(e.to_string()))?;
+    let (end_line, end_column) = line_column(&file_lines, source.len());
+    set_location(host, root, (1, 1), (end_line, end_column));
+
+    let cells = object_value(xot, notebook, source, &file_lines, "cells")
+        .map(|array| get_element_children(xot, array))
+        .unwrap_or_default();
+    let mut syntax_errors = Vec::new();
+    for (i, cell) in cells.into_iter().enumerate() {
+        let cell_type = object_value(xot, cell, source, &file_lines, "cell_type")
+            .and_then(|v| json_string(xot, v, source, &file_lines))
+            .unwrap_or_default();
+        let source_node = object_value(xot, cell, source, &file_lines, "source");
+
+        let cell_element = append_element(host, root, "cell");
+        set_attr(host, cell_element, "index", &(i + 1).to_string());
+        set_attr(host, cell_element, "type", &cell_type);
+        if let Some((start, end)) = source_node.and_then(|n| byte_range(xot, n, source, &file_lines)) {
+            set_location(host, cell_element, line_column(&file_lines, start), line_column(&file_lines, end));
+        }
+
+        let lang = match cell_type.as_str() {
+            "code" => kernel_language,
+            "markdown" => "markdown",
+            _ => continue,
+        };
+        set_attr(host, cell_element, "lang", lang);
+        if options.embedded_language.is_some_and(|only| only != lang) {
+            continue;
+        }
+        let Some(cell_source) = source_node.and_then(|n| cell_source(xot, n, source, &file_lines)) else {
+            continue;
+        };
+        if cell_source.text.trim().is_empty() {
+            continue;
+        }
+
+        let cell_options = ParseOptions {
+            language: Some(lang),
+            tree_mode: Some(tree_mode),
+            embedded_language: None,
+            ..*options
+        };
+        // A cell that fails to parse stays empty and is reported
+        let parsed = match parse_source(&cell_source.text, lang, file_label.clone(), &cell_options) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                if let Some((start, end)) = source_node.and_then(|n| byte_range(xot, n, source, &file_lines)) {
+                    let (line, column) = line_column(&file_lines, start);
+                    let (end_line, end_column) = line_column(&file_lines, end);
+                    syntax_errors.push(SyntaxError {
+                        line: line as u32,
+                        column: column as u32,
+                        end_line: end_line as u32,
+                        end_column: end_column as u32,
+                        kind: SyntaxErrorKind::Unparsed(format!("{} cell {}: {}", lang, i + 1, e)),
+                    });
+                }
+                continue;
+            }
+        };
+        let cell_lines = line_starts(&cell_source.text);
+        let relocate = |line, column| cell_source.relocate(&cell_lines, line, column);
+
+        let parsed_xot = parsed.documents.xot();
+        let Some(cell_root) = parsed.documents.document_node(parsed.doc_handle)
+            .and_then(|n| parsed_xot.document_element(n).ok())
+        else {
+            continue;
+        };
+        let copy = copy_relocated(parsed_xot, cell_root, host, &relocate)
+            .map_err(|e| ParseError::Parse(e.to_string()))?;
+        host.append(cell_element, copy).map_err(|e| ParseError::Parse(e.to_string()))?;
+
+        syntax_errors.extend(parsed.syntax_errors.into_iter().map(|error| {
+            let (line, column) = relocate(error.line as usize, error.column as usize);
+            let (end_line, end_column) = relocate(error.end_line as usize, error.end_column as usize);
+            SyntaxError {
+                line: line as u32,
+                column: column as u32,
+                end_line: end_line as u32,
+                end_column: end_column as u32,
+                ..error
+            }
+        }));
+    }
+
+    Ok(XeeParseResult {
+        documents,
+        doc_handle,
+        source_lines: std::sync::Arc::new(source.lines().map(|s| s.to_string()).collect()),
+        file_path: file_label,
+        language: "notebook".to_string(),
+        syntax_errors,
+        node_locations: None,
+        queries: None,
+    })
+}
+
+/// The value of `key` in a raw JSON `object` node
+fn object_value(xot: &Xot, object: XotNode, source: &str, line_starts: &[usize], key: &str) -> Option<XotNode> {
+    get_element_children(xot, object)
+        .into_iter()
+        .filter(|&pair| get_kind(xot, pair).as_deref() == Some("pair"))
+        .find_map(|pair| {
+            let mut children = get_element_children(xot, pair).into_iter();
+            let name = children.next()?;
+            if json_string(xot, name, source, line_starts)? != key {
+                return None;
+            }
+            // The value sits in a `value` field wrapper
+            let value = children.next()?;
+            match get_kind(xot, value) {
+                Some(_) => Some(value),
+                None => get_element_children(xot, value).into_iter().next(),
+            }
+        })
+}
+
+/// Decode a raw JSON `string` node
+fn json_string(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<String> {
+    if get_kind(xot, node).as_deref() != Some("string") {
+        return None;
+    }
+    let (start, end) = byte_range(xot, node, source, line_starts)?;
+    serde_json::from_str(&source[start..end]).ok()
+}
+
+/// A cell's `source`: a list of strings (one per line, as Jupyter writes it)
+/// or a single string.
+fn cell_source(xot: &Xot, node: XotNode, source: &str, line_starts: &[usize]) -> Option<CellSource> {
+    let strings = match get_kind(xot, node).as_deref() {
+        Some("string") => vec![node],
+        Some("array") => get_element_children(xot, node),
+        _ => return None,
+    };
+    let mut cell = CellSource { text: String::new(), pieces: Vec::new() };
+    for string in strings {
+        let Some(text) = json_string(xot, string, source, line_starts) else {
+            continue;
+        };
+        let line = get_line(xot, string, "line")?;
+        let column = get_line(xot, string, "column")?;
+        let (start, end) = byte_range(xot, string, source, line_starts)?;
+        let escapes = escape_offsets(&source[start + 1..end - 1]);
+        cell.pieces.push(Piece { offset: cell.text.len(), line, column: column + 1, escapes });
+        cell.text.push_str(&text);
+    }
+    Some(cell)
+}
+
+/// `(decoded, escaped)` byte offsets just after each escape sequence in the
+/// body of a JSON string (without its quotes)
+fn escape_offsets(escaped: &str) -> Vec<(usize, usize)> {
+    let bytes = escaped.as_bytes();
+    let mut offsets = Vec::new();
+    let (mut decoded, mut pos) = (0, 0);
+    while pos < bytes.len() {
+        if bytes[pos] != b'\\' {
+            decoded += 1;
+            pos += 1;
+            continue;
+        }
+        let (len, written) = match bytes.get(pos + 1) {
+            Some(b'u') => {
+                let unit = |at: usize| escaped.get(at..at + 4).and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                let high = unit(pos + 2).unwrap_or(0xFFFD);
+                let low = (escaped.get(pos + 6..pos + 8) == Some("\\u")).then(|| unit(pos + 8)).flatten();
+                match low {
+                    // A surrogate pair: one four-byte character
+                    Some(low) if (0xD800..0xDC00).contains(&high) && (0xDC00..0xE000).contains(&low) => (4, 12),
+                    _ => (char::from_u32(high).map_or(3, char::len_utf8), 6),
+                }
+            }
+            _ => (1, 2),
+        };
+        decoded += len;
+        pos += written;
+        offsets.push((decoded, pos));
+    }
+    offsets
+}
+
+fn set_location(xot: &mut Xot, node: XotNode, start: (usize, usize), end: (usize, usize)) {
+    set_attr(xot, node, "line", &start.0.to_string());
+    set_attr(xot, node, "column", &start.1.to_string());
+    set_attr(xot, node, "end_line", &end.0.to_string());
+    set_attr(xot, node, "end_column", &end.1.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Analysis\n"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "password = \"hunter2\"\n",
+    "print(password)"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": { "language": "python", "name": "python3" }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_parse_notebook() {
+        let options = ParseOptions::default();
+        let mut result = parse_notebook(NOTEBOOK, "analysis.ipynb".to_string(), &options).unwrap();
+        assert_eq!(result.language, "notebook");
+        assert_eq!(result.query("/notebook/cell").unwrap().len(), 2);
+        assert_eq!(result.query("//cell[@index='1'][@type='markdown']//heading").unwrap().len(), 1);
+
+        let calls = result.query("//cell[@type='code']//call").unwrap();
+        assert_eq!(calls.len(), 1);
+        // `print(password)` is the second string of the cell's source
+        assert_eq!(calls[0].line, 17);
+        assert_eq!(calls[0].column, 6);
+    }
+
+    #[test]
+    fn test_columns_count_escaped_text() {
+        let notebook = r#"{"cells": [{"cell_type": "code", "source": ["s = \"\\t\\u00e9\"; print(s)"]}]}"#;
+        let mut result = parse_notebook(notebook, "escapes.ipynb".to_string(), &ParseOptions::default()).unwrap();
+        let calls = result.query("//call").unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].line, 1);
+        assert_eq!(calls[0].column, notebook.find("print").unwrap() as u32 + 1);
+    }
+
+    #[test]
+    fn test_escape_offsets() {
+        assert_eq!(escape_offsets("ab"), vec![]);
+        assert_eq!(escape_offsets(r#"a\"b"#), vec![(2, 3)]);
+        // é is two bytes, written as six; the pair is one four-byte character
+        assert_eq!(escape_offsets(r"\u00e9\ud83d\ude00"), vec![(2, 6), (6, 18)]);
+    }
+
+    #[test]
+    fn test_parse_only_cells_in_one_language() {
+        let options = ParseOptions { embedded_language: Some("markdown"), ..ParseOptions::default() };
+        let mut result = parse_notebook(NOTEBOOK, "analysis.ipynb".to_string(), &options).unwrap();
+        assert_eq!(result.query("/notebook/cell").unwrap().len(), 2);
+        assert_eq!(result.query("//heading").unwrap().len(), 1);
+        assert!(result.query("//cell[@type='code']/*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_not_a_notebook() {
+        assert!(parse_notebook("[1, 2]", "x.ipynb".to_string(), &ParseOptions::default()).is_err());
+    }
+}
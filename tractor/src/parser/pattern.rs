@@ -0,0 +1,194 @@
+//! Query by example: turn a code snippet into an XPath that matches
+//! structurally equivalent code.
+//!
+//! The snippet is parsed like any other source, with each `$name` swapped
+//! for a plain identifier so the grammar accepts it. The resulting tree is
+//! written back out as one step per element, with a predicate per child and
+//! a child count, so the query ignores whitespace, comments and formatting
+//! but not structure. A child whose whole text is a `$name` matches any subtree in
+//! its position; using the same `$name` twice requires both subtrees to
+//! have the same text.
+
+use xot::{Node as XotNode, Xot};
+
+use crate::tree_mode::TreeMode;
+use crate::xpath::string_literal;
+use crate::xot_transform::helpers::{get_attr, get_element_children, get_element_name, get_text_children};
+use super::{parse_string_to_xot, ParseError};
+
+/// Stands in for `$` while the snippet is parsed.
+const WILDCARD_PREFIX: &str = "__tractor_";
+
+/// Comment element names across grammars. Comments are not part of the
+/// structure a pattern matches, in the pattern or in the code.
+const COMMENTS: &[&str] = &["comment", "line_comment", "block_comment", "multiline_comment"];
+
+/// An XPath matching code shaped like `pattern`, in `lang` trees built with
+/// `tree_mode`. `$name` identifiers in the pattern match any subtree.
+pub fn pattern_to_xpath(pattern: &str, lang: &str, tree_mode: Option<TreeMode>) -> Result<String, ParseError> {
+    if TreeMode::resolve(tree_mode, lang).map_err(ParseError::Parse)? == TreeMode::Data {
+        return Err(ParseError::Parse(format!(
+            "patterns need a code tree, not the data tree (use -t structure for {})", lang
+        )));
+    }
+
+    let source = replace_wildcards(pattern);
+    let result = parse_string_to_xot(&source, lang, "<pattern>".to_string(), tree_mode)?;
+    let xot = &result.xot;
+    let mut node = xot.document_element(result.root)
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+
+    if xot.descendants(node).any(|n| get_element_name(xot, n).as_deref() == Some("ERROR")) {
+        return Err(ParseError::Parse(format!("pattern is not valid {}: {}", lang, pattern.trim())));
+    }
+
+    // Unwrap the file and statement wrappers around the snippet itself
+    loop {
+        let children = get_element_children(xot, node);
+        let own_text = get_text_children(xot, node);
+        match children.as_slice() {
+            [child] if own_text.iter().all(|t| t.chars().all(|c| c == ';')) => node = *child,
+            _ => break,
+        }
+    }
+    if wildcard(xot, node).is_some() {
+        return Err(ParseError::Parse("pattern is only a wildcard; add the code around it".to_string()));
+    }
+
+    let mut wildcards = Vec::new();
+    let name = get_element_name(xot, node).unwrap_or_default();
+    let mut xpath = format!("//{}", step(xot, node, name, "", &mut wildcards));
+
+    // Repeated wildcards must match the same text, whitespace aside
+    for (i, (name, path)) in wildcards.iter().enumerate() {
+        if let Some((_, first)) = wildcards[..i].iter().find(|(other, _)| other == name) {
+            xpath.push_str(&format!("[replace({}, '\\s+', '') = replace({}, '\\s+', '')]", first, path));
+        }
+    }
+    Ok(xpath)
+}
+
+/// `$name` → an identifier the grammar accepts.
+fn replace_wildcards(pattern: &str) -> String {
+    let mut source = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '$' && (next.is_alphabetic() || next == '_') => source.push_str(WILDCARD_PREFIX),
+            _ => source.push(c),
+        }
+    }
+    source
+}
+
+/// The wildcard `node` stands for, when its whole text is one `$name`.
+fn wildcard(xot: &Xot, node: XotNode) -> Option<String> {
+    let text = string_value(xot, node);
+    let name = text.trim().strip_prefix(WILDCARD_PREFIX)?;
+    name.chars().all(|c| c.is_alphanumeric() || c == '_').then(|| name.to_string())
+}
+
+/// `selector` (the element name, positioned if needed) followed by
+/// predicates for `node`'s children. `path` is the selector path from the
+/// pattern's root to `node`; wildcards found below it are recorded with
+/// their paths in `wildcards`.
+fn step(xot: &Xot, node: XotNode, selector: String, path: &str, wildcards: &mut Vec<(String, String)>) -> String {
+    let children: Vec<XotNode> = get_element_children(xot, node)
+        .into_iter()
+        .filter(|&child| !get_element_name(xot, child).is_some_and(|name| COMMENTS.contains(&name.as_str())))
+        .collect();
+    if children.is_empty() {
+        let text = string_value(xot, node).trim().replace(WILDCARD_PREFIX, "$");
+        if text.is_empty() {
+            return selector;
+        }
+        return format!("{}[.={}]", selector, string_literal(&text));
+    }
+
+    let names: Vec<String> = children.iter()
+        .map(|&child| get_element_name(xot, child).unwrap_or_default())
+        .collect();
+    let code = format!("*[not({})]", COMMENTS.iter().map(|c| format!("self::{}", c)).collect::<Vec<_>>().join(" or "));
+    let mut xpath = selector;
+    for (i, (&child, name)) in children.iter().zip(&names).enumerate() {
+        let wildcard = wildcard(xot, child);
+        // A wildcard keeps only its slot: a field wrapper's name, otherwise
+        // its position. Other children repeat a name only with a position.
+        let selector = if wildcard.is_some() && get_attr(xot, child, "field").as_ref() != Some(name) {
+            format!("{}[{}]", code, i + 1)
+        } else if names.iter().filter(|n| *n == name).count() > 1 {
+            let position = names[..i].iter().filter(|n| *n == name).count() + 1;
+            format!("{}[{}]", name, position)
+        } else {
+            name.clone()
+        };
+        let child_path = if path.is_empty() { selector.clone() } else { format!("{}/{}", path, selector) };
+        match wildcard {
+            Some(wildcard) => {
+                wildcards.push((wildcard, child_path));
+                xpath.push_str(&format!("[{}]", selector));
+            }
+            None => {
+                let child_step = step(xot, child, selector, &child_path, wildcards);
+                xpath.push_str(&format!("[{}]", child_step));
+            }
+        }
+    }
+    xpath.push_str(&format!("[count({})={}]", code, children.len()));
+    xpath
+}
+
+/// All text under `node`, in document order.
+fn string_value(xot: &Xot, node: XotNode) -> String {
+    xot.descendants(node).filter_map(|n| xot.text_str(n)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, ParseInput, ParseOptions};
+
+    fn count_matches(xpath: &str, source: &str, lang: &str) -> usize {
+        let mut result = parse(
+            ParseInput::Inline { content: source, file_label: "<test>" },
+            ParseOptions { language: Some(lang), ..ParseOptions::default() },
+        ).unwrap();
+        result.query(xpath).unwrap().len()
+    }
+
+    #[test]
+    fn wildcards_match_any_argument() {
+        let xpath = pattern_to_xpath("Assert.AreEqual($a, $b)", "csharp", None).unwrap();
+        let source = r#"
+class Tests {
+    void Run() {
+        Assert.AreEqual(1, items.Count());
+        Assert.AreEqual(  expected,actual );
+        Assert.AreEqual(1, 2, "message");
+        Assert.IsTrue(done);
+    }
+}
+"#;
+        assert_eq!(count_matches(&xpath, source, "csharp"), 2, "{}", xpath);
+    }
+
+    #[test]
+    fn repeated_wildcards_match_the_same_code() {
+        let xpath = pattern_to_xpath("$a == $a", "python", None).unwrap();
+        let source = "x == x\nx == y\na.b == a . b\n";
+        assert_eq!(count_matches(&xpath, source, "python"), 2, "{}", xpath);
+    }
+
+    #[test]
+    fn ignores_comments_between_matched_code() {
+        let xpath = pattern_to_xpath("if $x:\n    a()\n    b()\n", "python", None).unwrap();
+        let source = "if ready:\n    a()\n    # then\n    b()\nif ready:\n    a()\n    b()\n    c()\n";
+        assert_eq!(count_matches(&xpath, source, "python"), 1, "{}", xpath);
+    }
+
+    #[test]
+    fn rejects_unparseable_and_wildcard_only_patterns() {
+        assert!(pattern_to_xpath("$a", "python", None).is_err());
+        assert!(pattern_to_xpath("def (:", "python", None).is_err());
+    }
+}
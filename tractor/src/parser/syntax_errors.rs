@@ -0,0 +1,94 @@
+//! Syntax errors found by TreeSitter
+//!
+//! TreeSitter never rejects input: it recovers from invalid syntax by wrapping
+//! the text it could not parse in an `ERROR` node, or by inserting a zero-width
+//! `MISSING` node for a token it expected. Both end up in the tree as ordinary
+//! elements, so queries keep running but quietly stop matching inside the
+//! broken region. Collecting them lets callers tell a clean result from one
+//! that is only clean because the grammar gave up.
+
+/// A region of the source the grammar could not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// 1-based start line
+    pub line: u32,
+    /// 1-based start column (bytes)
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+    pub kind: SyntaxErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// Text the grammar skipped (an `ERROR` node), first line only
+    Unexpected(String),
+    /// A token the grammar expected but did not find (a `MISSING` node)
+    Missing(String),
+    /// An embedded block (a notebook cell, a component's script) that could
+    /// not be parsed at all, with the reason
+    Unparsed(String),
+}
+
+/// Longest snippet of unexpected text quoted in a message
+const MAX_SNIPPET_CHARS: usize = 40;
+
+impl SyntaxError {
+    /// Human-readable description, e.g. "syntax error: missing `;`".
+    pub fn message(&self) -> String {
+        match &self.kind {
+            SyntaxErrorKind::Unexpected(text) if text.is_empty() => "syntax error".to_string(),
+            SyntaxErrorKind::Unexpected(text) => format!("syntax error: unexpected `{}`", text),
+            SyntaxErrorKind::Missing(token) => format!("syntax error: missing `{}`", token),
+            SyntaxErrorKind::Unparsed(reason) => format!("cannot parse embedded code: {}", reason),
+        }
+    }
+}
+
+/// Collect the outermost ERROR and all MISSING nodes below `root`, in source order.
+pub(crate) fn collect_syntax_errors(root: tree_sitter::Node, source: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    if root.has_error() {
+        collect(root, source, &mut errors);
+    }
+    errors
+}
+
+fn collect(node: tree_sitter::Node, source: &str, errors: &mut Vec<SyntaxError>) {
+    let kind = if node.is_missing() {
+        SyntaxErrorKind::Missing(node.kind().to_string())
+    } else if node.is_error() {
+        // One diagnostic per error region; nested errors add nothing
+        let text = node.utf8_text(source.as_bytes()).unwrap_or("");
+        SyntaxErrorKind::Unexpected(snippet(text))
+    } else {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.has_error() {
+                collect(child, source, errors);
+            }
+        }
+        return;
+    };
+
+    let start = node.start_position();
+    let end = node.end_position();
+    errors.push(SyntaxError {
+        line: start.row as u32 + 1,
+        column: start.column as u32 + 1,
+        end_line: end.row as u32 + 1,
+        end_column: end.column as u32 + 1,
+        kind,
+    });
+}
+
+/// First line of the unexpected text, shortened for display
+fn snippet(text: &str) -> String {
+    let first_line = text.trim().lines().next().unwrap_or("").trim_end();
+    if first_line.chars().count() > MAX_SNIPPET_CHARS {
+        let truncated: String = first_line.chars().take(MAX_SNIPPET_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        first_line.to_string()
+    }
+}
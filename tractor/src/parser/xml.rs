@@ -0,0 +1,100 @@
+//! Source locations for XML input
+//!
+//! XML files are not parsed by TreeSitter: they are loaded into Documents as
+//! they are, so queries use the file's own element and attribute names. To
+//! report match locations and support `tractor set`, every element gets a
+//! location covering it from `<` to the end of its closing tag.
+//!
+//! Locations come from a second parse of the same text with span tracking,
+//! walked in lockstep with the loaded document. They are returned as a map
+//! rather than added as attributes, so a file's own `line` or `column`
+//! attributes are queried and rendered unchanged.
+
+use std::collections::HashMap;
+
+use xot::{Node, SpanInfo, SpanInfoKey, Xot};
+
+use super::ParseError;
+use crate::xpath::NodeLocations;
+
+/// Source location of every element below `doc_node`.
+pub(crate) fn source_locations(xot: &Xot, doc_node: Node, source: &str) -> Result<NodeLocations, ParseError> {
+    let root = xot.document_element(doc_node)
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+
+    let mut spans_xot = Xot::new();
+    let (spans_doc, span_info) = spans_xot.parse_with_span_info(source)
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+    let spans_root = spans_xot.document_element(spans_doc)
+        .map_err(|e| ParseError::Parse(e.to_string()))?;
+
+    let line_starts = line_starts(source);
+    let mut pairs = Vec::new();
+    collect_element_pairs(xot, root, &spans_xot, spans_root, &mut pairs);
+
+    let mut locations = HashMap::with_capacity(pairs.len());
+    for (node, spans_node) in pairs {
+        let Some((start, end)) = element_byte_range(&span_info, spans_node, source) else {
+            continue;
+        };
+        let (line, column) = line_column(&line_starts, start);
+        let (end_line, end_column) = line_column(&line_starts, end);
+        locations.insert(node, (line as u32, column as u32, end_line as u32, end_column as u32));
+    }
+    Ok(locations)
+}
+
+/// Pair each element of the loaded document with its twin in the span-tracked parse.
+fn collect_element_pairs(xot: &Xot, node: Node, spans_xot: &Xot, spans_node: Node, pairs: &mut Vec<(Node, Node)>) {
+    pairs.push((node, spans_node));
+    let children = xot.children(node).filter(|&c| xot.element(c).is_some());
+    let spans_children = spans_xot.children(spans_node).filter(|&c| spans_xot.element(c).is_some());
+    for (child, spans_child) in children.zip(spans_children) {
+        collect_element_pairs(xot, child, spans_xot, spans_child, pairs);
+    }
+}
+
+/// Byte range of an element, from its `<` to the `>` closing its end tag
+/// (or of the start tag itself for empty elements).
+fn element_byte_range(span_info: &SpanInfo, node: Node, source: &str) -> Option<(usize, usize)> {
+    let start_span = span_info.get(SpanInfoKey::ElementStart(node))?;
+    // Empty elements may only record their start tag
+    let end_offset = span_info.get(SpanInfoKey::ElementEnd(node))
+        .unwrap_or(start_span)
+        .end;
+
+    let name_start = (start_span.start + 1).min(source.len());
+    let start = source[..name_start].rfind('<')?;
+    let end = if source[..end_offset].ends_with('>') {
+        end_offset
+    } else {
+        end_offset + source[end_offset..].find('>')? + 1
+    };
+    Some((start, end))
+}
+
+/// Byte offsets at which each line starts
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// 1-based line and byte column of a byte offset
+fn line_column(line_starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = line_starts.partition_point(|&start| start <= offset);
+    (line, offset - line_starts[line - 1] + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_column() {
+        let starts = line_starts("<a>\n  <b/>\n</a>\n");
+        assert_eq!(line_column(&starts, 0), (1, 1));
+        assert_eq!(line_column(&starts, 6), (2, 3));
+        assert_eq!(line_column(&starts, 11), (3, 1));
+    }
+}
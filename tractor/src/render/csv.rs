@@ -0,0 +1,183 @@
+//! CSV/TSV renderer: render a table data tree back to delimited text.
+//!
+//! The data tree has one `row` element per record, whose children are the
+//! cells named after their column (the original header text is in a `key`
+//! attribute when the name had to be sanitized):
+//!
+//! ```xml
+//! <row><email>ada@example.com</email><status>active</status></row>
+//! ```
+//!
+//! Renders to:
+//! ```text
+//! email,status
+//! ada@example.com,active
+//! ```
+//!
+//! The header is taken from the first row's cells. Cells are quoted only
+//! when their value needs it. Each cell's span is tracked, so updating a
+//! value re-renders just that cell. Adding columns or rows is not supported.
+
+use super::{text_content, RenderError, RenderOptions, SpanMap};
+use crate::xpath::XmlNode;
+
+/// Render a table data tree to delimited text.
+pub fn render_node(node: &XmlNode, opts: &RenderOptions, delimiter: char) -> Result<String, RenderError> {
+    let (rendered, _) = render_node_tracked(node, opts, delimiter)?;
+    Ok(rendered)
+}
+
+/// Render a table data tree to delimited text, tracking cell spans.
+pub fn render_node_tracked(
+    node: &XmlNode,
+    opts: &RenderOptions,
+    delimiter: char,
+) -> Result<(String, SpanMap), RenderError> {
+    let mut buf = String::new();
+    let mut span_map = SpanMap::new();
+    let rows = element_children(node);
+
+    if let Some(first) = rows.first() {
+        let header: Vec<String> = element_children(first).into_iter().map(column_name).collect();
+        let fields: Vec<String> = header.iter().map(|name| quote(name, delimiter)).collect();
+        buf.push_str(&fields.join(&delimiter.to_string()));
+        buf.push_str(&opts.newline);
+    }
+
+    for row in rows {
+        if let XmlNode::Element { name, .. } = row {
+            if name != "row" {
+                return Err(RenderError::UnsupportedNode(format!("<{}> (tables only hold rows)", name)));
+            }
+        }
+        // Start, end and span start of the previous cell
+        let mut previous: Option<(Option<Position>, Option<Position>, usize)> = None;
+        for (i, cell) in element_children(row).into_iter().enumerate() {
+            let XmlNode::Element { attributes, .. } = cell else {
+                continue;
+            };
+            // Cells created by upsert carry a `field` attribute; parsed cells never do
+            if get_attr(attributes, "field").is_some() {
+                return Err(RenderError::UnsupportedNode(format!("new column '{}'", column_name(cell))));
+            }
+            let before_delimiter = buf.len();
+            if i > 0 {
+                buf.push(delimiter);
+            }
+            let start = buf.len();
+            buf.push_str(&quote(&text_content(cell).unwrap_or_default(), delimiter));
+
+            // Cells padding a short row sit where its last field ends. Their
+            // span takes in the delimiters from there, so a value set on one
+            // is written with the fields it needs in front of it; padding
+            // cells share a position, and the last one's span covers them all.
+            let cell_start = position(attributes, "line", "column");
+            let span_start = match previous {
+                Some((prev_start, _, prev_span)) if cell_start.is_some() && cell_start == prev_start => prev_span,
+                Some((_, prev_end, _)) if cell_start.is_some() && cell_start == prev_end => before_delimiter,
+                _ => start,
+            };
+            record_span(attributes, span_start, buf.len(), &mut span_map);
+            previous = Some((cell_start, position(attributes, "end_line", "end_column"), span_start));
+        }
+        buf.push_str(&opts.newline);
+    }
+
+    Ok((buf, span_map))
+}
+
+/// Quote a field if it holds the delimiter, a quote or a line break.
+fn quote(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The header text of a cell's column: its `key` attribute, else its name.
+fn column_name(cell: &XmlNode) -> String {
+    match cell {
+        XmlNode::Element { name, attributes, .. } => get_attr(attributes, "key").unwrap_or_else(|| name.clone()),
+        _ => String::new(),
+    }
+}
+
+/// Record the byte span of a cell in the span map, keyed by (line, column).
+fn record_span(attributes: &[(String, String)], start: usize, end: usize, span_map: &mut SpanMap) {
+    if let (Some(line), Some(col)) = (
+        get_attr(attributes, "line").and_then(|v| v.parse::<u32>().ok()),
+        get_attr(attributes, "column").and_then(|v| v.parse::<u32>().ok()),
+    ) {
+        span_map.insert((line, col), (start, end));
+    }
+}
+
+/// A (line, column) position
+type Position = (u32, u32);
+
+fn position(attributes: &[(String, String)], line: &str, column: &str) -> Option<Position> {
+    Some((
+        get_attr(attributes, line)?.parse().ok()?,
+        get_attr(attributes, column)?.parse().ok()?,
+    ))
+}
+
+fn get_attr(attributes: &[(String, String)], name: &str) -> Option<String> {
+    attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+}
+
+fn element_children(node: &XmlNode) -> Vec<&XmlNode> {
+    match node {
+        XmlNode::Element { children, .. } => children
+            .iter()
+            .filter(|c| matches!(c, XmlNode::Element { .. }))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(name: &str, text: &str, line: u32, column: u32) -> XmlNode {
+        XmlNode::Element {
+            name: name.to_string(),
+            attributes: vec![
+                ("line".to_string(), line.to_string()),
+                ("column".to_string(), column.to_string()),
+            ],
+            children: vec![XmlNode::Text(text.to_string())],
+        }
+    }
+
+    fn element(name: &str, children: Vec<XmlNode>) -> XmlNode {
+        XmlNode::Element { name: name.to_string(), attributes: vec![], children }
+    }
+
+    #[test]
+    fn renders_header_and_rows() {
+        let root = element("document", vec![
+            element("row", vec![cell("email", "ada@example.com", 2, 1), cell("name", "Lovelace, Ada", 2, 17)]),
+            element("row", vec![cell("email", "grace@example.com", 3, 1), cell("name", "Grace \"Amazing\"", 3, 19)]),
+        ]);
+        let (rendered, spans) = render_node_tracked(&root, &RenderOptions::default(), ',').unwrap();
+        assert_eq!(
+            rendered,
+            "email,name\nada@example.com,\"Lovelace, Ada\"\ngrace@example.com,\"Grace \"\"Amazing\"\"\"\n",
+        );
+        let (start, end) = spans[&(2, 17)];
+        assert_eq!(&rendered[start..end], "\"Lovelace, Ada\"");
+    }
+
+    #[test]
+    fn new_columns_are_rejected() {
+        let mut added = cell("owner", "ops", 2, 5);
+        if let XmlNode::Element { attributes, .. } = &mut added {
+            attributes.push(("field".to_string(), "owner".to_string()));
+        }
+        let root = element("document", vec![element("row", vec![cell("flag", "beta", 2, 1), added])]);
+        assert!(render_node(&root, &RenderOptions::default(), '\t').is_err());
+    }
+}
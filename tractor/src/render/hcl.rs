@@ -0,0 +1,392 @@
+//! HCL renderer: render a data-tree back to HCL / Terraform source code.
+//!
+//! This is the inverse of the HCL data transform. Elements are classified as:
+//!
+//! - **Blocks** (`kind="block"`, or elements with element children and no
+//!   value kind): render as `type "label" "label" { ... }`, with the `type`,
+//!   `name` and `label3`, `label4`, ... attributes as labels.
+//! - **Attributes** (everything else): render as `key = value`, where the
+//!   value is formatted from its `kind` (`string`, `template`, `number`,
+//!   `bool`, `null`, `expression`, `object`, `tuple`).
+//!
+//! ## Example
+//!
+//! Data tree:
+//! ```xml
+//! <module name="vpc" kind="block">
+//!   <source kind="string">terraform-aws-modules/vpc/aws</source>
+//!   <version kind="string">5.0.0</version>
+//! </module>
+//! ```
+//!
+//! Renders to:
+//! ```hcl
+//! module "vpc" {
+//!   source = "terraform-aws-modules/vpc/aws"
+//!   version = "5.0.0"
+//! }
+//! ```
+
+use super::{RenderOptions, SpanMap};
+use crate::xpath::XmlNode;
+
+/// Render a data-tree XmlNode to HCL source code.
+///
+/// The `node` should be the content root (the `document` element) whose
+/// children are the top-level blocks and attributes.
+pub fn render_node(node: &XmlNode, opts: &RenderOptions) -> Result<String, super::RenderError> {
+    let (rendered, _) = render_node_tracked(node, opts)?;
+    Ok(rendered)
+}
+
+/// Render a data-tree XmlNode to HCL source code, tracking value spans.
+pub fn render_node_tracked(
+    node: &XmlNode,
+    opts: &RenderOptions,
+) -> Result<(String, SpanMap), super::RenderError> {
+    let mut buf = String::new();
+    let mut span_map = SpanMap::new();
+    if let XmlNode::Element { children, .. } = node {
+        render_body(&element_children(children), opts, &mut buf, &mut span_map)?;
+    }
+    Ok((buf, span_map))
+}
+
+/// Render a sequence of blocks and attributes, one per line.
+fn render_body(
+    items: &[&XmlNode],
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+) -> Result<(), super::RenderError> {
+    let indent = opts.current_indent();
+    for item in items {
+        buf.push_str(&indent);
+        if is_block(item) {
+            render_block(item, opts, buf, span_map)?;
+        } else {
+            render_attribute(item, opts, buf, span_map)?;
+        }
+        buf.push_str(&opts.newline);
+    }
+    Ok(())
+}
+
+/// Render a block: `type "label" "label" ... { body }`
+fn render_block(
+    node: &XmlNode,
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+) -> Result<(), super::RenderError> {
+    let XmlNode::Element { name, attributes, children } = node else {
+        return Ok(());
+    };
+
+    let start = buf.len();
+    buf.push_str(&get_attr(attributes, "key").unwrap_or_else(|| name.clone()));
+    let extra_labels = (3..).map_while(|i| get_attr(attributes, &format!("label{i}")));
+    let labels = ["type", "name"].iter().filter_map(|label| get_attr(attributes, label));
+    for value in labels.chain(extra_labels) {
+        buf.push_str(" \"");
+        buf.push_str(&escape_hcl_string(&value));
+        buf.push('"');
+    }
+
+    let body = element_children(children);
+    if body.is_empty() {
+        buf.push_str(" {}");
+    } else {
+        buf.push_str(" {");
+        buf.push_str(&opts.newline);
+        render_body(&body, &opts.indented(), buf, span_map)?;
+        buf.push_str(&opts.current_indent());
+        buf.push('}');
+    }
+
+    record_span(attributes, start, buf.len(), span_map);
+    Ok(())
+}
+
+/// Render an attribute: `key = value`
+fn render_attribute(
+    node: &XmlNode,
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+) -> Result<(), super::RenderError> {
+    let XmlNode::Element { name, attributes, .. } = node else {
+        return Ok(());
+    };
+    buf.push_str(&render_key(name, attributes));
+    buf.push_str(" = ");
+    render_value(node, opts, buf, span_map)
+}
+
+/// Render an element's value, recording its span.
+fn render_value(
+    node: &XmlNode,
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+) -> Result<(), super::RenderError> {
+    let XmlNode::Element { attributes, children, .. } = node else {
+        return Ok(());
+    };
+
+    let start = buf.len();
+    let kids = element_children(children);
+    let kind = get_attr(attributes, "kind");
+
+    match kind.as_deref() {
+        Some("tuple") => {
+            buf.push('[');
+            for (i, item) in kids.iter().enumerate() {
+                if i > 0 {
+                    buf.push_str(", ");
+                }
+                render_value(item, opts, buf, span_map)?;
+            }
+            buf.push(']');
+        }
+        Some("object") => render_object(&kids, opts, buf, span_map)?,
+        _ if !kids.is_empty() => render_object(&kids, opts, buf, span_map)?,
+        _ => {
+            let text = text_content(children).unwrap_or_default();
+            render_scalar(&text, kind.as_deref(), buf);
+        }
+    }
+
+    record_span(attributes, start, buf.len(), span_map);
+    Ok(())
+}
+
+/// Render an object value: `{ key = value ... }`
+fn render_object(
+    properties: &[&XmlNode],
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+) -> Result<(), super::RenderError> {
+    if properties.is_empty() {
+        buf.push_str("{}");
+        return Ok(());
+    }
+
+    let inner_opts = opts.indented();
+    let indent = inner_opts.current_indent();
+    buf.push('{');
+    buf.push_str(&opts.newline);
+    for prop in properties {
+        if let XmlNode::Element { name, attributes, .. } = prop {
+            buf.push_str(&indent);
+            buf.push_str(&render_key(name, attributes));
+            buf.push_str(" = ");
+            render_value(prop, &inner_opts, buf, span_map)?;
+            buf.push_str(&opts.newline);
+        }
+    }
+    buf.push_str(&opts.current_indent());
+    buf.push('}');
+    Ok(())
+}
+
+/// Render a scalar using its `kind` attribute, falling back to
+/// auto-detection (bool/null/number literals stay bare, the rest is quoted).
+fn render_scalar(text: &str, kind: Option<&str>, buf: &mut String) {
+    match kind {
+        Some("string") => push_quoted(text, buf),
+        // Template source text is already escaped
+        Some("template") => {
+            buf.push('"');
+            buf.push_str(text);
+            buf.push('"');
+        }
+        Some("number") | Some("bool") | Some("null") | Some("expression") => buf.push_str(text),
+        _ => match text {
+            "true" | "false" | "null" => buf.push_str(text),
+            _ if !text.is_empty() && text.parse::<f64>().is_ok() => buf.push_str(text),
+            _ => push_quoted(text, buf),
+        },
+    }
+}
+
+fn push_quoted(text: &str, buf: &mut String) {
+    buf.push('"');
+    buf.push_str(&escape_hcl_string(text));
+    buf.push('"');
+}
+
+/// Use the original key when the element name was sanitized; quote keys
+/// that are not valid HCL identifiers.
+fn render_key(name: &str, attributes: &[(String, String)]) -> String {
+    match get_attr(attributes, "key") {
+        Some(key) if is_identifier(&key) => key,
+        Some(key) => format!("\"{}\"", escape_hcl_string(&key)),
+        None => name.to_string(),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A block is an element marked `kind="block"` by the parser, or a new
+/// element (no kind) that has element children.
+fn is_block(node: &XmlNode) -> bool {
+    match node {
+        XmlNode::Element { attributes, children, .. } => match get_attr(attributes, "kind").as_deref() {
+            Some("block") => true,
+            Some(_) => false,
+            None => !element_children(children).is_empty(),
+        },
+        _ => false,
+    }
+}
+
+/// Escape a string for a quoted HCL template, including the `${` and `%{`
+/// sequences that would otherwise start an interpolation or directive.
+fn escape_hcl_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                result.push(c);
+                result.push(c);
+            }
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Record the byte span of a node in the span map, keyed by (line, column).
+fn record_span(
+    attributes: &[(String, String)],
+    start: usize,
+    end: usize,
+    span_map: &mut SpanMap,
+) {
+    if let (Some(line), Some(col)) = (
+        get_attr(attributes, "line").and_then(|v| v.parse::<u32>().ok()),
+        get_attr(attributes, "column").and_then(|v| v.parse::<u32>().ok()),
+    ) {
+        span_map.insert((line, col), (start, end));
+    }
+}
+
+fn get_attr(attributes: &[(String, String)], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.clone())
+}
+
+fn element_children(children: &[XmlNode]) -> Vec<&XmlNode> {
+    children
+        .iter()
+        .filter(|c| matches!(c, XmlNode::Element { .. }))
+        .collect()
+}
+
+fn text_content(children: &[XmlNode]) -> Option<String> {
+    let mut result = String::new();
+    for child in children {
+        if let XmlNode::Text(t) = child {
+            result.push_str(t);
+        }
+    }
+    if result.is_empty() {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(name: &str, kind: &str, text: &str) -> XmlNode {
+        XmlNode::Element {
+            name: name.to_string(),
+            attributes: vec![("kind".to_string(), kind.to_string())],
+            children: vec![XmlNode::Text(text.to_string())],
+        }
+    }
+
+    #[test]
+    fn renders_block_with_labels_and_attributes() {
+        let tree = XmlNode::Element {
+            name: "document".to_string(),
+            attributes: vec![],
+            children: vec![XmlNode::Element {
+                name: "module".to_string(),
+                attributes: vec![
+                    ("kind".to_string(), "block".to_string()),
+                    ("name".to_string(), "vpc".to_string()),
+                ],
+                children: vec![
+                    leaf("source", "string", "terraform-aws-modules/vpc/aws"),
+                    leaf("version", "string", "5.0.0"),
+                    leaf("cidr", "expression", "var.cidr"),
+                ],
+            }],
+        };
+        let opts = RenderOptions { indent: "  ".to_string(), ..RenderOptions::default() };
+        let rendered = render_node(&tree, &opts).unwrap();
+        assert_eq!(
+            rendered,
+            "module \"vpc\" {\n  source = \"terraform-aws-modules/vpc/aws\"\n  version = \"5.0.0\"\n  cidr = var.cidr\n}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_quoted_strings() {
+        let mut buf = String::new();
+        render_scalar("say \"hi\"", Some("string"), &mut buf);
+        assert_eq!(buf, r#""say \"hi\"""#);
+    }
+
+    #[test]
+    fn escapes_template_sequences() {
+        let mut buf = String::new();
+        render_scalar("${literal} 100%{x} $5", Some("string"), &mut buf);
+        assert_eq!(buf, r#""$${literal} 100%%{x} $5""#);
+    }
+
+    #[test]
+    fn renders_templates_verbatim() {
+        let mut buf = String::new();
+        render_scalar("${var.name}-bucket", Some("template"), &mut buf);
+        assert_eq!(buf, r#""${var.name}-bucket""#);
+    }
+
+    #[test]
+    fn renders_every_block_label() {
+        let tree = XmlNode::Element {
+            name: "document".to_string(),
+            attributes: vec![],
+            children: vec![XmlNode::Element {
+                name: "service".to_string(),
+                attributes: vec![
+                    ("kind".to_string(), "block".to_string()),
+                    ("type".to_string(), "a".to_string()),
+                    ("name".to_string(), "b".to_string()),
+                    ("label3".to_string(), "c".to_string()),
+                ],
+                children: vec![],
+            }],
+        };
+        let rendered = render_node(&tree, &RenderOptions::default()).unwrap();
+        assert_eq!(rendered, "service \"a\" \"b\" \"c\" {}\n");
+    }
+}
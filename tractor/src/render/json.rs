@@ -10,6 +10,15 @@
 //! They render as `"key": value`. Elements without this attribute are collection
 //! items (array elements) and render as bare values.
 //!
+//! ## Trailing commas and comments
+//!
+//! With [`RenderOptions::trailing_commas`] every member gets a trailing
+//! comma, matching JSONC and JSON5 files that use them. Their data trees
+//! have no comments, so upserts never re-render an object already in the
+//! file: updates splice in just the rendered value, and inserts a single
+//! member rendered by [`render_member`]. The comments around them stay
+//! where they are.
+//!
 //! ## Examples
 //!
 //! Data tree:
@@ -35,6 +44,9 @@
 use super::{RenderOptions, SpanMap};
 use crate::xpath::XmlNode;
 
+/// Renders one object or array member
+type RenderFn = fn(&XmlNode, &RenderOptions, &mut String, &mut SpanMap) -> Result<(), super::RenderError>;
+
 /// Render a data-tree XmlNode to JSON source code.
 ///
 /// The `node` should be the content root (e.g., File element) whose children
@@ -59,6 +71,14 @@ pub fn render_node_tracked(
     Ok((buf, span_map))
 }
 
+/// Render one object member (`"key": value`) at `opts`' indent level, for
+/// splicing into existing source.
+pub fn render_member(node: &XmlNode, opts: &RenderOptions) -> Result<String, super::RenderError> {
+    let mut buf = String::new();
+    render_property(node, opts, &mut buf, &mut SpanMap::new())?;
+    Ok(buf)
+}
+
 /// Render a node as a JSON value (object, array, or scalar).
 fn render_value(
     node: &XmlNode,
@@ -72,35 +92,8 @@ fn render_value(
             attributes,
             children,
         } => {
-            let element_children = element_children(children);
-            let text = text_content(children);
-
             // Track the value span for elements with a `start` attribute
-            let start_pos = buf.len();
-
-            if element_children.is_empty() {
-                // Leaf node — render as scalar value
-                if let Some(text) = &text {
-                    let kind = get_attr(attributes, "kind");
-                    render_scalar(text, kind.as_deref(), buf);
-                } else {
-                    // Empty element with no text — render as empty object
-                    buf.push_str("{}");
-                }
-            } else {
-                // Has element children — determine if object or array
-                let all_properties = element_children
-                    .iter()
-                    .all(|c| is_property_element(c));
-
-                if all_properties {
-                    render_object(&element_children, opts, buf, span_map)?;
-                } else {
-                    // Mixed or no properties — render as array
-                    render_array(&element_children, opts, buf, span_map)?;
-                }
-            }
-
+            let start_pos = render_contents(attributes, children, opts, buf, span_map)?;
             record_span(attributes, start_pos, buf.len(), span_map);
             Ok(())
         }
@@ -112,106 +105,91 @@ fn render_value(
     }
 }
 
-/// Render an object: `{ "key": value, ... }`
-fn render_object(
-    properties: &[&XmlNode],
+/// Render an element's value: a scalar, or an object or array of its element
+/// children. Returns the offset where the value starts.
+fn render_contents(
+    attributes: &[(String, String)],
+    children: &[XmlNode],
     opts: &RenderOptions,
     buf: &mut String,
     span_map: &mut SpanMap,
-) -> Result<(), super::RenderError> {
-    if properties.is_empty() {
-        buf.push_str("{}");
-        return Ok(());
-    }
-
-    let inner_opts = opts.indented();
-    let indent = inner_opts.current_indent();
-    let outer_indent = opts.current_indent();
-
-    buf.push('{');
-
-    for (i, prop) in properties.iter().enumerate() {
-        if i > 0 {
-            buf.push(',');
-        }
-        buf.push_str(&opts.newline);
-        buf.push_str(&indent);
-
-        if let XmlNode::Element {
-            name,
-            attributes,
-            children,
-            ..
-        } = prop
-        {
-            // Use original key from `key` attribute if present (sanitized names)
-            let key = get_attr(attributes, "key").unwrap_or_else(|| name.clone());
-            buf.push('"');
-            buf.push_str(&escape_json_string(&key));
-            buf.push_str("\": ");
-
-            let element_kids = element_children(children);
-            let text = text_content(children);
-
-            // Track value span (starts after "key": )
-            let value_start = buf.len();
-
-            if element_kids.is_empty() {
-                if let Some(text) = &text {
-                    let kind = get_attr(attributes, "kind");
-                    render_scalar(text, kind.as_deref(), buf);
-                } else {
-                    buf.push_str("{}");
-                }
-            } else {
-                let all_props = element_kids.iter().all(|c| is_property_element(c));
-                if all_props {
-                    render_object(&element_kids, &inner_opts, buf, span_map)?;
-                } else {
-                    render_array(&element_kids, &inner_opts, buf, span_map)?;
-                }
-            }
-
-            record_span(attributes, value_start, buf.len(), span_map);
+) -> Result<usize, super::RenderError> {
+    let entries = element_children(children);
+    let text = text_content(children);
+
+    let start = buf.len();
+    if entries.is_empty() {
+        if let Some(text) = &text {
+            // Leaf node — render as scalar value
+            let kind = get_attr(attributes, "kind");
+            render_scalar(text, kind.as_deref(), buf);
+        } else {
+            // Empty element with no text — render as empty object
+            buf.push_str("{}");
         }
+    } else if entries.iter().all(|c| is_property_element(c)) {
+        render_members(&entries, ('{', '}'), opts, buf, span_map, render_property)?;
+    } else {
+        // Mixed or no properties — render as array
+        render_members(&entries, ('[', ']'), opts, buf, span_map, render_value)?;
     }
-
-    buf.push_str(&opts.newline);
-    buf.push_str(&outer_indent);
-    buf.push('}');
-    Ok(())
+    Ok(start)
 }
 
-/// Render an array: `[ value, ... ]`
-fn render_array(
-    items: &[&XmlNode],
+/// Render a property: `"key": value`
+fn render_property(
+    prop: &XmlNode,
     opts: &RenderOptions,
     buf: &mut String,
     span_map: &mut SpanMap,
 ) -> Result<(), super::RenderError> {
-    if items.is_empty() {
-        buf.push_str("[]");
-        return Ok(());
+    if let XmlNode::Element {
+        name,
+        attributes,
+        children,
+    } = prop
+    {
+        // Use original key from `key` attribute if present (sanitized names)
+        let key = get_attr(attributes, "key").unwrap_or_else(|| name.clone());
+        buf.push('"');
+        buf.push_str(&escape_json_string(&key));
+        buf.push_str("\": ");
+
+        // Track value span (starts after "key": )
+        let value_start = render_contents(attributes, children, opts, buf, span_map)?;
+        record_span(attributes, value_start, buf.len(), span_map);
     }
+    Ok(())
+}
 
+/// Render the members of an object (`{ "key": value, ... }`) or an array
+/// (`[ value, ... ]`), one per line.
+fn render_members(
+    entries: &[&XmlNode],
+    (open, close): (char, char),
+    opts: &RenderOptions,
+    buf: &mut String,
+    span_map: &mut SpanMap,
+    render_member: RenderFn,
+) -> Result<(), super::RenderError> {
     let inner_opts = opts.indented();
     let indent = inner_opts.current_indent();
     let outer_indent = opts.current_indent();
 
-    buf.push('[');
+    buf.push(open);
 
-    for (i, item) in items.iter().enumerate() {
-        if i > 0 {
-            buf.push(',');
-        }
+    for (i, entry) in entries.iter().enumerate() {
         buf.push_str(&opts.newline);
         buf.push_str(&indent);
-        render_value(item, &inner_opts, buf, span_map)?;
+        render_member(entry, &inner_opts, buf, span_map)?;
+        if i + 1 < entries.len() || opts.trailing_commas {
+            buf.push(',');
+        }
     }
 
     buf.push_str(&opts.newline);
     buf.push_str(&outer_indent);
-    buf.push(']');
+    buf.push(close);
     Ok(())
 }
 
@@ -362,6 +340,7 @@ mod tests {
             indent: "  ".to_string(),
             indent_level: 0,
             newline: "\n".to_string(),
+            trailing_commas: false,
         }
     }
 
@@ -511,6 +490,24 @@ mod tests {
         assert!(result.contains("\"my-key\""), "should use original key from key attr");
     }
 
+    #[test]
+    fn renders_single_member() {
+        let member = make_prop_obj("b", vec![make_prop("c", "2")]);
+        let opts = RenderOptions { indent_level: 1, ..opts() };
+        assert_eq!(render_member(&member, &opts).unwrap(), "\"b\": {\n    \"c\": 2\n  }");
+    }
+
+    #[test]
+    fn trailing_commas_option() {
+        let root = make_container(
+            "File",
+            vec![make_prop("a", "1"), make_prop_obj("b", vec![make_prop("c", "2")])],
+        );
+        let opts = RenderOptions { trailing_commas: true, ..opts() };
+        let result = render_node(&root, &opts).unwrap();
+        assert_eq!(result, "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": 2,\n  },\n}\n");
+    }
+
     #[test]
     fn tracked_render_records_spans() {
         let root = make_container(
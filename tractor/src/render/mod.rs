@@ -16,7 +16,10 @@
 //! rather than the current approach of serializing from the data model.
 
 pub mod csharp;
+pub mod csv;
+pub mod hcl;
 pub mod json;
+pub mod xml;
 pub mod yaml;
 
 use crate::xpath::XmlNode;
@@ -58,6 +61,8 @@ pub struct RenderOptions {
     pub indent_level: usize,
     /// Newline string
     pub newline: String,
+    /// Write a comma after the last member of objects and arrays (JSONC)
+    pub trailing_commas: bool,
 }
 
 impl Default for RenderOptions {
@@ -66,6 +71,7 @@ impl Default for RenderOptions {
             indent: "    ".to_string(),
             indent_level: 0,
             newline: "\n".to_string(),
+            trailing_commas: false,
         }
     }
 }
@@ -325,8 +331,12 @@ pub fn render(node: &XmlNode, lang: &str, tree_mode: TreeMode, opts: &RenderOpti
     }
     match lang {
         "csharp" => csharp::render_node(node, opts),
-        "json" => json::render_node(node, opts),
+        "json" | "jsonc" | "json5" => json::render_node(node, opts),
         "yaml" | "yml" => yaml::render_node(node, opts),
+        "hcl" | "terraform" | "tf" => hcl::render_node(node, opts),
+        "xml" => xml::render_node(node, opts),
+        "csv" => csv::render_node(node, opts, ','),
+        "tsv" => csv::render_node(node, opts, '\t'),
         _ => Err(RenderError::UnsupportedLanguage(lang.to_string())),
     }
 }
@@ -353,8 +363,12 @@ pub fn render_with_spans(
         });
     }
     match lang {
-        "json" => json::render_node_tracked(node, opts),
+        "json" | "jsonc" | "json5" => json::render_node_tracked(node, opts),
         "yaml" | "yml" => yaml::render_node_tracked(node, opts),
+        "hcl" | "terraform" | "tf" => hcl::render_node_tracked(node, opts),
+        "xml" => xml::render_node_tracked(node, opts),
+        "csv" => csv::render_node_tracked(node, opts, ','),
+        "tsv" => csv::render_node_tracked(node, opts, '\t'),
         _ => {
             // Fall back to untracked render for languages that don't support span tracking
             let rendered = render(node, lang, tree_mode, opts)?;
@@ -0,0 +1,101 @@
+//! XML renderer: render a loaded XML document back to XML source.
+//!
+//! XML input is queried as-is, so the "data tree" is the document itself.
+//! Rendering writes elements, attributes, text, comments and processing
+//! instructions back out unchanged. Source locations of XML input are kept
+//! out of the tree, so every attribute belongs to the document.
+//!
+//! `tractor set` doesn't use this renderer for XML files: it edits their
+//! source in place to keep the original formatting.
+
+use super::{RenderOptions, SpanMap};
+use crate::xpath::XmlNode;
+
+/// Render an XmlNode tree to XML source.
+pub fn render_node(node: &XmlNode, opts: &RenderOptions) -> Result<String, super::RenderError> {
+    let (rendered, _) = render_node_tracked(node, opts)?;
+    Ok(rendered)
+}
+
+/// Render an XmlNode tree to XML source.
+///
+/// The span map is always empty: XML trees carry no location attributes.
+pub fn render_node_tracked(
+    node: &XmlNode,
+    _opts: &RenderOptions,
+) -> Result<(String, SpanMap), super::RenderError> {
+    let mut buf = String::new();
+    render(node, &mut buf);
+    Ok((buf, SpanMap::new()))
+}
+
+fn render(node: &XmlNode, buf: &mut String) {
+    match node {
+        XmlNode::Element { name, attributes, children } => {
+            buf.push('<');
+            buf.push_str(name);
+            for (key, value) in attributes {
+                buf.push(' ');
+                buf.push_str(key);
+                buf.push_str("=\"");
+                buf.push_str(&escape_attribute(value));
+                buf.push('"');
+            }
+
+            if children.is_empty() {
+                buf.push_str(" />");
+            } else {
+                buf.push('>');
+                for child in children {
+                    render(child, buf);
+                }
+                buf.push_str("</");
+                buf.push_str(name);
+                buf.push('>');
+            }
+        }
+        XmlNode::Text(text) => buf.push_str(&escape_text(text)),
+        XmlNode::Comment(comment) => {
+            buf.push_str("<!--");
+            buf.push_str(comment);
+            buf.push_str("-->");
+        }
+        XmlNode::ProcessingInstruction { target, data } => {
+            buf.push_str("<?");
+            buf.push_str(target);
+            if let Some(data) = data {
+                buf.push(' ');
+                buf.push_str(data);
+            }
+            buf.push_str("?>");
+        }
+        _ => {}
+    }
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn escape_attribute(s: &str) -> String {
+    escape_text(s).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_every_attribute() {
+        let tree = XmlNode::Element {
+            name: "Point".to_string(),
+            attributes: vec![
+                ("line".to_string(), "3".to_string()),
+                ("column".to_string(), "a\"b".to_string()),
+            ],
+            children: vec![],
+        };
+        let rendered = render_node(&tree, &RenderOptions::default()).unwrap();
+        assert_eq!(rendered, r#"<Point line="3" column="a&quot;b" />"#);
+    }
+}
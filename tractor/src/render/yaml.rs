@@ -422,6 +422,7 @@ mod tests {
             indent: "  ".to_string(),
             indent_level: 0,
             newline: "\n".to_string(),
+            trailing_commas: false,
         }
     }
 
@@ -27,14 +27,17 @@
 //! Both forms produce the same `Vec<Operation>`. When both are present,
 //! root-level keys are expanded first, then the operations list is appended.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 use serde::Deserialize;
 use tractor::declarative_set::parse_set_expr;
 use tractor::normalized_xpath::NormalizedXpath;
-use tractor::report::Severity;
+use tractor::report::{DiagnosticOrigin, ReportMatch, Severity};
 use tractor::rule::Rule;
 use tractor::tree_mode::TreeMode;
+use tractor::{LanguageConfig, LanguageTransforms, XPathLibrary, XPathValue, XPathVariables};
 
+use crate::cli::context::parse_timeout;
 use crate::executor::{
     QueryExpr, QueryOperation, SetMapping, SetOperation, SetReportMode, SetWriteMode,
     TestAssertion, TestOperation,
@@ -68,6 +71,41 @@ struct ConfigFile {
     #[serde(default, rename = "diff-lines")]
     diff_lines: Option<String>,
 
+    /// Language overrides: glob or file name → language, for files the
+    /// extension does not identify (`Jenkinsfile`, `*.csx`, `.babelrc`).
+    /// Kept in the order written, so the first overlapping pattern wins.
+    #[serde(default, deserialize_with = "ordered_pairs")]
+    languages: Vec<(String, String)>,
+
+    /// Default encodings: glob or file name → encoding, for legacy files
+    /// that are neither UTF-8 nor marked with a BOM (`"*.cs": windows-1252`),
+    /// instead of guessing theirs.
+    /// Kept in the order written, so the first overlapping pattern wins.
+    #[serde(default, deserialize_with = "ordered_pairs")]
+    encodings: Vec<(String, String)>,
+
+    /// Declarative tree transforms per language, on top of or instead of
+    /// the built-in transform.
+    #[serde(default)]
+    transforms: BTreeMap<String, LanguageConfig>,
+
+    /// User-defined XPath functions: signature → body
+    /// (`is-test-method($m): "$m/attributes/attribute[name = 'Test']"`),
+    /// callable from every query in the config.
+    #[serde(default)]
+    functions: BTreeMap<String, String>,
+
+    /// Named XPath fragments: name → expression, referenced as `$name`
+    /// from every query in the config.
+    #[serde(default, rename = "let")]
+    fragments: BTreeMap<String, String>,
+
+    /// Variables bound as `$name` in every query: name → value or list of
+    /// values (`max-params: 5`, `allowed-namespaces: [App.Core, App.Web]`).
+    /// `--var` on the command line overrides them.
+    #[serde(default)]
+    variables: BTreeMap<String, VariableConfig>,
+
     /// Root-level check shorthand (single check operation).
     #[serde(default)]
     check: Option<CheckConfig>,
@@ -89,6 +127,33 @@ struct ConfigFile {
     operations: Vec<OperationEntry>,
 }
 
+/// A `variables:` value: one scalar or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum VariableConfig {
+    One(VariableValueConfig),
+    Many(Vec<VariableValueConfig>),
+}
+
+/// A scalar variable value, typed as written in the config.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum VariableValueConfig {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+}
+
+impl From<VariableValueConfig> for XPathValue {
+    fn from(value: VariableValueConfig) -> Self {
+        match value {
+            VariableValueConfig::Boolean(b) => XPathValue::Boolean(b),
+            VariableValueConfig::Integer(i) => XPathValue::Integer(i),
+            VariableValueConfig::String(s) => XPathValue::String(s),
+        }
+    }
+}
+
 /// A single entry in the operations list.
 /// Deserialized from YAML like:
 ///   - check:
@@ -151,9 +216,19 @@ struct CheckRuleConfig {
     #[serde(default, rename = "tree-mode")]
     tree_mode: Option<String>,
     #[serde(default)]
+    timeout: Option<TimeoutConfig>,
+    #[serde(default)]
     expect: Vec<CheckExpectEntry>,
 }
 
+/// A rule's `timeout`: a number of seconds, or text like `500ms` or `2m`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TimeoutConfig {
+    Seconds(u64),
+    Text(String),
+}
+
 /// A single expectation entry for check rules in tractor config files.
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
@@ -294,9 +369,10 @@ fn parse_tree_mode(s: &str) -> Result<TreeMode, String> {
     match s {
         "raw" => Ok(TreeMode::Raw),
         "structure" => Ok(TreeMode::Structure),
+        "common" => Ok(TreeMode::Common),
         "data" => Ok(TreeMode::Data),
         other => Err(format!(
-            "invalid tree-mode '{}': use 'raw', 'structure', or 'data'",
+            "invalid tree-mode '{}': use 'raw', 'structure', 'common', or 'data'",
             other
         )),
     }
@@ -455,6 +531,11 @@ fn convert_check(config: CheckConfig, scope: &RootScope) -> Result<ConfigOperati
     let rules: Vec<Rule> = config.rules.into_iter().map(|r| {
         let severity = parse_severity(&r.severity)?;
         let rule_tree_mode = r.tree_mode.as_deref().map(parse_tree_mode).transpose()?;
+        let rule_timeout = match &r.timeout {
+            Some(TimeoutConfig::Seconds(seconds)) => Some(parse_timeout(&seconds.to_string())),
+            Some(TimeoutConfig::Text(text)) => Some(parse_timeout(text)),
+            None => None,
+        }.transpose().map_err(|e| format!("in rule '{}': {}", r.id, e))?;
         let mut rule = Rule::new(r.id, r.xpath).with_severity(severity);
         if let Some(reason) = r.reason {
             rule = rule.with_reason(reason);
@@ -474,6 +555,9 @@ fn convert_check(config: CheckConfig, scope: &RootScope) -> Result<ConfigOperati
         if let Some(tm) = rule_tree_mode {
             rule = rule.with_tree_mode(tm);
         }
+        if let Some(timeout) = rule_timeout {
+            rule = rule.with_timeout(timeout);
+        }
         let valid_examples: Vec<String> = r.expect.iter().filter_map(|e| e.valid.clone()).collect();
         let invalid_examples: Vec<String> = r.expect.iter().filter_map(|e| e.invalid.clone()).collect();
         if !valid_examples.is_empty() {
@@ -706,8 +790,116 @@ fn merge_scope(
     (op_files, exclude, diff_files, diff_lines)
 }
 
+/// Deserialize a mapping as `(key, value)` pairs in the order written,
+/// where the order decides between overlapping patterns.
+fn ordered_pairs<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<(String, String)>, D::Error> {
+    struct Pairs;
+
+    impl<'de> serde::de::Visitor<'de> for Pairs {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a mapping of patterns to names")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut pairs = Vec::new();
+            while let Some(pair) = map.next_entry()? {
+                pairs.push(pair);
+            }
+            Ok(pairs)
+        }
+    }
+
+    deserializer.deserialize_map(Pairs)
+}
+
+/// Validate `languages:` entries and resolve aliases to canonical names.
+fn convert_languages(languages: Vec<(String, String)>) -> Result<Vec<LanguageMapping>, Box<dyn std::error::Error>> {
+    languages.into_iter().map(|(pattern, language)| {
+        let info = tractor::language_info::get_language_info(&language).ok_or_else(|| format!(
+            "unknown language '{}' for '{}' in `languages` (see `tractor languages`)",
+            language, pattern
+        ))?;
+        Ok(LanguageMapping { pattern, language: info.name.to_string() })
+    }).collect()
+}
+
+/// Validate `encodings:` entries and resolve labels to canonical names.
+fn convert_encodings(encodings: Vec<(String, String)>) -> Result<Vec<EncodingMapping>, Box<dyn std::error::Error>> {
+    encodings.into_iter().map(|(pattern, label)| {
+        let encoding = tractor::encoding::lookup(&label).ok_or_else(|| format!(
+            "unknown encoding '{}' for '{}' in `encodings`",
+            label, pattern
+        ))?;
+        Ok(EncodingMapping { pattern, encoding: encoding.name().to_string() })
+    }).collect()
+}
+
+/// Build the `functions:` and `let:` library, checking every definition.
+fn convert_library(
+    functions: BTreeMap<String, String>,
+    fragments: BTreeMap<String, String>,
+) -> Result<XPathLibrary, Box<dyn std::error::Error>> {
+    let mut library = XPathLibrary::new();
+    for (signature, body) in &functions {
+        library.define_function(signature, body)
+            .map_err(|e| format!("{} in `functions`", e))?;
+    }
+    for (name, body) in &fragments {
+        library.define_fragment(name, body)
+            .map_err(|e| format!("{} in `let`", e))?;
+    }
+    library.validate()?;
+    Ok(library)
+}
+
+/// Validate `variables:` entries. A variable may not share its name with a
+/// `let:` fragment, which would silently replace it.
+fn convert_variables(
+    variables: BTreeMap<String, VariableConfig>,
+    library: &XPathLibrary,
+) -> Result<XPathVariables, Box<dyn std::error::Error>> {
+    let mut converted = XPathVariables::new();
+    for (name, value) in variables {
+        let values = match value {
+            VariableConfig::One(v) => vec![v.into()],
+            VariableConfig::Many(vs) => vs.into_iter().map(Into::into).collect(),
+        };
+        converted.set(&name, values).map_err(|e| format!("{} in `variables`", e))?;
+    }
+    if let Some((name, _)) = converted.iter().find(|(name, _)| library.has_fragment(name)) {
+        return Err(format!("'{}' is defined in both `variables` and `let`", name).into());
+    }
+    Ok(converted)
+}
+
+/// Validate `transforms:` entries and key them by canonical language name.
+fn convert_transforms(transforms: BTreeMap<String, LanguageConfig>) -> Result<LanguageTransforms, Box<dyn std::error::Error>> {
+    use tractor::xot_transform::helpers::sanitize_xml_name;
+
+    transforms.into_iter().map(|(language, config)| {
+        let info = tractor::language_info::get_language_info(&language).ok_or_else(|| format!(
+            "unknown language '{}' in `transforms` (see `tractor languages`)",
+            language
+        ))?;
+        if let Some(name) = config.element_mappings.values().find(|name| sanitize_xml_name(name) != **name) {
+            return Err(format!(
+                "invalid element name '{}' in `transforms.{}.rename`",
+                name, language
+            ).into());
+        }
+        Ok((info.name.to_string(), config))
+    }).collect()
+}
+
 fn config_to_operations(config: ConfigFile) -> Result<LoadedConfig, Box<dyn std::error::Error>> {
     let root_files = config.files;
+    let languages = convert_languages(config.languages)?;
+    let encodings = convert_encodings(config.encodings)?;
+    let transforms = convert_transforms(config.transforms)?;
+    let library = convert_library(config.functions, config.fragments)?;
+    let variables = convert_variables(config.variables, &library)?;
 
     let scope = RootScope {
         exclude: config.exclude,
@@ -747,12 +939,72 @@ fn config_to_operations(config: ConfigFile) -> Result<LoadedConfig, Box<dyn std:
         }
     }
 
+    let warnings = element_name_warnings(&ops, &transforms);
+
     Ok(LoadedConfig {
         root_files,
+        languages,
+        encodings,
+        transforms,
+        variables,
+        library,
         operations: ops,
+        warnings,
     })
 }
 
+/// Warn about check rules that test for element names their language never
+/// produces (`//function` in a C# rule), which would otherwise silently
+/// match nothing. Rules without an explicit language, languages whose
+/// element names are open-ended, and languages with user `transforms:` are
+/// skipped.
+fn element_name_warnings(operations: &[ConfigOperation], transforms: &LanguageTransforms) -> Vec<ReportMatch> {
+    let mut warnings = Vec::new();
+    for operation in operations {
+        let ConfigOperation::Check { op, .. } = operation else { continue };
+        for rule in &op.rules {
+            let Some(info) = rule.language.as_deref()
+                .or(op.ruleset_default_language.as_deref())
+                .and_then(tractor::language_info::get_language_info)
+            else { continue };
+            if transforms.contains_key(info.name) {
+                continue;
+            }
+            let xpath = rule.xpath.as_str();
+            let injected = tractor::wants_injections(xpath);
+            let Some(known) = tractor::element_vocabulary(info.name, rule.tree_mode.or(op.tree_mode), injected) else { continue };
+            for (name, start, end) in tractor::unknown_element_names(xpath, &known) {
+                let mut reason = format!("element '{}' never occurs in {} trees", name, info.name);
+                let suggestions = tractor::similar_names(name, known.iter().copied());
+                if !suggestions.is_empty() {
+                    let quoted: Vec<String> = suggestions.iter().map(|s| format!("'{}'", s)).collect();
+                    reason.push_str(&format!("; did you mean {}?", quoted.join(" or ")));
+                }
+                warnings.push(ReportMatch {
+                    file: String::new(),
+                    line: 1,
+                    column: start as u32 + 1,
+                    end_line: 1,
+                    end_column: end as u32 + 1,
+                    command: "check".to_string(),
+                    tree: None,
+                    value: None,
+                    source: Some(rule.xpath.to_string()),
+                    lines: Some(vec![rule.xpath.to_string()]),
+                    reason: Some(reason),
+                    severity: Some(Severity::Warning),
+                    message: None,
+                    origin: Some(DiagnosticOrigin::Config),
+                    rule_id: Some(rule.id.clone()),
+                    status: None,
+                    output: None,
+                });
+            }
+        }
+    }
+    warnings
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -766,17 +1018,57 @@ pub struct LoadedConfig {
     /// `None` when the key is missing (unrestricted); `Some(vec![])` when
     /// explicitly empty.
     pub root_files: Option<Vec<String>>,
+    /// Root-level `languages:` overrides, applied to every operation.
+    pub languages: Vec<LanguageMapping>,
+    /// Root-level `encodings:` defaults, applied to every operation.
+    pub encodings: Vec<EncodingMapping>,
+    /// Root-level `transforms:`, keyed by canonical language name.
+    pub transforms: LanguageTransforms,
+    /// Root-level `variables:`, bound in every query.
+    pub variables: XPathVariables,
+    /// Root-level `functions:` and `let:`, expanded in every query.
+    pub library: XPathLibrary,
     /// Parsed operations paired with their per-op input-resolution data.
     /// Sources/filters are filled in by the runner once the shared
     /// `FileResolver` has resolved each operation's file set.
     pub operations: Vec<ConfigOperation>,
+    /// Load-time warnings about check rules, reported alongside the
+    /// operations' results.
+    pub warnings: Vec<ReportMatch>,
+}
+
+/// One `languages:` entry: files matching `pattern` parse as `language`.
+///
+/// A pattern without `/` matches the file name anywhere in the tree
+/// (`Jenkinsfile`, `*.csx`); one with `/` is a glob relative to the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageMapping {
+    pub pattern: String,
+    /// Canonical language name
+    pub language: String,
+}
+
+/// One `encodings:` entry: files matching `pattern` that have no BOM and
+/// are not valid UTF-8 decode as `encoding`. Patterns work like
+/// [`LanguageMapping`] patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingMapping {
+    pub pattern: String,
+    /// Canonical encoding name (`windows-1252`, `UTF-16LE`)
+    pub encoding: String,
 }
 
 impl std::fmt::Debug for LoadedConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LoadedConfig")
             .field("root_files", &self.root_files)
+            .field("languages", &self.languages)
+            .field("encodings", &self.encodings)
+            .field("transforms", &self.transforms)
+            .field("variables", &self.variables)
+            .field("library", &self.library)
             .field("operations", &self.operations)
+            .field("warnings", &self.warnings)
             .finish()
     }
 }
@@ -865,6 +1157,31 @@ check:
         assert_eq!(c.rules[0].reason.as_deref(), Some("TODO found"));
     }
 
+    #[test]
+    fn parse_yaml_rule_timeouts() {
+        let yaml = r#"
+check:
+  rules:
+    - id: slow
+      xpath: "//call"
+      timeout: 500ms
+    - id: slower
+      xpath: "//call"
+      timeout: 2
+    - id: unlimited
+      xpath: "//call"
+"#;
+        let ops = parse_config_yaml(yaml).unwrap().operations;
+        let (_, c) = as_check(&ops[0]);
+        assert_eq!(c.rules[0].timeout, Some(std::time::Duration::from_millis(500)));
+        assert_eq!(c.rules[1].timeout, Some(std::time::Duration::from_secs(2)));
+        assert_eq!(c.rules[2].timeout, None);
+
+        let yaml = "check:\n  rules:\n    - id: bad\n      xpath: '//call'\n      timeout: soon\n";
+        let err = parse_config_yaml(yaml).unwrap_err().to_string();
+        assert!(err.contains("in rule 'bad'"), "{}", err);
+    }
+
     #[test]
     fn parse_yaml_root_level_set() {
         let yaml = r#"
@@ -982,6 +1299,52 @@ check:
         assert_eq!(c.rules[1].severity, Severity::Error);
     }
 
+    #[test]
+    fn warns_about_element_names_the_language_never_produces() {
+        let yaml = r#"
+check:
+  files: ["**/*.cs"]
+  language: csharp
+  rules:
+    - id: no-procedures
+      xpath: "//class/procedure[name = 'x']"
+    - id: no-methods
+      xpath: "//class/method[count(parameters/parameter) > 5]"
+    - id: raw
+      xpath: "//method_declaration"
+      tree-mode: raw
+"#;
+        let warnings = parse_config_yaml(yaml).unwrap().warnings;
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(warnings[0].rule_id.as_deref(), Some("no-procedures"));
+        assert_eq!(warnings[0].severity, Some(Severity::Warning));
+        assert_eq!(warnings[0].column, 9);
+        assert!(warnings[0].reason.as_deref().unwrap().contains("'procedure'"));
+
+        // User transforms can introduce any name
+        let yaml = format!("transforms:\n  csharp:\n    rename:\n      method_declaration: procedure\n{}", yaml);
+        assert!(parse_config_yaml(&yaml).unwrap().warnings.is_empty());
+    }
+
+    #[test]
+    fn knows_doc_comment_and_injected_element_names() {
+        let yaml = r#"
+check:
+  files: ["**/*.cs"]
+  language: csharp
+  rules:
+    - id: undocumented
+      xpath: "//method[not(doc/summary)]"
+    - id: raw-sql
+      xpath: "//call//injected//select"
+    - id: python-params
+      language: python
+      xpath: "//function[not(doc/param)]/name"
+"#;
+        let warnings = parse_config_yaml(yaml).unwrap().warnings;
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
     #[test]
     fn parse_yaml_set_with_exclude() {
         let yaml = r#"
@@ -1182,6 +1545,137 @@ check:
         assert!(inputs.files.is_empty(), "operation should have no files when not specified");
     }
 
+    #[test]
+    fn parse_languages_section() {
+        let yaml = r#"
+languages:
+  "*.csx": cs
+  "tools/*": python
+check:
+  rules:
+    - id: no-todo
+      xpath: "//comment"
+"#;
+        let loaded = parse_config_yaml(yaml).unwrap();
+        assert_eq!(loaded.languages, vec![
+            LanguageMapping { pattern: "*.csx".into(), language: "csharp".into() },
+            LanguageMapping { pattern: "tools/*".into(), language: "python".into() },
+        ]);
+
+        // Entries keep the order they are written in, not alphabetical order
+        let loaded = parse_config_yaml("languages:\n  \"*.h\": cpp\n  \"*.c\": c\n  \"a*\": bash\n").unwrap();
+        let patterns: Vec<&str> = loaded.languages.iter().map(|m| m.pattern.as_str()).collect();
+        assert_eq!(patterns, ["*.h", "*.c", "a*"]);
+        let loaded = parse_config_toml("[languages]\n\"*.h\" = \"cpp\"\n\"*.c\" = \"c\"\n\"a*\" = \"bash\"\n").unwrap();
+        let patterns: Vec<&str> = loaded.languages.iter().map(|m| m.pattern.as_str()).collect();
+        assert_eq!(patterns, ["*.h", "*.c", "a*"]);
+
+        let err = parse_config_yaml("languages:\n  Jenkinsfile: groovy\n").unwrap_err();
+        assert!(err.to_string().contains("unknown language 'groovy'"), "{}", err);
+    }
+
+    #[test]
+    fn parse_encodings_section() {
+        let yaml = r#"
+encodings:
+  "legacy/*": utf-16le
+  "*.cs": latin1
+"#;
+        // Kept in the order written
+        let loaded = parse_config_yaml(yaml).unwrap();
+        assert_eq!(loaded.encodings, vec![
+            EncodingMapping { pattern: "legacy/*".into(), encoding: "UTF-16LE".into() },
+            EncodingMapping { pattern: "*.cs".into(), encoding: "windows-1252".into() },
+        ]);
+
+        let err = parse_config_yaml("encodings:\n  \"*.cs\": cp-unknown\n").unwrap_err();
+        assert!(err.to_string().contains("unknown encoding 'cp-unknown'"), "{}", err);
+    }
+
+    #[test]
+    fn parse_transforms_section() {
+        let yaml = r#"
+transforms:
+  lua:
+    rename: { function_declaration: function }
+    flatten: [block]
+    lift: [parameters]
+  py:
+    mode: replace
+    modifiers: [decorator]
+"#;
+        let loaded = parse_config_yaml(yaml).unwrap();
+        let lua = &loaded.transforms["lua"];
+        assert_eq!(lua.mode, tractor::TransformBase::Extend);
+        assert_eq!(lua.map_element_name("function_declaration"), "function");
+        assert!(lua.should_flatten("block"));
+        assert_eq!(loaded.transforms["python"].mode, tractor::TransformBase::Replace);
+
+        let err = parse_config_yaml("transforms:\n  lua:\n    rename: { call: \"a b\" }\n").unwrap_err();
+        assert!(err.to_string().contains("invalid element name 'a b'"), "{}", err);
+    }
+
+    #[test]
+    fn functions_and_fragments_travel_with_the_queries() {
+        let yaml = r#"
+functions:
+  is-test-method($m): "$m/attributes/attribute[name = 'Test']"
+let:
+  $public: "modifiers/public"
+check:
+  rules:
+    - id: no-public-tests
+      xpath: "//method[is-test-method(.)][$public]"
+query:
+  queries:
+    - xpath: "//method[$public]"
+"#;
+        let loaded = parse_config_yaml(yaml).unwrap();
+        // Queries are kept as written, for reports and errors
+        let (_, check) = as_check(&loaded.operations[0]);
+        assert_eq!(check.rules[0].xpath.as_str(), "//method[is-test-method(.)][$public]");
+        let (_, query) = as_query(&loaded.operations[1]);
+        assert_eq!(query.queries[0].xpath.as_str(), "//method[$public]");
+        assert_eq!(loaded.library.expand("//method[$public]").unwrap(), "//method[(modifiers/public)]");
+    }
+
+    #[test]
+    fn invalid_functions_are_reported_at_load() {
+        let err = parse_config_yaml("functions:\n  is-test: \"1\"\n").unwrap_err();
+        assert!(err.to_string().contains("invalid function signature 'is-test'"), "{}", err);
+
+        let err = parse_config_yaml("functions:\n  a(): \"b()\"\n  b(): \"a()\"\n").unwrap_err();
+        assert!(err.to_string().contains("refers to itself"), "{}", err);
+
+        let err = parse_config_yaml("functions:\n  f($x): \"$x\"\n  g(): \"f()\"\n").unwrap_err();
+        assert!(err.to_string().contains("in function 'g': function 'f' takes 1 argument(s), got 0"), "{}", err);
+    }
+
+    #[test]
+    fn parse_variables_section() {
+        let yaml = r#"
+variables:
+  max-params: 5
+  strict: true
+  release: "10"
+  allowed-namespaces: [App.Core, App.Web]
+"#;
+        let loaded = parse_config_yaml(yaml).unwrap();
+        let vars: BTreeMap<&str, &[XPathValue]> = loaded.variables.iter().collect();
+        assert_eq!(vars["max-params"], [XPathValue::Integer(5)]);
+        assert_eq!(vars["strict"], [XPathValue::Boolean(true)]);
+        assert_eq!(vars["release"], [XPathValue::String("10".into())]);
+        assert_eq!(vars["allowed-namespaces"], [
+            XPathValue::String("App.Core".into()),
+            XPathValue::String("App.Web".into()),
+        ]);
+
+        let err = parse_config_yaml("variables:\n  file: x\n").unwrap_err();
+        assert!(err.to_string().contains("variable name 'file' is reserved"), "{}", err);
+        let err = parse_config_yaml("variables:\n  public: x\nlet:\n  public: \"modifiers/public\"\n").unwrap_err();
+        assert!(err.to_string().contains("'public' is defined in both"), "{}", err);
+    }
+
     #[test]
     fn operation_files_kept_independently_from_root() {
         let yaml = r#"
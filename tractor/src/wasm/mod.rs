@@ -84,6 +84,7 @@ fn resolve_wasm_tree_mode(tree_mode_str: Option<&str>, raw_mode: bool) -> Option
     match tree_mode_str {
         Some("raw") => Some(TreeMode::Raw),
         Some("structure") => Some(TreeMode::Structure),
+        Some("common") => Some(TreeMode::Common),
         Some("data") => Some(TreeMode::Data),
         Some(_) => None, // invalid string → auto-detect
         None if raw_mode => Some(TreeMode::Raw), // backwards compat
@@ -116,6 +117,10 @@ fn parse_ast_to_xml(
         walk_transform(&mut xot, root, transform_fn)
             .map_err(|e| format!("Transform failed: {}", e))?;
     }
+    if resolved == TreeMode::Common {
+        crate::languages::common::normalize(&mut xot, root, language)
+            .map_err(|e| format!("Transform failed: {}", e))?;
+    }
 
     // Render to XML string
     let options = RenderOptions::new()
@@ -568,6 +568,7 @@ impl XeeBuilder {
     /// `tree_mode` controls which tree representation is produced:
     /// - `Raw`: skip all transforms, output raw tree-sitter AST
     /// - `Structure`: apply syntax transform only (single branch)
+    /// - `Common`: structure, then rename to the cross-language vocabulary
     /// - `Data`: apply data transform only (single branch, data-aware languages only)
     #[cfg(feature = "native")]
     pub fn build_with_options(
@@ -598,7 +599,7 @@ impl XeeBuilder {
             TreeMode::Raw => {
                 // No transforms — keep raw tree-sitter AST
             }
-            TreeMode::Structure => {
+            TreeMode::Structure | TreeMode::Common => {
                 // Single-branch syntax transform
                 let doc_node = self.documents.document_node(doc_handle)
                     .ok_or_else(|| xot::Error::Io("Failed to get document node".to_string()))?;
@@ -609,6 +610,9 @@ impl XeeBuilder {
                     crate::languages::get_transform(lang)
                 };
                 crate::xot_transform::walk_transform(self.documents.xot_mut(), doc_node, transform_fn)?;
+                if tree_mode == TreeMode::Common {
+                    crate::languages::common::normalize(self.documents.xot_mut(), doc_node, lang)?;
+                }
             }
             TreeMode::Data => {
                 // Single-branch data transform (caller already validated lang supports it)
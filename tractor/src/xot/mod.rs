@@ -1,4 +1,5 @@
 //! Xot XML tree building and transformation infrastructure.
 
 pub mod builder;
+pub mod position;
 pub mod transform;
@@ -0,0 +1,195 @@
+//! Position lookup for `tractor at`: the elements containing a source
+//! position, each with XPaths that select it.
+//!
+//! The absolute path walks down from the document element, adding a
+//! position only where a name repeats among siblings. The relative path
+//! starts at the nearest element of the chain that its own step identifies
+//! in the whole document, with declarations identified by name
+//! (`//method[name='Run']`) rather than by position, and continues down
+//! with the absolute path's steps.
+
+use std::collections::HashMap;
+
+use xot::{Node as XotNode, Xot};
+
+use crate::xpath::{string_literal, NodeLocations};
+use super::transform::helpers::{get_attr, get_element_children, get_element_name};
+
+/// One element of the chain containing a position.
+#[derive(Debug, Clone)]
+pub struct ElementAt {
+    pub node: XotNode,
+    pub name: String,
+    /// Path from the document element, e.g. `/unit/class[2]/body/method`.
+    pub absolute: String,
+    /// Shortest path selecting only this element, e.g. `//class[name='Foo']`.
+    pub relative: String,
+}
+
+/// The elements containing `line`:`column` (1-based, column in bytes), from
+/// the document element down to the innermost one. Empty when the position
+/// is outside the tree. Elements in `locations` (XML input) use that
+/// location instead of their attributes.
+pub fn elements_at(
+    xot: &Xot,
+    root: XotNode,
+    locations: Option<&NodeLocations>,
+    line: u32,
+    column: u32,
+) -> Vec<ElementAt> {
+    let Ok(document_element) = xot.document_element(root) else {
+        return Vec::new();
+    };
+    let covers = |node| contains(xot, locations, node, (line, column));
+    let mut chain = Vec::new();
+    let mut current = Some(document_element).filter(|&node| covers(node));
+    while let Some(node) = current {
+        chain.push(node);
+        current = get_element_children(xot, node).into_iter().find(|&child| covers(child));
+    }
+    if chain.is_empty() {
+        return Vec::new();
+    }
+
+    let steps: Vec<Step> = chain.iter().map(|&node| Step::new(xot, node)).collect();
+    let counts = step_counts(xot, document_element);
+    let positioned: Vec<String> = chain.iter().map(|&node| positioned_step(xot, node)).collect();
+
+    let mut distinctive = None;
+    chain.iter().enumerate()
+        .map(|(depth, &node)| {
+            if counts.get(&steps[depth].key()) == Some(&1) {
+                distinctive = Some(depth);
+            }
+            let absolute = format!("/{}", positioned[..=depth].join("/"));
+            let relative = match distinctive {
+                Some(start) => std::iter::once(steps[start].xpath.as_str())
+                    .chain(positioned[start + 1..=depth].iter().map(String::as_str))
+                    .fold("/".to_string(), |path, step| path + "/" + step),
+                None => absolute.clone(),
+            };
+            ElementAt {
+                node,
+                name: steps[depth].name.clone(),
+                absolute,
+                relative,
+            }
+        })
+        .collect()
+}
+
+/// Whether `node`'s source range covers `position`. Elements without a
+/// range (markers) cover nothing.
+fn contains(xot: &Xot, locations: Option<&NodeLocations>, node: XotNode, position: (u32, u32)) -> bool {
+    if let Some(&(line, column, end_line, end_column)) = locations.and_then(|l| l.get(&node)) {
+        return (line, column) <= position && position < (end_line, end_column);
+    }
+    let attr = |name| get_attr(xot, node, name).and_then(|v| v.parse::<u32>().ok());
+    match (attr("line"), attr("column"), attr("end_line"), attr("end_column")) {
+        (Some(line), Some(column), Some(end_line), Some(end_column)) => {
+            (line, column) <= position && position < (end_line, end_column)
+        }
+        _ => false,
+    }
+}
+
+/// How many elements under `document_element` each step selects, keyed
+/// like [`Step::key`]: every element counts for its name, and for its name
+/// with each of its `name` children's text.
+fn step_counts(xot: &Xot, document_element: XotNode) -> HashMap<(String, Option<String>), usize> {
+    let mut counts = HashMap::new();
+    for node in xot.descendants(document_element).filter(|&node| xot.element(node).is_some()) {
+        let name = get_element_name(xot, node).unwrap_or_default();
+        let mut texts: Vec<String> = get_element_children(xot, node).into_iter()
+            .filter(|&child| get_element_name(xot, child).as_deref() == Some("name"))
+            .map(|child| string_value(xot, child))
+            .collect();
+        texts.sort();
+        texts.dedup();
+        for text in texts {
+            *counts.entry((name.clone(), Some(text))).or_insert(0) += 1;
+        }
+        *counts.entry((name, None)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// `node`'s name, with its position among same-named siblings if it has any.
+fn positioned_step(xot: &Xot, node: XotNode) -> String {
+    let name = get_element_name(xot, node).unwrap_or_default();
+    let Some(parent) = xot.parent(node).filter(|&p| xot.element(p).is_some()) else {
+        return name;
+    };
+    let siblings: Vec<XotNode> = get_element_children(xot, parent).into_iter()
+        .filter(|&sibling| get_element_name(xot, sibling).as_deref() == Some(name.as_str()))
+        .collect();
+    match siblings.iter().position(|&sibling| sibling == node) {
+        Some(i) if siblings.len() > 1 => format!("{}[{}]", name, i + 1),
+        _ => name,
+    }
+}
+
+/// A step of a relative path: the element name, plus its `name` child's
+/// text when it has a simple one.
+struct Step {
+    name: String,
+    child_name: Option<String>,
+    xpath: String,
+}
+
+impl Step {
+    fn new(xot: &Xot, node: XotNode) -> Self {
+        let name = get_element_name(xot, node).unwrap_or_default();
+        let child_name = if name == "name" { None } else { simple_name(xot, node) };
+        let xpath = match &child_name {
+            Some(text) => format!("{}[name={}]", name, string_literal(text)),
+            None => name.clone(),
+        };
+        Step { name, child_name, xpath }
+    }
+
+    /// The key of this step in [`step_counts`].
+    fn key(&self) -> (String, Option<String>) {
+        (self.name.clone(), self.child_name.clone())
+    }
+}
+
+/// The text of `node`'s first `name` child, when it is plain text on one line.
+fn simple_name(xot: &Xot, node: XotNode) -> Option<String> {
+    let name = get_element_children(xot, node).into_iter()
+        .find(|&child| get_element_name(xot, child).as_deref() == Some("name"))?;
+    let text = string_value(xot, name);
+    Some(text).filter(|t| !t.is_empty() && !t.contains('\n') && t.trim() == t)
+}
+
+/// All text under `node`, in document order.
+fn string_value(xot: &Xot, node: XotNode) -> String {
+    xot.descendants(node).filter_map(|n| xot.text_str(n)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_string_to_xot;
+
+    #[test]
+    fn chain_has_absolute_and_shortest_relative_paths() {
+        let source = "class Foo {\n    void Run() { }\n    void Stop() { Run(); }\n}\nclass Bar { }\n";
+        let result = parse_string_to_xot(source, "csharp", "<test>".to_string(), None).unwrap();
+
+        // Inside the `Run()` call in Stop
+        let chain = elements_at(&result.xot, result.root, None, 3, 19);
+        let names: Vec<&str> = chain.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names.first(), Some(&"unit"));
+        assert!(names.contains(&"class") && names.contains(&"method"), "{:?}", names);
+
+        let class = chain.iter().find(|e| e.name == "class").unwrap();
+        assert_eq!(class.absolute, "/unit/class[1]");
+        assert_eq!(class.relative, "//class[name='Foo']");
+        let method = chain.iter().find(|e| e.name == "method").unwrap();
+        assert_eq!(method.relative, "//method[name='Stop']");
+        assert!(method.absolute.starts_with("/unit/class[1]/"), "{}", method.absolute);
+
+        assert!(elements_at(&result.xot, result.root, None, 40, 1).is_empty());
+    }
+}
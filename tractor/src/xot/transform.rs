@@ -110,9 +110,12 @@ where
             // Move children to parent, transform them, then remove this node
             let children: Vec<XotNode> = xot.children(node).collect();
             for child in children {
+                // Check first: a text child merges into a neighbouring text
+                // node on insertion, which frees it
+                let is_element = xot.element(child).is_some();
                 xot.detach(child)?;
                 xot.insert_before(node, child)?;
-                if xot.element(child).is_some() {
+                if is_element {
                     walk_node(xot, child, transform_fn)?;
                 }
             }
@@ -447,16 +450,19 @@ pub mod helpers {
         Ok(())
     }
 
+    /// Operator semantic markers, as added inside `<op>` elements
+    pub const OPERATOR_MARKERS: &[&str] = &[
+        "equals", "not-equals", "compare", "less", "greater", "or-equal",
+        "plus", "minus", "multiply", "divide", "modulo", "power",
+        "logical", "bitwise", "shift", "nullish-coalescing",
+        "assign", "increment", "decrement",
+        "strict", "left", "right", "unsigned", "xor",
+        "contains", "identity", "not", "and", "or",
+    ];
+
     /// Check if an element name is an operator semantic marker
     pub fn is_operator_marker(name: &str) -> bool {
-        matches!(name,
-            "equals" | "not-equals" | "compare" | "less" | "greater" | "or-equal"
-            | "plus" | "minus" | "multiply" | "divide" | "modulo" | "power"
-            | "logical" | "bitwise" | "shift" | "nullish-coalescing"
-            | "assign" | "increment" | "decrement"
-            | "strict" | "left" | "right" | "unsigned" | "xor"
-            | "contains" | "identity" | "not" | "and" | "or"
-        )
+        OPERATOR_MARKERS.contains(&name)
     }
 
     /// Default singleton wrappers — wrappers that typically contain exactly
@@ -665,6 +671,431 @@ pub mod helpers {
         }
         Ok(())
     }
+
+    /// Replace all children of a node with a single text node
+    pub fn replace_with_text(xot: &mut Xot, node: XotNode, text: &str) -> Result<(), xot::Error> {
+        let children: Vec<XotNode> = xot.children(node).collect();
+        for child in children {
+            xot.detach(child)?;
+        }
+        let text_node = xot.new_text(text);
+        xot.append(node, text_node)?;
+        Ok(())
+    }
+}
+
+// =============================================================================
+// DOC COMMENTS - Attach documentation to the declarations it documents
+// =============================================================================
+
+/// Element names that hold a comment, across languages
+const COMMENT_NAMES: &[&str] = &["comment", "line_comment", "block_comment"];
+
+/// Siblings that may sit between a doc comment and its declaration
+const ATTRIBUTE_NAMES: &[&str] = &["attribute_item", "annotation", "marker_annotation", "decorator"];
+
+/// Go has no doc comment syntax: a comment group directly above one of
+/// these documents it.
+const GO_DECLARATIONS: &[&str] = &[
+    "package", "function", "method", "typedef", "const", "var",
+    "field_declaration", "method_elem", "method_spec",
+];
+
+/// Which way a doc comment points
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DocStyle {
+    /// `///`, `/** */`: documents the declaration that follows
+    Outer,
+    /// Rust `//!`, `/*! */`: documents the enclosing item
+    Inner,
+}
+
+/// A tag parsed out of a doc comment
+struct DocTag {
+    name: &'static str,
+    param: Option<String>,
+    text: String,
+}
+
+/// Elements [`attach_doc_comments`] adds: the `<doc>` wrapper and its tags
+pub const DOC_ELEMENTS: &[&str] = &["doc", "summary", "param", "returns"];
+
+/// Attach leading doc comments to the declarations they document.
+///
+/// Each run of doc comments (`///`, `/** */`, any comment group in Go)
+/// directly above a declaration is moved into a `<doc>` element prepended to
+/// it, followed by the tags the language's convention defines: `<summary>`,
+/// `<param name="...">` and `<returns>`. Attributes between the comment and
+/// the declaration (`#[derive]`, decorators) are skipped; a blank line is not.
+/// Rust inner docs (`//!`) attach to their enclosing item, Python docstrings
+/// to the function, class or module they open.
+pub fn attach_doc_comments(xot: &mut Xot, root: XotNode, lang: &str) -> Result<(), xot::Error> {
+    use helpers::*;
+
+    if lang == "python" {
+        return attach_docstrings(xot, root);
+    }
+
+    let comments: Vec<XotNode> = xot.descendants(root)
+        .filter(|&n| is_comment(xot, n))
+        .collect();
+    for comment in comments {
+        // Already moved as part of an earlier group
+        if get_parent(xot, comment).and_then(|p| get_element_name(xot, p)).as_deref() == Some("doc") {
+            continue;
+        }
+        if is_inline_node(xot, comment) {
+            continue;
+        }
+        let style = match doc_style(&node_text(xot, comment), lang) {
+            Some(style) => style,
+            None => continue,
+        };
+
+        let mut group = vec![comment];
+        let mut last = comment;
+        while let Some(next) = next_element_sibling(xot, last) {
+            if !is_comment(xot, next)
+                || !is_adjacent(xot, last, next)
+                || doc_style(&node_text(xot, next), lang) != Some(style)
+            {
+                break;
+            }
+            group.push(next);
+            last = next;
+        }
+
+        let target = match style {
+            DocStyle::Outer => declaration_after(xot, last, lang),
+            DocStyle::Inner => enclosing_item(xot, comment),
+        };
+        if let Some(target) = target {
+            let text: Vec<String> = group.iter().map(|&c| node_text(xot, c)).collect();
+            attach(xot, target, &group, &strip_comment_syntax(&text.join("\n")))?;
+        }
+    }
+    Ok(())
+}
+
+/// Python: a string literal opening a function, class or module body.
+fn attach_docstrings(xot: &mut Xot, root: XotNode) -> Result<(), xot::Error> {
+    use helpers::*;
+
+    let owners: Vec<XotNode> = xot.descendants(root)
+        .filter(|&n| matches!(get_element_name(xot, n).as_deref(), Some("module" | "function" | "class")))
+        .collect();
+    for owner in owners {
+        let mut container = owner;
+        if get_element_name(xot, owner).as_deref() != Some("module") {
+            container = match child_named(xot, owner, "body") {
+                Some(body) => body,
+                None => continue,
+            };
+        }
+        let mut first = get_element_children(xot, container).into_iter().next();
+        if let Some(block) = first.filter(|&n| get_element_name(xot, n).as_deref() == Some("block")) {
+            first = get_element_children(xot, block).into_iter().next();
+        }
+        let string = match first.filter(|&n| get_element_name(xot, n).as_deref() == Some("string")) {
+            Some(string) => string,
+            None => continue,
+        };
+        // f-strings and concatenations are not docstrings
+        if child_named(xot, string, "interpolation").is_some() {
+            continue;
+        }
+        let content = match child_named(xot, string, "string_content") {
+            Some(content) => node_text(xot, content),
+            None => continue,
+        };
+        attach(xot, owner, &[string], &dedent(&content))?;
+    }
+    Ok(())
+}
+
+/// Move `comments` into a new `<doc>` first child of `target` and add the
+/// tags parsed from `body` (the comment text without comment syntax).
+fn attach(xot: &mut Xot, target: XotNode, comments: &[XotNode], body: &str) -> Result<(), xot::Error> {
+    use helpers::*;
+
+    let doc = prepend_empty_element(xot, target, "doc")?;
+    copy_source_location(xot, comments[0], doc);
+    if let Some(&last) = comments.last() {
+        for attr in ["end_line", "end_column"] {
+            if let Some(v) = get_attr(xot, last, attr) {
+                set_attr(xot, doc, attr, &v);
+            }
+        }
+    }
+    for &comment in comments {
+        xot.detach(comment)?;
+        xot.append(doc, comment)?;
+    }
+    for tag in parse_doc_tags(body) {
+        let element = append_empty_element(xot, doc, tag.name)?;
+        if let Some(param) = &tag.param {
+            set_attr(xot, element, "name", param);
+        }
+        let text = xot.new_text(&tag.text);
+        xot.append(element, text)?;
+    }
+    Ok(())
+}
+
+fn is_comment(xot: &Xot, node: XotNode) -> bool {
+    helpers::get_element_name(xot, node).is_some_and(|name| COMMENT_NAMES.contains(&name.as_str()))
+}
+
+fn child_named(xot: &Xot, node: XotNode, name: &str) -> Option<XotNode> {
+    helpers::get_element_children(xot, node)
+        .into_iter()
+        .find(|&c| helpers::get_element_name(xot, c).as_deref() == Some(name))
+}
+
+/// All text under `node`; comments can be split over marker elements
+/// (Rust's `outer_doc_comment_marker`).
+fn node_text(xot: &Xot, node: XotNode) -> String {
+    xot.descendants(node)
+        .filter_map(|n| xot.text_str(n))
+        .collect()
+}
+
+fn next_element_sibling(xot: &Xot, node: XotNode) -> Option<XotNode> {
+    xot.following_siblings(node)
+        .filter(|&s| s != node)
+        .find(|&s| xot.element(s).is_some())
+}
+
+/// `next` starts on the line after `prev` ends, or on the same line.
+fn is_adjacent(xot: &Xot, prev: XotNode, next: XotNode) -> bool {
+    let (end, start) = match (helpers::get_line(xot, prev, "end_line"), helpers::get_line(xot, next, "line")) {
+        (Some(end), Some(start)) => (end, start),
+        _ => return false,
+    };
+    // Line comments that own their newline end at column 1 of the next line
+    let end = if helpers::get_line(xot, prev, "end_column") == Some(1) { end - 1 } else { end };
+    start <= end + 1
+}
+
+fn doc_style(text: &str, lang: &str) -> Option<DocStyle> {
+    let text = text.trim_start();
+    if lang == "rust" && (text.starts_with("//!") || text.starts_with("/*!")) {
+        return Some(DocStyle::Inner);
+    }
+    if (text.starts_with("///") && !text.starts_with("////"))
+        || (text.starts_with("/**") && !text.starts_with("/**/"))
+        || (lang == "go" && (text.starts_with("//") || text.starts_with("/*")))
+    {
+        return Some(DocStyle::Outer);
+    }
+    None
+}
+
+/// The declaration a doc comment ending at `last` documents, skipping
+/// attributes in between.
+fn declaration_after(xot: &Xot, last: XotNode, lang: &str) -> Option<XotNode> {
+    let mut prev = last;
+    loop {
+        let next = next_element_sibling(xot, prev)?;
+        if !is_adjacent(xot, prev, next) || is_comment(xot, next) {
+            return None;
+        }
+        let name = helpers::get_element_name(xot, next)?;
+        if ATTRIBUTE_NAMES.contains(&name.as_str()) {
+            prev = next;
+            continue;
+        }
+        if lang == "go" && !GO_DECLARATIONS.contains(&name.as_str()) {
+            return None;
+        }
+        return Some(next);
+    }
+}
+
+/// The item an inner doc comment sits in: its parent, or the item owning
+/// that parent when it is a body (`mod m { //! ... }`).
+fn enclosing_item(xot: &Xot, comment: XotNode) -> Option<XotNode> {
+    let parent = helpers::get_parent(xot, comment)?;
+    match helpers::get_element_name(xot, parent).as_deref() {
+        Some("body" | "block" | "declaration_list") => helpers::get_parent(xot, parent).or(Some(parent)),
+        _ => Some(parent),
+    }
+}
+
+/// Remove comment delimiters and leading `*` from each line.
+fn strip_comment_syntax(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let mut line = line.trim();
+            for prefix in ["///", "//!", "//", "/**", "/*!", "/*"] {
+                if let Some(rest) = line.strip_prefix(prefix) {
+                    line = rest;
+                    break;
+                }
+            }
+            line = line.strip_suffix("*/").unwrap_or(line).trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Trim every line of a docstring; indentation carries no meaning here.
+fn dedent(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n")
+}
+
+/// Parse the tags of a doc comment body: C# XML doc (`<summary>`,
+/// `<param name>`, `<returns>`), Javadoc/JSDoc (`@param`, `@return`), Sphinx
+/// (`:param x:`, `:returns:`), or just a summary from the first paragraph.
+fn parse_doc_tags(body: &str) -> Vec<DocTag> {
+    if ["<summary", "<param", "<returns"].iter().any(|t| body.contains(t)) {
+        return xml_doc_tags(body);
+    }
+    let description: Vec<&str> = body.lines().take_while(|line| !is_block_tag(line)).collect();
+    let mut tags: Vec<DocTag> = first_paragraph(&description)
+        .map(|text| DocTag { name: "summary", param: None, text })
+        .into_iter()
+        .collect();
+
+    let mut current: Option<DocTag> = None;
+    for line in body.lines().skip(description.len()) {
+        if is_block_tag(line) {
+            tags.extend(current.take());
+            current = parse_block_tag(line);
+        } else if let Some(tag) = current.as_mut() {
+            tag.text.push(' ');
+            tag.text.push_str(line);
+        }
+    }
+    tags.extend(current);
+    for tag in &mut tags {
+        tag.text = normalize_space(&tag.text);
+    }
+    tags
+}
+
+fn first_paragraph(lines: &[&str]) -> Option<String> {
+    let paragraph: Vec<&str> = lines.iter()
+        .map(|line| line.trim())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    if paragraph.is_empty() { None } else { Some(paragraph.join(" ")) }
+}
+
+fn is_block_tag(line: &str) -> bool {
+    line.starts_with('@') || line.strip_prefix(':').is_some_and(|rest| rest.contains(':'))
+}
+
+/// `@param x text`, `@param {string} x text`, `@returns text`,
+/// `:param x: text`, `:param int x: text`, `:returns: text`
+fn parse_block_tag(line: &str) -> Option<DocTag> {
+    if let Some(field) = line.strip_prefix(':') {
+        let (field, text) = field.split_once(':')?;
+        let mut words = field.split_whitespace();
+        let tag = words.next()?;
+        return doc_tag(tag, words.last(), text);
+    }
+    let line = line.strip_prefix('@')?;
+    let (tag, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix('{') {
+        Some(typed) => typed.split_once('}').map_or("", |(_, r)| r).trim_start(),
+        None => rest,
+    };
+    if matches!(tag, "param" | "arg" | "argument") {
+        let (name, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        doc_tag(tag, Some(name), text.trim_start().trim_start_matches("- "))
+    } else {
+        doc_tag(tag, None, rest)
+    }
+}
+
+fn doc_tag(tag: &str, param: Option<&str>, text: &str) -> Option<DocTag> {
+    let name = match tag {
+        "param" | "arg" | "argument" => "param",
+        "return" | "returns" => "returns",
+        _ => return None,
+    };
+    // JSDoc optional parameters: `[name]`, `[name=default]`
+    let param = param.map(|p| {
+        p.trim_matches(|c| c == '[' || c == ']')
+            .split('=')
+            .next()
+            .unwrap_or(p)
+            .to_string()
+    });
+    Some(DocTag { name, param, text: text.to_string() })
+}
+
+/// `<summary>`, `<param name="x">` and `<returns>` elements of a C# XML doc
+/// comment; inline tags such as `<see cref="T"/>` keep their reference.
+fn xml_doc_tags(body: &str) -> Vec<DocTag> {
+    let mut tags = Vec::new();
+    let mut rest = body;
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let head_end = match rest.find('>') {
+            Some(i) => i,
+            None => break,
+        };
+        let head = &rest[..head_end];
+        let tag = head.split_whitespace().next().unwrap_or("");
+        let name = match tag {
+            "summary" => "summary",
+            "param" => "param",
+            "returns" => "returns",
+            _ => continue,
+        };
+        if head.ends_with('/') {
+            continue;
+        }
+        let content = &rest[head_end + 1..];
+        let close = format!("</{}>", tag);
+        let end = match content.find(&close) {
+            Some(i) => i,
+            None => continue,
+        };
+        let param = if name == "param" { xml_attr(head, "name") } else { None };
+        tags.push(DocTag { name, param, text: normalize_space(&inline_xml_text(&content[..end])) });
+        rest = &content[end + close.len()..];
+    }
+    tags
+}
+
+fn xml_attr(head: &str, attr: &str) -> Option<String> {
+    let start = head.find(&format!("{}=", attr))? + attr.len() + 1;
+    let quote = head[start..].chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let value = &head[start + 1..];
+    Some(value[..value.find(quote)?].to_string())
+}
+
+/// Text of an XML doc fragment with inline tags replaced by what they name.
+fn inline_xml_text(fragment: &str) -> String {
+    let mut text = String::new();
+    let mut rest = fragment;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let close = match rest[open..].find('>') {
+            Some(i) => open + i,
+            None => {
+                rest = &rest[open..];
+                break;
+            }
+        };
+        let head = &rest[open + 1..close];
+        if let Some(reference) = xml_attr(head, "cref").or_else(|| xml_attr(head, "name")).or_else(|| xml_attr(head, "langword")) {
+            text.push_str(&reference);
+        }
+        rest = &rest[close + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+fn normalize_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 #[cfg(test)]
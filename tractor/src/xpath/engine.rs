@@ -1,15 +1,21 @@
 //! XPath 3.1 query engine implementation
 
-use super::{Match, XPathError};
+use super::{Match, ValidationResult, XPathError};
 use super::map_normalize::{try_normalize_and_serialize_map, extract_map_value_expr};
+use super::functions::{self, SourceMap};
+use super::library::XPathLibrary;
 use super::match_result::XmlNode;
+use super::variables::{XPathValue, XPathVariables};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::cell::RefCell;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
-use xee_xpath::{Documents, DocumentHandle, Itemable, Queries, Query, Sequence, SerializationParameters, query::SequenceQuery};
+use xee_interpreter::atomic::Atomic;
+use xee_xpath::{Documents, DocumentHandle, Item, Itemable, Queries, Query, Sequence, SerializationParameters, function::Array, query::SequenceQuery};
 use xee_xpath::context::{StaticContextBuilder, Variables};
 use xot::{Node, Value, Xot};
 use xot::xmlname::OwnedName;
@@ -57,8 +63,29 @@ static STRIP_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"\s*(line|column|end_line|end_column)="[^"]*""#).unwrap()
 });
 
+/// An element's `(line, column, end_line, end_column)`.
+pub type NodeLocation = (u32, u32, u32, u32);
+
+/// Source locations of elements that carry no location attributes.
+///
+/// XML input is queried with the file's own attributes only, so its element
+/// locations are kept here instead of in `line`/`column` attributes.
+pub type NodeLocations = HashMap<Node, NodeLocation>;
+
 /// Extract location directly from xot node attributes (fast path - no serialization)
-fn extract_location_from_xot(xot: &Xot, node: Node) -> (u32, u32, u32, u32) {
+///
+/// Attribute nodes carry no location of their own and report the location
+/// of the element they belong to. Elements listed in `node_locations` use
+/// that location instead of their attributes.
+fn extract_location_from_xot(xot: &Xot, node: Node, node_locations: Option<&NodeLocations>) -> (u32, u32, u32, u32) {
+    if let Value::Attribute(_) = xot.value(node) {
+        if let Some(parent) = xot.parent(node) {
+            return extract_location_from_xot(xot, parent, node_locations);
+        }
+    }
+    if let Some(location) = node_locations.and_then(|locations| locations.get(&node)) {
+        return *location;
+    }
     if let Value::Element(_) = xot.value(node) {
         let mut line = 1u32;
         let mut col = 1u32;
@@ -88,6 +115,34 @@ fn extract_location_from_xot(xot: &Xot, node: Node) -> (u32, u32, u32, u32) {
     }
 }
 
+/// String value of a matched node, leaving out `injected` sub-trees: their
+/// text repeats the host's own embedded code (see `parser::injection`).
+/// XML input is taken as-is, since its element names are the file's own.
+fn match_string_value(xot: &Xot, node: Node, language: &str) -> String {
+    let (Some(injected), Some(lang)) = (xot.name("injected"), xot.name("lang")) else {
+        return xot.string_value(node);
+    };
+    if language == "xml" || xot.element(node).is_none() {
+        return xot.string_value(node);
+    }
+    fn push_text(xot: &Xot, node: Node, injected: xot::NameId, lang: xot::NameId, value: &mut String) {
+        for child in xot.children(node) {
+            match xot.value(child) {
+                Value::Text(text) => value.push_str(text.get()),
+                Value::Element(element)
+                    if !(element.name() == injected && xot.attributes(child).get(lang).is_some()) =>
+                {
+                    push_text(xot, child, injected, lang, value);
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut value = String::new();
+    push_text(xot, node, injected, lang, &mut value);
+    value
+}
+
 /// Walk an xot node tree and build a native `XmlNode` IR.
 pub fn xot_node_to_xml_node(xot: &Xot, node: Node) -> XmlNode {
     match xot.value(node) {
@@ -202,185 +257,464 @@ fn json_value_to_xml_node(val: &serde_json::Value) -> XmlNode {
     }
 }
 
-// Thread-local cache for compiled XPath queries
-// Each thread gets its own compiled query to avoid RefCell conflicts
+/// The queries of one run, compiled before any file is parsed.
+///
+/// Adding a query checks that it compiles and expands its `tractor:` calls
+/// once; workers share the expansions through an `Arc`. xee programs hold
+/// `Rc`s and can't cross threads, so each worker builds the program of a
+/// query from its expansion the first time it runs it, and keeps the
+/// run's programs until it runs queries of another run. The thread that
+/// adds the queries keeps the programs it compiled to check them.
+///
+/// Queries run without one are compiled once per thread too, into a cache
+/// of their own.
+#[derive(Debug)]
+pub struct CompiledQueries {
+    id: u64,
+    variables: Option<Arc<XPathVariables>>,
+    library: Option<Arc<XPathLibrary>>,
+    prepared: HashMap<String, Arc<Prepared>>,
+}
+
+/// Identifies the runs whose programs the workers hold.
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
 thread_local! {
-    static QUERY_CACHE: RefCell<Option<(String, SequenceQuery)>> = const { RefCell::new(None) };
+    /// This thread's programs for the run it last ran queries of.
+    static PROGRAMS: RefCell<(u64, HashMap<String, CompiledQuery>)> = RefCell::new((0, HashMap::new()));
+
+    /// This thread's programs for queries run without [`CompiledQueries`]:
+    /// through the library API, wasm and [`XeeParseResult::query`](crate::XeeParseResult::query).
+    static QUERY_CACHE: RefCell<HashMap<String, CompiledQuery>> = RefCell::new(HashMap::new());
 }
 
-/// Build a StaticContextBuilder that declares the built-in tractor variables ($file).
-pub fn tractor_static_context() -> StaticContextBuilder<'static> {
+/// Most programs a thread keeps for a run, or for queries outside one. More
+/// distinct queries than this start over rather than growing without bound.
+const PROGRAM_CAPACITY: usize = 1024;
+
+/// Add `compiled` to `programs`, emptying them first when they are full.
+fn keep_program(programs: &mut HashMap<String, CompiledQuery>, xpath: &str, compiled: CompiledQuery) {
+    if programs.len() >= PROGRAM_CAPACITY {
+        programs.clear();
+    }
+    programs.insert(xpath.to_string(), compiled);
+}
+
+impl CompiledQueries {
+    /// An empty set of queries that declare and bind the user `variables`
+    pub fn new(variables: Option<Arc<XPathVariables>>) -> Self {
+        CompiledQueries {
+            id: NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed),
+            variables: variables.filter(|v| !v.is_empty()),
+            library: None,
+            prepared: HashMap::new(),
+        }
+    }
+
+    /// Expand the config's `functions:` and `let:` in the queries
+    pub fn with_library(mut self, library: Option<Arc<XPathLibrary>>) -> Self {
+        self.library = library.filter(|l| !l.is_empty());
+        self
+    }
+
+    /// Compile `xpath` for the run, or say why it doesn't compile
+    pub fn add(&mut self, xpath: &str) -> ValidationResult {
+        super::validation(xpath, |xpath| {
+            if self.prepared.contains_key(xpath) {
+                return Ok(());
+            }
+            let compiled = compile_query(xpath, self.variables.as_deref(), self.library.as_deref())?;
+            self.prepared.insert(xpath.to_string(), Arc::clone(&compiled.prepared));
+            self.with_programs(|programs| keep_program(programs, xpath, compiled));
+            Ok(())
+        })
+    }
+
+    /// The user variables the queries declare
+    pub fn variables(&self) -> Option<&XPathVariables> {
+        self.variables.as_deref()
+    }
+
+    /// `xpath` with the config's functions and fragments expanded, for
+    /// checks that read a query's text, like the tree passes it needs.
+    pub fn expand<'a>(&self, xpath: &'a str) -> Cow<'a, str> {
+        match self.library.as_deref().map(|library| library.expand(xpath)) {
+            Some(Ok(expanded)) => Cow::Owned(expanded),
+            _ => Cow::Borrowed(xpath),
+        }
+    }
+
+    /// Run `f` on this thread's program for `xpath`, building it on first
+    /// use. Queries that weren't added, such as the prefixes `--explain`
+    /// tries, are compiled here and kept for the run too.
+    fn with_program<R>(
+        &self,
+        xpath: &str,
+        f: impl FnOnce(&CompiledQuery) -> Result<R, XPathError>,
+    ) -> Result<R, XPathError> {
+        self.with_programs(|programs| {
+            if !programs.contains_key(xpath) {
+                let compiled = match self.prepared.get(xpath) {
+                    Some(prepared) => CompiledQuery {
+                        query: prepared.compile(self.variables.as_deref())?,
+                        prepared: Arc::clone(prepared),
+                    },
+                    None => compile_query(xpath, self.variables.as_deref(), self.library.as_deref())?,
+                };
+                keep_program(programs, xpath, compiled);
+            }
+            f(&programs[xpath])
+        })
+    }
+
+    /// This thread's programs for the run, dropping those of an earlier run
+    fn with_programs<R>(&self, f: impl FnOnce(&mut HashMap<String, CompiledQuery>) -> R) -> R {
+        PROGRAMS.with(|programs| {
+            let mut programs = programs.borrow_mut();
+            if programs.0 != self.id {
+                *programs = (self.id, HashMap::new());
+            }
+            f(&mut programs.1)
+        })
+    }
+}
+
+/// A query with its user functions and `tractor:` calls expanded, ready
+/// to compile.
+#[derive(Debug)]
+struct Prepared {
+    xpath: String,
+    /// Maps error positions back to the query with user functions expanded.
+    source_map: SourceMap,
+    /// Maps those back to the query as written, when it used any.
+    library_map: Option<SourceMap>,
+    /// Whether the query reads the source lines.
+    uses_source: bool,
+    /// Whether the query reads the locations of elements without location
+    /// attributes.
+    uses_locations: bool,
+}
+
+impl Prepared {
+    fn new(xpath: &str, library: Option<&XPathLibrary>) -> Result<Self, XPathError> {
+        let (xpath, library_map) = match library {
+            Some(library) => {
+                let (expanded, map) = library.expand_mapped(xpath).map_err(XPathError::Compile)?;
+                (Cow::Owned(expanded), Some(map))
+            }
+            None => (Cow::Borrowed(xpath), None),
+        };
+        let expanded = functions::expand(&xpath);
+        Ok(Prepared {
+            xpath: expanded.xpath.into_owned(),
+            source_map: expanded.source_map,
+            library_map,
+            uses_source: expanded.uses_source,
+            uses_locations: expanded.uses_locations,
+        })
+    }
+
+    fn compile(&self, user: Option<&XPathVariables>) -> Result<SequenceQuery, XPathError> {
+        Queries::new(tractor_static_context(user))
+            .sequence(&self.xpath)
+            .map_err(|e| {
+                let message = self.source_map.original_message(e.to_string());
+                XPathError::Compile(match &self.library_map {
+                    Some(map) => map.original_message(message),
+                    None => message,
+                })
+            })
+    }
+}
+
+/// A query compiled on this thread.
+pub(super) struct CompiledQuery {
+    query: SequenceQuery,
+    prepared: Arc<Prepared>,
+}
+
+/// Built-in variables the `tractor:` functions read.
+const TRACTOR_VARIABLES: &[&str] = &[
+    "tractor.language",
+    "tractor.lines",
+    "tractor.location-keys",
+    "tractor.location-values",
+];
+
+/// Build a StaticContextBuilder that declares the built-in tractor variables
+/// ($file, plus the ones the `tractor:` functions read) and the given user
+/// variables.
+pub fn tractor_static_context(user: Option<&XPathVariables>) -> StaticContextBuilder<'static> {
+    let mut names = vec![OwnedName::name("file")];
+    names.extend(TRACTOR_VARIABLES.iter().map(|name| OwnedName::name(name)));
+    names.extend(user.into_iter().flat_map(XPathVariables::iter).map(|(name, _)| OwnedName::name(name)));
     let mut scb = StaticContextBuilder::default();
-    scb.variable_names([OwnedName::name("file")]);
+    scb.variable_names(names);
     scb
 }
 
-/// Build a Variables map binding $file to the given path.
-fn tractor_variables(file_path: &str) -> Variables {
+/// Build a Variables map binding $file to the given path, plus the user
+/// variables. The source lines and element locations are only bound when
+/// the query reads them.
+fn tractor_variables(
+    file_path: &str,
+    language: &str,
+    source_lines: Option<&[String]>,
+    locations: Option<(Vec<Item>, Vec<Sequence>)>,
+    user: Option<&XPathVariables>,
+) -> Variables {
     let mut vars = Variables::default();
     vars.insert(OwnedName::name("file"), Sequence::from(file_path.to_string()));
+    vars.insert(OwnedName::name("tractor.language"), Sequence::from(language.to_string()));
+    let lines = source_lines.map_or_else(Sequence::default, |lines| {
+        let lines: Vec<Sequence> = lines.iter().map(|line| Sequence::from(line.clone())).collect();
+        Sequence::from(Array::from(lines))
+    });
+    vars.insert(OwnedName::name("tractor.lines"), lines);
+    let (keys, values) = locations.unwrap_or_default();
+    vars.insert(OwnedName::name("tractor.location-keys"), Sequence::from(keys));
+    vars.insert(OwnedName::name("tractor.location-values"), Sequence::from(Array::from(values)));
+    for (name, values) in user.into_iter().flat_map(XPathVariables::iter) {
+        let items: Vec<Item> = values.iter().map(|value| match value {
+            XPathValue::String(s) => Atomic::from(s.clone()).into(),
+            XPathValue::Integer(i) => Atomic::from(*i).into(),
+            XPathValue::Boolean(b) => Atomic::from(*b).into(),
+        }).collect();
+        vars.insert(OwnedName::name(name), Sequence::from(items));
+    }
     vars
 }
 
+/// Compile a query, expanding the user functions of `library` and its
+/// `tractor:` function calls.
+pub(super) fn compile_query(
+    xpath: &str,
+    user: Option<&XPathVariables>,
+    library: Option<&XPathLibrary>,
+) -> Result<CompiledQuery, XPathError> {
+    let prepared = Arc::new(Prepared::new(xpath, library)?);
+    Ok(CompiledQuery { query: prepared.compile(user)?, prepared })
+}
+
+/// `node_locations` as the `tractor:` functions look them up: the key of
+/// each located element below `root` (its position among its element
+/// siblings, for it and each ancestor, joined by `/`) and its location.
+fn location_variables(xot: &Xot, root: Node, node_locations: &NodeLocations) -> (Vec<Item>, Vec<Sequence>) {
+    fn walk(
+        xot: &Xot,
+        node: Node,
+        key: &str,
+        node_locations: &NodeLocations,
+        keys: &mut Vec<Item>,
+        values: &mut Vec<Sequence>,
+    ) {
+        let children = xot.children(node).filter(|&child| xot.element(child).is_some());
+        for (index, child) in children.enumerate() {
+            let key = if key.is_empty() { index.to_string() } else { format!("{}/{}", key, index) };
+            if let Some(&(line, column, end_line, end_column)) = node_locations.get(&child) {
+                keys.push(Atomic::from(key.clone()).into());
+                let location: Vec<Item> = [line, column, end_line, end_column].into_iter()
+                    .map(|n| Atomic::from(i64::from(n)).into())
+                    .collect();
+                values.push(Sequence::from(location));
+            }
+            walk(xot, child, &key, node_locations, keys, values);
+        }
+    }
+    let mut keys = Vec::with_capacity(node_locations.len());
+    let mut values = Vec::with_capacity(node_locations.len());
+    walk(xot, root, "", node_locations, &mut keys, &mut values);
+    (keys, values)
+}
+
 /// Execute a query directly on Documents (no XML parsing needed)
 ///
 /// This is the fast path - use when you've built directly into Documents
 /// using XeeBuilder.
+#[allow(clippy::too_many_arguments)]
 fn execute_direct_query(
     xpath: &str,
     documents: &mut Documents,
     doc_handle: DocumentHandle,
     source_lines: Arc<Vec<String>>,
     file_path: &str,
+    language: &str,
+    node_locations: Option<&NodeLocations>,
+    queries: Option<&CompiledQueries>,
 ) -> Result<Vec<Match>, XPathError> {
-    QUERY_CACHE.with(|cache| {
-        let mut cache = cache.borrow_mut();
-
-        // Check if we have a cached query for this XPath
-        let query = if let Some((cached_xpath, cached_query)) = cache.as_ref() {
-            if cached_xpath == xpath {
-                cached_query
-            } else {
-                // Different XPath, need to recompile
-                let queries = Queries::new(tractor_static_context());
-                let new_query = queries
-                    .sequence(xpath)
-                    .map_err(|e| XPathError::Compile(e.to_string()))?;
-                *cache = Some((xpath.to_string(), new_query));
-                &cache.as_ref().unwrap().1
+    let run = |compiled: &CompiledQuery| run_query(
+        compiled, xpath, documents, doc_handle, source_lines, file_path, language, node_locations,
+        queries.and_then(CompiledQueries::variables),
+    );
+    match queries {
+        Some(queries) => queries.with_program(xpath, run),
+        None => QUERY_CACHE.with(|programs| {
+            let mut programs = programs.borrow_mut();
+            if !programs.contains_key(xpath) {
+                keep_program(&mut programs, xpath, compile_query(xpath, None, None)?);
             }
-        } else {
-            // No cached query, compile it
-            let queries = Queries::new(tractor_static_context());
-            let new_query = queries
-                .sequence(xpath)
-                .map_err(|e| XPathError::Compile(e.to_string()))?;
-            *cache = Some((xpath.to_string(), new_query));
-            &cache.as_ref().unwrap().1
-        };
+            run(&programs[xpath])
+        }),
+    }
+}
 
-        // Execute the query with $file variable bound
-        let t1 = Instant::now();
-        let vars = tractor_variables(file_path);
-        let context_item = doc_handle.to_item(documents)
-            .map_err(|e| XPathError::Execute(e.to_string()))?;
-        let results = query
-            .execute_build_context(documents, |builder| {
-                builder.context_item(context_item);
-                builder.variables(vars);
-            })
-            .map_err(|e: xee_xpath::error::Error| XPathError::Execute(e.to_string()))?;
-        let t2 = Instant::now();
-
-        // Convert results to Match objects
-        let mut matches = Vec::new();
-        let mut xml_serialize_time = 0u64;
-        let mut string_value_time = 0u64;
-
-        for item in results.iter() {
-            match item {
-                xee_xpath::Item::Node(node) => {
-                    let xot = documents.xot();
-                    // Extract location directly from xot attributes (fast - no serialization)
-                    let (line, col, end_line, end_col) = extract_location_from_xot(xot, node);
-
-                    let ts0 = Instant::now();
-                    let value = xot.string_value(node);
-                    let ts1 = Instant::now();
-                    // Build native XmlNode IR (no XML string serialization)
-                    let xml_node = xot_node_to_xml_node(xot, node);
-                    let ts2 = Instant::now();
-
-                    string_value_time += (ts1 - ts0).as_micros() as u64;
-                    xml_serialize_time += (ts2 - ts1).as_micros() as u64;
-
-                    let m = Match::with_location(
-                        file_path.to_string(),
-                        line,
-                        col,
-                        end_line,
-                        end_col,
-                        value,
-                        Arc::clone(&source_lines),
-                    ).with_xml_node(xml_node);
-
-                    matches.push(m);
-                }
-                xee_xpath::Item::Atomic(atomic) => {
-                    let value = atomic.xpath_representation();
-                    matches.push(Match::new(file_path.to_string(), value));
-                }
-                xee_xpath::Item::Function(func) => {
-                    let json_str = function_to_json_string(&func, documents.xot_mut());
-                    let mut m = Match::new(file_path.to_string(), String::new());
-                    // Parse the JSON into structured XmlNode IR — value stays empty,
-                    // all data lives in the tree field.
-                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                        m.xml_node = Some(json_value_to_xml_node(&parsed));
-                    } else if let Some(result) = try_normalize_and_serialize_map(&func, documents) {
-                        // The direct JSON serialization failed (likely because a
-                        // map value is a multi-item sequence). Try normalizing the
-                        // map by wrapping sequence values in arrays.
-                        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&result.json) {
-                            for key in &result.sequence_keys {
-                                let leaf = key.rsplit('.').next().unwrap_or(key);
-                                if let Some(expr) = extract_map_value_expr(xpath, key) {
-                                    eprintln!(
-                                        "Warning: \"{}\": {} matched multiple values and was \
-                                         automatically converted to an array.\n\
-                                         With different input this could be a single value instead, \
-                                         which would break tools consuming this output.\n  \
-                                         Always array:  \"{}\": array {{ {} }}\n  \
-                                         Always single: \"{}\": ({})[1]",
-                                        leaf, expr, leaf, expr, leaf, expr
-                                    );
-                                } else {
-                                    eprintln!(
-                                        "Warning: \"{}\" matched multiple values and was \
-                                         automatically converted to an array.\n\
-                                         With different input this could be a single value instead, \
-                                         which would break tools consuming this output.\n  \
-                                         Always array:  \"{}\": array {{ expr }}\n  \
-                                         Always single: \"{}\": (expr)[1]",
-                                        leaf, leaf, leaf
-                                    );
-                                }
+/// Run a compiled query and convert its results to matches.
+#[allow(clippy::too_many_arguments)]
+fn run_query(
+    compiled: &CompiledQuery,
+    xpath: &str,
+    documents: &mut Documents,
+    doc_handle: DocumentHandle,
+    source_lines: Arc<Vec<String>>,
+    file_path: &str,
+    language: &str,
+    node_locations: Option<&NodeLocations>,
+    user: Option<&XPathVariables>,
+) -> Result<Vec<Match>, XPathError> {
+    // Execute the query with $file and the user variables bound
+    let t1 = Instant::now();
+    let context_item = doc_handle.to_item(documents)
+        .map_err(|e| XPathError::Execute(e.to_string()))?;
+    let locations = match (&context_item, node_locations) {
+        (Item::Node(root), Some(node_locations)) if compiled.prepared.uses_locations => {
+            Some(location_variables(documents.xot(), *root, node_locations))
+        }
+        _ => None,
+    };
+    let vars = tractor_variables(
+        file_path,
+        language,
+        compiled.prepared.uses_source.then_some(source_lines.as_slice()),
+        locations,
+        user,
+    );
+    let results = compiled.query
+        .execute_build_context(documents, |builder| {
+            builder.context_item(context_item);
+            builder.variables(vars);
+        })
+        .map_err(|e: xee_xpath::error::Error| {
+            XPathError::Execute(compiled.prepared.source_map.original_message(e.to_string()))
+        })?;
+    let t2 = Instant::now();
+
+    // Convert results to Match objects
+    let mut matches = Vec::new();
+    let mut xml_serialize_time = 0u64;
+    let mut string_value_time = 0u64;
+
+    for item in results.iter() {
+        match item {
+            xee_xpath::Item::Node(node) => {
+                let xot = documents.xot();
+                // Extract location directly from xot attributes (fast - no serialization)
+                let (line, col, end_line, end_col) = extract_location_from_xot(xot, node, node_locations);
+
+                let ts0 = Instant::now();
+                let value = match_string_value(xot, node, language);
+                let ts1 = Instant::now();
+                // Build native XmlNode IR (no XML string serialization)
+                let xml_node = xot_node_to_xml_node(xot, node);
+                let ts2 = Instant::now();
+
+                string_value_time += (ts1 - ts0).as_micros() as u64;
+                xml_serialize_time += (ts2 - ts1).as_micros() as u64;
+
+                let m = Match::with_location(
+                    file_path.to_string(),
+                    line,
+                    col,
+                    end_line,
+                    end_col,
+                    value,
+                    Arc::clone(&source_lines),
+                ).with_xml_node(xml_node);
+
+                matches.push(m);
+            }
+            xee_xpath::Item::Atomic(atomic) => {
+                let value = atomic.xpath_representation();
+                matches.push(Match::new(file_path.to_string(), value));
+            }
+            xee_xpath::Item::Function(func) => {
+                let json_str = function_to_json_string(&func, documents.xot_mut());
+                let mut m = Match::new(file_path.to_string(), String::new());
+                // Parse the JSON into structured XmlNode IR — value stays empty,
+                // all data lives in the tree field.
+                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&json_str) {
+                    m.xml_node = Some(json_value_to_xml_node(&parsed));
+                } else if let Some(result) = try_normalize_and_serialize_map(&func, documents) {
+                    // The direct JSON serialization failed (likely because a
+                    // map value is a multi-item sequence). Try normalizing the
+                    // map by wrapping sequence values in arrays.
+                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&result.json) {
+                        for key in &result.sequence_keys {
+                            let leaf = key.rsplit('.').next().unwrap_or(key);
+                            if let Some(expr) = extract_map_value_expr(xpath, key) {
+                                eprintln!(
+                                    "Warning: \"{}\": {} matched multiple values and was \
+                                     automatically converted to an array.\n\
+                                     With different input this could be a single value instead, \
+                                     which would break tools consuming this output.\n  \
+                                     Always array:  \"{}\": array {{ {} }}\n  \
+                                     Always single: \"{}\": ({})[1]",
+                                    leaf, expr, leaf, expr, leaf, expr
+                                );
+                            } else {
+                                eprintln!(
+                                    "Warning: \"{}\" matched multiple values and was \
+                                     automatically converted to an array.\n\
+                                     With different input this could be a single value instead, \
+                                     which would break tools consuming this output.\n  \
+                                     Always array:  \"{}\": array {{ expr }}\n  \
+                                     Always single: \"{}\": (expr)[1]",
+                                    leaf, leaf, leaf
+                                );
                             }
-                            m.xml_node = Some(json_value_to_xml_node(&parsed));
                         }
+                        m.xml_node = Some(json_value_to_xml_node(&parsed));
                     }
-                    matches.push(m);
                 }
+                matches.push(m);
             }
         }
-        let t3 = Instant::now();
+    }
+    let t3 = Instant::now();
 
-        TIMING_XML_SERIALIZE.fetch_add(xml_serialize_time, Ordering::Relaxed);
-        TIMING_STRING_VALUE.fetch_add(string_value_time, Ordering::Relaxed);
-        TIMING_MATCH_COUNT.fetch_add(matches.len() as u64, Ordering::Relaxed);
+    TIMING_XML_SERIALIZE.fetch_add(xml_serialize_time, Ordering::Relaxed);
+    TIMING_STRING_VALUE.fetch_add(string_value_time, Ordering::Relaxed);
+    TIMING_MATCH_COUNT.fetch_add(matches.len() as u64, Ordering::Relaxed);
 
-        // Record timing stats (no XML load time for direct queries!)
-        TIMING_QUERY_EXEC.fetch_add((t2 - t1).as_micros() as u64, Ordering::Relaxed);
-        TIMING_RESULT_PROC.fetch_add((t3 - t2).as_micros() as u64, Ordering::Relaxed);
-        TIMING_COUNT.fetch_add(1, Ordering::Relaxed);
+    // Record timing stats (no XML load time for direct queries!)
+    TIMING_QUERY_EXEC.fetch_add((t2 - t1).as_micros() as u64, Ordering::Relaxed);
+    TIMING_RESULT_PROC.fetch_add((t3 - t2).as_micros() as u64, Ordering::Relaxed);
+    TIMING_COUNT.fetch_add(1, Ordering::Relaxed);
 
-        Ok(matches)
-    })
+    Ok(matches)
 }
 
 /// XPath query engine using xee-xpath
 ///
-/// Queries are automatically cached per-thread for efficiency when querying
-/// many files with the same XPath expression.
+/// Give it the run's [`CompiledQueries`] to compile each query once per
+/// thread when querying many files with the same XPath expression.
 pub struct XPathEngine {
     verbose: bool,
     ignore_whitespace: bool,
+    language: String,
+    node_locations: Option<Arc<NodeLocations>>,
+    queries: Option<Arc<CompiledQueries>>,
 }
 
 impl XPathEngine {
     /// Create a new XPath engine
     pub fn new() -> Self {
-        XPathEngine { verbose: false, ignore_whitespace: false }
+        XPathEngine {
+            verbose: false,
+            ignore_whitespace: false,
+            language: String::new(),
+            node_locations: None,
+            queries: None,
+        }
     }
 
     /// Enable verbose mode for debugging
@@ -389,6 +723,25 @@ impl XPathEngine {
         self
     }
 
+    /// Set the language `tractor:language()` returns
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = language.to_string();
+        self
+    }
+
+    /// Report match locations from `node_locations` for the elements it lists
+    pub fn with_node_locations(mut self, node_locations: Option<Arc<NodeLocations>>) -> Self {
+        self.node_locations = node_locations;
+        self
+    }
+
+    /// Take compiled queries from the run's `queries`, which also declare
+    /// its user variables. Without them every query is compiled anew.
+    pub fn with_queries(mut self, queries: Option<Arc<CompiledQueries>>) -> Self {
+        self.queries = queries;
+        self
+    }
+
     /// Enable whitespace-insensitive matching
     /// When enabled, whitespace is stripped from text nodes before XPath matching
     pub fn with_ignore_whitespace(mut self, ignore: bool) -> Self {
@@ -411,7 +764,16 @@ impl XPathEngine {
         source_lines: Arc<Vec<String>>,
         file_path: &str,
     ) -> Result<Vec<Match>, XPathError> {
-        execute_direct_query(xpath, documents, doc_handle, source_lines, file_path)
+        execute_direct_query(
+            xpath,
+            documents,
+            doc_handle,
+            source_lines,
+            file_path,
+            &self.language,
+            self.node_locations.as_deref(),
+            self.queries.as_deref(),
+        )
     }
 
     /// Strip location metadata from XML
@@ -475,27 +837,46 @@ mod tests {
     fn test_query_caching() {
         use crate::parser::load_xml_string_to_documents;
 
-        // Test that querying the same XPath multiple times works
-        // (queries are cached per-thread automatically)
-        let xml1 = r#"<root><item>a</item></root>"#;
-        let xml2 = r#"<root><item>b</item><item>c</item></root>"#;
+        let mut queries = CompiledQueries::new(None);
+        assert!(queries.add("//item").valid);
+        let invalid = queries.add("//item[");
+        assert!(!invalid.valid);
+        assert!(invalid.error_start.is_some());
+        let engine = XPathEngine::new().with_queries(Some(Arc::new(queries)));
+
+        // Workers share the compiled queries, and compile others on demand
+        std::thread::scope(|scope| {
+            for (xml, count) in [("<root><item>a</item></root>", 1), ("<root><item>b</item><item>c</item></root>", 2)] {
+                let engine = &engine;
+                scope.spawn(move || {
+                    let mut result = load_xml_string_to_documents(xml, "test.xml".to_string()).unwrap();
+                    for xpath in ["//item", "//item", "/root/item"] {
+                        let matches = engine.query_documents(
+                            &mut result.documents, result.doc_handle,
+                            xpath, Arc::new(vec![]), "test.xml"
+                        ).unwrap();
+                        assert_eq!(matches.len(), count);
+                    }
+                });
+            }
+        });
+    }
 
-        let engine = XPathEngine::new();
+    #[test]
+    fn test_query_caching_without_a_run() {
+        use crate::parser::load_xml_string_to_documents;
 
-        let mut result1 = load_xml_string_to_documents(xml1, "test1.xml".to_string()).unwrap();
-        let matches1 = engine.query_documents(
-            &mut result1.documents, result1.doc_handle,
-            "//item", Arc::new(vec![]), "test1.xml"
-        ).unwrap();
-        assert_eq!(matches1.len(), 1);
+        let mut result = load_xml_string_to_documents("<root><item>a</item></root>", "test.xml".to_string()).unwrap();
+        for _ in 0..2 {
+            assert_eq!(result.query("//item[. = 'a']").unwrap().len(), 1);
+        }
+        assert!(QUERY_CACHE.with(|programs| programs.borrow().contains_key("//item[. = 'a']")));
 
-        // Same query, different document - should use cached query
-        let mut result2 = load_xml_string_to_documents(xml2, "test2.xml".to_string()).unwrap();
-        let matches2 = engine.query_documents(
-            &mut result2.documents, result2.doc_handle,
-            "//item", Arc::new(vec![]), "test2.xml"
-        ).unwrap();
-        assert_eq!(matches2.len(), 2);
+        // Full caches start over
+        for i in 0..=PROGRAM_CAPACITY {
+            result.query(&format!("//item[{}]", i + 1)).unwrap();
+        }
+        assert!(QUERY_CACHE.with(|programs| programs.borrow().len()) <= PROGRAM_CAPACITY);
     }
 
     #[test]
@@ -847,6 +1228,12 @@ mod tests {
                 tree_mode: None,
                 ignore_whitespace: false,
                 parse_depth: None,
+                injections: false,
+                doc_comments: false,
+                embedded_language: None,
+                transforms: None,
+                encoding: None,
+                queries: None,
             },
         ).unwrap();
 
@@ -872,4 +1259,123 @@ mod tests {
         assert_eq!(matches.len(), 1, "Should find number inside value");
     }
 
+    #[test]
+    fn test_tractor_functions() {
+        use crate::parser::{parse, ParseInput, ParseOptions};
+        use crate::tree_mode::TreeMode;
+
+        let source = "function greet(name) {\n  // say hi\n  return \"héllo \" + name;\n}\n";
+        let mut result = parse(
+            ParseInput::Inline { content: source, file_label: "src/app/greet.ts" },
+            ParseOptions { language: Some("typescript"), tree_mode: Some(TreeMode::Raw), ..Default::default() },
+        ).unwrap();
+
+        let mut count = |xpath: &str| result.query(xpath).unwrap().len();
+        assert_eq!(count("//function_declaration[tractor:lines-of(.) = 4]"), 1);
+        assert_eq!(count("//function_declaration[tractor:kind(.) = 'function_declaration']"), 1);
+        assert_eq!(count("//string[tractor:line(.) = 3 and tractor:column(.) = 10]"), 1);
+        assert_eq!(count("//string[tractor:source(.) = '\"héllo \"']"), 1);
+        assert_eq!(count("//function_declaration[contains(tractor:source(.), '// say hi')]"), 1);
+        assert_eq!(count("//program[tractor:file-name() = 'greet.ts' and tractor:dir() = 'src/app']"), 1);
+        assert_eq!(count("//program[tractor:language() = 'typescript']"), 1);
+        assert!(result.query("tractor:nope()").is_err(), "unknown functions fail to compile");
+    }
+
+    #[test]
+    fn test_tractor_functions_on_xml() {
+        use crate::parser::load_xml_string_to_documents;
+
+        // XML input keeps its locations out of the document's attributes
+        let xml = "<root>\n  <item name=\"a\" line=\"40\">\n    <value>héllo</value>\n  </item>\n</root>\n";
+        let mut result = load_xml_string_to_documents(xml, "data.xml".to_string()).unwrap();
+
+        let mut count = |xpath: &str| result.query(xpath).unwrap().len();
+        assert_eq!(count("//value[tractor:line(.) = 3 and tractor:column(.) = 5]"), 1);
+        assert_eq!(count("//item[tractor:line(.) = 2 and tractor:lines-of(.) = 3]"), 1);
+        assert_eq!(count("//item/@name[tractor:line(.) = 2]"), 1);
+        assert_eq!(count("//value[tractor:source(.) = '<value>héllo</value>']"), 1);
+        assert_eq!(count("//item[starts-with(tractor:source(.), '<item name=\"a\"')]"), 1);
+        assert_eq!(count("/root[tractor:lines-of(.) = 5]"), 1);
+    }
+
+    #[test]
+    fn test_tractor_function_errors_point_at_the_query() {
+        use crate::parser::load_xml_string_to_documents;
+
+        let mut result = load_xml_string_to_documents("<root/>", "data.xml".to_string()).unwrap();
+        let query = "tractor:line(.) + (1 + 'a')";
+        let error = result.query(query).unwrap_err().to_string();
+        let span = &error[error.rfind('(').unwrap() + 1..error.len() - 1];
+        let (start, end) = span.split_once("..").unwrap();
+        let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+        // The error is in `(1 + 'a')`, not in the library functions around the query
+        assert!(start >= query.find("(1").unwrap() && end <= query.len(), "{}", error);
+    }
+
+    #[test]
+    fn test_user_variables() {
+        use crate::parser::{parse, ParseInput, ParseOptions};
+        use crate::tree_mode::TreeMode;
+        use crate::xpath::{validate_xpath, XPathVariables};
+
+        let mut vars = XPathVariables::new();
+        vars.assign("max=2").unwrap();
+        vars.assign("names=a").unwrap();
+        vars.assign("names=c").unwrap();
+        let mut queries = CompiledQueries::new(Some(Arc::new(vars)));
+        assert!(queries.add("$max + 1").valid);
+        let queries = Arc::new(queries);
+
+        let content = "function f(a, b, c) {}";
+        let options = ParseOptions { language: Some("typescript"), tree_mode: Some(TreeMode::Raw), ..Default::default() };
+        let mut result = parse(
+            ParseInput::Inline { content, file_label: "f.ts" },
+            ParseOptions { queries: Some(&queries), ..options },
+        ).unwrap();
+        let xpath = "//formal_parameters[count(.//identifier) > $max]//identifier[. = $names]";
+        assert_eq!(result.query(xpath).unwrap().len(), 2);
+
+        // Other parses don't see them
+        let mut other = parse(ParseInput::Inline { content, file_label: "f.ts" }, options).unwrap();
+        assert!(other.query(xpath).is_err());
+        assert!(!validate_xpath("$max + 1").valid);
+    }
+
+    #[test]
+    fn test_user_functions() {
+        use crate::parser::{parse, ParseInput, ParseOptions};
+        use crate::tree_mode::TreeMode;
+
+        let mut library = XPathLibrary::new();
+        library.define_function("names($n)", "$n//identifier").unwrap();
+        library.define_function("all-names()", "count(.//identifier)").unwrap();
+        library.define_function("free()", "$a").unwrap();
+        library.define_fragment("many", "count(.//identifier) > 2").unwrap();
+        let mut queries = CompiledQueries::new(None).with_library(Some(Arc::new(library)));
+        let in_body = "//formal_parameters[count(names(.)) = 3]";
+        assert!(queries.add(in_body).valid);
+
+        // A body doesn't see the caller's variables; the error points at the call
+        let query = "for $a in 1 return free()";
+        let result = queries.add(query);
+        assert!(!result.valid);
+        assert_eq!(result.error_start, query.find("free"));
+
+        // Nor do errors in the query point into the expansion
+        let query = "names(.)[$many] + )";
+        assert_eq!(queries.add(query).error_start, Some(query.len() - 1));
+        let queries = Arc::new(queries);
+
+        let content = "function f(a, b, c) {}";
+        let options = ParseOptions { language: Some("typescript"), tree_mode: Some(TreeMode::Raw), ..Default::default() };
+        let mut result = parse(
+            ParseInput::Inline { content, file_label: "f.ts" },
+            ParseOptions { queries: Some(&queries), ..options },
+        ).unwrap();
+        assert_eq!(result.query(in_body).unwrap().len(), 1);
+        // Bodies run on the document, fragments in the caller's context
+        assert_eq!(result.query("//formal_parameters[all-names() = 4]").unwrap().len(), 1);
+        assert_eq!(result.query("//formal_parameters[$many]").unwrap().len(), 1);
+        assert_eq!(result.query("//identifier[$many]").unwrap().len(), 0);
+    }
 }
@@ -0,0 +1,113 @@
+//! Helpers for explaining why a query matches nothing (`--explain`).
+//!
+//! A location path like `//class/body/methd[name = 'x']` is split into its
+//! prefixes (`//class`, `//class/body`, …). Evaluating them in turn shows
+//! the step where matches drop to zero, and the element names found just
+//! before that step suggest what was meant instead.
+
+use super::scan;
+
+/// A prefix of a location path, ending with one of its steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPrefix {
+    /// The query up to and including this step.
+    pub xpath: String,
+    /// The same prefix without this step's predicates, if it has any.
+    pub without_predicates: Option<String>,
+    /// The element name the step tests for, if it is a plain name test.
+    pub name: Option<String>,
+    /// Byte range of the step in the query.
+    pub span: (usize, usize),
+    /// Byte range of the step's predicates in the query, if it has any.
+    pub predicate_span: Option<(usize, usize)>,
+}
+
+/// The prefixes of `xpath`, shortest first. Empty when `xpath` is not a
+/// single location path.
+pub fn path_prefixes(xpath: &str) -> Vec<PathPrefix> {
+    let Some(steps) = scan::path_steps(xpath) else {
+        return Vec::new();
+    };
+    steps.iter()
+        .map(|step| {
+            let test_end = step.predicates.unwrap_or(step.end);
+            PathPrefix {
+                xpath: xpath[..step.end].to_string(),
+                without_predicates: step.predicates.map(|p| xpath[..p].to_string()),
+                name: element_name(&xpath[step.start..test_end]).map(str::to_string),
+                span: (step.start, step.end),
+                predicate_span: step.predicates.map(|p| (p, step.end)),
+            }
+        })
+        .collect()
+}
+
+/// The element name a step's node test matches, for `name`, `child::name`
+/// and `descendant::name`; `None` for attributes, wildcards and kind tests.
+fn element_name(test: &str) -> Option<&str> {
+    let name = ["child::", "descendant::", "descendant-or-self::", "self::"]
+        .iter()
+        .find_map(|axis| test.strip_prefix(axis))
+        .unwrap_or(test);
+    Some(name).filter(|name| scan::is_plain_name(name))
+}
+
+/// Up to three of `candidates` that look like misspellings of `name`,
+/// closest first.
+pub fn similar_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let wanted = name.to_lowercase();
+    let max_distance = (wanted.chars().count() / 3).max(1);
+    let mut similar: Vec<(usize, &str)> = candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let distance = edit_distance(&wanted, &lower);
+            let related = wanted.len() >= 3 && (lower.contains(&wanted) || wanted.contains(&lower));
+            (distance <= max_distance || related).then_some((distance, candidate))
+        })
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar.into_iter().take(3).map(|(_, candidate)| candidate).collect()
+}
+
+/// Levenshtein distance between `a` and `b`, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_end_at_each_step() {
+        let prefixes = path_prefixes("//class/body/methd[name = 'x']");
+        let xpaths: Vec<&str> = prefixes.iter().map(|p| p.xpath.as_str()).collect();
+        assert_eq!(xpaths, ["//class", "//class/body", "//class/body/methd[name = 'x']"]);
+        assert_eq!(prefixes[2].without_predicates.as_deref(), Some("//class/body/methd"));
+        assert_eq!(prefixes[2].name.as_deref(), Some("methd"));
+        assert!(path_prefixes("//a | //b").is_empty());
+        assert_eq!(path_prefixes("//@name")[0].name, None);
+        assert_eq!(path_prefixes("//text()")[0].name, None);
+    }
+
+    #[test]
+    fn suggests_close_names_first() {
+        let candidates = ["method", "methods", "name", "method_declaration", "body"];
+        assert_eq!(similar_names("methd", candidates), ["method"]);
+        assert_eq!(similar_names("method", candidates), ["methods", "method_declaration"]);
+        assert!(similar_names("zzz", candidates).is_empty());
+    }
+}
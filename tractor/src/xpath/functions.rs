@@ -0,0 +1,351 @@
+//! The `tractor:` function library: source-aware helpers for queries.
+//!
+//! | Function                 | Returns                                              |
+//! |--------------------------|------------------------------------------------------|
+//! | `tractor:line(node)`     | start line of the node                               |
+//! | `tractor:column(node)`   | start column of the node                             |
+//! | `tractor:lines-of(node)` | number of lines the node spans                       |
+//! | `tractor:source(node)`   | original source text, whitespace and comments kept   |
+//! | `tractor:kind(node)`     | raw TreeSitter kind                                  |
+//! | `tractor:file-name()`    | file name of `$file`                                 |
+//! | `tractor:dir()`          | directory of `$file` (empty for a bare file name)    |
+//! | `tractor:language()`     | language the file was parsed as                      |
+//!
+//! Queryable as: `//method[tractor:lines-of(.) > 50]`
+//!
+//! xee only calls the functions of its own built-in library, so these are
+//! written in XPath and bound in a `let` around the query. Each
+//! `tractor:name(...)` call becomes a dynamic call of `$tractorname`, which
+//! is exactly as long, so positions in the query keep pointing at what the
+//! user wrote; `SourceMap` accounts for the prelude.
+//!
+//! The functions read what the engine binds for each document:
+//!
+//! - `$tractor.language`: the language,
+//! - `$tractor.lines`: an array of the source lines, so `tractor:source`
+//!   reads only the lines of its node,
+//! - `$tractor.location-keys` and `$tractor.location-values`: locations of
+//!   elements that carry no location attributes, as for XML input (see
+//!   `NodeLocations`). An element's key is its position among its element
+//!   siblings, for it and each of its ancestors, joined by `/`.
+//!
+//! Attribute and text nodes report the location of their element, like
+//! matches do.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use super::scan;
+
+/// Function name → inline function. Later entries may use earlier ones.
+const FUNCTIONS: &[(&str, &str)] = &[
+    ("location-index", r#"map:merge(for-each-pair(
+        $tractor.location-keys, 1 to count($tractor.location-keys), map:entry#2
+    ))"#),
+    // (line, column, end line, end column)
+    ("location", r#"function($n as node()) as xs:integer* {
+        if (map:size($tractorlocation-index) gt 0)
+        then
+            for $e in $n/ancestor-or-self::*[1],
+                $i in $tractorlocation-index(string-join($e/ancestor-or-self::* ! string(count(preceding-sibling::*)), '/'))
+            return $tractor.location-values($i)
+        else
+            for $e in $n/ancestor-or-self::*[@line][1]
+            return (
+                xs:integer($e/@line),
+                xs:integer($e/@column),
+                xs:integer(($e/@end_line, $e/@line)[1]),
+                xs:integer(($e/@end_column, $e/@column)[1])
+            )
+    }"#),
+    ("line", r#"function($n as node()) as xs:integer? {
+        $tractorlocation($n)[1]
+    }"#),
+    ("column", r#"function($n as node()) as xs:integer? {
+        $tractorlocation($n)[2]
+    }"#),
+    ("lines-of", r#"function($n as node()) as xs:integer? {
+        let $location := $tractorlocation($n)
+        return $location[3] - $location[1] + 1
+    }"#),
+    ("kind", r#"function($n as node()) as xs:string? {
+        $n/ancestor-or-self::*[1]/@kind ! string(.)
+    }"#),
+    ("file-name", r#"function() as xs:string {
+        tokenize($file, '[/\\]')[last()]
+    }"#),
+    ("dir", r#"function() as xs:string {
+        if (matches($file, '[/\\]')) then replace($file, '[/\\][^/\\]*$', '') else ''
+    }"#),
+    ("language", r#"function() as xs:string {
+        $tractor.language
+    }"#),
+    ("source-line", r#"function($line as xs:integer) as xs:string {
+        if ($line ge 1 and $line le array:size($tractor.lines)) then $tractor.lines($line) else ''
+    }"#),
+    // Columns count UTF-8 bytes; `substring` counts characters
+    ("char-index", r#"function($line as xs:string, $bytes as xs:integer) as xs:integer {
+        fold-left(string-to-codepoints($line), (0, 0), function($acc, $cp) {
+            if ($acc[2] lt $bytes)
+            then ($acc[1] + 1, $acc[2] + (if ($cp lt 128) then 1 else if ($cp lt 2048) then 2 else if ($cp lt 65536) then 3 else 4))
+            else $acc
+        })[1]
+    }"#),
+    ("source", r#"function($n as node()) as xs:string? {
+        let $location := $tractorlocation($n)
+        return
+            if (empty($location)) then ()
+            else
+                let $first := $tractorsource-line($location[1]),
+                    $last := $tractorsource-line($location[3]),
+                    $start := $tractorchar-index($first, $location[2] - 1),
+                    $end := $tractorchar-index($last, $location[4] - 1)
+                return
+                    if ($location[1] = $location[3])
+                    then substring($first, $start + 1, $end - $start)
+                    else string-join((
+                        substring($first, $start + 1),
+                        for $line in $location[1] + 1 to $location[3] - 1 return $tractorsource-line($line),
+                        substring($last, 1, $end)
+                    ), codepoints-to-string(10))
+    }"#),
+];
+
+/// Functions (and values) other library functions use.
+const DEPENDENCIES: &[(&str, &str)] = &[
+    ("location", "location-index"),
+    ("line", "location"),
+    ("column", "location"),
+    ("lines-of", "location"),
+    ("source", "location"),
+    ("source", "source-line"),
+    ("source", "char-index"),
+];
+
+/// Library function names that are helpers, not part of the public library.
+const INTERNAL: &[&str] = &["location-index", "location", "source-line", "char-index"];
+
+const PREFIX: &str = "tractor:";
+
+/// A query with its `tractor:` calls expanded into plain XPath.
+pub(crate) struct Expanded<'a> {
+    pub xpath: Cow<'a, str>,
+    pub source_map: SourceMap,
+    /// Whether the query reads the source lines (`$tractor.lines`).
+    pub uses_source: bool,
+    /// Whether the query reads element locations (`$tractor.location-*`).
+    pub uses_locations: bool,
+}
+
+/// Maps positions in an expanded query back to the query as written.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SourceMap {
+    /// Length of the `let` prelude prepended to the query.
+    pub(super) prelude: usize,
+    /// Length of the query as written.
+    pub(super) len: usize,
+    /// Where each bound function is defined in the prelude.
+    pub(super) bindings: Vec<(Range<usize>, String)>,
+    /// The calls in the query as written, with the bound functions each
+    /// one runs.
+    pub(super) calls: Vec<(Range<usize>, Vec<String>)>,
+    /// Parts of the query as written that were replaced by text of another
+    /// length, with where the replacement is after the prelude, in order.
+    pub(super) edits: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl SourceMap {
+    /// A map for a query that was not changed.
+    pub(super) fn unchanged(len: usize) -> Self {
+        SourceMap { len, ..SourceMap::default() }
+    }
+
+    /// Map a span of the expanded query to the query as written. A span in
+    /// the prelude maps to the first call that runs the function it is in,
+    /// and one starting in a replacement to what it replaced.
+    pub fn original_span(&self, start: usize, end: usize) -> (usize, usize) {
+        if start < self.prelude {
+            let function = self.bindings.iter()
+                .find(|(range, _)| range.contains(&start))
+                .map(|(_, name)| name);
+            let call = self.calls.iter()
+                .find(|(_, uses)| function.is_some_and(|f| uses.contains(f)))
+                .or(self.calls.first());
+            return call.map_or((0, self.len), |(range, _)| (range.start, range.end));
+        }
+        let (start, end) = (start - self.prelude, end.max(start) - self.prelude);
+        if let Some((original, _)) = self.edits.iter().find(|(_, new)| new.contains(&start)) {
+            return (original.start, original.end);
+        }
+        (self.original_position(start), self.original_position(end))
+    }
+
+    /// Where position `pos` after the prelude is in the query as written;
+    /// the end of what a replacement replaced for positions inside it.
+    fn original_position(&self, pos: usize) -> usize {
+        let original = match self.edits.iter().rev().find(|(_, new)| new.start < pos) {
+            Some((original, new)) if pos < new.end => original.end,
+            Some((original, new)) => original.end + (pos - new.end),
+            None => pos,
+        };
+        original.min(self.len)
+    }
+
+    /// Rewrite the `(start..end)` span xee ends its error messages with to
+    /// point into the query as written.
+    pub fn original_message(&self, message: String) -> String {
+        let Some(open) = message.rfind('(') else { return message };
+        let Some(span) = message[open + 1..].strip_suffix(')') else { return message };
+        let Some((start, end)) = span.split_once("..") else { return message };
+        let (Ok(start), Ok(end)) = (start.parse(), end.parse()) else { return message };
+        let (start, end) = self.original_span(start, end);
+        format!("{}({}..{})", &message[..open], start, end)
+    }
+}
+
+/// Expand `tractor:` calls. Queries that make none are returned unchanged.
+pub(crate) fn expand(xpath: &str) -> Expanded<'_> {
+    let calls = find_calls(xpath);
+    if calls.is_empty() {
+        return Expanded {
+            xpath: Cow::Borrowed(xpath),
+            source_map: SourceMap::unchanged(xpath.len()),
+            uses_source: false,
+            uses_locations: false,
+        };
+    }
+
+    let mut used: Vec<&'static str> = Vec::new();
+    let mut body = String::with_capacity(xpath.len());
+    let mut last = 0;
+    let mut call_uses = Vec::with_capacity(calls.len());
+    for (pos, name) in calls {
+        // `tractor:name` → `$tractorname`, keeping every position in place
+        body.push_str(&xpath[last..pos]);
+        body.push('$');
+        body.push_str(&PREFIX[..PREFIX.len() - 1]);
+        body.push_str(name);
+        last = pos + PREFIX.len() + name.len();
+        let uses = with_dependencies(name);
+        for function in &uses {
+            if !used.contains(function) {
+                used.push(*function);
+            }
+        }
+        call_uses.push((pos..last, uses.iter().map(|name| name.to_string()).collect()));
+    }
+    body.push_str(&xpath[last..]);
+
+    // Bind in library order, so dependencies come first
+    let mut prelude = String::from("let ");
+    let mut bindings = Vec::new();
+    for (name, function) in FUNCTIONS.iter().filter(|(name, _)| used.contains(name)) {
+        if !bindings.is_empty() {
+            prelude.push_str(",\n    ");
+        }
+        let start = prelude.len();
+        prelude.push_str(&format!("$tractor{} := {}", name, function));
+        bindings.push((start..prelude.len(), name.to_string()));
+    }
+    prelude.push_str("\nreturn (\n");
+
+    Expanded {
+        xpath: Cow::Owned(format!("{}{}\n)", prelude, body)),
+        source_map: SourceMap {
+            prelude: prelude.len(),
+            len: xpath.len(),
+            bindings,
+            calls: call_uses,
+            edits: Vec::new(),
+        },
+        uses_source: used.contains(&"source"),
+        uses_locations: used.contains(&"location"),
+    }
+}
+
+/// `name` and the library functions it uses, directly or not.
+fn with_dependencies(name: &str) -> Vec<&'static str> {
+    let mut uses: Vec<&'static str> = FUNCTIONS.iter()
+        .map(|(n, _)| *n)
+        .filter(|n| *n == name)
+        .collect();
+    let mut i = 0;
+    while i < uses.len() {
+        for (function, dependency) in DEPENDENCIES {
+            if *function == uses[i] && !uses.contains(dependency) {
+                uses.push(*dependency);
+            }
+        }
+        i += 1;
+    }
+    uses
+}
+
+/// Find `tractor:name(` calls to library functions outside string literals
+/// and comments, as (position, name) pairs.
+fn find_calls(xpath: &str) -> Vec<(usize, &str)> {
+    scan::names(xpath).into_iter()
+        .filter(|n| n.call)
+        .filter_map(|n| Some((n.start, n.name.strip_prefix(PREFIX)?)))
+        .filter(|(_, name)| is_library_function(name))
+        .collect()
+}
+
+fn is_library_function(name: &str) -> bool {
+    !INTERNAL.contains(&name) && FUNCTIONS.iter().any(|(n, _)| *n == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_without_calls_are_unchanged() {
+        let expanded = expand("//method[name = 'tractor:line(x)'] (: tractor:dir() :)");
+        assert!(matches!(expanded.xpath, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn calls_become_dynamic_calls_of_bound_functions() {
+        let expanded = expand("//method[tractor:lines-of(.) > 50]");
+        assert!(expanded.xpath.starts_with("let $tractorlocation-index := map:merge"));
+        assert!(expanded.xpath.ends_with("return (\n//method[$tractorlines-of(.) > 50]\n)"));
+        assert!(expanded.uses_locations);
+        assert!(!expanded.uses_source);
+    }
+
+    #[test]
+    fn source_binds_its_helpers_first() {
+        let expanded = expand("tractor:source(//method)");
+        assert!(expanded.uses_source);
+        let helper = expanded.xpath.find("$tractorchar-index :=").unwrap();
+        let location = expanded.xpath.find("$tractorlocation :=").unwrap();
+        let source = expanded.xpath.find("$tractorsource :=").unwrap();
+        assert!(helper < source && location < source);
+    }
+
+    #[test]
+    fn unknown_and_internal_names_are_left_alone() {
+        assert!(matches!(expand("tractor:nope(.)").xpath, Cow::Borrowed(_)));
+        assert!(matches!(expand("tractor:char-index('a', 1)").xpath, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn spans_map_back_to_the_query_as_written() {
+        let original = "tractor:line(.) + tractor:source(.) + )";
+        let expanded = expand(original);
+        let map = &expanded.source_map;
+
+        let error = expanded.xpath.rfind(" )").unwrap() + 1;
+        assert_eq!(map.original_span(error, error + 1), (original.len() - 1, original.len()));
+        assert_eq!(
+            map.original_message(format!("XPST0003 Parse error. ({}..{})", error, error + 1)),
+            format!("XPST0003 Parse error. ({}..{})", original.len() - 1, original.len()),
+        );
+
+        // An error inside a library function points at the call that ran it
+        let helper = expanded.xpath.find("fold-left").unwrap();
+        let call = original.find("tractor:source").unwrap();
+        assert_eq!(map.original_span(helper, helper + 9), (call, call + "tractor:source".len()));
+    }
+}
@@ -0,0 +1,496 @@
+//! User-defined XPath functions and named fragments.
+//!
+//! Config files declare these under `functions:` and `let:`:
+//!
+//! ```yaml
+//! functions:
+//!   is-test-method($m): "$m/attributes/attribute[name = 'Test']"
+//!   public-api(): "//method[public][not(ancestor::class[not(public)])]"
+//! let:
+//!   is-test: "attributes/attribute/name = 'Test' or name[starts-with(., 'test')]"
+//! ```
+//!
+//! They travel with the run's queries and are expanded when a query is
+//! compiled; rules and reports keep the query as written. Like the
+//! `tractor:` functions, each function a query uses is bound to an inline
+//! function in a `let` around it, and a call `is-test-method(.)` becomes a
+//! dynamic call `$is-test-method.1(.)`. So a body sees its parameters, the
+//! other functions and the run's variables, not the caller's variables,
+//! and runs with the document as its context item: `//` works, nodes of
+//! the caller come in as arguments. A fragment reference like
+//! `//method[$is-test]` is replaced by `(<body>)`, so it does run in the
+//! caller's context. Bodies may use other functions and fragments; cycles
+//! are rejected.
+//!
+//! Bound names are chosen apart from every variable the query and the
+//! definitions use. Functions may not share a name with a standard (`fn:`)
+//! function, whose calls they would replace.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use xee_xpath::Queries;
+use xee_xpath::context::StaticContextBuilder;
+
+use super::functions::SourceMap;
+use super::scan;
+
+/// Names the XPath grammar treats specially before a `(`, which a user
+/// function could never be called by.
+const RESERVED: &[&str] = &[
+    "array", "attribute", "comment", "document-node", "element", "empty-sequence",
+    "function", "if", "item", "map", "namespace-node", "node",
+    "processing-instruction", "schema-attribute", "schema-element", "switch",
+    "text", "typeswitch",
+];
+
+/// Highest arity looked up when checking for a standard function.
+const MAX_BUILTIN_ARITY: usize = 8;
+
+/// State of one expansion.
+#[derive(Default)]
+struct Scope<'a> {
+    /// Definitions being expanded, innermost last.
+    stack: Vec<(&'static str, &'a str)>,
+    /// Variable names in use, which bound names must avoid.
+    taken: BTreeSet<String>,
+    /// Variable the document is bound to, once a function is bound.
+    document: Option<String>,
+    /// Functions bound so far, dependencies first: name, variable,
+    /// definition and the functions it calls.
+    bound: Vec<Binding<'a>>,
+    /// Functions called, in order, for recording what a call runs.
+    called: Vec<&'a str>,
+}
+
+struct Binding<'a> {
+    name: &'a str,
+    variable: String,
+    definition: String,
+    calls: Vec<&'a str>,
+}
+
+impl Scope<'_> {
+    /// A variable name for `name` that is not in use yet.
+    fn fresh(&mut self, name: &str) -> String {
+        let fresh = (1..)
+            .map(|n| format!("{}.{}", name, n))
+            .find(|fresh| !self.taken.contains(fresh))
+            .expect("unbounded");
+        self.taken.insert(fresh.clone());
+        fresh
+    }
+
+    /// `name` and the functions it calls, directly or not.
+    fn with_dependencies(&self, name: &str) -> Vec<String> {
+        let mut uses = vec![name.to_string()];
+        let mut i = 0;
+        while i < uses.len() {
+            let binding = self.bound.iter().find(|b| b.name == uses[i]);
+            for call in binding.into_iter().flat_map(|b| &b.calls) {
+                if !uses.iter().any(|u| u == call) {
+                    uses.push(call.to_string());
+                }
+            }
+            i += 1;
+        }
+        uses
+    }
+}
+
+/// An expanded text.
+struct Text<'a> {
+    xpath: String,
+    /// Replacements made in the text as written and where they are in the
+    /// expansion, with the functions each one calls.
+    edits: Vec<(Range<usize>, Range<usize>, Vec<&'a str>)>,
+}
+
+/// A user function: parameter names (without `$`) and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Function {
+    params: Vec<String>,
+    body: String,
+}
+
+/// User-defined functions and fragments, expanded into queries by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XPathLibrary {
+    functions: BTreeMap<String, Function>,
+    fragments: BTreeMap<String, String>,
+}
+
+impl XPathLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.fragments.is_empty()
+    }
+
+    /// Define a function from a signature like `is-test-method($m)`.
+    pub fn define_function(&mut self, signature: &str, body: &str) -> Result<(), String> {
+        let (name, params) = parse_signature(signature)?;
+        if RESERVED.contains(&name.as_str()) {
+            return Err(format!("function name '{}' is reserved in XPath", name));
+        }
+        if is_builtin(&name) {
+            return Err(format!("function name '{}' is a standard XPath function", name));
+        }
+        self.functions.insert(name, Function { params, body: body.to_string() });
+        Ok(())
+    }
+
+    /// Define a fragment, referenced as `$name`. A leading `$` is optional.
+    pub fn define_fragment(&mut self, name: &str, body: &str) -> Result<(), String> {
+        let name = name.trim().trim_start_matches('$');
+        if !scan::is_plain_name(name) {
+            return Err(format!("invalid fragment name '{}'", name));
+        }
+        self.fragments.insert(name.to_string(), body.to_string());
+        Ok(())
+    }
+
+    /// Whether a fragment is defined as `$name`.
+    pub fn has_fragment(&self, name: &str) -> bool {
+        self.fragments.contains_key(name)
+    }
+
+    /// Check every definition expands, so cycles and bad calls are reported
+    /// once, against the definition, rather than on first use.
+    pub fn validate(&self) -> Result<(), String> {
+        for name in self.functions.keys() {
+            let mut scope = self.scope("");
+            self.bind(name, &mut scope)
+                .map_err(|e| format!("in function '{}': {}", name, e))?;
+        }
+        for (name, body) in &self.fragments {
+            let mut scope = self.scope(body);
+            scope.stack.push(("$", name.as_str()));
+            self.expand_in(body, &[], &mut scope)
+                .map_err(|e| format!("in fragment '${}': {}", name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Expand calls of user functions and references to fragments in
+    /// `xpath`. Queries that use neither are returned unchanged.
+    pub fn expand(&self, xpath: &str) -> Result<String, String> {
+        self.expand_mapped(xpath).map(|(xpath, _)| xpath)
+    }
+
+    /// Expand `xpath` like [`expand`](Self::expand), with a map from the
+    /// expansion back to `xpath`. Errors in the query end with the span of
+    /// the call at fault, as xee's do.
+    pub(super) fn expand_mapped(&self, xpath: &str) -> Result<(String, SourceMap), String> {
+        if self.is_empty() {
+            return Ok((xpath.to_string(), SourceMap::unchanged(xpath.len())));
+        }
+        let mut scope = self.scope(xpath);
+        let text = self.expand_text(xpath, &[], &mut scope)?;
+        let edits = text.edits.iter().map(|(from, to, _)| (from.clone(), to.clone())).collect();
+        let Some(document) = scope.document.take() else {
+            let map = SourceMap { len: xpath.len(), edits, ..SourceMap::default() };
+            return Ok((text.xpath, map));
+        };
+
+        let mut prelude = format!("let ${} := .", document);
+        let mut bindings = Vec::new();
+        for binding in &scope.bound {
+            prelude.push_str(",\n    ");
+            let start = prelude.len();
+            prelude.push_str(&format!("${} := {}", binding.variable, binding.definition));
+            bindings.push((start..prelude.len(), binding.name.to_string()));
+        }
+        prelude.push_str("\nreturn (\n");
+        let calls = text.edits.iter()
+            .filter(|(_, _, calls)| !calls.is_empty())
+            .map(|(from, _, calls)| {
+                let uses = calls.iter().flat_map(|call| scope.with_dependencies(call)).collect();
+                (from.clone(), uses)
+            })
+            .collect();
+        let map = SourceMap { prelude: prelude.len(), len: xpath.len(), bindings, calls, edits };
+        Ok((format!("{}{}\n)", prelude, text.xpath), map))
+    }
+
+    /// A scope for expanding `xpath`, with every variable name it and the
+    /// definitions use taken.
+    fn scope(&self, xpath: &str) -> Scope<'_> {
+        let texts = std::iter::once(xpath)
+            .chain(self.functions.values().map(|f| f.body.as_str()))
+            .chain(self.fragments.values().map(String::as_str));
+        let taken = texts
+            .flat_map(scan::names)
+            .filter(|name| name.variable)
+            .map(|name| name.name.to_string())
+            .collect();
+        Scope { taken, ..Scope::default() }
+    }
+
+    /// Expand `xpath`, leaving the variables in `params` (the parameters of
+    /// the function being expanded) alone, and binding the functions it
+    /// calls in `scope`.
+    fn expand_in<'a>(&'a self, xpath: &str, params: &[String], scope: &mut Scope<'a>) -> Result<String, String> {
+        self.expand_text(xpath, params, scope).map(|text| text.xpath)
+    }
+
+    /// Expand `xpath`, recording each replacement. The scope's stack holds
+    /// the definitions being expanded, to detect cycles: `("$", name)` for
+    /// fragments and `("", name)` for functions, which live in separate
+    /// namespaces.
+    fn expand_text<'a>(&'a self, xpath: &str, params: &[String], scope: &mut Scope<'a>) -> Result<Text<'a>, String> {
+        let mut out = String::with_capacity(xpath.len());
+        let mut edits = Vec::new();
+        let mut last = 0;
+        for name in scan::names(xpath) {
+            let called = scope.called.len();
+            let replacement = if name.variable {
+                let Some((key, body)) = self.fragments.get_key_value(name.name) else { continue };
+                if params.iter().any(|p| p == name.name) {
+                    continue;
+                }
+                let body = self.enter(("$", key.as_str()), scope, |lib, scope| lib.expand_in(body, &[], scope))?;
+                format!("({})", body)
+            } else if name.call {
+                let Some((key, function)) = self.functions.get_key_value(name.name) else { continue };
+                let (args, _) = scan::call_arguments(xpath, name.end).ok_or_else(|| {
+                    format!("unbalanced parentheses in call of '{}' ({}..{})", key, name.start, name.end)
+                })?;
+                if args.len() != function.params.len() {
+                    return Err(format!(
+                        "function '{}' takes {} argument(s), got {} ({}..{})",
+                        key, function.params.len(), args.len(), name.start, name.end
+                    ));
+                }
+                format!("${}", self.bind(key, scope)?)
+            } else {
+                continue;
+            };
+            out.push_str(&xpath[last..name.start]);
+            let at = out.len();
+            out.push_str(&replacement);
+            let calls = scope.called[called..].to_vec();
+            edits.push((name.start..name.end, at..out.len(), calls));
+            last = name.end;
+        }
+        out.push_str(&xpath[last..]);
+        Ok(Text { xpath: out, edits })
+    }
+
+    /// The variable function `name` is bound to, binding it (and the
+    /// functions it calls) first when this is its first call.
+    fn bind<'a>(&'a self, name: &'a str, scope: &mut Scope<'a>) -> Result<String, String> {
+        scope.called.push(name);
+        if let Some(binding) = scope.bound.iter().find(|b| b.name == name) {
+            return Ok(binding.variable.clone());
+        }
+        let function = &self.functions[name];
+        let called = scope.called.len();
+        let body = self.enter(("", name), scope, |lib, scope| lib.expand_in(&function.body, &function.params, scope))?;
+        let calls = scope.called.split_off(called);
+        let document = match &scope.document {
+            Some(document) => document.clone(),
+            None => {
+                let document = scope.fresh("document");
+                scope.document = Some(document.clone());
+                document
+            }
+        };
+        let params: Vec<String> = function.params.iter().map(|p| format!("${}", p)).collect();
+        let definition = format!("function({}) {{ ${} ! ({}) }}", params.join(", "), document, body);
+        let variable = scope.fresh(name);
+        scope.bound.push(Binding { name, variable: variable.clone(), definition, calls });
+        Ok(variable)
+    }
+
+    /// Run `f` with `name` pushed on the expansion stack.
+    fn enter<'a>(
+        &'a self,
+        name: (&'static str, &'a str),
+        scope: &mut Scope<'a>,
+        f: impl FnOnce(&'a Self, &mut Scope<'a>) -> Result<String, String>,
+    ) -> Result<String, String> {
+        if scope.stack.contains(&name) {
+            return Err(format!("'{}{}' refers to itself", name.0, name.1));
+        }
+        scope.stack.push(name);
+        let result = f(self, scope);
+        scope.stack.pop();
+        result
+    }
+}
+
+/// Whether `name` is a standard (`fn:`) function at any arity.
+fn is_builtin(name: &str) -> bool {
+    let queries = Queries::new(StaticContextBuilder::default());
+    (0..=MAX_BUILTIN_ARITY).any(|arity| queries.sequence(&format!("{}#{}", name, arity)).is_ok())
+}
+
+/// Split `name($a, $b)` into its name and parameter names.
+fn parse_signature(signature: &str) -> Result<(String, Vec<String>), String> {
+    let invalid = || format!("invalid function signature '{}', expected e.g. 'name($arg)'", signature);
+    let (name, rest) = signature.trim().split_once('(').ok_or_else(invalid)?;
+    let params = rest.strip_suffix(')').ok_or_else(invalid)?;
+    let name = name.trim();
+    if !scan::is_plain_name(name) {
+        return Err(invalid());
+    }
+    let params: Vec<String> = if params.trim().is_empty() {
+        Vec::new()
+    } else {
+        params.split(',')
+            .map(|p| {
+                let p = p.trim().strip_prefix('$').ok_or_else(invalid)?;
+                if scan::is_plain_name(p) { Ok(p.to_string()) } else { Err(invalid()) }
+            })
+            .collect::<Result<_, _>>()?
+    };
+    if let Some(p) = params.iter().enumerate().find_map(|(i, p)| params[..i].contains(p).then_some(p)) {
+        return Err(format!("duplicate parameter '${}' in '{}'", p, signature));
+    }
+    Ok((name.to_string(), params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> XPathLibrary {
+        let mut library = XPathLibrary::new();
+        library.define_function("is-test-method($m)", "$m/attributes/attribute[name = 'Test']").unwrap();
+        library.define_function("public-api()", "//method[public]").unwrap();
+        library.define_fragment("async", "async or modifiers/async").unwrap();
+        library
+    }
+
+    #[test]
+    fn calls_run_functions_bound_around_the_query() {
+        assert_eq!(
+            library().expand("//method[is-test-method(.)]").unwrap(),
+            "let $document.1 := .,\n    \
+             $is-test-method.1 := function($m) { $document.1 ! ($m/attributes/attribute[name = 'Test']) }\n\
+             return (\n//method[$is-test-method.1(.)]\n)",
+        );
+        assert_eq!(
+            library().expand("count(public-api())").unwrap(),
+            "let $document.1 := .,\n    \
+             $public-api.1 := function() { $document.1 ! (//method[public]) }\n\
+             return (\ncount($public-api.1())\n)",
+        );
+    }
+
+    #[test]
+    fn fragments_are_substituted() {
+        assert_eq!(library().expand("//method[$async]").unwrap(), "//method[(async or modifiers/async)]");
+        assert_eq!(library().expand("//method[name = '$async']").unwrap(), "//method[name = '$async']");
+    }
+
+    #[test]
+    fn nested_definitions_bind_what_they_use_first() {
+        let mut library = library();
+        library.define_function("tests()", "//method[is-test-method(.)][$async]").unwrap();
+        library.define_function("test-count()", "count(tests()) + count(tests())").unwrap();
+        library.validate().unwrap();
+        let expanded = library.expand("test-count() + count(tests())").unwrap();
+        let binding = |name: &str| expanded.find(&format!("${} :=", name)).unwrap();
+        assert!(binding("is-test-method.1") < binding("tests.1") && binding("tests.1") < binding("test-count.1"));
+        assert_eq!(expanded.matches(":= function").count(), 3, "each function is bound once");
+        assert!(expanded.contains("//method[$is-test-method.1(.)][(async or modifiers/async)]"));
+        assert!(expanded.ends_with("return (\n$test-count.1() + count($tests.1())\n)"));
+    }
+
+    #[test]
+    fn parameters_shadow_fragments() {
+        let mut library = library();
+        library.define_function("f($async)", "$async + 1").unwrap();
+        assert!(library.expand("f(2)").unwrap().contains("function($async) { $document.1 ! ($async + 1) }"));
+    }
+
+    #[test]
+    fn bound_names_avoid_names_in_use() {
+        let mut library = XPathLibrary::new();
+        library.define_function("f($x)", "$x").unwrap();
+        // The query's own `$f.1` and `$document.1` keep their meaning
+        let expanded = library.expand("for $f.1 in 1, $document.1 in 2 return f($f.1)").unwrap();
+        assert!(expanded.starts_with("let $document.2 := .,\n    $f.2 := function($x)"), "{}", expanded);
+        assert!(expanded.ends_with("return $f.2($f.1)\n)"), "{}", expanded);
+    }
+
+    #[test]
+    fn functions_fragments_and_elements_may_share_names() {
+        let mut library = XPathLibrary::new();
+        library.define_function("method($m)", "$m/name").unwrap();
+        library.define_fragment("method", "public").unwrap();
+        // Only calls are functions, only variables are fragments; steps are elements
+        let expanded = library.expand("//method[method(.)][$method]").unwrap();
+        assert!(expanded.ends_with("return (\n//method[$method.1(.)][(public)]\n)"), "{}", expanded);
+    }
+
+    #[test]
+    fn standard_function_names_are_rejected() {
+        let mut library = XPathLibrary::new();
+        let err = library.define_function("count($x)", "1").unwrap_err();
+        assert!(err.contains("standard XPath function"), "{}", err);
+        assert!(library.define_function("contains($x, $y, $z)", "1").is_err(), "any arity collides");
+        assert!(library.define_function("count-tests($x)", "1").is_ok());
+    }
+
+    #[test]
+    fn recursive_definitions_are_rejected() {
+        let mut library = XPathLibrary::new();
+        library.define_function("depth($n)", "if ($n/..) then 1 + depth($n/..) else 0").unwrap();
+        let err = library.validate().unwrap_err();
+        assert_eq!(err, "in function 'depth': 'depth' refers to itself");
+
+        let mut library = XPathLibrary::new();
+        library.define_function("a()", "b()").unwrap();
+        library.define_function("b()", "a()").unwrap();
+        assert!(library.validate().unwrap_err().contains("'a' refers to itself"));
+
+        // Through a fragment, which lives in a namespace of its own
+        let mut library = XPathLibrary::new();
+        library.define_function("f()", "//x[$g]").unwrap();
+        library.define_fragment("g", "f()").unwrap();
+        assert!(library.validate().unwrap_err().contains("refers to itself"));
+        assert!(library.expand("$g").unwrap_err().contains("'$g' refers to itself"));
+    }
+
+    #[test]
+    fn bad_calls_point_at_the_call() {
+        let err = library().expand("//a[is-test-method()]").unwrap_err();
+        assert_eq!(err, "function 'is-test-method' takes 1 argument(s), got 0 (4..18)");
+    }
+
+    #[test]
+    fn spans_map_back_to_the_query_as_written() {
+        let mut library = library();
+        library.define_function("broken()", "1 + 'a'").unwrap();
+        let original = "//method[$async][is-test-method(.)] + broken() + )";
+        let (expanded, map) = library.expand_mapped(original).unwrap();
+
+        // After replacements of other lengths
+        let error = expanded.rfind(" )").unwrap() + 1;
+        assert_eq!(map.original_span(error, error + 1), (original.len() - 1, original.len()));
+        // Inside one
+        let fragment = expanded.find("modifiers").unwrap();
+        let reference = original.find("$async").unwrap();
+        assert_eq!(map.original_span(fragment, fragment + 1), (reference, reference + "$async".len()));
+        // In the prelude: the call of the function it is in
+        let body = expanded.find("1 + 'a'").unwrap();
+        let call = original.find("broken").unwrap();
+        assert_eq!(map.original_span(body, body + 7), (call, call + "broken".len()));
+    }
+
+    #[test]
+    fn signatures_are_checked() {
+        let mut library = XPathLibrary::new();
+        assert!(library.define_function("f", "1").is_err());
+        assert!(library.define_function("f(m)", "1").is_err());
+        assert!(library.define_function("f($a, $a)", "1").is_err());
+        assert!(library.define_function("ns:f()", "1").is_err());
+        assert!(library.define_function("text()", "1").is_err());
+        assert!(library.define_fragment("$ok", "1").is_ok());
+    }
+}
@@ -0,0 +1,275 @@
+//! Words a query needs in the source text to match anything, so files
+//! without them can be skipped before parsing.
+//!
+//! The tree keeps the source text, whitespace between tokens included, so
+//! a node whose string value contains `unsafe` belongs to a file whose text
+//! does. A query that is a single location path only matches nodes that
+//! pass every predicate of every step, so a predicate comparing a node's
+//! text with a string literal (`contains(., 'unsafe')`, `name = 'eval'`)
+//! makes the literal's words required. Anything less certain — `or`,
+//! `not()`, functions around the compared path, attributes (which hold
+//! TreeSitter kinds, not source text), variables — adds nothing, so the
+//! analysis may miss a file that could be skipped but never skips one that
+//! could match.
+
+use super::scan;
+
+/// The words (runs of letters, digits and `_`) that occur in every file
+/// `xpath` can match. Empty when nothing is certain.
+pub fn required_literals(xpath: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    collect_path(xpath.trim(), &mut words);
+    words
+}
+
+/// Words required by the predicates of the location path `path`.
+fn collect_path(path: &str, words: &mut Vec<String>) {
+    let Some(steps) = scan::path_steps(path) else {
+        return;
+    };
+    for step in steps {
+        let Some(start) = step.predicates else { continue };
+        if !is_node_test(&path[step.start..start]) {
+            continue;
+        }
+        for predicate in predicates(&path[start..step.end]) {
+            for conjunct in conjuncts(predicate) {
+                collect_conjunct(conjunct.trim(), words);
+            }
+        }
+    }
+}
+
+/// Words required by one `and`-ed part of a predicate.
+fn collect_conjunct(conjunct: &str, words: &mut Vec<String>) {
+    // contains(., 'unsafe'), starts-with(name, 'test')
+    if let Some(call) = scan::names(conjunct).first().filter(|n| n.start == 0 && n.call) {
+        let function = call.name.strip_prefix("fn:").unwrap_or(call.name);
+        if let Some((args, end)) = scan::call_arguments(conjunct, call.end) {
+            if end == conjunct.len()
+                && matches!(function, "contains" | "starts-with" | "ends-with")
+                && args.len() == 2
+                && is_text_operand(conjunct[args[0].0..args[0].1].trim())
+            {
+                if let Some(literal) = string_literal(conjunct[args[1].0..args[1].1].trim()) {
+                    add_words(&literal, words);
+                }
+            }
+            return;
+        }
+    }
+
+    // name = 'eval', 'eval' eq name
+    if let Some((left, right)) = split_comparison(conjunct) {
+        let literal = match (string_literal(left), string_literal(right)) {
+            (Some(literal), None) if is_text_operand(right) => literal,
+            (None, Some(literal)) if is_text_operand(left) => literal,
+            _ => return,
+        };
+        add_words(&literal, words);
+        return;
+    }
+
+    // call[name = 'eval']: the nested path must match something too
+    collect_path(conjunct, words);
+}
+
+fn add_words(literal: &str, words: &mut Vec<String>) {
+    for word in literal.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        if !word.is_empty() && !words.iter().any(|w| w == word) {
+            words.push(word.to_string());
+        }
+    }
+}
+
+/// Whether a step's test (before its predicates) selects nodes whose text
+/// is source text: elements, text and comments, not attributes or values
+/// computed by functions.
+fn is_node_test(test: &str) -> bool {
+    let test = match test.split_once("::") {
+        Some(("attribute" | "namespace", _)) => return false,
+        Some((_, test)) => test,
+        None => test,
+    };
+    matches!(test, "." | ".." | "*" | "node()" | "text()" | "comment()" | "element()")
+        || is_element_name(test)
+}
+
+/// Whether `operand` is a relative path of element steps (`.`, `name`,
+/// `body/call/name`), whose string values are source text.
+fn is_text_operand(operand: &str) -> bool {
+    !operand.is_empty()
+        && !operand.starts_with('/')
+        && operand
+            .split('/')
+            .all(|step| step.is_empty() || matches!(step, "." | ".." | "*") || is_element_name(step))
+}
+
+/// A plain name, not a `$variable`.
+fn is_element_name(text: &str) -> bool {
+    !text.starts_with('$') && scan::is_plain_name(text)
+}
+
+/// The value of `text` when it is exactly one string literal.
+fn string_literal(text: &str) -> Option<String> {
+    let quote = ['\'', '"'].into_iter().find(|&q| text.starts_with(q))?.to_string();
+    let inner = text.strip_prefix(quote.as_str())?.strip_suffix(quote.as_str())?;
+    // Quotes inside are doubled; a single one ends the literal early
+    let escaped = quote.repeat(2);
+    if inner.replace(&escaped, "").contains(quote.as_str()) {
+        return None;
+    }
+    Some(inner.replace(&escaped, &quote))
+}
+
+/// The contents of each `[...]` in a step's predicate list.
+fn predicates(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(next) = scan::skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'[' | b'(' | b'{' => {
+                if depth == 0 {
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            b']' | b')' | b'}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 && bytes[i] == b']' {
+                    found.push(&text[start..i]);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    found
+}
+
+/// The parts of `predicate` joined by a top-level `and`. Empty when it has
+/// a top-level `or`, since then no part is certain.
+fn conjuncts(predicate: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (keyword, at) in top_level_keywords(predicate) {
+        match keyword {
+            "or" => return Vec::new(),
+            _ => {
+                parts.push(&predicate[start..at]);
+                start = at + keyword.len();
+            }
+        }
+    }
+    parts.push(&predicate[start..]);
+    parts
+}
+
+/// The `and` and `or` operators outside brackets and literals, with their
+/// positions. A name only counts as an operator between whitespace, after
+/// something else.
+fn top_level_keywords(text: &str) -> Vec<(&'static str, usize)> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(next) = scan::skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'[' | b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' => depth = depth.saturating_sub(1),
+            b if depth == 0 && b.is_ascii_whitespace() => {
+                let rest = &text[i + 1..];
+                for keyword in ["and", "or"] {
+                    let operator = rest.strip_prefix(keyword)
+                        .is_some_and(|after| after.starts_with(|c: char| c.is_ascii_whitespace() || c == '('));
+                    if operator && !text[..i].trim().is_empty() {
+                        found.push((keyword, i + 1));
+                        i += keyword.len();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    found
+}
+
+/// The two sides of a conjunct that is a single `=` or `eq` comparison.
+fn split_comparison(conjunct: &str) -> Option<(&str, &str)> {
+    let bytes = conjunct.as_bytes();
+    let mut found = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(next) = scan::skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'[' | b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => {
+                let previous = i.checked_sub(1).map(|p| bytes[p]);
+                let part_of_other = matches!(previous, Some(b'!' | b'<' | b'>' | b':'))
+                    || bytes.get(i + 1) == Some(&b'>');
+                if part_of_other || found.is_some() {
+                    return None;
+                }
+                found = Some((i, 1));
+            }
+            b if depth == 0 && b.is_ascii_whitespace() && conjunct[i + 1..].starts_with("eq ") => {
+                if found.is_some() {
+                    return None;
+                }
+                found = Some((i + 1, 2));
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    let (at, len) = found?;
+    Some((conjunct[..at].trim(), conjunct[at + len..].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_words_compared_with_node_text() {
+        assert_eq!(required_literals("//comment[contains(., 'TODO')]"), ["TODO"]);
+        assert_eq!(required_literals("//call[function/name = 'eval']"), ["eval"]);
+        assert_eq!(required_literals("//call[name='os.system' and arguments]"), ["os", "system"]);
+        assert_eq!(required_literals("//class[name = 'A']//method[starts-with(name, \"test_\")]"), ["A", "test_"]);
+        assert_eq!(required_literals("//call[member[name = 'exec']]"), ["exec"]);
+        assert_eq!(required_literals("//string[. eq 'it''s']"), ["it", "s"]);
+    }
+
+    #[test]
+    fn ignores_anything_uncertain() {
+        let none: [&str; 0] = [];
+        assert_eq!(required_literals("//comment[contains(., 'TODO') or contains(., 'FIXME')]"), none);
+        assert_eq!(required_literals("//comment[not(contains(., 'TODO'))]"), none);
+        assert_eq!(required_literals("//comment[contains(lower-case(.), 'todo')]"), none);
+        assert_eq!(required_literals("//method[@kind = 'method_declaration']"), none);
+        assert_eq!(required_literals("//a[name != 'x']"), none);
+        assert_eq!(required_literals("//a[$file = 'x.cs']"), none);
+        assert_eq!(required_literals("//a[b = 'x'] | //c"), none);
+        assert_eq!(required_literals("//a/tractor:kind(.)[. = 'if_statement']"), none);
+        assert_eq!(required_literals("//method/@kind[. = 'x']"), none);
+        assert_eq!(required_literals("//a[name = 'x' = true()]"), none);
+    }
+}
@@ -3,15 +3,27 @@
 //! This module provides XPath query capabilities for the parsed XML AST.
 
 mod engine;
+mod explain;
+mod functions;
+mod library;
+mod literals;
 mod map_normalize;
 mod match_result;
+mod scan;
+mod variables;
 
-pub use engine::{XPathEngine, print_timing_stats, xot_node_to_xml_node};
+pub use engine::{CompiledQueries, NodeLocation, NodeLocations, XPathEngine, print_timing_stats, xot_node_to_xml_node};
+pub use explain::{path_prefixes, similar_names, PathPrefix};
+pub use library::XPathLibrary;
+pub use literals::required_literals;
+pub use variables::{XPathValue, XPathVariables};
 pub use match_result::{Match, XmlNode};
 
 // Re-export xee-xpath types needed for direct query API
 pub use xee_xpath::{Documents, DocumentHandle};
 
+use std::collections::BTreeSet;
+
 use serde::Serialize;
 use thiserror::Error;
 
@@ -76,21 +88,23 @@ impl ValidationResult {
 /// Returns a ValidationResult indicating whether the expression is valid
 /// and any errors or warnings.
 pub fn validate_xpath(xpath: &str) -> ValidationResult {
-    use xee_xpath::Queries;
-    use crate::xpath::engine::tractor_static_context;
+    // Compile with tractor's built-in variables like $file and the
+    // `tractor:` functions
+    validation(xpath, |xpath| engine::compile_query(xpath, None, None).map(drop))
+}
 
-    // Empty query check
-    let trimmed = xpath.trim();
-    if trimmed.is_empty() {
+/// The result of checking `xpath` with `compile`.
+fn validation(xpath: &str, compile: impl FnOnce(&str) -> Result<(), XPathError>) -> ValidationResult {
+    if xpath.trim().is_empty() {
         return ValidationResult::err("XPath expression is empty".to_string());
     }
-
-    // Try to compile the query (with tractor's built-in variables like $file)
-    let queries = Queries::new(tractor_static_context());
-    match queries.sequence(xpath) {
-        Ok(_) => ValidationResult::ok(),
+    match compile(xpath) {
+        Ok(()) => ValidationResult::ok(),
         Err(e) => {
-            let error_msg = e.to_string();
+            let error_msg = match e {
+                XPathError::Compile(message) => message,
+                other => other.to_string(),
+            };
             let (message, start, end) = parse_xpath_error(&error_msg);
             match (start, end) {
                 (Some(s), Some(e)) => ValidationResult::err_at(message, s, e),
@@ -100,6 +114,36 @@ pub fn validate_xpath(xpath: &str) -> ValidationResult {
     }
 }
 
+/// Element names `xpath` tests for that `known` doesn't contain, each once,
+/// with the byte range of its first use. `element_vocabulary` in the parser
+/// gives the names a language can produce.
+pub fn unknown_element_names<'a>(xpath: &'a str, known: &BTreeSet<&str>) -> Vec<(&'a str, usize, usize)> {
+    let mut unknown: Vec<(&str, usize, usize)> = Vec::new();
+    for test in scan::element_tests(xpath) {
+        if !known.contains(test.name) && !unknown.iter().any(|(name, _, _)| *name == test.name) {
+            unknown.push((test.name, test.start, test.end));
+        }
+    }
+    unknown
+}
+
+/// Whether `xpath` tests for elements named `name` (`//name`, `a[name]`),
+/// not counting strings, attributes or functions of that name.
+pub fn tests_element(xpath: &str, name: &str) -> bool {
+    scan::element_tests(xpath).iter().any(|test| test.name == name)
+}
+
+/// `text` as an XPath string literal.
+pub fn string_literal(text: &str) -> String {
+    if !text.contains('\'') {
+        format!("'{}'", text)
+    } else if !text.contains('"') {
+        format!("\"{}\"", text)
+    } else {
+        format!("'{}'", text.replace('\'', "''"))
+    }
+}
+
 /// Parse XPath error message and extract position info
 ///
 /// xee-xpath errors look like: "XPST0003 Parse error. (8..8)"
@@ -198,6 +242,15 @@ mod validation_tests {
         assert!(result.error_end.is_some(), "Should have error end position");
     }
 
+    #[test]
+    fn test_error_positions_with_tractor_functions() {
+        let query = "tractor:source(.) + )";
+        let result = validate_xpath(query);
+        assert!(!result.valid);
+        assert!(result.error_start.unwrap() > query.find('+').unwrap());
+        assert!(result.error_end.unwrap() <= query.len());
+    }
+
     #[test]
     fn test_empty_xpath() {
         let result = validate_xpath("");
@@ -205,6 +258,13 @@ mod validation_tests {
         assert!(result.error.unwrap().contains("empty"));
     }
 
+    #[test]
+    fn test_unknown_element_names() {
+        let known: BTreeSet<&str> = ["class", "method", "name"].into_iter().collect();
+        let unknown = unknown_element_names("//class/function[name = 'f'] | //function/@kind", &known);
+        assert_eq!(unknown, [("function", 8, 16)]);
+    }
+
     #[test]
     fn test_parse_xpath_error() {
         let (msg, start, end) = parse_xpath_error("XPST0003 Parse error. (8..8)");
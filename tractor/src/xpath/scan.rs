@@ -0,0 +1,324 @@
+//! A minimal XPath scanner for rewriting queries as text.
+//!
+//! Finds names (`foo`, `xs:integer`, `$var`) outside string literals and
+//! comments, and the arguments of function calls. It is not a parser: it
+//! knows just enough of the grammar to rewrite calls and variables safely.
+
+/// A name in a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Name<'a> {
+    /// Start of the name, or of its `$` for a variable.
+    pub start: usize,
+    /// End of the name.
+    pub end: usize,
+    /// The (possibly prefixed) name, without `$`.
+    pub name: &'a str,
+    /// `$name`
+    pub variable: bool,
+    /// Followed by `(`, so a function call (or a kind test like `text()`).
+    pub call: bool,
+}
+
+/// All names in `xpath`, in order.
+pub(super) fn names(xpath: &str) -> Vec<Name<'_>> {
+    let bytes = xpath.as_bytes();
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(next) = skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        let starts_name = is_name_start(bytes[i]) && (i == 0 || !is_name_byte(bytes[i - 1]));
+        if !starts_name {
+            i += 1;
+            continue;
+        }
+        let mut end = name_end(bytes, i);
+        // A prefixed name (`xs:integer`), but not an axis (`child::`) or `:=`
+        if end + 1 < bytes.len() && bytes[end] == b':' && is_name_start(bytes[end + 1]) {
+            end = name_end(bytes, end + 1);
+        }
+        let variable = i > 0 && bytes[i - 1] == b'$';
+        let call = !variable && xpath[end..].trim_start().starts_with('(');
+        names.push(Name {
+            start: if variable { i - 1 } else { i },
+            end,
+            name: &xpath[i..end],
+            variable,
+            call,
+        });
+        i = end;
+    }
+    names
+}
+
+/// The arguments of the call whose name ends at `name_end`, as spans, and
+/// the position just after its closing parenthesis. `None` when the
+/// parentheses are unbalanced.
+pub(super) fn call_arguments(xpath: &str, name_end: usize) -> Option<(Vec<(usize, usize)>, usize)> {
+    let bytes = xpath.as_bytes();
+    let open = name_end + xpath[name_end..].find('(')?;
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut arg_start = open + 1;
+    let mut i = open + 1;
+    while i < bytes.len() {
+        if let Some(next) = skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' if depth == 0 => {
+                if !xpath[arg_start..i].trim().is_empty() || !args.is_empty() {
+                    args.push((arg_start, i));
+                }
+                return Some((args, i + 1));
+            }
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                args.push((arg_start, i));
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The element name tests in `xpath` (`a` and `b` in `//a[b]`), as opposed
+/// to function names, axes, attributes, keywords and prefixed names.
+///
+/// Tracks whether an operand or an operator is expected next, so `or` in
+/// `a or b` is a keyword but `//or` is an element.
+pub(super) fn element_tests(xpath: &str) -> Vec<Name<'_>> {
+    let bytes = xpath.as_bytes();
+    let mut tests = Vec::new();
+    let mut operand = true;
+    // The next name is an attribute (`@a`, `attribute::a`) or a lookup key (`?a`)
+    let mut not_element = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(next) = skip_literal_or_comment(bytes, i) {
+            operand = false;
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => {}
+            b'0'..=b'9' if operand => {
+                i += bytes[i..].iter().take_while(|b| b.is_ascii_alphanumeric() || **b == b'.').count();
+                operand = false;
+                continue;
+            }
+            b'$' => {
+                i = name_end(bytes, i + 1);
+                operand = false;
+                continue;
+            }
+            b'@' | b'?' => {
+                not_element = true;
+                operand = true;
+            }
+            b if is_name_start(b) => {
+                let mut end = name_end(bytes, i);
+                let prefixed = end + 1 < bytes.len() && bytes[end] == b':' && is_name_start(bytes[end + 1]);
+                if prefixed {
+                    end = name_end(bytes, end + 1);
+                }
+                let name = &xpath[i..end];
+                let rest = xpath[end..].trim_start();
+                let axis = rest.starts_with("::");
+                if axis {
+                    operand = true;
+                } else if !operand {
+                    // A keyword operator; `instance of` and `cast as` take
+                    // a second keyword
+                    operand = !matches!(name, "instance" | "treat" | "cast" | "castable");
+                } else if rest.starts_with(['(', '{', '$']) {
+                    // A call, kind test, constructor or `for $x`-style keyword
+                    operand = true;
+                } else {
+                    if !not_element && !prefixed {
+                        tests.push(Name { start: i, end, name, variable: false, call: false });
+                    }
+                    operand = false;
+                }
+                not_element = axis && matches!(name, "attribute" | "namespace");
+                i = end;
+                continue;
+            }
+            b'*' => operand = !operand,
+            b')' | b']' | b'}' | b'.' => operand = false,
+            _ => operand = true,
+        }
+        i += 1;
+    }
+    tests
+}
+
+/// A step of a location path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Step {
+    /// Start of the step, after the `/` or `//` before it.
+    pub start: usize,
+    /// End of the step, including its predicates.
+    pub end: usize,
+    /// Start of the step's first predicate, if it has any.
+    pub predicates: Option<usize>,
+}
+
+/// The steps of `xpath` when it is a single location path (`//a/b[c]`).
+/// `None` for anything else, such as unions, comparisons or arithmetic at
+/// the top level, or unbalanced brackets.
+pub(super) fn path_steps(xpath: &str) -> Option<Vec<Step>> {
+    let bytes = xpath.as_bytes();
+    let mut steps = Vec::new();
+    let mut depth = 0usize;
+    let mut i = bytes.iter().take_while(|&&b| b == b'/').count();
+    let mut start = i;
+    let mut predicates = None;
+    while i < bytes.len() {
+        if let Some(next) = skip_literal_or_comment(bytes, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'[' => {
+                if depth == 0 && predicates.is_none() {
+                    predicates = Some(i);
+                }
+                depth += 1;
+            }
+            b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' => depth = depth.checked_sub(1)?,
+            b'/' if depth == 0 => {
+                steps.push(Step { start, end: i, predicates: predicates.take() });
+                i += bytes[i..].iter().take_while(|&&b| b == b'/').count();
+                start = i;
+                continue;
+            }
+            b'|' | b',' | b'=' | b'<' | b'>' | b'!' | b'+' if depth == 0 => return None,
+            b if depth == 0 && b.is_ascii_whitespace() => return None,
+            _ => {}
+        }
+        i += 1;
+    }
+    if depth != 0 || start == bytes.len() {
+        return None;
+    }
+    steps.push(Step { start, end: bytes.len(), predicates });
+    Some(steps)
+}
+
+/// Whether `name` is a single unprefixed XPath name, as user functions,
+/// fragments and variables are named.
+pub(super) fn is_plain_name(name: &str) -> bool {
+    let found = names(name);
+    matches!(found.as_slice(), [n] if n.start == 0 && n.end == name.len() && !n.name.contains(':'))
+}
+
+/// If a string literal or (nested) comment starts at `i`, the position
+/// just after it.
+pub(super) fn skip_literal_or_comment(bytes: &[u8], i: usize) -> Option<usize> {
+    match bytes[i] {
+        quote @ (b'"' | b'\'') => {
+            // Doubled quotes escape themselves, so skipping to each next
+            // quote in turn stays in sync
+            let len = bytes[i + 1..].iter().position(|&b| b == quote).unwrap_or(bytes.len() - i - 1);
+            Some(i + len + 2)
+        }
+        b'(' if bytes.get(i + 1) == Some(&b':') => {
+            let mut depth = 0;
+            let mut j = i;
+            while j < bytes.len() {
+                if bytes[j..].starts_with(b"(:") {
+                    depth += 1;
+                    j += 2;
+                } else if bytes[j..].starts_with(b":)") {
+                    depth -= 1;
+                    j += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    j += 1;
+                }
+            }
+            Some(j)
+        }
+        _ => None,
+    }
+}
+
+fn name_end(bytes: &[u8], start: usize) -> usize {
+    start + bytes[start..].iter().take_while(|&&b| is_name_byte(b)).count()
+}
+
+fn is_name_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.') || b >= 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_of(xpath: &str) -> Vec<&str> {
+        names(xpath).into_iter().map(|n| n.name).collect()
+    }
+
+    #[test]
+    fn skips_literals_and_comments() {
+        assert_eq!(names_of("//a[b = 'c d' or e = \"f\"] (: g (: h :) :)"), ["a", "b", "or", "e"]);
+        assert_eq!(names_of("'it''s' = x"), ["x"]);
+    }
+
+    #[test]
+    fn distinguishes_prefixes_axes_and_variables() {
+        let found = names("child::x[xs:integer(@n) = $limit]");
+        assert_eq!(found.iter().map(|n| n.name).collect::<Vec<_>>(), ["child", "x", "xs:integer", "n", "limit"]);
+        assert!(found[2].call);
+        assert!(found[4].variable);
+        assert_eq!(found[4].start, "child::x[xs:integer(@n) = ".len());
+    }
+
+    #[test]
+    fn finds_element_tests_but_not_keywords() {
+        let tests = |xpath| element_tests(xpath).into_iter().map(|n| n.name).collect::<Vec<_>>();
+        assert_eq!(tests("//class[name = 'x' or @static]/method[count(parameter) > 2]"), ["class", "name", "method", "parameter"]);
+        assert_eq!(tests("//if or //return"), ["if", "return"]);
+        assert_eq!(tests("for $m in //method return $m/body"), ["method", "body"]);
+        assert_eq!(tests("//a[. instance of element()] | child::b | attribute::c | xs:d"), ["a", "b"]);
+        assert_eq!(tests("//a[2 * 3 div 1]/*/b"), ["a", "b"]);
+    }
+
+    #[test]
+    fn splits_location_paths_into_steps() {
+        let xpath = "//class[name = 'a/b']/body//method[@static][1]";
+        let steps: Vec<&str> = path_steps(xpath).unwrap().iter().map(|s| &xpath[s.start..s.end]).collect();
+        assert_eq!(steps, ["class[name = 'a/b']", "body", "method[@static][1]"]);
+        let last = path_steps(xpath).unwrap()[2];
+        assert_eq!(&xpath[last.start..last.predicates.unwrap()], "method");
+        assert_eq!(path_steps("//a | //b"), None);
+        assert_eq!(path_steps("count(//a) > 1"), None);
+        assert_eq!(path_steps("//a["), None);
+        assert_eq!(path_steps("//a/"), None);
+    }
+
+    #[test]
+    fn splits_call_arguments_at_top_level_commas() {
+        let xpath = "f(a[b, c], 'x,y', g(1, 2)) + 1";
+        let (args, end) = call_arguments(xpath, 1).unwrap();
+        let args: Vec<&str> = args.iter().map(|&(s, e)| xpath[s..e].trim()).collect();
+        assert_eq!(args, ["a[b, c]", "'x,y'", "g(1, 2)"]);
+        assert_eq!(&xpath[end..], " + 1");
+        assert_eq!(call_arguments("f()", 1).unwrap().0, vec![]);
+        assert_eq!(call_arguments("f(a", 1), None);
+    }
+}
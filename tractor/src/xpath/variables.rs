@@ -0,0 +1,104 @@
+//! User variables, bound as `$name` in every query alongside `$file`.
+//!
+//! Values come from `--var name=value` on the command line and the
+//! config's `variables:` section. A run passes them to every parse through
+//! [`ParseOptions::variables`](crate::parser::ParseOptions::variables), and
+//! the engine declares and binds them in each query on the parsed tree.
+
+use std::collections::BTreeMap;
+
+use super::scan;
+
+/// A single variable value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XPathValue {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl XPathValue {
+    /// Type a command-line value: integers become `xs:integer`, everything
+    /// else stays a string.
+    pub fn infer(text: &str) -> Self {
+        match text.parse::<i64>() {
+            Ok(i) if i.to_string() == text => XPathValue::Integer(i),
+            _ => XPathValue::String(text.to_string()),
+        }
+    }
+}
+
+/// Named variables; each holds a sequence of values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XPathVariables {
+    values: BTreeMap<String, Vec<XPathValue>>,
+}
+
+impl XPathVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Bind `name` to `values`, replacing any earlier binding.
+    pub fn set(&mut self, name: &str, values: Vec<XPathValue>) -> Result<(), String> {
+        let name = name.trim().trim_start_matches('$');
+        if !scan::is_plain_name(name) {
+            return Err(format!("invalid variable name '{}'", name));
+        }
+        if name == "file" || name.starts_with("tractor") {
+            return Err(format!("variable name '{}' is reserved", name));
+        }
+        self.values.insert(name.to_string(), values);
+        Ok(())
+    }
+
+    /// Parse a `name=value` assignment. Repeating a name appends to its
+    /// sequence, so `--var ns=A --var ns=B` binds `$ns` to `('A', 'B')`.
+    pub fn assign(&mut self, assignment: &str) -> Result<(), String> {
+        let (name, value) = assignment.split_once('=')
+            .ok_or_else(|| format!("invalid variable '{}', expected NAME=VALUE", assignment))?;
+        let mut values = self.values.get(name.trim().trim_start_matches('$')).cloned().unwrap_or_default();
+        values.push(XPathValue::infer(value));
+        self.set(name, values)
+    }
+
+    /// Add `other`'s variables, replacing those with the same name.
+    pub fn extend(&mut self, other: XPathVariables) {
+        self.values.extend(other.values);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[XPathValue])> {
+        self.values.iter().map(|(name, values)| (name.as_str(), values.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assignments_are_typed_and_accumulate() {
+        let mut vars = XPathVariables::new();
+        vars.assign("max-params=5").unwrap();
+        vars.assign("ns=App.Core").unwrap();
+        vars.assign("$ns=App.Web").unwrap();
+        vars.assign("padded=007").unwrap();
+        let values: BTreeMap<&str, &[XPathValue]> = vars.iter().collect();
+        assert_eq!(values["max-params"], [XPathValue::Integer(5)]);
+        assert_eq!(values["ns"], [XPathValue::String("App.Core".into()), XPathValue::String("App.Web".into())]);
+        assert_eq!(values["padded"], [XPathValue::String("007".into())]);
+    }
+
+    #[test]
+    fn invalid_and_reserved_names_are_rejected() {
+        let mut vars = XPathVariables::new();
+        assert!(vars.assign("no-value").is_err());
+        assert!(vars.assign("a b=1").is_err());
+        assert!(vars.assign("file=x").unwrap_err().contains("reserved"));
+        assert!(vars.assign("tractor.source=x").unwrap_err().contains("reserved"));
+    }
+}
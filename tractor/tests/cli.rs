@@ -24,6 +24,7 @@ cli_suite! {
         add_name => tractor query "sample.rs" -x "function[name='add']" => count 1;
         main_name => tractor query "sample.rs" -x "function[name='main']" => count 1;
         file_variable => tractor query "sample.rs" -x "$file" => count 1;
+        var_sequence => tractor query "sample.rs" -x "function[name = $names]" --var "names=add" --var "names=main" => count 2;
         let_rename => tractor query "sample.rs" -x "let" => count 1;
         binary_op => tractor query "sample.rs" -x "binary[op='+']" => count 1;
         call_rename => tractor query "sample.rs" -x "call" => count 1;
@@ -67,6 +68,61 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    csv in "languages/csv" {
+        rows => tractor query "sample.csv" -x "//row" => count 3;
+        active_flags => tractor query "sample.csv" -x "//row[enabled='true']/flag" => count 2;
+        quoted_cell => tractor query "sample.csv" -x "//owner[.='Payments, EU']" => count 1;
+        escaped_quotes => tractor query "sample.csv" -x "//owner[.='search \"core\"']" => count 1;
+        sanitized_header => tractor query "sample.csv" -x "//display_name[@key='display name']" => count 3;
+        padded_cell => tractor query "sample.csv" -x "//row[flag='dark_mode']/display_name[.='']" => count 1;
+        set_padded_cell => tractor set "sample.csv" -x "//row[flag='dark_mode']/display_name" --value "Dark mode" --stdout => stdout_contains "dark_mode,true,web,Dark mode";
+    }
+}
+
+#[test]
+fn csv_has_only_a_data_tree() {
+    cli_case!({
+        tractor query "sample.csv" -x "//row" -t "structure";
+        expect => {
+            exit 1;
+            stdout_contains "only the data tree is available for language 'csv'";
+        }
+    })
+    .in_fixture("languages/csv")
+    .run();
+}
+
+cli_suite! {
+    dart in "languages/dart" {
+        methods_exist => tractor query "sample.dart" -x "//method" => count 3;
+        method_name => tractor query "sample.dart" -x "//method[name='add']" => count 1;
+        class_name => tractor query "sample.dart" -x "//class[name='Sample']" => count 1;
+        functions => tractor query "sample.dart" -x "//function[name='main']" => count 1;
+        constructor => tractor query "sample.dart" -x "//constructor" => count 1;
+        imports => tractor query "sample.dart" -x "//import" => count 1;
+        calls => tractor query "sample.dart" -x "//call" => count 5;
+        method_calls => tractor query "sample.dart" -x "//method//call[name='print']" => count 1;
+        binary_op => tractor query "sample.dart" -x "//method/body//binary[op='+']" => count 1;
+        private_members => tractor query "sample.dart" -x "//*[private]" => count 2;
+        async_method => tractor query "sample.dart" -x "//method[async]" => count 1;
+    }
+}
+
+cli_suite! {
+    dockerfile in "languages/dockerfile" {
+        file_root => tractor query "sample.dockerfile" -x "file" => count 1;
+        from_instructions => tractor query "sample.dockerfile" -x "//from" => count 2;
+        image_tag => tractor query "sample.dockerfile" -x "//from/image[name='node'][tag='20-alpine']" => count 1;
+        stage_alias => tractor query "sample.dockerfile" -x "//from[alias='build']" => count 1;
+        pinned_by_digest => tractor query "sample.dockerfile" -x "//from/image[digest]" => count 1;
+        unpinned => tractor query "sample.dockerfile" -x "//from/image[not(digest)]" => count 1;
+        copy_from_stage => tractor query "sample.dockerfile" -x "//copy/param[name='from'][value='build']" => count 1;
+        run_command => tractor query "sample.dockerfile" -x "//run/command[.='npm ci']" => count 1;
+        user => tractor query "sample.dockerfile" -x "//user[name='app']" => count 1;
+    }
+}
+
 cli_suite! {
     go in "languages/go" {
         functions_exist => tractor query "sample.go" -x "function" => count 3;
@@ -81,6 +137,40 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    graphql in "languages/graphql" {
+        file_root => tractor query "sample.graphql" -x "/file" => count 1;
+        types => tractor query "sample.graphql" -x "//type[name]" => count 2;
+        fields => tractor query "sample.graphql" -x "//type[name='User']/field" => count 3;
+        arguments => tractor query "sample.graphql" -x "//field[name='users']/argument" => count 2;
+        argument_default => tractor query "sample.graphql" -x "//argument[name='first']/default/int[.='10']" => count 1;
+        directives => tractor query "sample.graphql" -x "//field/directive" => count 2;
+        deprecated => tractor query "sample.graphql" -x "//field[directive/name='deprecated']/name[.='users']" => count 1;
+        implements => tractor query "sample.graphql" -x "//type[implements/type='Node']" => count 1;
+        enum_values => tractor query "sample.graphql" -x "//enum[name='Role']/value" => count 2;
+        input_fields => tractor query "sample.graphql" -x "//input/field[name='role']" => count 1;
+        comments => tractor query "sample.graphql" -x "//comment" => count 1;
+    }
+}
+
+cli_suite! {
+    hcl in "languages/hcl" {
+        document_root => tractor query "sample.tf" -x "//document" -t "data" => count 1;
+        resources => tractor query "sample.tf" -x "//resource[@type='aws_s3_bucket']" => count 2;
+        resource_name => tractor query "sample.tf" -x "//resource[@name='logs']/bucket" -t "data" => count 1;
+        module_version => tractor query "sample.tf" -x "//module[@name='vpc']/version[.='5.0.0']" -t "data" => count 1;
+        variable_default => tractor query "sample.tf" -x "//variable[@name='region']/default[.='eu-west-1']" -t "data" => count 1;
+        object_value => tractor query "sample.tf" -x "//tags/team[.='platform']" -t "data" => count 1;
+        template_value => tractor query "sample.tf" -x "//bucket[@kind='template']" -t "data" => count 1;
+        expression_value => tractor query "sample.tf" -x "//cidr[@kind='expression'][.='var.cidr']" -t "data" => count 1;
+        structure_attributes => tractor query "sample.tf" -x "//module/attribute[name='version']" => count 1;
+        bucket_attributes => tractor query "sample.tf" -x "//resource[@type='aws_s3_bucket']/attribute[name='bucket']" => count 2;
+        data_has_no_attributes => tractor query "sample.tf" -x "//module/attribute" -t "data" => count 0;
+        comments => tractor query "sample.tf" -x "//comment" => count 1;
+        set_module_version => tractor set "sample.tf" -x "//module[@name='vpc']/version" --value "5.1.0" --stdout => stdout_contains "version = \"5.1.0\"";
+    }
+}
+
 cli_suite! {
     ini in "languages/ini" {
         global_name => tractor query "sample.ini" -x "//name[.='my-app']" => count 1;
@@ -129,6 +219,40 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    jsonc in "languages/jsonc" {
+        top_level_keys => tractor query "sample.jsonc" -x "/*/*" => count 2;
+        count_sees_only_data => tractor query "sample.jsonc" -x "//compilerOptions[count(*) = 4]" => count 1;
+        no_comments_in_data => tractor query "sample.jsonc" -x "//comment" => count 0;
+        trailing_comma_array => tractor query "sample.jsonc" -x "//compilerOptions/paths" => count 2;
+        structure_comments => tractor query "sample.jsonc" -x "//comment" -t "structure" => count 3;
+    }
+}
+
+cli_suite! {
+    json5 in "languages/json5" {
+        top_level_keys => tractor query "sample.json5" -x "/*/*" => count 4;
+        single_quoted_value => tractor query "sample.json5" -x "//name[.='tractor']" => count 1;
+        hex_number_keeps_spelling => tractor query "sample.json5" -x "//port[.='0x1F90']" => count 1;
+        trailing_comma_array => tractor query "sample.json5" -x "//tags" => count 2;
+        structure_comments => tractor query "sample.json5" -x "//comment" -t "structure" => count 2;
+    }
+}
+
+cli_suite! {
+    kotlin in "languages/kotlin" {
+        methods_exist => tractor query "sample.kt" -x "//method" => count 3;
+        method_name => tractor query "sample.kt" -x "//method[name='add']" => count 1;
+        class_name => tractor query "sample.kt" -x "//class[name='Sample']" => count 1;
+        functions => tractor query "sample.kt" -x "//function[name='main']" => count 1;
+        package => tractor query "sample.kt" -x "//package" => count 1;
+        imports => tractor query "sample.kt" -x "//import" => count 1;
+        calls => tractor query "sample.kt" -x "//call" => count 5;
+        private_methods => tractor query "sample.kt" -x "//method[private]" => count 1;
+        suspend_method => tractor query "sample.kt" -x "//method[suspend]" => count 1;
+    }
+}
+
 cli_suite! {
     markdown in "languages/markdown" {
         headings => tractor query "sample.md" -x "//heading" => count 2;
@@ -146,6 +270,70 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    notebook in "languages/notebook" {
+        cells => tractor query "sample.ipynb" -x "/notebook/cell" => count 4;
+        python_cells => tractor query "sample.ipynb" -x "//cell[@type='code'][@lang='python']/module" => count 2;
+        markdown_cells => tractor query "sample.ipynb" -x "//cell[@type='markdown']//heading" => count 1;
+        raw_cells_unparsed => tractor query "sample.ipynb" -x "//cell[@type='raw']/*" => count 0;
+        calls_by_cell => tractor query "sample.ipynb" -x "//cell[@index='3']//call[name='print']" => count 1;
+        assignments => tractor query "sample.ipynb" -x "//assign[left/name='API_KEY']" => count 1;
+        functions => tractor query "sample.ipynb" -x "//function[name='summarize']" => count 1;
+    }
+}
+
+#[test]
+fn notebook_rules_see_only_cells_in_their_language() {
+    // Two code cells for the Python rule, one markdown cell for the other
+    let config = "check:\n  rules:\n    - id: python-cells\n      xpath: \"//cell/*\"\n      reason: \"python\"\n      language: python\n    - id: markdown-cells\n      xpath: \"//cell/*\"\n      reason: \"markdown\"\n      language: markdown\n";
+    cli_case!({
+        tractor check --config "tractor.yml" "sample.ipynb";
+        expect => {
+            exit 1;
+            stdout_contains "3 errors in 1 file";
+        }
+    })
+    .in_fixture("languages/notebook")
+    .temp_fixture()
+    .seed_file("tractor.yml", config)
+    .run();
+}
+
+#[test]
+fn config_language_mappings_place_cli_files() {
+    // `.txt` is not a known extension; the mapping makes it Python
+    let config = "languages:\n  \"*.txt\": python\ncheck:\n  rules:\n    - id: no-eval\n      xpath: \"//call[name='eval']\"\n      reason: \"eval\"\n      language: python\n";
+    cli_case!({
+        tractor check --config "tractor.yml" "notes.txt";
+        expect => {
+            exit 1;
+            stdout_contains "1 error in 1 file";
+        }
+    })
+    .in_fixture("languages/python")
+    .temp_fixture()
+    .seed_file("tractor.yml", config)
+    .seed_file("notes.txt", "eval(x)\n")
+    .run();
+}
+
+cli_suite! {
+    protobuf in "languages/protobuf" {
+        file_root => tractor query "sample.proto" -x "/file" => count 1;
+        messages => tractor query "sample.proto" -x "//message[name='User']" => count 1;
+        numbered_fields => tractor query "sample.proto" -x "//message/field[@number]" => count 3;
+        repeated_field => tractor query "sample.proto" -x "//field[repeated][name='emails']" => count 1;
+        qualified_type => tractor query "sample.proto" -x "//field[type='google.protobuf.Timestamp']" => count 1;
+        unique_numbers => tractor query "sample.proto" -x "//message[count(field/@number) = count(distinct-values(field/@number))]" => count 1;
+        enum_values => tractor query "sample.proto" -x "//enum[name='Status']/value[@number='1']" => count 1;
+        rpcs => tractor query "sample.proto" -x "//service/rpc" => count 2;
+        rpc_types => tractor query "sample.proto" -x "//rpc[name='GetUser'][request='GetUserRequest'][response='User']" => count 1;
+        streaming => tractor query "sample.proto" -x "//rpc[response/stream]/name[.='ListUsers']" => count 1;
+        commented_rpcs => tractor query "sample.proto" -x "//rpc[preceding-sibling::*[1][self::comment]]" => count 1;
+        package => tractor query "sample.proto" -x "//package" => count 1;
+    }
+}
+
 cli_suite! {
     python in "languages/python" {
         functions_exist => tractor query "sample.py" -x "function" => count 3;
@@ -174,6 +362,31 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    svelte in "languages/svelte" {
+        blocks => tractor query "sample.svelte" -x "/component/*" => count 3;
+        markup => tractor query "sample.svelte" -x "/component/template[@lang='html']//element" => count 3;
+        markup_skips_script => tractor query "sample.svelte" -x "//template//script" => count 0;
+        script => tractor query "sample.svelte" -x "//script[@lang='javascript']//function[name='increment']" => count 1;
+        console_calls => tractor query "sample.svelte" -x "//script//call[function/member/object='console']" => count 1;
+        styles => tractor query "sample.svelte" -x "//style//rule_set" => count 1;
+    }
+}
+
+cli_suite! {
+    swift in "languages/swift" {
+        methods_exist => tractor query "sample.swift" -x "//method" => count 3;
+        method_name => tractor query "sample.swift" -x "//method[name='add']" => count 1;
+        class_name => tractor query "sample.swift" -x "//class[name='Sample']" => count 1;
+        functions => tractor query "sample.swift" -x "//function[name='main']" => count 1;
+        constructor => tractor query "sample.swift" -x "//constructor" => count 1;
+        imports => tractor query "sample.swift" -x "//import" => count 1;
+        calls => tractor query "sample.swift" -x "//call" => count 5;
+        private_methods => tractor query "sample.swift" -x "//method[private]" => count 1;
+        binary_op => tractor query "sample.swift" -x "//binary[op='+']" => count 1;
+    }
+}
+
 cli_suite! {
     toml in "languages/toml" {
         title => tractor query "sample.toml" -x "//title[.='My App']" => count 1;
@@ -272,6 +485,35 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    vue in "languages/vue" {
+        blocks => tractor query "sample.vue" -x "/component/*" => count 3;
+        template => tractor query "sample.vue" -x "/component/template[@lang='html']//element" => count 3;
+        script_setup => tractor query "sample.vue" -x "//script[@lang='typescript'][@setup]" => count 1;
+        functions => tractor query "sample.vue" -x "//script//function[name='greet']" => count 1;
+        imports => tractor query "sample.vue" -x "//script//import" => count 1;
+        console_calls => tractor query "sample.vue" -x "//script//call[function/member/object='console']" => count 1;
+        scoped_style => tractor query "sample.vue" -x "//style[@scoped]//rule_set" => count 1;
+    }
+}
+
+#[test]
+fn component_rules_see_only_blocks_in_their_language() {
+    // One block each: the script for the TypeScript rule, the style for the CSS rule
+    let config = "check:\n  rules:\n    - id: typescript-blocks\n      xpath: \"/component/*/*\"\n      reason: \"typescript\"\n      language: typescript\n    - id: css-blocks\n      xpath: \"/component/*/*\"\n      reason: \"css\"\n      language: css\n";
+    cli_case!({
+        tractor check --config "tractor.yml" "sample.vue";
+        expect => {
+            exit 1;
+            stdout_contains "2 errors in 1 file";
+        }
+    })
+    .in_fixture("languages/vue")
+    .temp_fixture()
+    .seed_file("tractor.yml", config)
+    .run();
+}
+
 cli_suite! {
     xml in "languages/xml" {
         items => tractor query "sample.xml" -x "item" => count 3;
@@ -332,6 +574,13 @@ cli_suite! {
     }
 }
 
+cli_suite! {
+    pattern in "string-input" {
+        pattern_wildcards => tractor pattern "greet($name)" -s "greet('a'); greet(user.name); greet(); greet(a, b);" -l "javascript" => count 2;
+        pattern_repeated_wildcard => tractor pattern "$a == $a" -s "x == x\nx == y\na.b == a . b" -l "python" => count 2;
+    }
+}
+
 cli_suite! {
     xpath_expressions in "xpath-expressions" {
         let_expression => tractor query -s "let x = 1; let y = 2;" -l "typescript" -x "let $v := //variable return $v/name" -v "value" => count 2;
@@ -388,6 +637,26 @@ fn markdown_round_trip_extracts_javascript_block() {
     assert_eq!("1", parsed.stdout);
 }
 
+#[test]
+fn at_prints_xpaths_for_each_enclosing_element() {
+    let output = command(["at", "MyApp/Services/UserService.cs:3:18"])
+        .in_fixture("variables")
+        .capture();
+    assert_eq!(0, output.status);
+    assert!(output.stdout.contains("//class[name='UserService']"), "{}", output.stdout);
+    assert!(output.stdout.contains("/unit/namespace/"), "{}", output.stdout);
+}
+
+#[test]
+fn at_reads_xml_locations() {
+    let output = command(["at", "sample.xml:10:7"])
+        .in_fixture("languages/xml")
+        .capture();
+    assert_eq!(0, output.status);
+    assert!(output.stdout.contains("/project/items/item[1]/status"), "{}", output.stdout);
+    assert!(output.stdout.contains("//item[name='Login']/status"), "{}", output.stdout);
+}
+
 #[test]
 fn string_input_requires_language() {
     command(["query", "--string", "let x = 1;"])
@@ -32,6 +32,12 @@ fn parse_test_inline(
             tree_mode,
             ignore_whitespace: false,
             parse_depth: None,
+            injections: false,
+            doc_comments: false,
+            embedded_language: None,
+            transforms: None,
+            encoding: None,
+            queries: None,
         },
     )
 }
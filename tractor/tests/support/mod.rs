@@ -730,8 +730,8 @@ fn observe_match_count(command: &TractorInvocation, cwd: &Path) -> usize {
         .unwrap_or("query");
 
     match subcommand {
-        "query" | "check" | "test" => {}
-        _ => panic!("count assertions are only supported for query/check/test commands"),
+        "query" | "check" | "test" | "pattern" => {}
+        _ => panic!("count assertions are only supported for query/check/test/pattern commands"),
     }
 
     let observed = run_invocation(&command.with_count_view(), cwd);
@@ -209,6 +209,21 @@ tractor "src/**/*.js" --diff-lines "main..HEAD" -x "//function" -v count`} />
         </tbody>
       </table>
 
+      <h2>Functions</h2>
+      <table className="doc-table">
+        <thead>
+          <tr><th>Function</th><th>Description</th></tr>
+        </thead>
+        <tbody>
+          <tr><td><code>tractor:line(.)</code>, <code>tractor:column(.)</code></td><td>Start position of a node</td></tr>
+          <tr><td><code>tractor:lines-of(.)</code></td><td>Number of lines a node spans</td></tr>
+          <tr><td><code>tractor:source(.)</code></td><td>Original source text of a node</td></tr>
+          <tr><td><code>tractor:kind(.)</code></td><td>Raw TreeSitter kind of a node</td></tr>
+          <tr><td><code>tractor:file-name()</code>, <code>tractor:dir()</code></td><td>Parts of <code>$file</code></td></tr>
+          <tr><td><code>tractor:language()</code></td><td>Language of the current file</td></tr>
+        </tbody>
+      </table>
+
       <h2>Key CLI Flags</h2>
       <table className="doc-table">
         <thead>
@@ -221,7 +236,7 @@ tractor "src/**/*.js" --diff-lines "main..HEAD" -x "//function" -v count`} />
           <tr><td><code>-d</code></td><td>Limit tree depth</td></tr>
           <tr><td><code>-n</code></td><td>Limit number of matches</td></tr>
           <tr><td><code>-W</code></td><td>Ignore whitespace in string matching</td></tr>
-          <tr><td><code>-t</code></td><td>Tree mode (structure, data, raw)</td></tr>
+          <tr><td><code>-t</code></td><td>Tree mode (structure, common, data, raw)</td></tr>
           <tr><td><code>--meta</code></td><td>Include position and kind metadata</td></tr>
           <tr><td><code>--diff-files</code></td><td>Only files changed in a git range</td></tr>
           <tr><td><code>--diff-lines</code></td><td>Only matches in changed hunks</td></tr>
@@ -56,6 +56,7 @@ tractor file1.js file2.js`} />
         </thead>
         <tbody>
           <tr><td><code>structure</code></td><td>Semantic syntax tree with transforms</td><td>Code languages (JS, Rust, etc.)</td></tr>
+          <tr><td><code>common</code></td><td>Structure tree with one vocabulary for all languages (<Link to="/docs/languages/code#common-vocabulary">details</Link>)</td><td>—</td></tr>
           <tr><td><code>data</code></td><td>Data projection — keys become elements, values become text</td><td>JSON, YAML, TOML, INI</td></tr>
           <tr><td><code>raw</code></td><td>Raw parser output, no transforms (advanced)</td><td>—</td></tr>
         </tbody>
@@ -92,6 +93,18 @@ document/
       />
       <p>Without <code>-W</code>, the match would fail because the source has spaces: <code>( name )</code>.</p>
 
+      <h3>--var NAME=VALUE</h3>
+      <p>
+        Bind <code>$NAME</code> in every query, next to <code>$file</code>. Integer values are bound as numbers and
+        everything else as strings. Repeat a name to bind a sequence. <code>--var</code> overrides the config's{' '}
+        <code>variables</code> of the same name.
+      </p>
+      <CodeBlock
+        language="bash"
+        code={`tractor check --config tractor.yml --var max-params=4
+tractor src/**/*.cs -x "//namespace[not(name = $allowed)]" --var allowed=App.Core --var allowed=App.Web`}
+      />
+
       <h2>View</h2>
 
       <h3>-v, --view</h3>
@@ -108,6 +121,7 @@ document/
           <tr><td><code>count</code></td><td>Total number of matches</td></tr>
           <tr><td><code>schema</code></td><td>Structural overview of element types</td></tr>
           <tr><td><code>query</code></td><td>Echo the query (useful for debugging shell escaping)</td></tr>
+          <tr><td><code>parse-errors</code></td><td>Report syntax errors in parsed files (see <code>--fail-on-parse-error</code>)</td></tr>
         </tbody>
       </table>
       <Example
@@ -388,6 +402,43 @@ tractor check "src/**/*.js" --diff-lines "main..HEAD" -x "//comment[contains(.,'
       <p>Show detailed output, including which files are being processed.</p>
       <CodeBlock language="bash" code={`tractor "src/**/*.js" -x "//function" --verbose`} />
 
+      <h3>--fail-on-parse-error</h3>
+      <p>
+        Report syntax errors as errors and fail the run. The parser recovers from invalid syntax by wrapping
+        what it couldn't parse in <code>ERROR</code> nodes, so rules can quietly stop matching in broken files.
+        Use <code>-v +parse-errors</code> to list syntax errors as warnings without failing.
+      </p>
+      <CodeBlock language="bash" code={`tractor check "src/**/*.cs" -x "//method[not(body)]" --reason "missing body" --fail-on-parse-error`} />
+
+      <h3>--explain</h3>
+      <p>
+        When a query, check rule, or test assertion matches nothing, explain why. Tractor evaluates the
+        location path one step at a time, reports the step where matches drop to zero, and suggests
+        similarly named elements found just before it. The explanation is an <code>info</code> note and
+        never fails the run.
+      </p>
+      <Example
+        command={`tractor "src/**/*.cs" -x "//class/body/methd" --explain`}
+        output={`info(xpath): '//class/body' matches 12 nodes, but '//class/body/methd' matches none; did you mean 'method'?
+1 | //class/body/methd
+                 ^~~~~`}
+      />
+
+      <h3>--timeout-per-file</h3>
+      <p>
+        Stop evaluating a file's queries after this long (<code>500ms</code>, <code>30s</code>, <code>2m</code>).
+        The file gets a warning for the query that was running, the file's remaining queries are skipped,
+        and the rest of the run finishes. Check rules can also set their own <code>timeout</code> in the config.
+        A stopped query can't be interrupted, so it keeps running in the background until it finishes. Once
+        one per core (at least four) is still running, tractor doesn't wait for them: it skips the time-limited
+        queries on the next files with a warning until one of them finishes.
+      </p>
+      <Example
+        command={`tractor check "gen/**/*.cs" -x "//*[. = following::*]" --reason "duplicate" --timeout-per-file 10s`}
+        output={`gen/Schema.cs:1:1: warning: query timed out after 10s on this file and keeps running in the background until it finishes; the remaining queries on this file were skipped
+1 warning in 1 file`}
+      />
+
       <h3>--debug</h3>
       <p>Show the full tree with match highlights and metadata. Useful for debugging queries. Debug mode uses XML output to show all internal detail.</p>
       <Example
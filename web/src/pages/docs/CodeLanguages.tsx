@@ -19,7 +19,7 @@ export function CodeLanguages() {
           <tr><th>Language</th><th>Extensions</th><th>-l value</th><th>Aliases</th><th>Support</th></tr>
         </thead>
         <tbody>
-          <tr><td>C#</td><td><code>.cs</code></td><td><code>csharp</code></td><td><code>cs</code></td><td><span className="badge badge-full">Full</span></td></tr>
+          <tr><td>C#</td><td><code>.cs, .csx, .cake</code></td><td><code>csharp</code></td><td><code>cs</code></td><td><span className="badge badge-full">Full</span></td></tr>
           <tr><td>JavaScript</td><td><code>.js, .mjs, .cjs, .jsx</code></td><td><code>javascript</code></td><td><code>js, jsx</code></td><td><span className="badge badge-full">Full</span></td></tr>
           <tr><td>TypeScript</td><td><code>.ts</code></td><td><code>typescript</code></td><td><code>ts</code></td><td><span className="badge badge-full">Full</span></td></tr>
           <tr><td>TSX</td><td><code>.tsx</code></td><td><code>tsx</code></td><td>—</td><td><span className="badge badge-full">Full</span></td></tr>
@@ -28,6 +28,12 @@ export function CodeLanguages() {
           <tr><td>Java</td><td><code>.java</code></td><td><code>java</code></td><td>—</td><td><span className="badge badge-full">Full</span></td></tr>
           <tr><td>Rust</td><td><code>.rs</code></td><td><code>rust</code></td><td><code>rs</code></td><td><span className="badge badge-full">Full</span></td></tr>
           <tr><td>T-SQL</td><td><code>.sql</code></td><td><code>tsql</code></td><td><code>mssql</code></td><td><span className="badge badge-full">Full</span></td></tr>
+          <tr><td>Kotlin</td><td><code>.kt, .kts</code></td><td><code>kotlin</code></td><td><code>kt</code></td><td><span className="badge badge-good">Good</span></td></tr>
+          <tr><td>Swift</td><td><code>.swift</code></td><td><code>swift</code></td><td>—</td><td><span className="badge badge-good">Good</span></td></tr>
+          <tr><td>Dart</td><td><code>.dart</code></td><td><code>dart</code></td><td>—</td><td><span className="badge badge-good">Good</span></td></tr>
+          <tr><td>Dockerfile</td><td><code>Dockerfile, .dockerfile</code></td><td><code>dockerfile</code></td><td><code>docker</code></td><td><span className="badge badge-good">Good</span></td></tr>
+          <tr><td>Protobuf</td><td><code>.proto</code></td><td><code>protobuf</code></td><td><code>proto</code></td><td><span className="badge badge-good">Good</span></td></tr>
+          <tr><td>GraphQL</td><td><code>.graphql, .gql</code></td><td><code>graphql</code></td><td><code>gql</code></td><td><span className="badge badge-good">Good</span></td></tr>
           <tr><td>Ruby</td><td><code>.rb, .rake, .gemspec</code></td><td><code>ruby</code></td><td><code>rb</code></td><td><span className="badge badge-good">Good</span></td></tr>
           <tr><td>C</td><td><code>.c, .h</code></td><td><code>c</code></td><td>—</td><td><span className="badge badge-basic">Basic</span></td></tr>
           <tr><td>C++</td><td><code>.cpp, .cc, .cxx, .hpp, .hxx, .hh</code></td><td><code>cpp</code></td><td>—</td><td><span className="badge badge-basic">Basic</span></td></tr>
@@ -40,6 +46,9 @@ export function CodeLanguages() {
           <tr><td>R</td><td><code>.r</code></td><td><code>r</code></td><td>—</td><td><span className="badge badge-basic">Basic</span></td></tr>
           <tr><td>Julia</td><td><code>.jl</code></td><td><code>julia</code></td><td>—</td><td><span className="badge badge-basic">Basic</span></td></tr>
           <tr><td>Markdown</td><td><code>.md, .markdown, .mdx</code></td><td><code>markdown</code></td><td><code>md, mdx</code></td><td><span className="badge badge-full">Full</span></td></tr>
+          <tr><td>Jupyter Notebook</td><td><code>.ipynb</code></td><td><code>notebook</code></td><td><code>ipynb, jupyter</code></td><td><span className="badge badge-full">Full</span></td></tr>
+          <tr><td>Vue</td><td><code>.vue</code></td><td><code>vue</code></td><td>—</td><td><span className="badge badge-full">Full</span></td></tr>
+          <tr><td>Svelte</td><td><code>.svelte</code></td><td><code>svelte</code></td><td>—</td><td><span className="badge badge-full">Full</span></td></tr>
         </tbody>
       </table>
       <p>
@@ -66,6 +75,10 @@ tractor "src/**/*.js" "src/**/*.ts" -x "//function/name" -v value`} />
         When reading from stdin, use <code>-l</code> to specify the language:
       </p>
       <CodeBlock language="bash" code={`echo 'fn main() {}' | tractor -l rust -x "//function/name" -v value`} />
+      <p>
+        Files without a recognized extension are detected from a shebang line (<code>#!/usr/bin/env python3</code>) or an editor
+        modeline. Other files can be mapped in <code>tractor.yml</code> with a <Link to="/docs/commands/run">languages</Link> section.
+      </p>
 
       <h2>Bash</h2>
       <Example
@@ -101,6 +114,131 @@ done` }}
 (use -d to increase depth, or -x to query specific elements)`}
       />
 
+      <h2>Jupyter Notebooks</h2>
+      <p>
+        Each cell of a notebook is parsed on its own: code cells in the kernel's language (Python unless the notebook's
+        metadata says otherwise), markdown cells as Markdown. The trees are collected
+        as <code>notebook/cell[@index][@type]</code>, with <code>@index</code> counting cells from 1:
+      </p>
+      <CodeBlock language="bash" code={`# print() left in code cells
+tractor "**/*.ipynb" -x "//cell[@type='code']//call[name='print']"`} />
+      <p>
+        Line numbers point into the <code>.ipynb</code> file, at the JSON string holding the cell's line.
+        Rules with <code>language: python</code> also run on notebooks, and see only the Python cells; the other cells
+        are left empty for them. Likewise for <code>language: markdown</code>.
+      </p>
+
+      <h2>Vue and Svelte</h2>
+      <p>
+        Single-file components are split into their blocks, each parsed with its own language: markup as HTML,
+        <code>&lt;script&gt;</code> as JavaScript or TypeScript (<code>lang="ts"</code>), <code>&lt;style&gt;</code> as CSS.
+        The trees are collected as <code>component/template</code>, <code>component/script</code>
+        and <code>component/style</code>, with <code>@lang</code> naming the language and attributes
+        like <code>setup</code> or <code>scoped</code> carried over:
+      </p>
+      <CodeBlock language="bash" code={`# Functions declared in TypeScript component scripts
+tractor "src/**/*.vue" -x "//script[@lang='typescript']//function"`} />
+      <p>
+        Line numbers point into the component file. Rules for TypeScript, JavaScript, HTML or CSS also run on components,
+        and see only the blocks in their language.
+      </p>
+
+      <h2>Embedded Languages</h2>
+      <p>
+        Code embedded in another file is parsed with its own language and added under the host node
+        as <code>injected[@lang='...']</code>, with line numbers from the host file:
+      </p>
+      <ul>
+        <li>Markdown fenced code blocks, using the info string (<code>```sql</code>, <code>```ts</code>)</li>
+        <li>HTML <code>&lt;script&gt;</code> (JavaScript or JSON) and <code>&lt;style&gt;</code> (CSS)</li>
+        <li>C# string literals passed to SQL APIs: <code>migrationBuilder.Sql</code>, <code>FromSqlRaw</code>, <code>SqlCommand</code>, Dapper's <code>Query</code>/<code>Execute</code></li>
+        <li>GitHub Actions <code>run:</code> scripts in <code>.github/workflows/</code> and <code>action.yml</code> (bash, or python per <code>shell:</code>)</li>
+      </ul>
+      <CodeBlock language="bash" code={`# T-SQL rules over SQL in EF Core migrations
+tractor "Migrations/*.cs" -x "//injected[@lang='tsql']//update[not(where)]"
+
+# Shell commands in workflows
+tractor ".github/workflows/*.yml" -x "//run/injected//command[name='curl']"`} />
+      <p>
+        Injected trees are only built for queries that test for <code>injected</code> elements; other queries and rules, even in
+        the same run, see the host tree unchanged. The injected tree repeats the host's text, so a match's value leaves it out.
+      </p>
+
+      <h2>Doc Comments</h2>
+      <p>
+        Doc comments directly above a declaration are moved into a <code>doc</code> child of that declaration,
+        so attributes or decorators in between don't break the association. Where the language has a convention,
+        the comment is parsed into <code>summary</code>, <code>param[@name]</code> and <code>returns</code>:
+      </p>
+      <ul>
+        <li>C# <code>///</code> XML doc comments (<code>&lt;summary&gt;</code>, <code>&lt;param name&gt;</code>, <code>&lt;returns&gt;</code>)</li>
+        <li>Javadoc and JSDoc <code>/** */</code> (<code>@param</code>, <code>@return</code>)</li>
+        <li>Rust <code>///</code>, and <code>//!</code> for the enclosing item</li>
+        <li>Go comment groups directly above a declaration</li>
+        <li>Python docstrings, including Sphinx <code>:param x:</code> fields</li>
+      </ul>
+      <CodeBlock language="bash" code={`# Public methods without an XML doc summary
+tractor "src/**/*.cs" -x "//method[public][not(doc/summary)]/name"
+
+# Python functions without a docstring
+tractor "**/*.py" -x "//function[not(doc)]/name"`} />
+      <p>
+        Like injected trees, <code>doc</code> elements are only built for queries that test for <code>doc</code> elements;
+        other rules in the same run still see the comments where they were written.
+      </p>
+
+      <h2 id="common-vocabulary">Common Vocabulary</h2>
+      <p>
+        Each language keeps its own terms in the structure tree: a C# <code>method</code>, a Java <code>ctor</code>,
+        a Go <code>param</code>, a Rust <code>line_comment</code>. With <code>-t common</code> the concepts every
+        language shares get one name, so a rule written once works across a polyglot repository:
+      </p>
+      <table className="doc-table">
+        <thead>
+          <tr><th>Element</th><th>Covers</th></tr>
+        </thead>
+        <tbody>
+          <tr><td><code>function</code></td><td>Function, method and constructor declarations</td></tr>
+          <tr><td><code>class</code></td><td>Class declarations (structs, interfaces and traits keep their names)</td></tr>
+          <tr><td><code>call</code></td><td>Function and method calls</td></tr>
+          <tr><td><code>import</code></td><td>Imports, <code>using</code> directives, Rust <code>use</code></td></tr>
+          <tr><td><code>parameter</code></td><td>A declared parameter, always a direct child of its <code>function</code></td></tr>
+          <tr><td><code>string</code></td><td>String literals, including interpolated and template strings</td></tr>
+          <tr><td><code>comment</code></td><td>Line, block and doc comments</td></tr>
+        </tbody>
+      </table>
+      <CodeBlock language="bash" code={`# Functions with more than five parameters, in any language
+tractor "src/**/*" -t common -x "//function[count(parameter) > 5]/name"`} />
+      <p>
+        Everything else is left as the structure tree has it. It covers C#, TypeScript/JavaScript, Python, Go, Rust,
+        Java, Ruby, Kotlin, Swift and Dart; a Kotlin primary constructor is a <code>function</code> of its class.
+        In TypeScript, the target of a call is a <code>callee</code> rather than a <code>function</code>. Rules choose
+        the tree with <code>tree-mode: common</code>.
+      </p>
+
+      <h2>Custom Transforms</h2>
+      <p>
+        Languages with <strong>basic</strong> support can be given a vocabulary of your own in <code>tractor.yml</code>.
+        The same keys adjust the output of a built-in transform:
+      </p>
+      <CodeBlock language="yaml" code={`transforms:
+  lua:
+    rename:
+      function_declaration: function
+      identifier: name
+    flatten: [block]            # promote children, drop the wrapper
+    skip: [comment]             # remove with content
+    lift: [parameters]          # field="parameters" → <parameters> wrapper
+  scala:
+    mode: replace               # start from the raw tree instead
+    modifiers: [modifiers]      # <modifiers>private</modifiers> → <private/>
+    known-modifiers: [private, protected, final]`} />
+      <p>
+        By default (<code>mode: extend</code>) the transform runs after the built-in one, so element names are the ones
+        <code>-v schema</code> shows. With <code>mode: replace</code> it runs on the raw parser tree (<code>-t raw</code>) instead.
+        Transforms apply to the structure tree only.
+      </p>
+
       <h2>Discovering the Tree</h2>
       <p>
         Every language produces a different tree. The workflow is always the same:
@@ -16,6 +16,15 @@ const SETTINGS_YAML = `database:
 debug: true
 log_level: verbose`;
 
+const MAIN_TF = `module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "5.0.0"
+}
+
+resource "aws_s3_bucket" "logs" {
+  bucket = "my-logs"
+}`;
+
 const CONFIG_TOML = `[database]
 host = "localhost"
 port = 5432`;
@@ -25,7 +34,7 @@ export function DataLanguages() {
     <DocLayout>
       <h1>Data Languages</h1>
       <p className="doc-lead">
-        JSON, YAML, TOML, and INI files are parsed into a data tree where keys become elements and values become text. You query them the same way as code — but the tree is shaped like the data, not like syntax.
+        JSON, YAML, TOML, INI, and HCL (Terraform) files have a data tree where keys become elements and values become text. You query them the same way as code — but the tree is shaped like the data, not like syntax. HCL is queried as blocks and attributes by default; see below.
       </p>
 
       <h2>How It Works</h2>
@@ -52,6 +61,27 @@ document/
         output="localhost"
       />
 
+      <h3>JSONC and JSON5</h3>
+      <p>
+        <code>tsconfig.json</code>, <code>jsconfig.json</code>, <code>devcontainer.json</code>, JSON files
+        under <code>.vscode/</code>, and <code>.jsonc</code> files are parsed as JSONC: comments and
+        trailing commas are allowed. The data view leaves comments out, so <code>*</code> and <code>count(*)</code> see
+        only the data; the structure view (<code>-t structure</code>) keeps them as <code>comment</code> elements.
+        <Link to="/docs/commands/set">tractor set</Link> edits the file in place, keeping comments and the
+        trailing-comma style:
+      </p>
+      <CodeBlock language="bash" code={`tractor set tsconfig.json -x "//compilerOptions/target" --value es2022`} />
+      <p>
+        Use <code>-l jsonc</code> for other JSON files with comments.
+      </p>
+      <p>
+        <code>.json5</code> files are parsed as JSON5: on top of comments and trailing commas, keys may be
+        unquoted, strings single-quoted, and numbers written as <code>0x1F</code>, <code>.5</code>,
+        <code>+1</code>, <code>Infinity</code> or <code>NaN</code>. Values keep their JSON5 spelling in the
+        tree, and <code>tractor set</code> edits JSON5 files in place as well; new keys and strings are
+        written in JSON syntax, which JSON5 accepts.
+      </p>
+
       <h3>YAML</h3>
       <Example
         file={{ name: 'settings.yaml', language: 'yaml', content: SETTINGS_YAML }}
@@ -91,6 +121,48 @@ document/
       <p>INI files work the same way — sections become parent elements, keys become children.</p>
       <CodeBlock language="bash" code={`echo '[database]\nhost = localhost\nport = 5432' | tractor -l ini -x "//database/host" -v value`} />
 
+      <h3>HCL / Terraform</h3>
+      <p>
+        Blocks keep their block type as element name, with labels as attributes: two labels become <code>@type</code> and <code>@name</code>, a single label becomes <code>@name</code>. By default each attribute is an <code>attribute</code> element with its <code>name</code>, and references and function calls stay syntax, so queries and rules see the configuration as written.
+      </p>
+      <Example
+        file={{ name: 'main.tf', language: 'hcl', content: MAIN_TF }}
+        command={`tractor main.tf -x "//resource[@type='aws_s3_bucket']/attribute[name='bucket']/string" -v value`}
+        output="my-logs"
+      />
+      <p>
+        Use <code>-t data</code> for the data tree, where attributes become child elements named after their keys:
+      </p>
+      <CodeBlock language="bash" code={`tractor main.tf -t data -x "//resource[@type='aws_s3_bucket']/bucket" -v value`} />
+      <p>
+        <Link to="/docs/commands/set">tractor set</Link> always edits the data tree, so it can update values in place, e.g. to bump a module version:
+      </p>
+      <CodeBlock language="bash" code={`tractor set main.tf -x "//module[@name='vpc']/version" --value 5.1.0`} />
+
+      <h3>XML</h3>
+      <p>
+        XML files (<code>.xml</code>, <code>.csproj</code>, <code>.props</code>, <code>.targets</code>, <code>.resx</code>, <code>.xaml</code>) are queried as they are: element and attribute names are the file's own. Matches report their line and column, and <Link to="/docs/commands/set">tractor set</Link> can update element text or attributes, or add missing ones. Edits are made in place, so the rest of the file keeps its formatting:
+      </p>
+      <CodeBlock language="bash" code={`tractor set App.csproj -x "//PackageReference[@Include='Newtonsoft.Json']/@Version" --value 13.0.3`} />
+
+      <h3>CSV and TSV</h3>
+      <p>
+        <code>.csv</code> and <code>.tsv</code> files become a <code>document</code> of <code>row</code> elements, one per
+        record. The header row names the cells, so a row reads like a record (header names that are not valid element
+        names are sanitized, with the original in a <code>key</code> attribute):
+      </p>
+      <CodeBlock language="bash" code={`tractor users.csv -x "//row[status='active']/email" -v value`} />
+      <p>
+        Each cell reports its own line and column, so <code>tractor check</code> rules work on tables just as they do on
+        YAML, and <Link to="/docs/commands/set">tractor set</Link> can change a cell's value (quoting it if needed):
+      </p>
+      <CodeBlock language="bash" code={`tractor set flags.csv -x "//row[flag='new_checkout']/enabled" --value true`} />
+      <p>
+        Rows shorter than the header still have every cell; setting one of the missing cells adds the fields it needs
+        to the row. Adding columns or rows is not supported. Tables have only the data tree, so <code>-t structure</code>{' '}
+        and <code>-t raw</code> are errors.
+      </p>
+
       <h2>Arrays</h2>
       <p>
         Array items become repeated elements with the same name. In JSON, array elements inside an object key repeat that key's element name:
@@ -177,7 +249,7 @@ document/
         </thead>
         <tbody>
           <tr><td><code>structure</code></td><td>Code languages (JS, Rust, C#, etc.)</td><td>Syntax elements: function, class, method, parameters, etc.</td></tr>
-          <tr><td><code>data</code></td><td>JSON, YAML, TOML, INI</td><td>Data mirrors file structure: keys are elements, values are text</td></tr>
+          <tr><td><code>data</code></td><td>JSON, YAML, TOML, INI, CSV</td><td>Data mirrors file structure: keys are elements, values are text</td></tr>
           <tr><td><code>raw</code></td><td>Any (manual override)</td><td>Raw parser output with no transforms</td></tr>
         </tbody>
       </table>
@@ -192,9 +264,12 @@ document/
         </thead>
         <tbody>
           <tr><td>JSON</td><td><code>.json</code></td></tr>
+          <tr><td>JSONC</td><td><code>.jsonc</code>, <code>tsconfig.json</code>, <code>.vscode/*.json</code></td></tr>
+          <tr><td>JSON5</td><td><code>.json5</code></td></tr>
           <tr><td>YAML</td><td><code>.yaml</code>, <code>.yml</code></td></tr>
           <tr><td>TOML</td><td><code>.toml</code></td></tr>
           <tr><td>INI</td><td><code>.ini</code></td></tr>
+          <tr><td>CSV / TSV</td><td><code>.csv</code>, <code>.tsv</code></td></tr>
         </tbody>
       </table>
 
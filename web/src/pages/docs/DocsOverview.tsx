@@ -131,13 +131,13 @@ program/
 
       <h2>Supported Languages</h2>
       <p>
-        Tractor supports 27 languages. Code languages are parsed into a syntax tree. Data formats (JSON, YAML, TOML, INI) are parsed into a data tree where keys become elements.
+        Tractor supports 27 languages. Code languages are parsed into a syntax tree. Data formats (JSON, YAML, TOML, INI) are parsed into a data tree where keys become elements; HCL has one too, next to its default block structure.
       </p>
       <p>
-        <strong>Code:</strong> JavaScript, TypeScript, TSX, C#, Rust, Python, Go, Java, Ruby, C++, C, HTML, CSS, Bash, PHP, Scala, Lua, Haskell, OCaml, R, Julia, Markdown, T-SQL
+        <strong>Code:</strong> JavaScript, TypeScript, TSX, C#, Rust, Python, Go, Java, Kotlin, Swift, Dart, Ruby, C++, C, HTML, CSS, Bash, PHP, Scala, Lua, Haskell, OCaml, R, Julia, Markdown, T-SQL, Dockerfile, Protobuf, GraphQL
       </p>
       <p>
-        <strong>Data:</strong> <Link to="/docs/languages/data">JSON, YAML, TOML, INI</Link>
+        <strong>Data:</strong> <Link to="/docs/languages/data">JSON, YAML, TOML, INI, HCL</Link>
       </p>
 
       <h2>Guides</h2>
@@ -189,6 +189,41 @@ function add(a, b) {
         output="greet"
       />
 
+      <h2>Query by example</h2>
+      <p>
+        Don't know the XPath yet? Write the code you're looking for and let <code>tractor pattern</code> build the query.
+        Identifiers starting with <code>$</code> match any code in their place, and using the same one twice requires both places to hold the same code.
+        Whitespace and formatting are ignored; structure isn't, so <code>Assert.AreEqual($a, $b)</code> doesn't match a call with three arguments.
+      </p>
+      <CodeBlock
+        language="bash"
+        code={`# Print the generated XPath (e.g. to paste into a rule)
+tractor pattern -l csharp 'Assert.AreEqual($a, $b)'
+
+# Or run it directly; the language comes from the files
+tractor pattern 'Assert.AreEqual($a, $b)' "tests/**/*.cs" -v source
+
+# Comparisons of a value with itself
+tractor pattern -l python '$x == $x' "src/**/*.py"`}
+      />
+      <p>
+        The query is built for the tree mode given with <code>-t</code> (structure by default), so use the same mode wherever you reuse it.
+      </p>
+
+      <h2>From a position to a query</h2>
+      <p>
+        <code>tractor at FILE:LINE:COL</code> lists every element containing that position, outermost first.
+        Each line shows an absolute path from the root and a path that selects only that element, starting from the nearest enclosing element its own step identifies, using names where declarations have them.
+        The innermost element's tree follows, so you can see what to match on. Leave out the column to use the line's first non-blank character.
+        The file is parsed as queries parse it, so XML, notebooks, Vue and Svelte components, and CSV work too.
+        The encoding of a legacy file that is not UTF-8 is guessed; name it with <code>--encoding</code> when the guess is wrong, e.g. <code>--encoding windows-1252</code>.
+      </p>
+      <CodeBlock
+        language="bash"
+        code={`tractor at src/Services/UserService.cs:42:17
+tractor at src/Services/UserService.cs:42 -d 2`}
+      />
+
       <h2>Multi-language</h2>
       <p>Tractor works with 20+ languages. The same query syntax applies everywhere:</p>
 
@@ -269,7 +304,7 @@ fn add(a: i32, b: i32) -> i32 {
           <tr><td><code>-s, --string</code></td><td>Inline source code (alternative to file)</td></tr>
           <tr><td><code>-n, --limit</code></td><td>Limit output to first N matches</td></tr>
           <tr><td><code>-d, --depth</code></td><td>Limit tree output depth</td></tr>
-          <tr><td><code>-t, --tree</code></td><td>Tree mode: raw, structure, data</td></tr>
+          <tr><td><code>-t, --tree</code></td><td>Tree mode: raw, structure, common, data</td></tr>
           <tr><td><code>-W</code></td><td>Ignore whitespace in string matching</td></tr>
           <tr><td><code>-g, --group</code></td><td>Group output by file</td></tr>
           <tr><td><code>--diff-files</code></td><td>Only files changed in a git diff range</td></tr>
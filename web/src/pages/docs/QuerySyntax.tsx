@@ -289,6 +289,31 @@ tractor "src/**/*.cs" -x "//namespace[not(contains($file, translate(string(name)
 # Simply check the file path
 tractor "src/**/*.cs" -x "$file" -v value`} />
 
+      <h2>Tractor Functions</h2>
+      <p>
+        The <code>tractor:</code> functions give access to a node's location and original source text:
+      </p>
+      <table className="doc-table">
+        <thead>
+          <tr><th>Function</th><th>Returns</th></tr>
+        </thead>
+        <tbody>
+          <tr><td><code>tractor:line(node)</code></td><td>Start line of the node</td></tr>
+          <tr><td><code>tractor:column(node)</code></td><td>Start column of the node</td></tr>
+          <tr><td><code>tractor:lines-of(node)</code></td><td>Number of lines the node spans</td></tr>
+          <tr><td><code>tractor:source(node)</code></td><td>Original source text of the node, including whitespace and comments</td></tr>
+          <tr><td><code>tractor:kind(node)</code></td><td>The raw TreeSitter kind (e.g. <code>method_declaration</code>)</td></tr>
+          <tr><td><code>tractor:file-name()</code></td><td>File name of <code>$file</code>, without its directory</td></tr>
+          <tr><td><code>tractor:dir()</code></td><td>Directory of <code>$file</code></td></tr>
+          <tr><td><code>tractor:language()</code></td><td>Language the file was parsed as</td></tr>
+        </tbody>
+      </table>
+      <CodeBlock language="bash" code={`# Methods longer than 50 lines
+tractor "src/**/*.cs" -x "//method[tractor:lines-of(.) > 50]/name" -v value
+
+# Test classes must live in a file named after them
+tractor "tests/**/*.cs" -x "//class[tractor:file-name() != concat(name, '.cs')]"`} />
+
       <h2>Tips</h2>
       <ul>
         <li><strong>Start with <code>//</code></strong> — you almost never need full paths from root.</li>
@@ -107,6 +107,7 @@ example.js:3:3: error: getAll methods in repositories should use orderBy
           <tr><td><code>message</code></td><td>No</td><td>Custom message template (<code>{'{value}'}</code>, <code>{'{line}'}</code>, etc.)</td></tr>
           <tr><td><code>include</code></td><td>No</td><td>File patterns for this rule only (relative to config file directory)</td></tr>
           <tr><td><code>exclude</code></td><td>No</td><td>File patterns to exclude for this rule (relative to config file directory)</td></tr>
+          <tr><td><code>timeout</code></td><td>No</td><td>How long the rule may run on one file (<code>500ms</code>, <code>30s</code>, <code>2m</code>, or seconds). When it runs out, the rule is skipped for that file with a warning</td></tr>
           <tr><td><code>expect</code></td><td>No</td><td>Test examples (see below)</td></tr>
         </tbody>
       </table>
@@ -134,6 +135,68 @@ example.js:3:3: error: getAll methods in repositories should use orderBy
         When you run <code>tractor run</code>, the <code>expect</code> entries are also validated. If a <code>valid</code> example matches the rule (or an <code>invalid</code> example doesn't), the run fails.
       </p>
 
+      <h2>Shared Functions and Fragments</h2>
+      <p>
+        Define a predicate once and reuse it across rules. <code>functions</code> declares XPath functions by signature, and{' '}
+        <code>let</code> declares fragments referenced as <code>$name</code>. Both work in every query in the config:
+      </p>
+      <CodeBlock
+        language="yaml"
+        title="tractor.yml"
+        code={`functions:
+  is-test-method($m): "$m/attributes/attribute[name = 'Test' or name = 'Fact']"
+  public-api(): "//method[public][not(ancestor::class[not(public)])]"
+let:
+  async: "modifiers/async or returns[contains(., 'Task')]"
+check:
+  rules:
+    - id: async-test-void
+      xpath: "//method[is-test-method(.)][$async][returns = 'void']"
+      reason: "Async tests must return Task"
+    - id: public-api-docs
+      xpath: "public-api()[not(comment)]"
+      reason: "Public API needs documentation"`}
+      />
+      <p>
+        A function body sees its parameters and the config's variables, not the caller's variables, and runs with the
+        document as its context item: <code>//</code> searches the caller's file, and nodes of the caller come in as
+        arguments, like <code>is-test-method(.)</code>. A fragment is pasted into the query, so it runs in the caller's
+        context. Bodies may call other functions and use fragments. Reports and errors show the query as written.
+      </p>
+      <p>
+        Definitions that refer to themselves, directly or through others, are rejected, as are names XPath reserves
+        (<code>text</code>, <code>node</code>, <code>if</code>, ...) and names of standard functions
+        (<code>count</code>, <code>contains</code>, ...); these are reported when the config loads. A call with the
+        wrong number of arguments is reported against its rule before any file is read.
+      </p>
+
+      <h2>Variables</h2>
+      <p>
+        <code>variables</code> binds values as <code>$name</code> in every query, so one rule can be tuned per project
+        instead of copied with different literals:
+      </p>
+      <CodeBlock
+        language="yaml"
+        title="tractor.yml"
+        code={`variables:
+  max-params: 5
+  allowed-namespaces: [App.Core, App.Web]
+check:
+  rules:
+    - id: too-many-params
+      xpath: "//method[count(parameter) > $max-params]"
+      reason: "Too many parameters"
+    - id: unknown-namespace
+      xpath: "//namespace[not(name = $allowed-namespaces)]"
+      reason: "Namespace is not in the allowed list"`}
+      />
+      <p>
+        Integers and booleans keep their YAML type; quote a value to bind it as a string. A list binds a sequence.
+        Override a value for one run with <code>--var</code>, e.g.{' '}
+        <code>tractor run --var release=$(git describe --tags)</code>. A variable may not share its name with a{' '}
+        <code>let</code> fragment, and <code>file</code> and names starting with <code>tractor</code> are reserved.
+      </p>
+
       <h2>Multiple Operation Types</h2>
       <p>
         Use the <code>operations</code> list to mix check, test, query, and set operations:
@@ -158,6 +221,20 @@ operations:
           expect: some
           message: "At least one class expected"`}
       />
+      <p>
+        Every XPath in the config is compiled before any file is parsed. If one fails to compile,
+        tractor reports all invalid expressions together (with the rule id for check rules) and
+        runs none of the operations, so a typo in the last rule never leaves a set half-applied.
+      </p>
+      <p>
+        Check rules with a language are also checked against the element names that language's
+        trees can contain. A C# rule testing for <code>//methd</code> gets a warning suggesting{' '}
+        <code>method</code> instead of silently matching nothing. Languages with their own{' '}
+        <code>transforms</code> entry are not checked, and neither are <code>common</code> or{' '}
+        <code>data</code> tree modes, or languages whose element names come from the file's keys
+        (TOML, INI, .env, HCL). Rules testing for <code>injected</code> may also use the element
+        names of the code the language can embed.
+      </p>
 
       <h2>Set Operations</h2>
       <p>
@@ -305,6 +382,48 @@ tractor run --config rules.yaml src/app.js`} />
         These are applied after the operation-level file resolution, so they can only narrow — never widen — the file set.
       </p>
 
+      <h3>Language mapping</h3>
+      <p>
+        Files are matched to a language by extension. Files with an unknown extension are also checked for a shebang
+        (<code>#!/usr/bin/env python3</code>) or an editor modeline near the start of the file (<code>vim: set ft=ruby:</code>, <code>-*- mode: python -*-</code>).
+        Use <code>languages</code> to map other files explicitly. Patterns without a <code>/</code> match the file name anywhere in the tree.
+        Patterns with a <code>/</code> are globs relative to the config file, and take precedence over file-name patterns.
+        When several patterns of the same kind match, the first one listed wins:
+      </p>
+      <CodeBlock
+        language="yaml"
+        code={`languages:
+  "*.props.in": xml
+  "scripts/*": bash          # extensionless helper scripts
+  "*.h": cpp                 # C++ headers instead of C`}
+      />
+      <p>
+        Config mappings take precedence over the extension table. Path patterns are checked before file-name patterns.
+        The language must be one tractor supports (see <code>tractor languages</code>).
+      </p>
+
+      <h3>File encodings</h3>
+      <p>
+        Files with a byte order mark are read as UTF-8, UTF-16LE or UTF-16BE. Other files are read as UTF-16 when they
+        look like UTF-16 text, and as UTF-8 when they are valid UTF-8. Any other text file is read in the encoding{' '}
+        <code>encodings</code> gives it, or else in the legacy encoding its bytes suggest (Windows-1252 for Western
+        European text, Shift_JIS, Windows-1251, ...). Files with control bytes no text holds are skipped as binary.
+        Set an encoding for files whose guess comes out wrong; patterns work like <code>languages</code> patterns:
+      </p>
+      <CodeBlock
+        language="yaml"
+        code={`encodings:
+  "*.cs": windows-1252
+  "legacy/jp/*": shift_jis
+  "*.rc": utf-16le           # UTF-16 files saved without a BOM`}
+      />
+      <p>
+        A configured UTF-16 encoding applies to every file it matches that has no BOM. Encoding names follow the{' '}
+        <a href="https://encoding.spec.whatwg.org/#names-and-labels">WHATWG Encoding Standard</a>.
+        Lines and columns in results count the decoded text, not the bytes on disk. <code>tractor set</code> and{' '}
+        <code>tractor update</code> write a changed file back in its original encoding, keeping any BOM.
+      </p>
+
       <h3>File limits</h3>
       <p>
         Tractor protects against accidentally globbing too many files:
@@ -317,6 +436,33 @@ tractor run --config rules.yaml src/app.js`} />
       <CodeBlock language="bash" code={`# Increase the limit for large monorepos
 tractor run --max-files 50000`} />
 
+      <h3>Time limits</h3>
+      <p>
+        A query that compares every element with every other one can run for minutes on a large generated
+        file. Give such a rule a <code>timeout</code>, or cap all queries on a file with{' '}
+        <code>--timeout-per-file</code>. When a limit runs out, tractor reports a warning on the file for the
+        rule, skips what was stopped, and finishes the rest of the run. The stopped query keeps running in the
+        background until it finishes; while one per core (at least four) is still running, the time-limited
+        rules on other files are skipped with a warning rather than started.
+      </p>
+      <CodeBlock language="yaml" code={`check:
+  rules:
+    - id: duplicate-literal
+      xpath: "//string[. = following::string]"
+      reason: "Repeated string literal"
+      timeout: 5s`} />
+      <CodeBlock language="bash" code={`tractor run --timeout-per-file 30s`} />
+
+      <h3>Skipping files early</h3>
+      <p>
+        When a rule compares text with a literal — <code>contains(., 'TODO')</code>,{' '}
+        <code>name = 'eval'</code> — a file whose source doesn't contain those words can't match, so tractor
+        skips the rule there without parsing the file. Only literals every match needs count: a rule with{' '}
+        <code>or</code>, <code>not()</code> or an attribute comparison runs on every file as before. Files are
+        always parsed when parse errors are reported, with <code>--ignore-whitespace</code>, and for data
+        trees, XML and notebooks, where the tree's text isn't the file's text.
+      </p>
+
       <h3>Debugging with --verbose</h3>
       <p>
         Use <code>--verbose</code> to see each file resolution step — which patterns are being expanded, the base directory, and how many files remain after each intersection: